@@ -0,0 +1,47 @@
+//! Time source for timestamps recorded by services and reviews.
+//!
+//! Everything that stamps `created_at`/`updated_at` reads the time through a
+//! [`Clock`] so tests can pin it with [`FixedClock`].
+
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// A source of the current time.
+pub trait Clock: Debug + Send + Sync {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The current time as an RFC 3339 string, the format stored in records.
+    fn now_rfc3339(&self) -> String {
+        self.now().to_rfc3339()
+    }
+}
+
+/// Shared clock handle.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The default clock: the system's wall-clock time.
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that always returns the same instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -3,6 +3,11 @@
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::sdk::circuit_breaker::CircuitBreakerConfig;
+use crate::sdk::retry::BackoffParams;
+use crate::service::stats::ChangeDetection;
 
 /// Command-line arguments for the Context Engine server.
 #[derive(Parser, Debug, Clone)]
@@ -27,6 +32,18 @@ pub struct Args {
     #[arg(short, long, env = "CONTEXT_ENGINE_DEBUG")]
     pub debug: bool,
 
+    /// Log output format: text or json
+    #[arg(long, default_value = "text", env = "CONTEXT_ENGINE_LOG_FORMAT")]
+    pub log_format: LogFormat,
+
+    /// Exit after this many seconds without a request (0 = never)
+    #[arg(long, default_value = "0", env = "CONTEXT_ENGINE_IDLE_TIMEOUT")]
+    pub idle_timeout: u64,
+
+    /// Ping a stdio client every this many seconds (0 = never)
+    #[arg(long, default_value = "0", env = "CONTEXT_ENGINE_KEEPALIVE_INTERVAL")]
+    pub keepalive_interval: u64,
+
     /// Enable file watcher
     #[arg(long, default_value = "true", env = "CONTEXT_ENGINE_WATCH")]
     pub watch: bool,
@@ -47,6 +64,82 @@ pub struct Args {
     #[arg(long, default_value = "8000", env = "CONTEXT_ENGINE_TOKEN_BUDGET")]
     pub token_budget: usize,
 
+    /// Timeout for each request to the Augment backend (seconds)
+    #[arg(long, default_value = "30", env = "CONTEXT_ENGINE_REQUEST_TIMEOUT")]
+    pub request_timeout: u64,
+
+    /// Maximum idle connections kept open to the Augment backend
+    #[arg(long, default_value = "32", env = "CONTEXT_ENGINE_POOL_SIZE")]
+    pub pool_size: usize,
+
+    /// Maximum attempts per backend request, including the first (0 = no limit)
+    #[arg(long, default_value = "0", env = "CONTEXT_ENGINE_RETRY_MAX_ATTEMPTS")]
+    pub retry_max_attempts: u32,
+
+    /// Delay before the first retry of a failed backend request (milliseconds)
+    #[arg(
+        long,
+        default_value = "100",
+        env = "CONTEXT_ENGINE_RETRY_BASE_DELAY_MS"
+    )]
+    pub retry_base_delay_ms: u64,
+
+    /// Upper bound on the delay between retries (milliseconds)
+    #[arg(
+        long,
+        default_value = "30000",
+        env = "CONTEXT_ENGINE_RETRY_MAX_DELAY_MS"
+    )]
+    pub retry_max_delay_ms: u64,
+
+    /// Consecutive backend failures before calls fail fast (0 = never)
+    #[arg(
+        long,
+        default_value = "5",
+        env = "CONTEXT_ENGINE_CIRCUIT_FAILURE_THRESHOLD"
+    )]
+    pub circuit_failure_threshold: u32,
+
+    /// How long backend calls fail fast before a probe is allowed (seconds)
+    #[arg(long, default_value = "30", env = "CONTEXT_ENGINE_CIRCUIT_COOLDOWN")]
+    pub circuit_cooldown: u64,
+
+    /// Maximum tool calls executing at once; the rest wait (0 = no limit)
+    #[arg(
+        long,
+        default_value = "16",
+        env = "CONTEXT_ENGINE_MAX_CONCURRENT_TOOLS"
+    )]
+    pub max_concurrent_tools: usize,
+
+    /// How workspace stats detect changed files: mtime or hash
+    #[arg(
+        long,
+        default_value = "mtime",
+        env = "CONTEXT_ENGINE_STATS_CHANGE_DETECTION"
+    )]
+    pub stats_change_detection: ChangeDetection,
+
+    /// Semantic search results kept in the result cache (0 = no caching)
+    #[arg(
+        long,
+        default_value = "128",
+        env = "CONTEXT_ENGINE_SEARCH_CACHE_CAPACITY"
+    )]
+    pub search_cache_capacity: usize,
+
+    /// How long a cached search result stays fresh (seconds)
+    #[arg(long, default_value = "300", env = "CONTEXT_ENGINE_SEARCH_CACHE_TTL")]
+    pub search_cache_ttl: u64,
+
+    /// Files per indexing batch sent to the backend
+    #[arg(long, default_value = "100", env = "CONTEXT_ENGINE_INDEX_BATCH_SIZE")]
+    pub index_batch_size: usize,
+
+    /// Maximum indexing batches uploaded concurrently
+    #[arg(long, default_value = "4", env = "CONTEXT_ENGINE_INDEX_CONCURRENCY")]
+    pub index_concurrency: usize,
+
     /// Enable metrics collection
     #[arg(long, env = "CONTEXT_ENGINE_METRICS")]
     pub metrics: bool,
@@ -54,6 +147,84 @@ pub struct Args {
     /// Metrics port
     #[arg(long, default_value = "9090", env = "CONTEXT_ENGINE_METRICS_PORT")]
     pub metrics_port: u16,
+
+    /// Allowed CORS origins for the HTTP server (comma-separated, `*` for any)
+    #[arg(long, value_delimiter = ',', env = "CONTEXT_ENGINE_ALLOWED_ORIGINS")]
+    pub allowed_origins: Vec<String>,
+
+    /// Maximum HTTP request body size in bytes
+    #[arg(long, default_value = "1048576", env = "CONTEXT_ENGINE_MAX_BODY_SIZE")]
+    pub max_body_size: usize,
+
+    /// Maximum entries in the `arguments` of a tool call over HTTP
+    #[arg(long, default_value = "64", env = "CONTEXT_ENGINE_MAX_TOOL_ARGUMENTS")]
+    pub max_tool_arguments: usize,
+
+    /// Requests per second each HTTP client may make to `/mcp/*` (0 = no limit)
+    #[arg(long, default_value = "0", env = "CONTEXT_ENGINE_RATE_LIMIT_RPS")]
+    pub rate_limit_rps: f64,
+
+    /// Requests an HTTP client may make in a burst above its rate
+    #[arg(long, default_value = "20", env = "CONTEXT_ENGINE_RATE_LIMIT_BURST")]
+    pub rate_limit_burst: u32,
+
+    /// Extra gitignore-style patterns to exclude from indexing (comma-separated).
+    /// Applied after `.gitignore`; prefix with `!` to force-include a path
+    #[arg(
+        long = "exclude",
+        value_delimiter = ',',
+        env = "CONTEXT_ENGINE_EXCLUDE"
+    )]
+    pub exclude_globs: Vec<String>,
+
+    /// Follow symlinks when walking the workspace; links leaving the
+    /// workspace are still skipped
+    #[arg(long, env = "CONTEXT_ENGINE_FOLLOW_SYMLINKS")]
+    pub follow_symlinks: bool,
+
+    /// Additional workspace roots that file tools can target with their
+    /// `root` argument (comma-separated)
+    #[arg(long = "root", value_delimiter = ',', env = "CONTEXT_ENGINE_ROOTS")]
+    pub roots: Vec<PathBuf>,
+
+    /// Only register tools in these categories (comma-separated: retrieval,
+    /// index, memory, planning, review); empty registers every category
+    #[arg(
+        long = "enable-categories",
+        value_delimiter = ',',
+        env = "CONTEXT_ENGINE_ENABLED_CATEGORIES"
+    )]
+    pub enabled_tool_categories: Vec<String>,
+
+    /// Tools not to register, by name or glob such as `git_*` (comma-separated)
+    #[arg(
+        long = "disable-tools",
+        value_delimiter = ',',
+        env = "CONTEXT_ENGINE_DISABLED_TOOLS"
+    )]
+    pub disabled_tools: Vec<String>,
+
+    /// Refuse tools that write to disk (plans, memories, the index)
+    #[arg(long, env = "CONTEXT_ENGINE_READ_ONLY")]
+    pub read_only: bool,
+
+    /// Shannon entropy (bits per character) above which a token is reported
+    /// as a possible secret
+    #[arg(
+        long,
+        default_value = "4.2",
+        env = "CONTEXT_ENGINE_SECRET_ENTROPY_THRESHOLD"
+    )]
+    pub secret_entropy_threshold: f64,
+
+    /// Shortest token checked for high entropy
+    #[arg(long, default_value = "20", env = "CONTEXT_ENGINE_SECRET_MIN_LENGTH")]
+    pub secret_min_length: usize,
+
+    /// Base of the commit ranges review and git tools diff when given no
+    /// base (default: the remote's default branch)
+    #[arg(long, env = "CONTEXT_ENGINE_DEFAULT_BASE_REF")]
+    pub default_base_ref: Option<String>,
 }
 
 /// Transport mode.
@@ -65,6 +236,17 @@ pub enum Transport {
     Http,
 }
 
+/// Log output format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
 /// Server configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -78,6 +260,12 @@ pub struct Config {
     pub debug: bool,
     /// File watcher enabled
     pub watch: bool,
+    /// Seconds without a request before the server exits (0 = never)
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    /// Seconds between keepalive pings to a stdio client (0 = never)
+    #[serde(default)]
+    pub keepalive_interval_secs: u64,
     /// API key
     pub api_key: Option<String>,
     /// API URL
@@ -86,10 +274,198 @@ pub struct Config {
     pub max_file_size: usize,
     /// Token budget
     pub token_budget: usize,
+    /// Backend request timeout in seconds
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout_secs: u64,
+    /// Backend connection pool size (idle connections kept open)
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+    /// Maximum attempts per backend request (0 = no limit)
+    #[serde(default)]
+    pub retry_max_attempts: u32,
+    /// Delay before the first retry in milliseconds
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Maximum delay between retries in milliseconds
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+    /// Consecutive backend failures that open the circuit breaker (0 = disabled)
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub circuit_failure_threshold: u32,
+    /// Seconds the circuit breaker stays open before probing the backend
+    #[serde(default = "default_circuit_cooldown")]
+    pub circuit_cooldown_secs: u64,
+    /// Maximum tool calls executing at once (0 = no limit)
+    #[serde(default = "default_max_concurrent_tools")]
+    pub max_concurrent_tools: usize,
+    /// How workspace stats detect changed files
+    #[serde(default)]
+    pub stats_change_detection: ChangeDetection,
+    /// Semantic search results kept in the result cache (0 = no caching)
+    #[serde(default = "default_search_cache_capacity")]
+    pub search_cache_capacity: usize,
+    /// Seconds a cached search result stays fresh
+    #[serde(default = "default_search_cache_ttl")]
+    pub search_cache_ttl_secs: u64,
+    /// Files per indexing batch
+    #[serde(default = "default_index_batch_size")]
+    pub index_batch_size: usize,
+    /// Maximum indexing batches in flight
+    #[serde(default = "default_index_concurrency")]
+    pub index_concurrency: usize,
     /// Metrics enabled
     pub metrics: bool,
     /// Metrics port
     pub metrics_port: u16,
+    /// Allowed CORS origins (empty = same-origin only, `["*"]` = any)
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Maximum HTTP request body size in bytes
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+    /// Maximum entries in the `arguments` of a tool call over HTTP
+    #[serde(default = "default_max_tool_arguments")]
+    pub max_tool_arguments: usize,
+    /// Requests per second per HTTP client on `/mcp/*` (0 = no limit)
+    #[serde(default)]
+    pub rate_limit_rps: f64,
+    /// Requests per HTTP client allowed in a burst
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+    /// Extra gitignore-style exclude patterns, applied after the defaults and
+    /// `.gitignore`; `!pattern` re-includes matching paths
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// Follow symlinks that stay inside the workspace when walking it
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Additional roots, besides `workspace`, that file tools can read from
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+    /// Tool categories to register; empty means all of them
+    #[serde(default)]
+    pub enabled_tool_categories: Vec<String>,
+    /// Names or globs of tools not to register
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+    /// Refuse calls to tools that write to disk
+    #[serde(default)]
+    pub read_only: bool,
+    /// Entropy in bits per character above which a token is a possible secret
+    #[serde(default = "default_secret_entropy_threshold")]
+    pub secret_entropy_threshold: f64,
+    /// Shortest token checked for high entropy
+    #[serde(default = "default_secret_min_length")]
+    pub secret_min_length: usize,
+    /// Base of commit ranges given no base; `None` detects the remote's
+    /// default branch
+    #[serde(default)]
+    pub default_base_ref: Option<String>,
+}
+
+fn default_request_timeout() -> u64 {
+    30
+}
+
+fn default_pool_size() -> usize {
+    32
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    100
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_circuit_failure_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_cooldown() -> u64 {
+    30
+}
+
+fn default_max_concurrent_tools() -> usize {
+    16
+}
+
+fn default_max_body_size() -> usize {
+    1024 * 1024
+}
+
+fn default_max_tool_arguments() -> usize {
+    64
+}
+
+fn default_rate_limit_burst() -> u32 {
+    20
+}
+
+fn default_secret_entropy_threshold() -> f64 {
+    4.2
+}
+
+fn default_secret_min_length() -> usize {
+    20
+}
+
+fn default_search_cache_capacity() -> usize {
+    128
+}
+
+fn default_search_cache_ttl() -> u64 {
+    300
+}
+
+fn default_index_batch_size() -> usize {
+    100
+}
+
+fn default_index_concurrency() -> usize {
+    4
+}
+
+impl Config {
+    /// Backend request timeout as a [`Duration`].
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// Idle shutdown window, if enabled.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_secs > 0).then(|| Duration::from_secs(self.idle_timeout_secs))
+    }
+
+    /// Keepalive ping interval, if enabled.
+    pub fn keepalive_interval(&self) -> Option<Duration> {
+        (self.keepalive_interval_secs > 0)
+            .then(|| Duration::from_secs(self.keepalive_interval_secs))
+    }
+
+    /// Time-to-live of cached search results as a [`Duration`].
+    pub fn search_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.search_cache_ttl_secs)
+    }
+
+    /// Backoff parameters for retried backend requests.
+    pub fn backoff(&self) -> BackoffParams {
+        BackoffParams {
+            initial_ms: self.retry_base_delay_ms,
+            max_ms: self.retry_max_delay_ms,
+            max_tries: (self.retry_max_attempts > 0).then_some(self.retry_max_attempts),
+            ..BackoffParams::default()
+        }
+    }
+
+    /// Circuit breaker thresholds for backend requests.
+    pub fn circuit_breaker(&self) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: self.circuit_failure_threshold,
+            cooldown: Duration::from_secs(self.circuit_cooldown_secs),
+        }
+    }
 }
 
 impl From<Args> for Config {
@@ -102,12 +478,41 @@ impl From<Args> for Config {
             port: args.port,
             debug: args.debug,
             watch: args.watch,
+            idle_timeout_secs: args.idle_timeout,
+            keepalive_interval_secs: args.keepalive_interval,
             api_key: args.api_key,
             api_url: args.api_url,
             max_file_size: args.max_file_size,
             token_budget: args.token_budget,
+            request_timeout_secs: args.request_timeout,
+            pool_size: args.pool_size,
+            retry_max_attempts: args.retry_max_attempts,
+            retry_base_delay_ms: args.retry_base_delay_ms,
+            retry_max_delay_ms: args.retry_max_delay_ms,
+            circuit_failure_threshold: args.circuit_failure_threshold,
+            circuit_cooldown_secs: args.circuit_cooldown,
+            max_concurrent_tools: args.max_concurrent_tools,
+            stats_change_detection: args.stats_change_detection,
+            search_cache_capacity: args.search_cache_capacity,
+            search_cache_ttl_secs: args.search_cache_ttl,
+            index_batch_size: args.index_batch_size,
+            index_concurrency: args.index_concurrency,
             metrics: args.metrics,
             metrics_port: args.metrics_port,
+            allowed_origins: args.allowed_origins,
+            max_body_size: args.max_body_size,
+            max_tool_arguments: args.max_tool_arguments,
+            rate_limit_rps: args.rate_limit_rps,
+            rate_limit_burst: args.rate_limit_burst,
+            exclude_globs: args.exclude_globs,
+            follow_symlinks: args.follow_symlinks,
+            roots: args.roots,
+            enabled_tool_categories: args.enabled_tool_categories,
+            disabled_tools: args.disabled_tools,
+            read_only: args.read_only,
+            secret_entropy_threshold: args.secret_entropy_threshold,
+            secret_min_length: args.secret_min_length,
+            default_base_ref: args.default_base_ref,
         }
     }
 }
@@ -120,12 +525,41 @@ impl Default for Config {
             port: 3000,
             debug: false,
             watch: true,
+            idle_timeout_secs: 0,
+            keepalive_interval_secs: 0,
             api_key: None,
             api_url: None,
             max_file_size: 1024 * 1024,
             token_budget: 8000,
+            request_timeout_secs: default_request_timeout(),
+            pool_size: default_pool_size(),
+            retry_max_attempts: 0,
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            retry_max_delay_ms: default_retry_max_delay_ms(),
+            circuit_failure_threshold: default_circuit_failure_threshold(),
+            circuit_cooldown_secs: default_circuit_cooldown(),
+            max_concurrent_tools: default_max_concurrent_tools(),
+            stats_change_detection: ChangeDetection::default(),
+            search_cache_capacity: default_search_cache_capacity(),
+            search_cache_ttl_secs: default_search_cache_ttl(),
+            index_batch_size: default_index_batch_size(),
+            index_concurrency: default_index_concurrency(),
             metrics: false,
             metrics_port: 9090,
+            allowed_origins: Vec::new(),
+            max_body_size: default_max_body_size(),
+            max_tool_arguments: default_max_tool_arguments(),
+            rate_limit_rps: 0.0,
+            rate_limit_burst: default_rate_limit_burst(),
+            exclude_globs: Vec::new(),
+            follow_symlinks: false,
+            roots: Vec::new(),
+            enabled_tool_categories: Vec::new(),
+            disabled_tools: Vec::new(),
+            read_only: false,
+            secret_entropy_threshold: default_secret_entropy_threshold(),
+            secret_min_length: default_secret_min_length(),
+            default_base_ref: None,
         }
     }
 }
@@ -169,12 +603,22 @@ mod tests {
         assert_eq!(config.port, 3000);
         assert!(!config.debug);
         assert!(config.watch);
+        assert_eq!(config.idle_timeout(), None);
+        assert_eq!(config.keepalive_interval(), None);
         assert!(config.api_key.is_none());
         assert!(config.api_url.is_none());
         assert_eq!(config.max_file_size, 1024 * 1024);
         assert_eq!(config.token_budget, 8000);
+        assert_eq!(config.request_timeout(), Duration::from_secs(30));
         assert!(!config.metrics);
         assert_eq!(config.metrics_port, 9090);
+        assert!(config.allowed_origins.is_empty());
+
+        // Retry defaults match the SDK's built-in backoff
+        let (backoff, builtin) = (config.backoff(), BackoffParams::default());
+        assert_eq!(backoff.initial_ms, builtin.initial_ms);
+        assert_eq!(backoff.max_ms, builtin.max_ms);
+        assert_eq!(backoff.max_tries, builtin.max_tries);
     }
 
     #[test]
@@ -217,6 +661,7 @@ mod tests {
         assert_eq!(config.api_key, Some("test-key".to_string()));
         assert_eq!(config.max_file_size, 2097152);
         assert!(config.metrics);
+        assert_eq!(config.request_timeout_secs, 30);
     }
 
     #[test]
@@ -226,13 +671,43 @@ mod tests {
             transport: Transport::Http,
             port: 4000,
             debug: true,
+            log_format: LogFormat::Json,
+            idle_timeout: 600,
+            keepalive_interval: 15,
             watch: false,
             api_key: Some("key123".to_string()),
             api_url: Some("https://api.test.com".to_string()),
             max_file_size: 500000,
             token_budget: 4000,
+            request_timeout: 5,
+            pool_size: 4,
+            retry_max_attempts: 3,
+            retry_base_delay_ms: 250,
+            retry_max_delay_ms: 2000,
+            circuit_failure_threshold: 3,
+            circuit_cooldown: 10,
+            max_concurrent_tools: 8,
+            stats_change_detection: ChangeDetection::Hash,
+            search_cache_capacity: 32,
+            search_cache_ttl: 60,
+            index_batch_size: 50,
+            index_concurrency: 2,
             metrics: true,
             metrics_port: 9095,
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            max_body_size: 4096,
+            max_tool_arguments: 8,
+            rate_limit_rps: 2.5,
+            rate_limit_burst: 5,
+            exclude_globs: vec!["vendor/".to_string()],
+            follow_symlinks: true,
+            roots: vec![PathBuf::from("/test/shared")],
+            enabled_tool_categories: vec!["retrieval".to_string()],
+            disabled_tools: vec!["git_*".to_string()],
+            read_only: true,
+            secret_entropy_threshold: 3.5,
+            secret_min_length: 32,
+            default_base_ref: Some("develop".to_string()),
         };
 
         let config: Config = args.into();
@@ -243,5 +718,38 @@ mod tests {
         assert!(config.debug);
         assert!(!config.watch);
         assert_eq!(config.api_key, Some("key123".to_string()));
+        assert_eq!(config.allowed_origins, vec!["https://app.example.com"]);
+        assert_eq!(config.max_body_size, 4096);
+        assert_eq!(config.max_tool_arguments, 8);
+        assert_eq!(config.rate_limit_rps, 2.5);
+        assert_eq!(config.rate_limit_burst, 5);
+        assert_eq!(config.exclude_globs, vec!["vendor/"]);
+        assert!(config.follow_symlinks);
+        assert_eq!(config.roots, vec![PathBuf::from("/test/shared")]);
+        assert_eq!(config.enabled_tool_categories, vec!["retrieval"]);
+        assert_eq!(config.disabled_tools, vec!["git_*"]);
+        assert!(config.read_only);
+        assert_eq!(config.secret_entropy_threshold, 3.5);
+        assert_eq!(config.secret_min_length, 32);
+        assert_eq!(config.default_base_ref.as_deref(), Some("develop"));
+        assert_eq!(config.request_timeout_secs, 5);
+        assert_eq!(config.pool_size, 4);
+        assert_eq!(config.idle_timeout(), Some(Duration::from_secs(600)));
+        assert_eq!(config.keepalive_interval(), Some(Duration::from_secs(15)));
+        assert_eq!(config.index_batch_size, 50);
+        assert_eq!(config.stats_change_detection, ChangeDetection::Hash);
+        assert_eq!(config.index_concurrency, 2);
+        assert_eq!(config.max_concurrent_tools, 8);
+        assert_eq!(config.search_cache_capacity, 32);
+        assert_eq!(config.search_cache_ttl(), Duration::from_secs(60));
+
+        let backoff = config.backoff();
+        assert_eq!(backoff.max_tries, Some(3));
+        assert_eq!(backoff.initial_ms, 250);
+        assert_eq!(backoff.max_ms, 2000);
+
+        let breaker = config.circuit_breaker();
+        assert_eq!(breaker.failure_threshold, 3);
+        assert_eq!(breaker.cooldown, Duration::from_secs(10));
     }
 }
@@ -19,8 +19,8 @@ pub enum Error {
     #[error("Authentication error: {0}")]
     Auth(String),
 
-    #[error("Credentials not found: {0}")]
-    CredentialsNotFound(String),
+    #[error("Missing credentials: {0}")]
+    MissingCredentials(String),
 
     #[error("Blob too large: file exceeds maximum size of {max_size} bytes")]
     BlobTooLarge { max_size: usize },
@@ -31,6 +31,9 @@ pub enum Error {
     #[error("Indexing timeout: backend did not finish indexing within {seconds} seconds")]
     IndexingTimeout { seconds: u64 },
 
+    #[error("Backend unavailable: circuit breaker open, retry in {retry_after_secs} seconds")]
+    BackendUnavailable { retry_after_secs: u64 },
+
     // ===== MCP Errors =====
     #[error("MCP protocol error: {0}")]
     McpProtocol(String),
@@ -44,6 +47,9 @@ pub enum Error {
     #[error("Tool execution failed: {0}")]
     ToolExecutionFailed(String),
 
+    #[error("Server is read-only: '{0}' modifies state and is disabled")]
+    ReadOnly(String),
+
     // ===== Service Errors =====
     #[error("Workspace not found: {0}")]
     WorkspaceNotFound(String),
@@ -57,6 +63,16 @@ pub enum Error {
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+
+    // ===== Memory Errors =====
+    #[error("Invalid memory namespace: {0}")]
+    InvalidNamespace(String),
+
     // ===== Planning Errors =====
     #[error("Plan not found: {0}")]
     PlanNotFound(String),
@@ -64,15 +80,38 @@ pub enum Error {
     #[error("Plan already exists: {0}")]
     PlanAlreadyExists(String),
 
+    #[error("Plan {plan_id} has no version {version} (latest is {latest})")]
+    PlanVersionNotFound {
+        plan_id: String,
+        version: u32,
+        latest: u32,
+    },
+
     #[error("Step not found: step {0}")]
     StepNotFound(u32),
 
+    #[error("Invalid step transition: step {step} cannot go from {from} to {to}")]
+    InvalidStepTransition {
+        step: u32,
+        from: &'static str,
+        to: &'static str,
+    },
+
     #[error("Step blocked by dependencies: {0:?}")]
     StepBlocked(Vec<u32>),
 
+    #[error("Unknown dependency: step {step} depends on missing step {dependency}")]
+    UnknownDependency { step: u32, dependency: u32 },
+
+    #[error("Dependency cycle: {}", .0.iter().map(u32::to_string).collect::<Vec<_>>().join(" -> "))]
+    DependencyCycle(Vec<u32>),
+
     #[error("Approval required: {0}")]
     ApprovalRequired(String),
 
+    #[error("Approval request not found: {0}")]
+    ApprovalNotFound(String),
+
     // ===== Review Errors =====
     #[error("Invalid diff format: {0}")]
     InvalidDiffFormat(String),
@@ -80,9 +119,15 @@ pub enum Error {
     #[error("Review session not found: {0}")]
     ReviewSessionNotFound(String),
 
+    #[error("Review not found: {0}")]
+    ReviewNotFound(String),
+
     #[error("Invariant check failed: {0}")]
     InvariantCheckFailed(String),
 
+    #[error("Git command failed: {0}")]
+    Git(String),
+
     // ===== I/O Errors =====
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -124,18 +169,104 @@ impl Error {
         }
     }
 
-    /// Check if this error is retriable (transient failures).
-    pub fn is_retriable(&self) -> bool {
+    /// Classify an IO error on the workspace file `path`.
+    ///
+    /// Missing files and permission problems get their own variants so
+    /// callers can tell them apart from other IO failures.
+    pub fn file_io(path: impl Into<String>, error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => Self::FileNotFound(path.into()),
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied(path.into()),
+            _ => Self::Io(error),
+        }
+    }
+
+    /// Stable, machine-readable code for this error, used in tool results.
+    pub fn code(&self) -> &'static str {
         match self {
-            Self::Api { status, .. } => {
-                *status == 499 || *status == 503 || (*status >= 500 && *status < 600)
+            Self::Api { .. } => "api_error",
+            Self::Auth(_) => "auth_failed",
+            Self::MissingCredentials(_) => "missing_credentials",
+            Self::BlobTooLarge { .. } => "blob_too_large",
+            Self::IndexNotInitialized => "index_not_initialized",
+            Self::IndexingTimeout { .. } => "indexing_timeout",
+            Self::BackendUnavailable { .. } => "backend_unavailable",
+            Self::McpProtocol(_) => "protocol_error",
+            Self::ToolNotFound(_) => "tool_not_found",
+            Self::InvalidToolArguments(_) => "invalid_arguments",
+            Self::ToolExecutionFailed(_) => "tool_failed",
+            Self::ReadOnly(_) => "read_only",
+            Self::WorkspaceNotFound(_) => "workspace_not_found",
+            Self::FileNotFound(_) => "file_not_found",
+            Self::FileTooLarge { .. } => "file_too_large",
+            Self::InvalidPath(_) => "invalid_path",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::ResourceNotFound(_) => "resource_not_found",
+            Self::InvalidNamespace(_) => "invalid_namespace",
+            Self::PlanNotFound(_) => "plan_not_found",
+            Self::PlanVersionNotFound { .. } => "plan_version_not_found",
+            Self::PlanAlreadyExists(_) => "plan_exists",
+            Self::StepNotFound(_) => "step_not_found",
+            Self::InvalidStepTransition { .. } => "invalid_step_transition",
+            Self::StepBlocked(_) => "step_blocked",
+            Self::UnknownDependency { .. } => "unknown_dependency",
+            Self::DependencyCycle(_) => "dependency_cycle",
+            Self::ApprovalRequired(_) => "approval_required",
+            Self::ApprovalNotFound(_) => "approval_not_found",
+            Self::InvalidDiffFormat(_) => "invalid_diff",
+            Self::ReviewSessionNotFound(_) => "review_session_not_found",
+            Self::ReviewNotFound(_) => "review_not_found",
+            Self::InvariantCheckFailed(_) => "invariant_failed",
+            Self::Git(_) => "git_error",
+            Self::Io(_) => "io_error",
+            Self::Json(_) => "json_error",
+            Self::Yaml(_) => "yaml_error",
+            Self::Http(_) => "backend_unreachable",
+            Self::HttpServer(_) => "http_server_error",
+            Self::Internal(_) => "internal_error",
+            Self::Config(_) => "config_error",
+            Self::Timeout { .. } => "timeout",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Structured details for this error, if it carries any.
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::Api { status, .. } => Some(serde_json::json!({ "status": status })),
+            Self::FileTooLarge { path, size } => {
+                Some(serde_json::json!({ "path": path, "size": size }))
+            }
+            Self::BlobTooLarge { max_size } => Some(serde_json::json!({ "max_size": max_size })),
+            Self::Timeout { seconds } | Self::IndexingTimeout { seconds } => {
+                Some(serde_json::json!({ "seconds": seconds }))
+            }
+            Self::BackendUnavailable { retry_after_secs } => {
+                Some(serde_json::json!({ "retry_after_secs": retry_after_secs }))
+            }
+            Self::PlanVersionNotFound {
+                version, latest, ..
+            } => Some(serde_json::json!({ "version": version, "latest": latest })),
+            Self::StepNotFound(step) => Some(serde_json::json!({ "step": step })),
+            Self::InvalidStepTransition { step, from, to } => {
+                Some(serde_json::json!({ "step": step, "from": from, "to": to }))
+            }
+            Self::StepBlocked(steps) => Some(serde_json::json!({ "blocked_by": steps })),
+            Self::UnknownDependency { step, dependency } => {
+                Some(serde_json::json!({ "step": step, "dependency": dependency }))
             }
-            Self::Http(e) => e.is_timeout() || e.is_connect(),
-            Self::Timeout { .. } => true,
-            _ => false,
+            Self::DependencyCycle(cycle) => Some(serde_json::json!({ "cycle": cycle })),
+            _ => None,
         }
     }
 
+    /// Check if this error is retriable (transient failures).
+    ///
+    /// See [`crate::sdk::retry::is_retryable`].
+    pub fn is_retriable(&self) -> bool {
+        crate::sdk::retry::is_retryable(self)
+    }
+
     /// Check if this error is a chat-specific retriable error.
     pub fn is_chat_retriable(&self) -> bool {
         match self {
@@ -166,13 +297,27 @@ mod tests {
         let auth_err = Error::Auth("Invalid token".to_string());
         assert_eq!(auth_err.to_string(), "Authentication error: Invalid token");
 
-        let creds_err = Error::CredentialsNotFound("~/.augment/session.json".to_string());
+        let creds_err = Error::MissingCredentials("~/.augment/session.json".to_string());
         assert_eq!(
             creds_err.to_string(),
-            "Credentials not found: ~/.augment/session.json"
+            "Missing credentials: ~/.augment/session.json"
         );
     }
 
+    #[test]
+    fn test_error_code_and_details() {
+        assert_eq!(
+            Error::FileNotFound("a.rs".to_string()).code(),
+            "file_not_found"
+        );
+        assert_eq!(Error::api(503, "Unavailable", "").code(), "api_error");
+        assert_eq!(
+            Error::api(503, "Unavailable", "").details(),
+            Some(serde_json::json!({ "status": 503 }))
+        );
+        assert!(Error::Cancelled.details().is_none());
+    }
+
     #[test]
     fn test_error_is_retriable() {
         // API errors
@@ -259,6 +404,20 @@ mod tests {
             size: 10_000_000,
         };
         assert!(file_too_large.to_string().contains("10000000 bytes"));
+
+        let kinds = [
+            (std::io::ErrorKind::NotFound, "file_not_found"),
+            (std::io::ErrorKind::PermissionDenied, "permission_denied"),
+            (std::io::ErrorKind::InvalidData, "io_error"),
+        ];
+        for (kind, code) in kinds {
+            let error = Error::file_io("src/lib.rs", std::io::Error::from(kind));
+            assert_eq!(error.code(), code);
+        }
+        assert_eq!(
+            Error::file_io("secret.txt", std::io::ErrorKind::PermissionDenied.into()).to_string(),
+            "Permission denied: secret.txt"
+        );
     }
 
     #[test]
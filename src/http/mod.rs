@@ -2,62 +2,156 @@
 //!
 //! Provides an alternative to stdio transport for web-based clients.
 
+pub mod rate_limit;
+
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{rejection::JsonRejection, DefaultBodyLimit, Query, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
+use futures::StreamExt;
 use serde::Deserialize;
+use std::convert::Infallible;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::Config;
 use crate::error::Result;
-use crate::mcp::handler::McpHandler;
+use crate::mcp::handler::{tool_error_result, McpHandler};
+use crate::mcp::progress::ProgressReporter;
 use crate::mcp::protocol::*;
+use crate::mcp::server::shutdown_signal;
+use crate::mcp::transport::Message;
+use crate::service::ContextService;
+use rate_limit::RateLimiter;
+
+/// How long shutdown waits for in-flight requests before exiting anyway.
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
 /// HTTP server state.
 #[derive(Clone)]
 pub struct HttpState {
     handler: Arc<McpHandler>,
+    context_service: Arc<ContextService>,
     server_info: ServerInfo,
+    /// Most entries accepted in a tool call's `arguments`
+    max_tool_arguments: usize,
 }
 
 /// Start the HTTP server.
-pub async fn start_server(config: &Config, handler: Arc<McpHandler>) -> Result<()> {
+pub async fn start_server(
+    config: &Config,
+    handler: Arc<McpHandler>,
+    context_service: Arc<ContextService>,
+) -> Result<()> {
+    let app = router(config, handler, context_service);
+
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!("Starting HTTP server on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    // Stop accepting connections on SIGINT/SIGTERM, then give in-flight
+    // requests a bounded amount of time to finish.
+    let shutdown = Arc::new(tokio::sync::Notify::new());
+    // Connection info gives the rate limiter each client's address
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            shutdown.notify_one();
+        }
+    });
+    let drain_deadline = async {
+        shutdown.notified().await;
+        tokio::time::sleep(SHUTDOWN_TIMEOUT).await;
+    };
+
+    tokio::select! {
+        result = server => result?,
+        _ = drain_deadline => {
+            warn!("Timed out waiting for in-flight HTTP requests after {:?}", SHUTDOWN_TIMEOUT);
+        }
+    }
+
+    info!("HTTP server stopped");
+    Ok(())
+}
+
+/// Build the HTTP router.
+pub fn router(
+    config: &Config,
+    handler: Arc<McpHandler>,
+    context_service: Arc<ContextService>,
+) -> Router {
     let state = HttpState {
         handler,
+        context_service,
         server_info: ServerInfo {
             name: "context-engine".to_string(),
             version: crate::VERSION.to_string(),
         },
+        max_tool_arguments: config.max_tool_arguments,
     };
 
-    let app = Router::new()
-        .route("/health", get(health_check))
+    let mut mcp = Router::new()
         .route("/mcp/initialize", post(initialize))
         .route("/mcp/tools/list", get(list_tools))
-        .route("/mcp/tools/call", post(call_tool))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .route("/mcp/tools/call", post(call_tool));
+    if let Some(limiter) = RateLimiter::from_config(config) {
+        mcp = mcp.route_layer(middleware::from_fn_with_state(
+            Arc::new(limiter),
+            rate_limit::rate_limit,
+        ));
+    }
+
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/livez", get(liveness))
+        .route("/readyz", get(readiness))
+        .merge(mcp)
+        .layer(DefaultBodyLimit::max(config.max_body_size))
+        .layer(cors_layer(&config.allowed_origins))
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .with_state(state)
+}
 
-    let addr = format!("0.0.0.0:{}", config.port);
-    info!("Starting HTTP server on {}", addr);
+/// Build the CORS layer for the configured origins.
+///
+/// An empty list sends no CORS headers, so browsers only allow same-origin
+/// requests; `*` allows any origin.
+fn cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    if allowed_origins.iter().any(|origin| origin == "*") {
+        return cors.allow_origin(Any);
+    }
 
-    Ok(())
+    let origins: Vec<HeaderValue> = allowed_origins
+        .iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                warn!("Ignoring invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect();
+
+    cors.allow_origin(AllowOrigin::list(origins))
 }
 
 /// Health check endpoint.
@@ -68,6 +162,47 @@ async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// Liveness probe: the process is up and serving requests.
+async fn liveness() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Readiness probe: the workspace has been indexed once and the backend
+/// answers a ping.
+async fn readiness(State(state): State<HttpState>) -> impl IntoResponse {
+    let service = &state.context_service;
+    if !service.is_ready() {
+        let status = service.status().await;
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "status": "not_ready",
+                "reason": "initial index not complete",
+                "file_count": status.file_count
+            })),
+        );
+    }
+
+    if let Err(e) = service.ping().await {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "status": "not_ready",
+                "reason": format!("backend unreachable: {}", e)
+            })),
+        );
+    }
+
+    let status = service.status().await;
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "ready",
+            "file_count": status.file_count
+        })),
+    )
+}
+
 /// Initialize endpoint.
 async fn initialize(State(state): State<HttpState>) -> impl IntoResponse {
     let result = InitializeResult {
@@ -77,6 +212,7 @@ async fn initialize(State(state): State<HttpState>) -> impl IntoResponse {
             resources: None,
             prompts: None,
             logging: Some(LoggingCapability {}),
+            completions: None,
         },
         server_info: state.server_info,
     };
@@ -98,11 +234,57 @@ struct CallToolRequest {
     arguments: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// Query parameters of the call tool endpoint.
+#[derive(Debug, Default, Deserialize)]
+struct CallToolQuery {
+    /// Stream progress and the result as server-sent events
+    #[serde(default)]
+    stream: bool,
+}
+
 /// Call tool endpoint.
+///
+/// With `?stream=true` the response is an SSE stream instead: `progress`
+/// events carrying `notifications/progress` params, then one `result` event
+/// with the tool result. Events are correlated by a generated call id, which
+/// is also the progress token and the SSE event id.
+///
+/// Bodies over the configured size limit get `413`; bodies that aren't a
+/// valid call, or whose `arguments` have too many entries, get `400`.
 async fn call_tool(
     State(state): State<HttpState>,
-    Json(req): Json<CallToolRequest>,
-) -> impl IntoResponse {
+    Query(query): Query<CallToolQuery>,
+    body: std::result::Result<Json<CallToolRequest>, JsonRejection>,
+) -> Response {
+    let req = match body {
+        Ok(Json(req)) => req,
+        Err(rejection) => {
+            let status = match rejection.status() {
+                StatusCode::PAYLOAD_TOO_LARGE => StatusCode::PAYLOAD_TOO_LARGE,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            return (
+                status,
+                Json(serde_json::json!({ "error": rejection.body_text() })),
+            )
+                .into_response();
+        }
+    };
+    if req.arguments.len() > state.max_tool_arguments {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!(
+                    "Too many arguments: {} (at most {})",
+                    req.arguments.len(),
+                    state.max_tool_arguments
+                )
+            })),
+        )
+            .into_response();
+    }
+
     let handler = match state.handler.get_tool(&req.name) {
         Some(h) => h,
         None => {
@@ -111,17 +293,417 @@ async fn call_tool(
                 Json(serde_json::json!({
                     "error": format!("Tool not found: {}", req.name)
                 })),
-            );
+            )
+                .into_response();
         }
     };
 
+    if let Err(e) = state.handler.validate_arguments(&req.name, &req.arguments) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = state.handler.check_writable(&req.name) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response();
+    }
+
+    if query.stream {
+        return stream_tool_call(state.handler, handler, req.arguments).into_response();
+    }
+
+    let _slot = state.handler.acquire_execution_slot().await;
     match handler.execute(req.arguments).await {
-        Ok(result) => (StatusCode::OK, Json(serde_json::to_value(result).unwrap())),
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({
                 "error": e.to_string()
             })),
-        ),
+        )
+            .into_response(),
+    }
+}
+
+/// Run a tool in the background, streaming its progress and result.
+fn stream_tool_call(
+    registry: Arc<McpHandler>,
+    handler: Arc<dyn crate::mcp::handler::ToolHandler>,
+    arguments: std::collections::HashMap<String, serde_json::Value>,
+) -> Sse<impl futures::Stream<Item = std::result::Result<Event, Infallible>>> {
+    let call_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel(64);
+
+    let progress = ProgressReporter::new(serde_json::json!(call_id), tx.clone());
+    let id = call_id.clone();
+    tokio::spawn(async move {
+        let _slot = registry.acquire_execution_slot().await;
+        // Execution failures are reported in the result, as over stdio
+        let result = match handler
+            .execute_with_progress(arguments, Some(progress))
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => tool_error_result("", &e),
+        };
+        let response = JsonRpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: RequestId::String(id),
+            result: serde_json::to_value(result).ok(),
+            error: None,
+        };
+        let _ = tx.send(Message::Response(response)).await;
+    });
+
+    let events = ReceiverStream::new(rx).filter_map(move |msg| {
+        let event = match msg {
+            Message::Notification(n) => Event::default()
+                .event("progress")
+                .json_data(n.params.unwrap_or_default()),
+            Message::Response(r) => Event::default()
+                .event("result")
+                .json_data(r.result.unwrap_or_default()),
+            Message::Request(_) => return std::future::ready(None),
+        };
+        std::future::ready(event.ok().map(|e| Ok(e.id(call_id.clone()))))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn test_router(allowed_origins: &[&str]) -> Router {
+        let config = Config {
+            allowed_origins: allowed_origins.iter().map(|s| s.to_string()).collect(),
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        router(&config, Arc::new(McpHandler::new()), service)
+    }
+
+    fn get_request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/mcp/tools/call")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_configured_origin() {
+        let app = test_router(&["https://app.example.com"]).await;
+        let response = app
+            .oneshot(preflight("https://app.example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some(&HeaderValue::from_static("https://app.example.com"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cors_rejects_other_origin() {
+        let app = test_router(&["https://app.example.com"]).await;
+        let response = app
+            .oneshot(preflight("https://evil.example.com"))
+            .await
+            .unwrap();
+
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_empty_list_is_same_origin_only() {
+        let app = test_router(&[]).await;
+        let response = app
+            .oneshot(preflight("https://app.example.com"))
+            .await
+            .unwrap();
+
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_wildcard_allows_any() {
+        let app = test_router(&["*"]).await;
+        let response = app
+            .oneshot(preflight("https://any.example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some(&HeaderValue::from_static("*"))
+        );
+    }
+
+    struct EchoTool;
+
+    #[async_trait::async_trait]
+    impl crate::mcp::handler::ToolHandler for EchoTool {
+        fn definition(&self) -> Tool {
+            Tool {
+                name: "echo".to_string(),
+                description: "Echo the text argument".to_string(),
+                input_schema: serde_json::json!({ "type": "object" }),
+            }
+        }
+
+        async fn execute(
+            &self,
+            args: std::collections::HashMap<String, serde_json::Value>,
+        ) -> Result<ToolResult> {
+            let text = crate::mcp::handler::get_optional_string_arg(&args, "text");
+            Ok(crate::mcp::handler::success_result(
+                text.unwrap_or_default(),
+            ))
+        }
+    }
+
+    async fn echo_router(max_body_size: usize) -> Router {
+        let config = Config {
+            max_body_size,
+            max_tool_arguments: 2,
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let mut handler = McpHandler::new();
+        handler.register(EchoTool);
+        router(&config, Arc::new(handler), service)
+    }
+
+    fn call_request(body: impl Into<Body>) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/mcp/tools/call")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.into())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_call_body_limits() {
+        let app = echo_router(1024).await;
+        let normal = app
+            .clone()
+            .oneshot(call_request(
+                r#"{"name": "echo", "arguments": {"text": "hi"}}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(normal.status(), StatusCode::OK);
+
+        let oversized = format!(
+            r#"{{"name": "echo", "arguments": {{"text": "{}"}}}}"#,
+            "x".repeat(2048)
+        );
+        let response = app.clone().oneshot(call_request(oversized)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let invalid = app
+            .clone()
+            .oneshot(call_request(r#"{"arguments": {}}"#))
+            .await
+            .unwrap();
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+        let crowded = app
+            .oneshot(call_request(
+                r#"{"name": "echo", "arguments": {"a": 1, "b": 2, "c": 3}}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(crowded.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(crowded.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Too many arguments: 3"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_returns_429_with_retry_after() {
+        let config = Config {
+            rate_limit_rps: 0.5,
+            rate_limit_burst: 2,
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let app = router(&config, Arc::new(McpHandler::new()), service);
+        let list = |ip: [u8; 4], token: &str| {
+            Request::builder()
+                .uri("/mcp/tools/list")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .extension(axum::extract::ConnectInfo(std::net::SocketAddr::from((
+                    ip, 40000,
+                ))))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let alice = [10, 0, 0, 1];
+
+        // A new bearer token per request doesn't get a new bucket
+        for token in ["t1", "t2"] {
+            let response = app.clone().oneshot(list(alice, token)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let limited = app.clone().oneshot(list(alice, "t3")).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "2");
+
+        // Other clients and non-MCP routes are unaffected
+        let other = app
+            .clone()
+            .oneshot(list([10, 0, 0, 2], "t1"))
+            .await
+            .unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+        let health = app.oneshot(get_request("/livez")).await.unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_livez_always_ok() {
+        let app = test_router(&[]).await;
+        let response = app.oneshot(get_request("/livez")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_not_ready_before_initialize() {
+        let app = test_router(&[]).await;
+        let response = app.oneshot(get_request("/readyz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_waits_for_first_index() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+        let config = backend.config(temp_dir.path());
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let app = router(&config, Arc::new(McpHandler::new()), service.clone());
+
+        service.initialize().await.unwrap();
+        let response = app.clone().oneshot(get_request("/readyz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "not_ready");
+        assert_eq!(body["file_count"], 0);
+
+        service.index_workspace().await.unwrap();
+        let pings = backend.requests("find-missing").len();
+        let response = app.oneshot(get_request("/readyz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["file_count"], 1);
+        assert_eq!(backend.requests("find-missing").len(), pings + 1);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_call_sends_progress_before_result() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        backend.set_delay("batch-upload", std::time::Duration::from_millis(50));
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..200 {
+            std::fs::write(
+                temp_dir.path().join(format!("file_{}.rs", i)),
+                format!("fn f{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+        let config = Config {
+            index_batch_size: 10,
+            index_concurrency: 1,
+            ..backend.config(temp_dir.path())
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let mut handler = McpHandler::new();
+        handler.register(crate::tools::index::IndexWorkspaceTool::new(
+            service.clone(),
+        ));
+        let app = router(&config, Arc::new(handler), service);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/mcp/tools/call?stream=true")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                r#"{"name": "index_workspace", "arguments": {}}"#,
+            ))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<(&str, &str, serde_json::Value)> = body
+            .split("\n\n")
+            .filter(|block| block.contains("event:"))
+            .map(|block| {
+                let field = |name: &str| {
+                    block
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .unwrap()
+                        .trim()
+                };
+                (
+                    field("event:"),
+                    field("id:"),
+                    serde_json::from_str(field("data:")).unwrap(),
+                )
+            })
+            .collect();
+
+        let (last, progress) = events.split_last().unwrap();
+        assert_eq!(last.0, "result");
+        assert_eq!(last.2["is_error"], false);
+        assert!(!progress.is_empty());
+        let call_id = last.1;
+        for (event, id, data) in progress {
+            assert_eq!(*event, "progress");
+            assert_eq!(*id, call_id);
+            assert_eq!(data["progressToken"], call_id);
+            assert_eq!(data["total"], 200);
+        }
     }
 }
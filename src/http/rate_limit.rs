@@ -0,0 +1,222 @@
+//! Per-client rate limiting for the HTTP transport.
+//!
+//! Each client gets a token bucket that holds up to `burst` tokens and
+//! refills at `rps` tokens a second; a request spends one token. Clients are
+//! told apart by IP address: bearer tokens are not checked by this server,
+//! so keying on them would let a client skip its limit by sending a new
+//! token with every request. A request finding its bucket empty gets `429
+//! Too Many Requests` with a `Retry-After` header saying when a token will
+//! be back.
+//!
+//! A bucket left alone long enough to refill is the same as a new one, so
+//! such idle buckets are swept out periodically. If the map still reaches
+//! [`MAX_BUCKETS`], a new client evicts the one seen least recently.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// Most client buckets kept at once.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Shortest time between sweeps of idle buckets.
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets shared by every request, keyed by client.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rps: f64,
+    burst: f64,
+    capacity: usize,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_client: HashMap<String, Bucket>,
+    last_sweep: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// A limiter refilling `rps` tokens a second into buckets of `burst`.
+    pub fn new(rps: f64, burst: u32) -> Self {
+        Self {
+            rps,
+            burst: burst.max(1) as f64,
+            capacity: MAX_BUCKETS,
+            buckets: Mutex::new(Buckets {
+                by_client: HashMap::new(),
+                last_sweep: None,
+            }),
+        }
+    }
+
+    /// The limiter configured in `config`, or `None` if limiting is off.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        (config.rate_limit_rps > 0.0)
+            .then(|| Self::new(config.rate_limit_rps, config.rate_limit_burst))
+    }
+
+    /// Spend a token of `client` at `now`.
+    ///
+    /// Fails with how long until the bucket holds a token again.
+    pub fn check_at(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let idle = self.refill_time().max(MIN_SWEEP_INTERVAL);
+        if buckets
+            .last_sweep
+            .is_none_or(|last| now.saturating_duration_since(last) >= idle)
+        {
+            buckets
+                .by_client
+                .retain(|_, bucket| now.saturating_duration_since(bucket.updated) < idle);
+            buckets.last_sweep = Some(now);
+        }
+        if buckets.by_client.len() >= self.capacity && !buckets.by_client.contains_key(client) {
+            let oldest = buckets
+                .by_client
+                .iter()
+                .min_by_key(|(_, bucket)| bucket.updated)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                buckets.by_client.remove(&oldest);
+            }
+        }
+
+        let bucket = buckets
+            .by_client
+            .entry(client.to_string())
+            .or_insert(Bucket {
+                tokens: self.burst,
+                updated: now,
+            });
+        bucket.tokens = self.refilled(*bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rps))
+        }
+    }
+
+    /// How long an empty bucket takes to fill up.
+    fn refill_time(&self) -> Duration {
+        Duration::from_secs_f64(self.burst / self.rps)
+    }
+
+    /// Tokens in `bucket` at `now`, counting the refill since its update.
+    fn refilled(&self, bucket: Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.rps).min(self.burst)
+    }
+}
+
+/// The key identifying the client that sent `request`: its peer address.
+fn client_key(request: &Request) -> String {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Middleware rejecting requests from clients over their rate.
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = client_key(&request);
+    match limiter.check_at(&client, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(serde_json::json!({
+                    "error": "Rate limit exceeded",
+                    "retry_after_secs": retry_after
+                })),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_refill_per_client() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check_at("a", start).is_ok());
+        }
+        assert_eq!(
+            limiter.check_at("a", start),
+            Err(Duration::from_millis(500))
+        );
+        assert!(limiter.check_at("b", start).is_ok());
+
+        // Half a second refills one token at 2 per second
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check_at("a", later).is_ok());
+        assert!(limiter.check_at("a", later).is_err());
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evicted() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let start = Instant::now();
+        for client in 0..100 {
+            limiter.check_at(&client.to_string(), start).unwrap();
+        }
+        assert_eq!(limiter.buckets.lock().unwrap().by_client.len(), 100);
+
+        // After a full refill (1.5s here) the old buckets are swept
+        let later = start + Duration::from_secs(2);
+        limiter.check_at("new", later).unwrap();
+        assert_eq!(limiter.buckets.lock().unwrap().by_client.len(), 1);
+    }
+
+    #[test]
+    fn test_full_map_evicts_least_recently_seen() {
+        let mut limiter = RateLimiter::new(1.0, 1);
+        limiter.capacity = 2;
+        let start = Instant::now();
+        limiter.check_at("a", start).unwrap();
+        limiter
+            .check_at("b", start + Duration::from_millis(10))
+            .unwrap();
+        limiter
+            .check_at("c", start + Duration::from_millis(20))
+            .unwrap();
+
+        let buckets = limiter.buckets.lock().unwrap();
+        let mut clients: Vec<&String> = buckets.by_client.keys().collect();
+        clients.sort();
+        assert_eq!(clients, ["b", "c"]);
+    }
+}
@@ -22,9 +22,11 @@
 //! - **Memory**: Persistent memory storage for agent context
 //! - **Reactive Review**: Session-based PR reviews with parallel execution
 
+pub mod clock;
 pub mod config;
 pub mod error;
 pub mod http;
+pub mod logging;
 pub mod mcp;
 pub mod metrics;
 pub mod reactive;
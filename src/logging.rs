@@ -0,0 +1,119 @@
+//! Log subscriber setup.
+//!
+//! Logs go to stderr (stdout carries the stdio transport). The text format
+//! is for people; the JSON format writes one object per line for log
+//! pipelines:
+//!
+//! ```json
+//! {"timestamp":"...","level":"INFO","target":"context_engine_rs::mcp::server","message":"...","span":{"name":"request","method":"tools/call","id":"7"}}
+//! ```
+//!
+//! `span` is the innermost active span, so a log emitted while handling a
+//! request carries that request's id.
+
+use tracing::{Level, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::FmtSubscriber;
+
+use crate::config::LogFormat;
+
+/// Build a subscriber writing `format` logs at `level` and above to `writer`.
+pub fn subscriber<W>(
+    format: LogFormat,
+    level: Level,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = FmtSubscriber::builder()
+        .with_max_level(level)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .flatten_event(true)
+                .with_current_span(true)
+                .with_span_list(false)
+                .finish(),
+        ),
+    }
+}
+
+/// Install the global subscriber, logging to stderr.
+pub fn init(format: LogFormat, debug: bool) {
+    let level = if debug { Level::DEBUG } else { Level::INFO };
+    tracing::subscriber::set_global_default(subscriber(format, level, std::io::stderr))
+        .expect("Failed to set tracing subscriber");
+}
+
+/// In-memory log sink for tests.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LogCapture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl LogCapture {
+    /// A JSON subscriber writing into this capture.
+    pub(crate) fn json_subscriber(&self) -> Box<dyn Subscriber + Send + Sync> {
+        subscriber(LogFormat::Json, Level::DEBUG, self.clone())
+    }
+
+    /// Captured log lines parsed as JSON.
+    pub(crate) fn json_lines(&self) -> Vec<serde_json::Value> {
+        let bytes = self.0.lock().unwrap();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("log line is JSON"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for LogCapture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl<'w> MakeWriter<'w> for LogCapture {
+    type Writer = Self;
+
+    fn make_writer(&'w self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_have_expected_fields() {
+        let capture = LogCapture::default();
+        tracing::subscriber::with_default(capture.json_subscriber(), || {
+            let span = tracing::info_span!("request", method = "tools/call", id = "7");
+            let _entered = span.enter();
+            tracing::info!(tool = "codebase_retrieval", "Calling tool");
+        });
+
+        let lines = capture.json_lines();
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert!(line["timestamp"].is_string());
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], "context_engine_rs::logging::tests");
+        assert_eq!(line["message"], "Calling tool");
+        assert_eq!(line["tool"], "codebase_retrieval");
+        assert_eq!(line["span"]["name"], "request");
+        assert_eq!(line["span"]["id"], "7");
+    }
+}
@@ -5,14 +5,14 @@
 
 use clap::Parser;
 use std::sync::Arc;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{error, info};
 
 use context_engine_rs::config::{Args, Config, Transport};
 use context_engine_rs::error::Result;
 use context_engine_rs::mcp::handler::McpHandler;
 use context_engine_rs::mcp::server::McpServer;
 use context_engine_rs::mcp::transport::StdioTransport;
+use context_engine_rs::sdk::resolve_credentials;
 use context_engine_rs::service::{ContextService, MemoryService, PlanningService};
 use context_engine_rs::tools;
 use context_engine_rs::VERSION;
@@ -22,18 +22,7 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Initialize logging
-    let log_level = if args.debug {
-        Level::DEBUG
-    } else {
-        Level::INFO
-    };
-
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_writer(std::io::stderr)
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
+    context_engine_rs::logging::init(args.log_format, args.debug);
 
     // Build configuration from args
     let config: Config = args.into();
@@ -42,19 +31,21 @@ async fn main() -> Result<()> {
     info!("Workspace: {:?}", config.workspace);
     info!("Transport: {:?}", config.transport);
 
+    // Fail fast with guidance instead of at the first backend call
+    resolve_credentials(config.api_key.as_deref(), config.api_url.as_deref()).await?;
+
     // Initialize services
     let context_service = Arc::new(ContextService::new(&config).await?);
     let memory_service = Arc::new(MemoryService::new(&config.workspace).await?);
     let planning_service = Arc::new(PlanningService::new(&config.workspace).await?);
 
-    // Initialize the context index
-    info!("Initializing codebase index...");
-    context_service.initialize().await?;
-    let status = context_service.status().await;
-    info!("Index ready: {} files indexed", status.file_count);
-
     // Create MCP handler and register tools
     let mut handler = McpHandler::new();
+    handler.set_max_concurrent_tools(config.max_concurrent_tools);
+    handler.set_read_only(config.read_only);
+    if config.read_only {
+        info!("Read-only mode: tools that write to disk are refused");
+    }
     tools::register_all_tools(
         &mut handler,
         context_service.clone(),
@@ -66,15 +57,34 @@ async fn main() -> Result<()> {
     // Start the server based on transport mode
     match config.transport {
         Transport::Stdio => {
+            info!("Initializing codebase index...");
+            context_service.initialize().await?;
+            let status = context_service.status().await;
+            info!("Index ready: {} files indexed", status.file_count);
+
             info!("Starting stdio transport...");
-            let server = McpServer::new(handler, "context-engine");
+            let server = McpServer::new(handler, "context-engine")
+                .with_context(context_service.clone())
+                .with_idle_timeout(config.idle_timeout())
+                .with_keepalive(config.keepalive_interval());
             let transport = StdioTransport::new();
             server.run(transport).await?;
         }
         Transport::Http => {
+            // Serve probes while the first index runs; /readyz reports
+            // ready once it completes.
+            let service = context_service.clone();
+            tokio::spawn(async move {
+                info!("Indexing workspace in the background...");
+                match service.index_workspace().await {
+                    Ok(result) => info!("Index ready: {} files indexed", result.indexed),
+                    Err(e) => error!("Initial indexing failed: {}", e),
+                }
+            });
+
             info!("Starting HTTP transport on port {}...", config.port);
             let handler = Arc::new(handler);
-            context_engine_rs::http::start_server(&config, handler).await?;
+            context_engine_rs::http::start_server(&config, handler, context_service).await?;
         }
     }
 
@@ -0,0 +1,128 @@
+//! Argument completion for `completion/complete`.
+
+use crate::mcp::handler::McpHandler;
+use crate::mcp::protocol::{CompleteParams, Completion};
+use crate::service::ContextService;
+
+/// Maximum number of values returned by a completer.
+pub const MAX_COMPLETIONS: usize = 20;
+
+/// Compute completions for a request.
+///
+/// Arguments named `path` complete workspace paths when a workspace is
+/// attached. Otherwise tool names are completed for `ref/tool` references and
+/// for any argument named `tool`. Other references yield no values.
+pub async fn complete(
+    handler: &McpHandler,
+    context: Option<&ContextService>,
+    params: &CompleteParams,
+) -> Completion {
+    let argument = &params.argument;
+    if let (Some(context), "path") = (context, argument.name.as_str()) {
+        return complete_file_path(context, &argument.value).await;
+    }
+    if params.reference.ref_type == "ref/tool" || argument.name == "tool" {
+        return complete_tool_name(handler, &argument.value);
+    }
+    Completion::default()
+}
+
+/// Workspace paths starting with `prefix`, skipping excluded entries.
+///
+/// Prefixes that escape the workspace complete to nothing.
+pub async fn complete_file_path(context: &ContextService, prefix: &str) -> Completion {
+    let paths = context.complete_path(prefix).await.unwrap_or_default();
+    Completion::capped(paths, MAX_COMPLETIONS)
+}
+
+/// Registered tool names starting with `prefix`, sorted.
+pub fn complete_tool_name(handler: &McpHandler, prefix: &str) -> Completion {
+    let mut names: Vec<String> = handler
+        .list_tools()
+        .into_iter()
+        .map(|tool| tool.name)
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort();
+    Completion::capped(names, MAX_COMPLETIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::handler::{success_result, ToolHandler};
+    use crate::mcp::protocol::{Tool, ToolResult};
+    use async_trait::async_trait;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    struct NamedTool(&'static str);
+
+    #[async_trait]
+    impl ToolHandler for NamedTool {
+        fn definition(&self) -> Tool {
+            Tool {
+                name: self.0.to_string(),
+                description: String::new(),
+                input_schema: serde_json::json!({ "type": "object" }),
+            }
+        }
+
+        async fn execute(&self, _args: HashMap<String, Value>) -> crate::error::Result<ToolResult> {
+            Ok(success_result(""))
+        }
+    }
+
+    fn params(value: serde_json::Value) -> CompleteParams {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_complete_tool_names_by_prefix() {
+        let mut handler = McpHandler::new();
+        for name in ["review_diff", "get_file", "review_auto", "reindex_file"] {
+            handler.register(NamedTool(name));
+        }
+
+        let completion = complete(
+            &handler,
+            None,
+            &params(serde_json::json!({
+                "ref": { "type": "ref/tool" },
+                "argument": { "name": "name", "value": "rev" }
+            })),
+        )
+        .await;
+        assert_eq!(completion.values, vec!["review_auto", "review_diff"]);
+        assert_eq!(completion.total, Some(2));
+        assert!(!completion.has_more);
+
+        // An argument named `tool` completes tool names on any reference
+        let completion = complete(
+            &handler,
+            None,
+            &params(serde_json::json!({
+                "ref": { "type": "ref/prompt", "name": "explain_symbol" },
+                "argument": { "name": "tool", "value": "re" }
+            })),
+        )
+        .await;
+        assert_eq!(completion.values.len(), 3);
+    }
+
+    #[test]
+    fn test_completion_is_capped() {
+        let mut handler = McpHandler::new();
+        let names: Vec<&'static str> = (0..30)
+            .map(|i| &*Box::leak(format!("tool_{:02}", i).into_boxed_str()))
+            .collect();
+        for name in names {
+            handler.register(NamedTool(name));
+        }
+
+        let completion = complete_tool_name(&handler, "tool_");
+        assert_eq!(completion.values.len(), MAX_COMPLETIONS);
+        assert_eq!(completion.total, Some(30));
+        assert!(completion.has_more);
+    }
+}
@@ -1,12 +1,18 @@
 //! MCP request and notification handlers.
 
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
 
-use crate::error::Result;
-use crate::mcp::protocol::{Tool, ToolResult};
+use crate::error::{Error, Result};
+use crate::mcp::progress::ProgressReporter;
+use crate::mcp::protocol::{ContentBlock, Tool, ToolError, ToolResult};
 
 /// Handler for MCP tool calls.
 #[async_trait]
@@ -16,11 +22,44 @@ pub trait ToolHandler: Send + Sync {
 
     /// Execute the tool with the given arguments.
     async fn execute(&self, arguments: HashMap<String, Value>) -> Result<ToolResult>;
+
+    /// Execute the tool, reporting progress if the client asked for it.
+    ///
+    /// Tools that run long enough to be worth reporting on override this;
+    /// the default ignores `progress` and calls [`execute`](Self::execute).
+    async fn execute_with_progress(
+        &self,
+        arguments: HashMap<String, Value>,
+        progress: Option<ProgressReporter>,
+    ) -> Result<ToolResult> {
+        let _ = progress;
+        self.execute(arguments).await
+    }
+
+    /// Whether the registry validates arguments against
+    /// [`input_schema`](Tool::input_schema) before calling the tool.
+    ///
+    /// Tools whose schema is descriptive rather than exact can opt out and
+    /// check their arguments themselves.
+    fn validates_arguments(&self) -> bool {
+        true
+    }
+
+    /// Whether the tool writes to disk (plans, memories, the index), and so
+    /// is refused when the server is read-only.
+    fn is_mutating(&self) -> bool {
+        false
+    }
 }
 
 /// Registry of tool handlers.
 pub struct McpHandler {
     tools: HashMap<String, Arc<dyn ToolHandler>>,
+    categories: HashMap<String, &'static str>,
+    validators: HashMap<String, Arc<jsonschema::Validator>>,
+    execution_slots: Option<Arc<Semaphore>>,
+    queued: Arc<AtomicUsize>,
+    read_only: bool,
 }
 
 impl McpHandler {
@@ -28,19 +67,120 @@ impl McpHandler {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            categories: HashMap::new(),
+            validators: HashMap::new(),
+            execution_slots: None,
+            queued: Arc::new(AtomicUsize::new(0)),
+            read_only: false,
+        }
+    }
+
+    /// Limit how many tool calls execute at once (0 = no limit).
+    pub fn set_max_concurrent_tools(&mut self, max: usize) {
+        self.execution_slots = (max > 0).then(|| Arc::new(Semaphore::new(max)));
+    }
+
+    /// Refuse calls to mutating tools (see [`ToolHandler::is_mutating`]).
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Whether mutating tools are refused.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Check that tool `name` may run, failing with [`Error::ReadOnly`] if
+    /// it is mutating and the server is read-only.
+    pub fn check_writable(&self, name: &str) -> Result<()> {
+        match self.tools.get(name) {
+            Some(tool) if self.read_only && tool.is_mutating() => {
+                Err(Error::ReadOnly(name.to_string()))
+            }
+            _ => Ok(()),
         }
     }
 
+    /// Wait for a free execution slot; the call runs while the permit is held.
+    ///
+    /// Callers waiting for a slot are published as the
+    /// `context_engine_tool_calls_queued` gauge. Returns `None` when
+    /// concurrency is unlimited.
+    pub async fn acquire_execution_slot(&self) -> Option<OwnedSemaphorePermit> {
+        let slots = self.execution_slots.clone()?;
+        if let Ok(permit) = slots.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+        let _queued = QueuedCall::new(self.queued.clone());
+        slots.acquire_owned().await.ok()
+    }
+
+    /// Number of tool calls waiting for an execution slot.
+    pub fn queued_tool_calls(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
     /// Register a tool handler.
     pub fn register<T: ToolHandler + 'static>(&mut self, handler: T) {
-        let tool = handler.definition();
-        self.tools.insert(tool.name.clone(), Arc::new(handler));
+        self.register_arc(Arc::new(handler));
     }
 
     /// Register a tool handler (Arc version).
+    ///
+    /// The tool's input schema is compiled here; a schema that doesn't
+    /// compile is logged and the tool's arguments go unvalidated.
     pub fn register_arc(&mut self, handler: Arc<dyn ToolHandler>) {
         let tool = handler.definition();
-        self.tools.insert(tool.name.clone(), handler);
+        self.validators.remove(&tool.name);
+        if handler.validates_arguments() {
+            match jsonschema::validator_for(&tool.input_schema) {
+                Ok(validator) => {
+                    self.validators
+                        .insert(tool.name.clone(), Arc::new(validator));
+                }
+                Err(e) => warn!("Not validating arguments of '{}': {}", tool.name, e),
+            }
+        }
+        self.tools.insert(tool.name, handler);
+    }
+
+    /// Remove tool `name`, returning whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.validators.remove(name);
+        self.categories.remove(name);
+        self.tools.remove(name).is_some()
+    }
+
+    /// Check `arguments` against the input schema of tool `name`.
+    ///
+    /// Returns [`Error::InvalidToolArguments`] listing every violation.
+    /// Tools that opted out of validation always pass.
+    pub fn validate_arguments(&self, name: &str, arguments: &HashMap<String, Value>) -> Result<()> {
+        let Some(validator) = self.validators.get(name) else {
+            return Ok(());
+        };
+        let instance = Value::Object(
+            arguments
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        );
+        let violations: Vec<String> = validator
+            .iter_errors(&instance)
+            .map(|e| match e.instance_path.as_str() {
+                "" => e.to_string(),
+                path => format!("{}: {}", path, e),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidToolArguments(format!(
+                "{}: {}",
+                name,
+                violations.join("; ")
+            )))
+        }
     }
 
     /// Get all registered tools.
@@ -62,6 +202,20 @@ impl McpHandler {
     pub fn tool_count(&self) -> usize {
         self.tools.len()
     }
+
+    /// Assign `category` to every registered tool that doesn't have one yet.
+    ///
+    /// Called after registering each group of tools.
+    pub fn categorize(&mut self, category: &'static str) {
+        for name in self.tools.keys() {
+            self.categories.entry(name.clone()).or_insert(category);
+        }
+    }
+
+    /// The category a tool was registered under, if any.
+    pub fn category(&self, name: &str) -> Option<&'static str> {
+        self.categories.get(name).copied()
+    }
 }
 
 impl Default for McpHandler {
@@ -70,6 +224,24 @@ impl Default for McpHandler {
     }
 }
 
+/// Counts a call as queued until dropped, so cancelled waits are uncounted.
+struct QueuedCall(Arc<AtomicUsize>);
+
+impl QueuedCall {
+    fn new(queued: Arc<AtomicUsize>) -> Self {
+        let count = queued.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!("context_engine_tool_calls_queued").set(count as f64);
+        Self(queued)
+    }
+}
+
+impl Drop for QueuedCall {
+    fn drop(&mut self) {
+        let count = self.0.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::gauge!("context_engine_tool_calls_queued").set(count as f64);
+    }
+}
+
 /// Helper macro for creating tool input schemas.
 #[macro_export]
 macro_rules! tool_schema {
@@ -93,7 +265,68 @@ pub fn success_result(text: impl Into<String>) -> ToolResult {
     ToolResult {
         content: vec![text_content(text)],
         is_error: false,
+        error: None,
+    }
+}
+
+/// Helper to create a successful result holding `value` as pretty JSON.
+///
+/// A value that fails to serialize gives an error result rather than a panic
+/// or a failed call.
+pub fn json_result<T: Serialize + ?Sized>(value: &T) -> ToolResult {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => success_result(json),
+        Err(e) => tool_error_result("Failed to serialize result", &Error::from(e)),
+    }
+}
+
+/// Helper to create a `resource_link` block for a workspace-relative file.
+///
+/// Returns `None` if the path cannot be expressed as a `file://` URI.
+pub fn file_link_content(workspace: &Path, relative_path: &str) -> Option<ContentBlock> {
+    Some(ContentBlock::ResourceLink {
+        uri: crate::mcp::resources::file_uri(workspace, relative_path)?,
+        name: relative_path.to_string(),
+        description: None,
+        mime_type: None,
+    })
+}
+
+/// Helper to create a successful result with a text block followed by
+/// `resource_link` blocks for each distinct workspace-relative path.
+pub fn success_result_with_links<'a>(
+    text: impl Into<String>,
+    workspace: &Path,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> ToolResult {
+    success_result(text).with_blocks(link_blocks(workspace, paths))
+}
+
+/// Like [`json_result`], followed by `resource_link` blocks as in
+/// [`success_result_with_links`] when serialization succeeds.
+pub fn json_result_with_links<'a, T: Serialize + ?Sized>(
+    value: &T,
+    workspace: &Path,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> ToolResult {
+    let result = json_result(value);
+    if result.is_error {
+        return result;
     }
+    result.with_blocks(link_blocks(workspace, paths))
+}
+
+/// `resource_link` blocks for each distinct path.
+fn link_blocks<'a>(
+    workspace: &Path,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Vec<ContentBlock> {
+    let mut seen = std::collections::HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| seen.insert(*path))
+        .filter_map(|path| file_link_content(workspace, path))
+        .collect()
 }
 
 /// Helper to create an error tool result.
@@ -101,9 +334,40 @@ pub fn error_result(text: impl Into<String>) -> ToolResult {
     ToolResult {
         content: vec![text_content(text)],
         is_error: true,
+        error: None,
+    }
+}
+
+/// Helper to create an error tool result with a machine-readable code.
+pub fn coded_error_result(
+    code: impl Into<String>,
+    message: impl Into<String>,
+    details: Option<Value>,
+) -> ToolResult {
+    let message = message.into();
+    ToolResult {
+        content: vec![text_content(message.clone())],
+        is_error: true,
+        error: Some(ToolError {
+            code: code.into(),
+            message,
+            details,
+        }),
     }
 }
 
+/// Helper to create an error tool result from an [`Error`], using its code.
+///
+/// `context` is prefixed to the message, e.g. "Failed to read file".
+pub fn tool_error_result(context: &str, error: &Error) -> ToolResult {
+    let message = if context.is_empty() {
+        error.to_string()
+    } else {
+        format!("{}: {}", context, error)
+    };
+    coded_error_result(error.code(), message, error.details())
+}
+
 /// Helper to extract a required string argument.
 pub fn get_string_arg(args: &HashMap<String, Value>, name: &str) -> Result<String> {
     args.get(name)
@@ -179,6 +443,110 @@ mod tests {
         }
     }
 
+    struct StrictTool {
+        validate: bool,
+    }
+
+    #[async_trait]
+    impl ToolHandler for StrictTool {
+        fn definition(&self) -> Tool {
+            Tool {
+                name: "strict".to_string(),
+                description: "Requires a path".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "limit": { "type": "integer" }
+                    },
+                    "required": ["path"]
+                }),
+            }
+        }
+
+        async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
+            Ok(success_result("ok"))
+        }
+
+        fn validates_arguments(&self) -> bool {
+            self.validate
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execution_slots_limit_concurrency() {
+        let mut handler = McpHandler::new();
+        handler.set_max_concurrent_tools(2);
+        let handler = Arc::new(handler);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let calls: Vec<_> = (0..6)
+            .map(|_| {
+                let (handler, running, peak) = (handler.clone(), running.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let _permit = handler.acquire_execution_slot().await;
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(handler.queued_tool_calls(), 4);
+        for call in calls {
+            call.await.unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(handler.queued_tool_calls(), 0);
+
+        // Unlimited by default
+        assert!(McpHandler::new().acquire_execution_slot().await.is_none());
+    }
+
+    #[test]
+    fn test_validate_arguments_against_schema() {
+        let mut handler = McpHandler::new();
+        handler.register(StrictTool { validate: true });
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), json!("src/lib.rs"));
+        args.insert("limit".to_string(), json!(5));
+        assert!(handler.validate_arguments("strict", &args).is_ok());
+
+        // Missing required field
+        let err = handler
+            .validate_arguments("strict", &HashMap::new())
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidToolArguments(_)));
+        assert!(
+            err.to_string().contains("\"path\" is a required property"),
+            "{}",
+            err
+        );
+
+        // Wrong-typed field
+        args.insert("limit".to_string(), json!("five"));
+        let err = handler.validate_arguments("strict", &args).unwrap_err();
+        assert!(err.to_string().contains("/limit: "), "{}", err);
+        assert!(
+            err.to_string().contains("is not of type \"integer\""),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_validation_can_be_skipped_per_tool() {
+        let mut handler = McpHandler::new();
+        handler.register(StrictTool { validate: false });
+        assert!(handler
+            .validate_arguments("strict", &HashMap::new())
+            .is_ok());
+    }
+
     #[test]
     fn test_handler_registration() {
         let mut handler = McpHandler::new();
@@ -230,6 +598,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_success_result_with_links() {
+        let result = success_result_with_links(
+            "found",
+            Path::new("/repo"),
+            ["src/a.rs", "src/b.rs", "src/a.rs"],
+        );
+        assert!(!result.is_error);
+        assert_eq!(result.content.len(), 3);
+        match &result.content[1] {
+            ContentBlock::ResourceLink { uri, name, .. } => {
+                assert_eq!(uri, "file:///repo/src/a.rs");
+                assert_eq!(name, "src/a.rs");
+            }
+            other => panic!("expected resource link, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tool_error_result() {
+        let result = tool_error_result(
+            "Failed to reindex",
+            &Error::FileTooLarge {
+                path: "big.bin".to_string(),
+                size: 10,
+            },
+        );
+        assert!(result.is_error);
+        let error = result.error.unwrap();
+        assert_eq!(error.code, "file_too_large");
+        assert_eq!(
+            error.message,
+            "Failed to reindex: File too large: big.bin (10 bytes)"
+        );
+        assert_eq!(
+            error.details,
+            Some(json!({ "path": "big.bin", "size": 10 }))
+        );
+    }
+
     #[test]
     fn test_get_string_arg() {
         let mut args = HashMap::new();
@@ -288,6 +696,33 @@ mod tests {
         assert_eq!(result.content.len(), 1);
     }
 
+    #[test]
+    fn test_json_result_reports_serialization_failure() {
+        struct Unserializable;
+
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                _: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("cannot serialize"))
+            }
+        }
+
+        let result = json_result(&Unserializable);
+        assert!(result.is_error);
+        let error = result.error.unwrap();
+        assert_eq!(error.code, "json_error");
+        assert!(
+            error.message.contains("cannot serialize"),
+            "{}",
+            error.message
+        );
+
+        let result = json_result(&json!({ "ok": true }));
+        assert!(!result.is_error);
+    }
+
     #[test]
     fn test_error_result() {
         let result = error_result("Error!");
@@ -9,13 +9,23 @@
 //! - `server` - MCP server implementation
 //! - `transport` - Transport layer (stdio, HTTP/SSE)
 //! - `handler` - Request/notification handlers
+//! - `prompts` - Prompt templates
+//! - `resources` - Resource URIs for workspace files and search hits
+//! - `completion` - Argument completion
+//! - `progress` - Progress notifications for long-running tools
 
+pub mod completion;
 pub mod handler;
+pub mod progress;
+pub mod prompts;
 pub mod protocol;
+pub mod resources;
 pub mod server;
 pub mod transport;
 
 pub use handler::McpHandler;
+pub use prompts::PromptRegistry;
 pub use protocol::*;
+pub use resources::ResourceRegistry;
 pub use server::McpServer;
 pub use transport::{StdioTransport, Transport};
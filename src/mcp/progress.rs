@@ -0,0 +1,65 @@
+//! Progress notifications for long-running tool calls.
+//!
+//! A client opts in by sending `_meta.progressToken` with `tools/call`. The
+//! server then hands the tool a [`ProgressReporter`] that emits
+//! `notifications/progress` messages tagged with that token.
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::mcp::protocol::{JsonRpcNotification, ProgressParams, JSONRPC_VERSION};
+use crate::mcp::transport::Message;
+
+/// Sends `notifications/progress` for a single request.
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    token: Value,
+    outgoing: mpsc::Sender<Message>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter for `token` that writes to the transport's outgoing channel.
+    pub fn new(token: Value, outgoing: mpsc::Sender<Message>) -> Self {
+        Self { token, outgoing }
+    }
+
+    /// The client-supplied progress token.
+    pub fn token(&self) -> &Value {
+        &self.token
+    }
+
+    /// Report progress.
+    ///
+    /// Notifications are best-effort: if the outgoing channel is full the
+    /// update is dropped rather than stalling the tool.
+    pub fn report(&self, progress: u64, total: Option<u64>, message: Option<&str>) {
+        let notification = self.notification(progress, total, message);
+        if let Err(e) = self.outgoing.try_send(notification) {
+            debug!("Dropped progress notification: {}", e);
+        }
+    }
+
+    /// Report progress, waiting for room in the outgoing channel.
+    ///
+    /// For notifications that carry results, which must not be dropped.
+    /// Returns `false` once the transport has gone away.
+    pub async fn send(&self, progress: u64, total: Option<u64>, message: Option<&str>) -> bool {
+        let notification = self.notification(progress, total, message);
+        self.outgoing.send(notification).await.is_ok()
+    }
+
+    fn notification(&self, progress: u64, total: Option<u64>, message: Option<&str>) -> Message {
+        let params = ProgressParams {
+            progress_token: self.token.clone(),
+            progress,
+            total,
+            message: message.map(str::to_string),
+        };
+        Message::Notification(JsonRpcNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: "notifications/progress".to_string(),
+            params: serde_json::to_value(params).ok(),
+        })
+    }
+}
@@ -0,0 +1,274 @@
+//! Prompt templates exposed via `prompts/list` and `prompts/get`.
+//!
+//! Templates use `{{name}}` placeholders for arguments. A section wrapped in
+//! `{{#name}}...{{/name}}` is kept only when that argument is non-empty, so
+//! optional arguments don't leave dangling headings behind.
+
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::mcp::protocol::{ContentBlock, GetPromptResult, Prompt, PromptArgument, PromptMessage};
+
+/// A prompt with its message template.
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    prompt: Prompt,
+    template: String,
+}
+
+impl PromptTemplate {
+    /// Create a template with no arguments.
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Self {
+        Self {
+            prompt: Prompt {
+                name: name.into(),
+                description: Some(description.into()),
+                arguments: Vec::new(),
+            },
+            template: template.into(),
+        }
+    }
+
+    /// Add an argument.
+    pub fn argument(mut self, name: &str, description: &str, required: bool) -> Self {
+        self.prompt.arguments.push(PromptArgument {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            required,
+        });
+        self
+    }
+
+    /// The prompt definition.
+    pub fn prompt(&self) -> &Prompt {
+        &self.prompt
+    }
+
+    /// Render the template with the given arguments.
+    pub fn render(&self, args: &HashMap<String, String>) -> Result<GetPromptResult> {
+        for arg in &self.prompt.arguments {
+            let present = args.get(&arg.name).is_some_and(|v| !v.trim().is_empty());
+            if arg.required && !present {
+                return Err(Error::InvalidToolArguments(format!(
+                    "Prompt '{}' requires argument '{}'",
+                    self.prompt.name, arg.name
+                )));
+            }
+        }
+
+        let mut text = self.template.clone();
+        for arg in &self.prompt.arguments {
+            let value = args.get(&arg.name).map(|v| v.trim()).unwrap_or("");
+            text = render_section(&text, &arg.name, !value.is_empty());
+            text = text.replace(&format!("{{{{{}}}}}", arg.name), value);
+        }
+
+        Ok(GetPromptResult {
+            description: self.prompt.description.clone(),
+            messages: vec![PromptMessage {
+                role: "user".to_string(),
+                content: ContentBlock::Text {
+                    text: text.trim().to_string(),
+                },
+            }],
+        })
+    }
+}
+
+/// Keep or drop every `{{#name}}...{{/name}}` section.
+fn render_section(text: &str, name: &str, keep: bool) -> String {
+    let open = format!("{{{{#{}}}}}", name);
+    let close = format!("{{{{/{}}}}}", name);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(&open) {
+        let Some(len) = rest[start..].find(&close) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        if keep {
+            out.push_str(&rest[start + open.len()..start + len]);
+        }
+        rest = &rest[start + len + close.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Registry of prompt templates.
+#[derive(Debug, Clone, Default)]
+pub struct PromptRegistry {
+    templates: HashMap<String, PromptTemplate>,
+}
+
+impl PromptRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in review and planning prompts.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for template in builtin_prompts() {
+            registry.register(template);
+        }
+        registry
+    }
+
+    /// Register a template, replacing any with the same name.
+    pub fn register(&mut self, template: PromptTemplate) {
+        self.templates
+            .insert(template.prompt.name.clone(), template);
+    }
+
+    /// All prompts, sorted by name.
+    pub fn list(&self) -> Vec<Prompt> {
+        let mut prompts: Vec<Prompt> = self.templates.values().map(|t| t.prompt.clone()).collect();
+        prompts.sort_by(|a, b| a.name.cmp(&b.name));
+        prompts
+    }
+
+    /// Render a prompt by name.
+    pub fn get(&self, name: &str, args: &HashMap<String, String>) -> Result<GetPromptResult> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| Error::InvalidToolArguments(format!("Unknown prompt: {}", name)))?
+            .render(args)
+    }
+}
+
+/// Built-in prompts registered by default.
+fn builtin_prompts() -> Vec<PromptTemplate> {
+    vec![
+        PromptTemplate::new(
+            "review_pr",
+            "Review a pull request diff for bugs, risks, and invariant violations",
+            r#"Review the following pull request diff.
+
+1. Run `review_diff` on the diff to collect findings.
+2. Run `analyze_risk` to gauge the blast radius of the change.
+3. Use `codebase_retrieval` or `get_file` to check callers and surrounding code before flagging an issue.
+4. If the project defines invariants, run `check_invariants` on the diff.
+
+Report findings grouped by severity, each with file, line, and a concrete fix. Say explicitly if nothing blocks merging.
+{{#focus}}
+Focus areas: {{focus}}
+{{/focus}}
+```diff
+{{diff}}
+```"#,
+        )
+        .argument("diff", "Unified diff of the pull request", true)
+        .argument(
+            "focus",
+            "Optional areas to focus on (e.g. security, performance)",
+            false,
+        ),
+        PromptTemplate::new(
+            "plan_feature",
+            "Plan the implementation of a feature as ordered, verifiable steps",
+            r#"Plan the implementation of this feature:
+
+{{goal}}
+{{#constraints}}
+Constraints:
+{{constraints}}
+{{/constraints}}
+1. Use `codebase_retrieval` to find the code the feature touches and any existing patterns to follow.
+2. Create the plan with `create_plan`, then add concrete steps with `add_step`, noting dependencies between steps.
+3. Each step should name the files it changes and how it will be verified.
+4. Call out risks and open questions before implementation starts."#,
+        )
+        .argument("goal", "What the feature should do", true)
+        .argument(
+            "constraints",
+            "Optional constraints (deadlines, compatibility, scope limits)",
+            false,
+        ),
+        PromptTemplate::new(
+            "explain_symbol",
+            "Explain what a symbol does and how it is used",
+            r#"Explain the symbol `{{symbol}}` in this codebase.
+
+1. Use `grep` to find its definition and call sites, and `get_file` with `around_line` to read them.
+2. Use `codebase_retrieval` for related concepts the name alone doesn't surface.
+
+Describe what it does, its inputs and outputs, important invariants, and where it is used. Cite file paths and line numbers."#,
+        )
+        .argument("symbol", "Function, type, or module name", true),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn text(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            ContentBlock::Text { text } => text,
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_defaults_are_listed() {
+        let names: Vec<String> = PromptRegistry::with_defaults()
+            .list()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["explain_symbol", "plan_feature", "review_pr"]);
+    }
+
+    #[test]
+    fn test_review_pr_interpolates_diff() {
+        let registry = PromptRegistry::with_defaults();
+        let result = registry
+            .get("review_pr", &args(&[("diff", "+fn added() {}")]))
+            .unwrap();
+        let text = text(&result);
+        assert_eq!(result.messages[0].role, "user");
+        assert!(text.contains("```diff\n+fn added() {}\n```"));
+        assert!(text.contains("review_diff"));
+        assert!(!text.contains("Focus areas"));
+        assert!(!text.contains("{{"));
+    }
+
+    #[test]
+    fn test_optional_section_kept_when_present() {
+        let registry = PromptRegistry::with_defaults();
+        let result = registry
+            .get(
+                "plan_feature",
+                &args(&[("goal", "Add SSO login"), ("constraints", "No new deps")]),
+            )
+            .unwrap();
+        assert!(text(&result).contains("Add SSO login"));
+        assert!(text(&result).contains("Constraints:\nNo new deps"));
+
+        let result = registry
+            .get("plan_feature", &args(&[("goal", "Add SSO login")]))
+            .unwrap();
+        assert!(!text(&result).contains("Constraints:"));
+    }
+
+    #[test]
+    fn test_missing_required_argument_and_unknown_prompt() {
+        let registry = PromptRegistry::with_defaults();
+        assert!(registry.get("explain_symbol", &HashMap::new()).is_err());
+        assert!(registry.get("nope", &HashMap::new()).is_err());
+    }
+}
@@ -61,6 +61,15 @@ pub enum RequestId {
     Number(i64),
 }
 
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(id) => f.write_str(id),
+            Self::Number(id) => write!(f, "{}", id),
+        }
+    }
+}
+
 // ===== MCP-Specific Types =====
 
 /// Server capabilities.
@@ -74,6 +83,8 @@ pub struct ServerCapabilities {
     pub prompts: Option<PromptsCapability>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logging: Option<LoggingCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
 }
 
 /// Tools capability.
@@ -103,6 +114,10 @@ pub struct PromptsCapability {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LoggingCapability {}
 
+/// Completions capability.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionsCapability {}
+
 /// Server info.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerInfo {
@@ -134,6 +149,21 @@ pub struct ToolResult {
     pub content: Vec<ContentBlock>,
     #[serde(default)]
     pub is_error: bool,
+    /// Machine-readable failure, alongside the human-readable text content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ToolError>,
+}
+
+/// Typed error payload of a failed tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolError {
+    /// Stable error code, e.g. `file_not_found` (see [`crate::error::Error::code`])
+    pub code: String,
+    /// Human-readable message
+    pub message: String,
+    /// Optional structured details
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
 }
 
 /// Content block in a tool result.
@@ -152,6 +182,170 @@ pub enum ContentBlock {
         mime_type: Option<String>,
         text: Option<String>,
     },
+    /// Link to a resource (e.g. a workspace file) the client can open.
+    #[serde(rename = "resource_link", rename_all = "camelCase")]
+    ResourceLink {
+        uri: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+}
+
+impl ToolResult {
+    /// Append extra content blocks after the existing ones.
+    pub fn with_blocks(mut self, blocks: impl IntoIterator<Item = ContentBlock>) -> Self {
+        self.content.extend(blocks);
+        self
+    }
+}
+
+/// Prompt definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// Argument accepted by a prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A message produced by rendering a prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: ContentBlock,
+}
+
+/// List prompts result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+}
+
+/// Get prompt params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+/// Get prompt result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+/// A resource the client can read with `resources/read`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// List resources result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+}
+
+/// Read resource params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceParams {
+    pub uri: String,
+}
+
+/// The text of a read resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub text: String,
+}
+
+/// Read resource result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+}
+
+/// Completion request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteParams {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+}
+
+/// What is being completed (`ref/prompt`, `ref/resource`, `ref/tool`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionReference {
+    #[serde(rename = "type")]
+    pub ref_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+/// The argument being completed and its partial value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Completion values.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+impl Completion {
+    /// Keep the first `max` values, recording the total and whether more exist.
+    pub fn capped(mut values: Vec<String>, max: usize) -> Self {
+        let total = values.len();
+        values.truncate(max);
+        Self {
+            values,
+            total: Some(total),
+            has_more: total > max,
+        }
+    }
+}
+
+/// Completion result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
 }
 
 /// List tools result.
@@ -166,6 +360,29 @@ pub struct CallToolParams {
     pub name: String,
     #[serde(default)]
     pub arguments: HashMap<String, Value>,
+    #[serde(default, rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
+}
+
+/// Request metadata (`_meta`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token the client wants `notifications/progress` tagged with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<Value>,
+}
+
+/// Params of a `notifications/progress` notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressParams {
+    pub progress_token: Value,
+    pub progress: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 // ===== Error Codes =====
@@ -177,6 +394,7 @@ pub mod error_codes {
     pub const METHOD_NOT_FOUND: i32 = -32601;
     pub const INVALID_PARAMS: i32 = -32602;
     pub const INTERNAL_ERROR: i32 = -32603;
+    pub const RESOURCE_NOT_FOUND: i32 = -32002;
 }
 
 #[cfg(test)]
@@ -201,6 +419,29 @@ mod tests {
         assert_eq!(parsed.method, "tools/call");
     }
 
+    #[test]
+    fn test_resource_link_serialization() {
+        let block = ContentBlock::ResourceLink {
+            uri: "file:///repo/src/lib.rs".to_string(),
+            name: "src/lib.rs".to_string(),
+            description: None,
+            mime_type: Some("text/x-rust".to_string()),
+        };
+
+        let value = serde_json::to_value(&block).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "resource_link",
+                "uri": "file:///repo/src/lib.rs",
+                "name": "src/lib.rs",
+                "mimeType": "text/x-rust"
+            })
+        );
+        let parsed: ContentBlock = serde_json::from_value(value).unwrap();
+        assert!(matches!(parsed, ContentBlock::ResourceLink { .. }));
+    }
+
     #[test]
     fn test_jsonrpc_response_success() {
         let response = JsonRpcResponse {
@@ -267,11 +508,13 @@ mod tests {
                 text: "Success".to_string(),
             }],
             is_error: false,
+            error: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"type\":\"text\""));
         assert!(json.contains("\"is_error\":false"));
+        assert!(!json.contains("\"error\""));
     }
 
     #[test]
@@ -281,11 +524,35 @@ mod tests {
                 text: "Error occurred".to_string(),
             }],
             is_error: true,
+            error: None,
         };
 
         assert!(result.is_error);
     }
 
+    #[test]
+    fn test_tool_result_error_round_trip() {
+        let result = ToolResult {
+            content: vec![ContentBlock::Text {
+                text: "File not found: src/missing.rs".to_string(),
+            }],
+            is_error: true,
+            error: Some(ToolError {
+                code: "file_not_found".to_string(),
+                message: "File not found: src/missing.rs".to_string(),
+                details: Some(json!({ "path": "src/missing.rs" })),
+            }),
+        };
+
+        let value = serde_json::to_value(&result).unwrap();
+        assert_eq!(value["error"]["code"], "file_not_found");
+        assert_eq!(value["error"]["details"]["path"], "src/missing.rs");
+
+        let parsed: ToolResult = serde_json::from_value(value).unwrap();
+        assert!(parsed.is_error);
+        assert_eq!(parsed.error, result.error);
+    }
+
     #[test]
     fn test_content_block_variants() {
         let text = ContentBlock::Text {
@@ -318,6 +585,7 @@ mod tests {
             resources: None,
             prompts: None,
             logging: None,
+            completions: None,
         };
 
         let json = serde_json::to_string(&caps).unwrap();
@@ -351,6 +619,7 @@ mod tests {
                 args.insert("top_k".to_string(), json!(10));
                 args
             },
+            meta: None,
         };
 
         let json = serde_json::to_string(&params).unwrap();
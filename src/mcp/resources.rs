@@ -0,0 +1,182 @@
+//! Resources exposed via `resources/list` and `resources/read`.
+//!
+//! Two URI schemes are served:
+//!
+//! - `file:///absolute/path` names a file under the workspace or another
+//!   registered root. It is read with the same escape and size checks as
+//!   `get_file`.
+//! - `context-engine://snippet/<id>` names a search hit by a hash of its
+//!   path and text (see [`crate::service::snippets`]). Reading it returns
+//!   the snippet exactly as the search returned it.
+//!
+//! Retrieval tools attach `resource_link` blocks with both kinds of URI, so
+//! a client can re-open any hit. `resources/list` lists the recent hits;
+//! files are readable but not listed, since a workspace can hold far more
+//! files than a client wants to page through.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::mcp::protocol::{ReadResourceResult, Resource, ResourceContents};
+use crate::service::ContextService;
+
+/// Prefix of search-hit URIs; the snippet id follows.
+pub const SNIPPET_URI_PREFIX: &str = "context-engine://snippet/";
+
+/// MIME type reported for file and snippet text.
+const TEXT_MIME: &str = "text/plain";
+
+/// The `file://` URI of `relative_path` under `root`, if it has one.
+pub fn file_uri(root: &Path, relative_path: &str) -> Option<String> {
+    reqwest::Url::from_file_path(root.join(relative_path))
+        .ok()
+        .map(|uri| uri.to_string())
+}
+
+/// The URI of the search hit with id `id`.
+pub fn snippet_uri(id: &str) -> String {
+    format!("{}{}", SNIPPET_URI_PREFIX, id)
+}
+
+/// Resolves resource URIs against the workspace and remembered search hits.
+#[derive(Clone)]
+pub struct ResourceRegistry {
+    context: Arc<ContextService>,
+}
+
+impl ResourceRegistry {
+    /// Create a registry serving `context`'s roots and search hits.
+    pub fn new(context: Arc<ContextService>) -> Self {
+        Self { context }
+    }
+
+    /// The remembered search hits, most recent first.
+    pub fn list(&self) -> Vec<Resource> {
+        self.context
+            .recent_snippets()
+            .into_iter()
+            .map(|(id, snippet)| Resource {
+                uri: snippet_uri(&id),
+                description: Some(format!("Search hit in {}", snippet.path)),
+                name: snippet.path,
+                mime_type: Some(TEXT_MIME.to_string()),
+            })
+            .collect()
+    }
+
+    /// Read the resource at `uri`.
+    ///
+    /// Unknown schemes, forgotten snippets and missing files fail with
+    /// [`Error::ResourceNotFound`] or [`Error::FileNotFound`]; files outside
+    /// every registered root fail with [`Error::InvalidPath`].
+    pub async fn read(&self, uri: &str) -> Result<ReadResourceResult> {
+        let text = match uri.strip_prefix(SNIPPET_URI_PREFIX) {
+            Some(id) => {
+                self.context
+                    .snippet(id)
+                    .ok_or_else(|| {
+                        Error::ResourceNotFound(format!(
+                            "{} (search hits are only kept for a while; search again)",
+                            uri
+                        ))
+                    })?
+                    .content
+            }
+            None => self.read_file(uri).await?,
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some(TEXT_MIME.to_string()),
+                text,
+            }],
+        })
+    }
+
+    /// Read a `file://` URI relative to the root that contains it.
+    async fn read_file(&self, uri: &str) -> Result<String> {
+        let path = reqwest::Url::parse(uri)
+            .ok()
+            .filter(|url| url.scheme() == "file")
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| Error::ResourceNotFound(uri.to_string()))?;
+
+        for (index, root) in self.context.roots().into_iter().enumerate() {
+            let Some(relative) = path.strip_prefix(root).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            let root = (index > 0).then(|| root.display().to_string());
+            return self.context.read_file_in(root.as_deref(), relative).await;
+        }
+        Err(Error::InvalidPath(format!(
+            "'{}' is outside the registered roots",
+            path.display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::handler::ToolHandler;
+    use crate::mcp::protocol::ContentBlock;
+    use crate::sdk::mock_backend::MockBackend;
+    use crate::tools::retrieval::SearchCodeTool;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_search_hit_uris_read_back() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval("Path: src/lib.rs\npub fn parse_config() {}");
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub fn parse_config() {}\nfn helper() {}\n",
+        )
+        .unwrap();
+        let context = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        context.index_workspace().await.unwrap();
+        let registry = ResourceRegistry::new(context.clone());
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("config parsing"));
+        let result = SearchCodeTool::new(context).execute(args).await.unwrap();
+        let uris: Vec<&str> = result
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ResourceLink { uri, .. } => Some(uri.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(uris.len(), 2);
+        assert!(uris[0].starts_with("file://"));
+        assert!(uris[1].starts_with(SNIPPET_URI_PREFIX));
+
+        let file = registry.read(uris[0]).await.unwrap();
+        assert_eq!(
+            file.contents[0].text,
+            "pub fn parse_config() {}\nfn helper() {}\n"
+        );
+        let snippet = registry.read(uris[1]).await.unwrap();
+        assert_eq!(snippet.contents[0].text, "pub fn parse_config() {}");
+        assert_eq!(snippet.contents[0].uri, uris[1]);
+        assert_eq!(registry.list()[0].uri, uris[1]);
+
+        let unknown = registry
+            .read(&snippet_uri("0123456789abcdef0123456789abcdef"))
+            .await
+            .unwrap_err();
+        assert_eq!(unknown.code(), "resource_not_found");
+        let outside = registry.read("file:///etc/passwd").await.unwrap_err();
+        assert_eq!(outside.code(), "invalid_path");
+    }
+}
@@ -1,18 +1,39 @@
 //! MCP server implementation.
 
 use serde_json::Value;
+use std::future::Future;
 use std::sync::Arc;
-use tracing::{debug, error, info, warn};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 use crate::error::{Error, Result};
-use crate::mcp::handler::McpHandler;
+use crate::mcp::completion;
+use crate::mcp::handler::{tool_error_result, McpHandler};
+use crate::mcp::progress::ProgressReporter;
+use crate::mcp::prompts::PromptRegistry;
 use crate::mcp::protocol::*;
+use crate::mcp::resources::ResourceRegistry;
 use crate::mcp::transport::{Message, Transport};
+use crate::service::ContextService;
 use crate::VERSION;
 
+/// How long shutdown waits for in-flight requests before abandoning them.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Prefix of the ids of keepalive pings sent to the client.
+const KEEPALIVE_ID_PREFIX: &str = "keepalive-";
+
 /// MCP server.
+#[derive(Clone)]
 pub struct McpServer {
     handler: Arc<McpHandler>,
+    prompts: Arc<PromptRegistry>,
+    context: Option<Arc<ContextService>>,
+    resources: Option<ResourceRegistry>,
+    idle_timeout: Option<Duration>,
+    keepalive: Option<Duration>,
     name: String,
     version: String,
 }
@@ -22,48 +43,196 @@ impl McpServer {
     pub fn new(handler: McpHandler, name: impl Into<String>) -> Self {
         Self {
             handler: Arc::new(handler),
+            prompts: Arc::new(PromptRegistry::with_defaults()),
+            context: None,
+            resources: None,
+            idle_timeout: None,
+            keepalive: None,
             name: name.into(),
             version: VERSION.to_string(),
         }
     }
 
-    /// Run the server with the given transport.
-    pub async fn run<T: Transport>(&self, mut transport: T) -> Result<()> {
+    /// Replace the prompt registry (defaults to the built-in prompts).
+    pub fn with_prompts(mut self, prompts: PromptRegistry) -> Self {
+        self.prompts = Arc::new(prompts);
+        self
+    }
+
+    /// Attach the workspace so `path` arguments can be completed and its
+    /// files and search hits can be read as resources.
+    pub fn with_context(mut self, context: Arc<ContextService>) -> Self {
+        self.resources = Some(ResourceRegistry::new(context.clone()));
+        self.context = Some(context);
+        self
+    }
+
+    /// Shut down after `timeout` with no requests (`None`, the default, never does).
+    ///
+    /// The window restarts whenever a message arrives or a request finishes,
+    /// so a long-running tool call doesn't count as idle time.
+    pub fn with_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Send the client a `ping` request every `interval` (`None`, the
+    /// default, never does), so clients that reap quiet sessions keep this
+    /// one open through long tool calls.
+    ///
+    /// Pings don't count as activity for the idle timeout, and neither do
+    /// the client's answers.
+    pub fn with_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.keepalive = interval;
+        self
+    }
+
+    /// Run the server with the given transport until the input closes or
+    /// SIGINT/SIGTERM is received.
+    pub async fn run<T: Transport>(&self, transport: T) -> Result<()> {
+        self.run_until(transport, shutdown_signal()).await
+    }
+
+    /// Run the server until the input closes, `shutdown` resolves or the
+    /// idle timeout passes.
+    ///
+    /// Requests are handled concurrently, so a `ping` is answered even while
+    /// a slow tool call runs. On shutdown no new requests are accepted and
+    /// in-flight ones get up to [`SHUTDOWN_TIMEOUT`] to finish.
+    pub async fn run_until<T, F>(&self, mut transport: T, shutdown: F) -> Result<()>
+    where
+        T: Transport,
+        F: Future<Output = ()>,
+    {
         info!("Starting MCP server: {} v{}", self.name, self.version);
 
         let (mut incoming, outgoing) = transport.start().await?;
+        let mut active_requests = JoinSet::new();
+        tokio::pin!(shutdown);
+        let idle_timeout = self.idle_timeout.unwrap_or(Duration::MAX);
+        let idle = tokio::time::sleep(idle_timeout);
+        tokio::pin!(idle);
+        let mut keepalive = self.keepalive.map(|period| {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+        let mut pings_sent: u64 = 0;
 
-        while let Some(msg) = incoming.recv().await {
-            match msg {
-                Message::Request(req) => {
-                    let response = self.handle_request(req).await;
-                    if outgoing.send(Message::Response(response)).await.is_err() {
-                        error!("Failed to send response");
-                        break;
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    info!("Shutdown requested, no longer accepting requests");
+                    break;
+                }
+                () = &mut idle, if self.idle_timeout.is_some() && active_requests.is_empty() => {
+                    info!("No requests for {:?}, shutting down", idle_timeout);
+                    break;
+                }
+                () = next_tick(&mut keepalive) => {
+                    pings_sent += 1;
+                    let ping = Message::Request(JsonRpcRequest {
+                        jsonrpc: JSONRPC_VERSION.to_string(),
+                        id: RequestId::String(format!("{}{}", KEEPALIVE_ID_PREFIX, pings_sent)),
+                        method: "ping".to_string(),
+                        params: None,
+                    });
+                    // A full outgoing queue already shows activity
+                    if outgoing.try_send(ping).is_err() {
+                        debug!("Skipping keepalive ping, output is busy");
                     }
                 }
-                Message::Notification(notif) => {
-                    self.handle_notification(notif).await;
+                Some(joined) = active_requests.join_next(), if !active_requests.is_empty() => {
+                    if let Err(e) = joined {
+                        error!("Request task failed: {}", e);
+                    }
+                    if self.idle_timeout.is_some() {
+                        idle.as_mut().reset(tokio::time::Instant::now() + idle_timeout);
+                    }
                 }
-                Message::Response(_) => {
-                    warn!("Received unexpected response");
+                msg = incoming.recv() => {
+                    let Some(msg) = msg else { break };
+                    if self.idle_timeout.is_some() && !matches!(msg, Message::Response(_)) {
+                        idle.as_mut().reset(tokio::time::Instant::now() + idle_timeout);
+                    }
+                    match msg {
+                        Message::Request(req) => {
+                            let server = self.clone();
+                            let outgoing = outgoing.clone();
+                            active_requests.spawn(async move {
+                                let response =
+                                    server.handle_request(req, Some(outgoing.clone())).await;
+                                if outgoing.send(Message::Response(response)).await.is_err() {
+                                    error!("Failed to send response");
+                                }
+                            });
+                        }
+                        Message::Notification(notif) => {
+                            self.handle_notification(notif).await;
+                        }
+                        Message::Response(res) => match &res.id {
+                            RequestId::String(id) if id.starts_with(KEEPALIVE_ID_PREFIX) => {
+                                debug!("Keepalive ping {} answered", id);
+                            }
+                            _ => warn!("Received unexpected response"),
+                        },
+                    }
                 }
             }
         }
 
+        if !active_requests.is_empty() {
+            info!(
+                "Waiting for {} in-flight request(s) to finish",
+                active_requests.len()
+            );
+            let drain = async { while active_requests.join_next().await.is_some() {} };
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, drain).await.is_err() {
+                warn!(
+                    "Abandoning {} request(s) still running after {:?}",
+                    active_requests.len(),
+                    SHUTDOWN_TIMEOUT
+                );
+                active_requests.abort_all();
+            }
+        }
+
         transport.stop().await?;
         info!("MCP server stopped");
         Ok(())
     }
 
-    /// Handle a JSON-RPC request.
-    async fn handle_request(&self, req: JsonRpcRequest) -> JsonRpcResponse {
-        debug!("Handling request: {} (id: {:?})", req.method, req.id);
+    /// Handle a JSON-RPC request, sending any notifications it produces
+    /// (such as progress) to `outgoing`.
+    ///
+    /// Dispatch runs inside a `request` span carrying the method and id, so
+    /// every log emitted while handling it can be tied back to the request.
+    async fn handle_request(
+        &self,
+        req: JsonRpcRequest,
+        outgoing: Option<mpsc::Sender<Message>>,
+    ) -> JsonRpcResponse {
+        let span = info_span!("request", method = %req.method, id = %req.id);
+        self.dispatch_request(req, outgoing).instrument(span).await
+    }
+
+    async fn dispatch_request(
+        &self,
+        req: JsonRpcRequest,
+        outgoing: Option<mpsc::Sender<Message>>,
+    ) -> JsonRpcResponse {
+        debug!("Handling request");
 
         let result = match req.method.as_str() {
             "initialize" => self.handle_initialize(req.params).await,
             "tools/list" => self.handle_list_tools().await,
-            "tools/call" => self.handle_call_tool(req.params).await,
+            "tools/call" => self.handle_call_tool(req.params, outgoing).await,
+            "prompts/list" => self.handle_list_prompts().await,
+            "prompts/get" => self.handle_get_prompt(req.params).await,
+            "resources/list" => self.handle_list_resources().await,
+            "resources/read" => self.handle_read_resource(req.params).await,
+            "completion/complete" => self.handle_completion(req.params).await,
             "ping" => Ok(serde_json::json!({})),
             _ => Err(Error::McpProtocol(format!(
                 "Unknown method: {}",
@@ -83,7 +252,15 @@ impl McpServer {
                 id: req.id,
                 result: None,
                 error: Some(JsonRpcError {
-                    code: error_codes::INTERNAL_ERROR,
+                    code: match e {
+                        Error::InvalidToolArguments(_) | Error::InvalidPath(_) => {
+                            error_codes::INVALID_PARAMS
+                        }
+                        Error::ResourceNotFound(_) | Error::FileNotFound(_) => {
+                            error_codes::RESOURCE_NOT_FOUND
+                        }
+                        _ => error_codes::INTERNAL_ERROR,
+                    },
                     message: e.to_string(),
                     data: None,
                 }),
@@ -114,9 +291,15 @@ impl McpServer {
             protocol_version: MCP_VERSION.to_string(),
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability { list_changed: true }),
-                resources: None,
-                prompts: None,
+                resources: self.resources.as_ref().map(|_| ResourcesCapability {
+                    subscribe: false,
+                    list_changed: false,
+                }),
+                prompts: Some(PromptsCapability {
+                    list_changed: false,
+                }),
                 logging: Some(LoggingCapability {}),
+                completions: Some(CompletionsCapability {}),
             },
             server_info: ServerInfo {
                 name: self.name.clone(),
@@ -134,8 +317,74 @@ impl McpServer {
         Ok(serde_json::to_value(result)?)
     }
 
+    /// Handle list prompts request.
+    async fn handle_list_prompts(&self) -> Result<Value> {
+        let result = ListPromptsResult {
+            prompts: self.prompts.list(),
+        };
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle get prompt request.
+    async fn handle_get_prompt(&self, params: Option<Value>) -> Result<Value> {
+        let params: GetPromptParams = params
+            .ok_or_else(|| Error::InvalidToolArguments("Missing params".to_string()))
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| Error::InvalidToolArguments(e.to_string()))
+            })?;
+
+        let result = self.prompts.get(&params.name, &params.arguments)?;
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle list resources request.
+    async fn handle_list_resources(&self) -> Result<Value> {
+        let result = ListResourcesResult {
+            resources: self
+                .resources
+                .as_ref()
+                .map(ResourceRegistry::list)
+                .unwrap_or_default(),
+        };
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle read resource request.
+    async fn handle_read_resource(&self, params: Option<Value>) -> Result<Value> {
+        let params: ReadResourceParams = params
+            .ok_or_else(|| Error::InvalidToolArguments("Missing params".to_string()))
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| Error::InvalidToolArguments(e.to_string()))
+            })?;
+
+        let resources = self
+            .resources
+            .as_ref()
+            .ok_or_else(|| Error::ResourceNotFound(params.uri.clone()))?;
+        let result = resources.read(&params.uri).await?;
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle completion request.
+    async fn handle_completion(&self, params: Option<Value>) -> Result<Value> {
+        let params: CompleteParams = params
+            .ok_or_else(|| Error::InvalidToolArguments("Missing params".to_string()))
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| Error::InvalidToolArguments(e.to_string()))
+            })?;
+
+        let result = CompleteResult {
+            completion: completion::complete(&self.handler, self.context.as_deref(), &params).await,
+        };
+        Ok(serde_json::to_value(result)?)
+    }
+
     /// Handle call tool request.
-    async fn handle_call_tool(&self, params: Option<Value>) -> Result<Value> {
+    async fn handle_call_tool(
+        &self,
+        params: Option<Value>,
+        outgoing: Option<mpsc::Sender<Message>>,
+    ) -> Result<Value> {
         let params: CallToolParams = params
             .ok_or_else(|| Error::InvalidToolArguments("Missing params".to_string()))
             .and_then(|v| {
@@ -146,8 +395,538 @@ impl McpServer {
             .handler
             .get_tool(&params.name)
             .ok_or_else(|| Error::ToolNotFound(params.name.clone()))?;
+        self.handler
+            .validate_arguments(&params.name, &params.arguments)?;
+        if let Err(e) = self.handler.check_writable(&params.name) {
+            return Ok(serde_json::to_value(tool_error_result("", &e))?);
+        }
+
+        let progress = params
+            .meta
+            .and_then(|meta| meta.progress_token)
+            .zip(outgoing)
+            .map(|(token, outgoing)| ProgressReporter::new(token, outgoing));
 
-        let result = handler.execute(params.arguments).await?;
+        let _slot = self.handler.acquire_execution_slot().await;
+        debug!(tool = %params.name, "Calling tool");
+
+        // Execution failures are reported in the result, not as protocol errors
+        let result = match handler
+            .execute_with_progress(params.arguments, progress)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => tool_error_result("", &e),
+        };
         Ok(serde_json::to_value(result)?)
     }
 }
+
+/// The next tick of `interval`, or never without one.
+async fn next_tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolve when the process receives SIGINT (Ctrl-C) or SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutdown signal received");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::mcp::handler::{success_result, ToolHandler};
+    use crate::sdk::mock_backend::MockBackend;
+    use crate::tools::index::IndexWorkspaceTool;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+    use tokio::sync::{mpsc, oneshot};
+
+    /// Transport backed by in-memory channels.
+    struct ChannelTransport {
+        incoming: Option<mpsc::Receiver<Message>>,
+        outgoing: mpsc::Sender<Message>,
+    }
+
+    #[async_trait]
+    impl Transport for ChannelTransport {
+        async fn start(&mut self) -> Result<(mpsc::Receiver<Message>, mpsc::Sender<Message>)> {
+            Ok((self.incoming.take().unwrap(), self.outgoing.clone()))
+        }
+
+        async fn stop(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct SlowTool;
+
+    #[async_trait]
+    impl ToolHandler for SlowTool {
+        fn definition(&self) -> Tool {
+            Tool {
+                name: "slow".to_string(),
+                description: "Sleeps before answering".to_string(),
+                input_schema: serde_json::json!({ "type": "object" }),
+            }
+        }
+
+        async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(success_result("done"))
+        }
+    }
+
+    fn call_request(id: i64, name: &str) -> Message {
+        Message::Request(JsonRpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: RequestId::Number(id),
+            method: "tools/call".to_string(),
+            params: Some(serde_json::json!({ "name": name, "arguments": {} })),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_logs_carry_request_span() {
+        let capture = crate::logging::LogCapture::default();
+        let _guard = tracing::subscriber::set_default(capture.json_subscriber());
+
+        let mut handler = McpHandler::new();
+        handler.register(SlowTool);
+        let server = McpServer::new(handler, "test");
+        let Message::Request(req) = call_request(42, "slow") else {
+            unreachable!()
+        };
+        let response = server.handle_request(req, None).await;
+        assert!(response.error.is_none());
+
+        let lines = capture.json_lines();
+        let call = lines
+            .iter()
+            .find(|line| line["message"] == "Calling tool")
+            .expect("tool call is logged");
+        assert_eq!(call["tool"], "slow");
+        assert_eq!(call["span"]["name"], "request");
+        assert_eq!(call["span"]["method"], "tools/call");
+        assert_eq!(call["span"]["id"], "42");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments_are_rejected_before_execute() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let mut handler = McpHandler::new();
+        handler.register(crate::tools::retrieval::GetFileTool::new(service));
+        let server = McpServer::new(handler, "test");
+
+        let response = server
+            .handle_request(
+                JsonRpcRequest {
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    id: RequestId::Number(1),
+                    method: "tools/call".to_string(),
+                    params: Some(serde_json::json!({
+                        "name": "get_file",
+                        "arguments": { "path": 42 }
+                    })),
+                },
+                None,
+            )
+            .await;
+        let error = response.error.expect("validation error");
+        assert_eq!(error.code, error_codes::INVALID_PARAMS);
+        assert!(error.message.contains("/path"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_prompts_get_interpolates_arguments() {
+        let server = McpServer::new(McpHandler::new(), "test");
+
+        let list = server
+            .handle_request(
+                JsonRpcRequest {
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    id: RequestId::Number(1),
+                    method: "prompts/list".to_string(),
+                    params: None,
+                },
+                None,
+            )
+            .await;
+        let prompts = list.result.unwrap()["prompts"].as_array().unwrap().clone();
+        assert!(prompts.iter().any(|p| p["name"] == "explain_symbol"));
+
+        let response = server
+            .handle_request(
+                JsonRpcRequest {
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    id: RequestId::Number(2),
+                    method: "prompts/get".to_string(),
+                    params: Some(serde_json::json!({
+                        "name": "explain_symbol",
+                        "arguments": { "symbol": "ContextService::search" }
+                    })),
+                },
+                None,
+            )
+            .await;
+        let result = response.result.unwrap();
+        assert_eq!(result["messages"][0]["role"], "user");
+        let text = result["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.contains("`ContextService::search`"));
+
+        let response = server
+            .handle_request(
+                JsonRpcRequest {
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    id: RequestId::Number(3),
+                    method: "prompts/get".to_string(),
+                    params: Some(serde_json::json!({ "name": "explain_symbol" })),
+                },
+                None,
+            )
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_blocks_mutating_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("lib.rs"), "fn parse_config() {}\n").unwrap();
+        let config = Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let context = Arc::new(ContextService::new(&config).await.unwrap());
+        let memory = Arc::new(
+            crate::service::MemoryService::new(temp_dir.path())
+                .await
+                .unwrap(),
+        );
+        let mut handler = McpHandler::new();
+        handler.register(crate::tools::memory::StoreMemoryTool::new(memory.clone()));
+        handler.register(crate::tools::retrieval::GrepTool::new(context.clone()));
+        handler.register(crate::tools::review::RunStaticAnalysisTool::new(context));
+        handler.set_read_only(true);
+        let server = McpServer::new(handler, "test");
+
+        let call = |id: i64, name: &str, arguments: Value| JsonRpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: RequestId::Number(id),
+            method: "tools/call".to_string(),
+            params: Some(serde_json::json!({ "name": name, "arguments": arguments })),
+        };
+
+        let response = server
+            .handle_request(
+                call(
+                    1,
+                    "add_memory",
+                    serde_json::json!({ "key": "k", "value": "v" }),
+                ),
+                None,
+            )
+            .await;
+        let result = response.result.unwrap();
+        assert_eq!(result["is_error"], true);
+        assert_eq!(result["error"]["code"], "read_only");
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Server is read-only"));
+        assert!(memory.list("default", None).await.unwrap().is_empty());
+        assert!(!temp_dir
+            .path()
+            .join(".context-engine/memory/default/memory.json")
+            .exists());
+
+        let response = server
+            .handle_request(
+                call(2, "grep", serde_json::json!({ "pattern": "parse_config" })),
+                None,
+            )
+            .await;
+        let result = response.result.unwrap();
+        assert_eq!(result["is_error"], false);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("lib.rs"));
+
+        let response = server
+            .handle_request(
+                call(
+                    3,
+                    "run_static_analysis",
+                    serde_json::json!({ "linters": ["clippy"] }),
+                ),
+                None,
+            )
+            .await;
+        let result = response.result.unwrap();
+        assert_eq!(result["is_error"], true);
+        assert_eq!(result["error"]["code"], "read_only");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_requests() {
+        let mut handler = McpHandler::new();
+        handler.register(SlowTool);
+        let server = McpServer::new(handler, "test");
+
+        let (in_tx, in_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = ChannelTransport {
+            incoming: Some(in_rx),
+            outgoing: out_tx,
+        };
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let run = tokio::spawn(async move {
+            server
+                .run_until(transport, async {
+                    let _ = shutdown_rx.await;
+                })
+                .await
+        });
+
+        in_tx.send(call_request(1, "slow")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown_tx.send(()).unwrap();
+
+        run.await.unwrap().unwrap();
+
+        match out_rx.recv().await {
+            Some(Message::Response(response)) => {
+                assert_eq!(response.id, RequestId::Number(1));
+                assert!(response.error.is_none());
+            }
+            other => panic!("expected a response, got {:?}", other),
+        }
+
+        // Requests arriving after shutdown are not processed.
+        assert!(in_tx.send(call_request(2, "slow")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ping_is_answered_during_slow_tool_call() {
+        let mut handler = McpHandler::new();
+        handler.register(SlowTool);
+        let server =
+            McpServer::new(handler, "test").with_keepalive(Some(Duration::from_millis(50)));
+
+        let (in_tx, in_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = ChannelTransport {
+            incoming: Some(in_rx),
+            outgoing: out_tx,
+        };
+        let run = tokio::spawn(async move {
+            server
+                .run_until(transport, std::future::pending::<()>())
+                .await
+        });
+
+        in_tx.send(call_request(1, "slow")).await.unwrap();
+        in_tx
+            .send(Message::Request(JsonRpcRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: RequestId::Number(2),
+                method: "ping".to_string(),
+                params: None,
+            }))
+            .await
+            .unwrap();
+
+        // The ping is answered first; keepalive pings go out while the tool runs
+        let mut responses = Vec::new();
+        let mut keepalive_ids = Vec::new();
+        while responses.len() < 2 {
+            match tokio::time::timeout(Duration::from_secs(2), out_rx.recv()).await {
+                Ok(Some(Message::Response(response))) => responses.push(response.id),
+                Ok(Some(Message::Request(request))) => {
+                    assert_eq!(request.method, "ping");
+                    keepalive_ids.push(request.id);
+                }
+                other => panic!("unexpected output: {:?}", other),
+            }
+        }
+        assert_eq!(responses, vec![RequestId::Number(2), RequestId::Number(1)]);
+        assert_eq!(
+            keepalive_ids.first(),
+            Some(&RequestId::String("keepalive-1".to_string()))
+        );
+
+        // The client's answer to a keepalive ping is accepted quietly
+        in_tx
+            .send(Message::Response(JsonRpcResponse {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: keepalive_ids[0].clone(),
+                result: Some(serde_json::json!({})),
+                error: None,
+            }))
+            .await
+            .unwrap();
+        drop(in_tx);
+        run.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_shuts_down_without_traffic() {
+        let server = McpServer::new(McpHandler::new(), "test")
+            .with_idle_timeout(Some(Duration::from_millis(150)));
+
+        // Keep the input open so only the idle timer can end the run
+        let (in_tx, in_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = ChannelTransport {
+            incoming: Some(in_rx),
+            outgoing: out_tx,
+        };
+
+        let started = std::time::Instant::now();
+        let run = tokio::spawn(async move {
+            server
+                .run_until(transport, std::future::pending::<()>())
+                .await
+        });
+
+        // Activity restarts the window
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        in_tx
+            .send(Message::Request(JsonRpcRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: RequestId::Number(1),
+                method: "ping".to_string(),
+                params: None,
+            }))
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), run)
+            .await
+            .expect("idle timeout fires")
+            .unwrap()
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_emits_progress_notifications() {
+        let backend = MockBackend::start().await;
+        backend.set_delay("batch-upload", Duration::from_millis(50));
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..200 {
+            std::fs::write(
+                temp_dir.path().join(format!("file_{}.rs", i)),
+                format!("fn f{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+
+        // Small, serial batches so indexing spans several progress intervals
+        let config = Config {
+            index_batch_size: 10,
+            index_concurrency: 1,
+            ..backend.config(temp_dir.path())
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let mut handler = McpHandler::new();
+        handler.register(IndexWorkspaceTool::new(service));
+        let server = McpServer::new(handler, "test");
+
+        let (in_tx, in_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(64);
+        let transport = ChannelTransport {
+            incoming: Some(in_rx),
+            outgoing: out_tx,
+        };
+        let run = tokio::spawn(async move {
+            server
+                .run_until(transport, std::future::pending::<()>())
+                .await
+        });
+
+        in_tx
+            .send(Message::Request(JsonRpcRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: RequestId::Number(1),
+                method: "tools/call".to_string(),
+                params: Some(serde_json::json!({
+                    "name": "index_workspace",
+                    "arguments": {},
+                    "_meta": { "progressToken": "index-1" }
+                })),
+            }))
+            .await
+            .unwrap();
+
+        let mut updates = Vec::new();
+        loop {
+            match out_rx.recv().await {
+                Some(Message::Notification(n)) => {
+                    assert_eq!(n.method, "notifications/progress");
+                    let params: ProgressParams = serde_json::from_value(n.params.unwrap()).unwrap();
+                    assert_eq!(params.progress_token, "index-1");
+                    assert_eq!(params.total, Some(200));
+                    updates.push(params.progress);
+                }
+                Some(Message::Response(response)) => {
+                    assert_eq!(response.id, RequestId::Number(1));
+                    assert!(response.error.is_none());
+                    break;
+                }
+                other => panic!("unexpected message: {:?}", other),
+            }
+        }
+        drop(in_tx);
+        run.await.unwrap().unwrap();
+
+        assert!(
+            updates.iter().any(|&done| done < 200),
+            "expected intermediate progress, saw {:?}",
+            updates
+        );
+        assert!(updates.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(updates.last(), Some(&200));
+    }
+}
@@ -7,10 +7,12 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
-use uuid::Uuid;
 
+use crate::clock::{system_clock, SharedClock};
 use crate::error::{Error, Result};
-use crate::reviewer::{ReviewConfig, ReviewPipeline};
+use crate::reviewer::{
+    default_id_gen, FileFindingsCache, ReviewConfig, ReviewPipeline, SharedIdGen,
+};
 use crate::service::ContextService;
 use crate::types::review::*;
 
@@ -25,6 +27,12 @@ pub struct ReviewSession {
     pub target: String,
     /// Reviews in this session
     pub reviews: Vec<Review>,
+    /// Files whose findings were reused because their diff was unchanged
+    #[serde(default)]
+    pub cache_hits: usize,
+    /// Files analyzed across the session's reviews
+    #[serde(default)]
+    pub files_analyzed: usize,
     /// Session metadata
     pub metadata: HashMap<String, serde_json::Value>,
     /// Creation timestamp
@@ -44,10 +52,17 @@ pub enum SessionStatus {
 }
 
 /// Reactive review manager.
+///
+/// Each session caches the findings of every file diff it has analyzed, so
+/// re-reviewing an updated diff (say, after a force-push) only analyzes the
+/// files whose diff changed.
 pub struct ReactiveReviewManager {
     context_service: Arc<ContextService>,
     sessions: Arc<RwLock<HashMap<String, ReviewSession>>>,
+    file_caches: Arc<RwLock<HashMap<String, FileFindingsCache>>>,
     config: ReviewConfig,
+    ids: SharedIdGen,
+    clock: SharedClock,
 }
 
 impl ReactiveReviewManager {
@@ -56,20 +71,37 @@ impl ReactiveReviewManager {
         Self {
             context_service,
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            file_caches: Arc::new(RwLock::new(HashMap::new())),
             config,
+            ids: default_id_gen(),
+            clock: system_clock(),
         }
     }
 
+    /// Generate session, review and finding ids with `ids` instead of random UUIDs.
+    pub fn with_id_gen(mut self, ids: SharedIdGen) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Stamp sessions and reviews with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Start a new review session.
     pub async fn start_session(&self, target: String) -> Result<ReviewSession> {
-        let id = Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().to_rfc3339();
+        let id = self.ids.next_id();
+        let now = self.clock.now_rfc3339();
 
         let session = ReviewSession {
             id: id.clone(),
             status: SessionStatus::Active,
             target,
             reviews: Vec::new(),
+            cache_hits: 0,
+            files_analyzed: 0,
             metadata: HashMap::new(),
             created_at: now.clone(),
             updated_at: now,
@@ -101,21 +133,50 @@ impl ReactiveReviewManager {
     }
 
     /// Add a review to a session.
+    ///
+    /// Files whose diff section is unchanged since an earlier review in the
+    /// session reuse that review's findings instead of being re-analyzed.
     pub async fn add_review(&self, session_id: &str, diff: &str) -> Result<Review> {
-        let pipeline = ReviewPipeline::new(self.context_service.clone(), self.config.clone());
-        let review = pipeline.review_diff(diff, None).await?;
+        if !self.sessions.read().await.contains_key(session_id) {
+            return Err(Error::ReviewSessionNotFound(session_id.to_string()));
+        }
 
-        {
-            let mut sessions = self.sessions.write().await;
-            let session = sessions
-                .get_mut(session_id)
-                .ok_or_else(|| Error::ReviewSessionNotFound(session_id.to_string()))?;
+        let pipeline = ReviewPipeline::new(self.context_service.clone(), self.config.clone())
+            .with_id_gen(self.ids.clone())
+            .with_clock(self.clock.clone())
+            .with_workspace_baseline()
+            .await?;
+        let mut cache = self
+            .file_caches
+            .write()
+            .await
+            .remove(session_id)
+            .unwrap_or_default();
+        let result = pipeline.review_diff_incremental(diff, &mut cache).await;
+        self.file_caches
+            .write()
+            .await
+            .insert(session_id.to_string(), cache);
+        let result = result?;
 
-            session.reviews.push(review.clone());
-            session.updated_at = chrono::Utc::now().to_rfc3339();
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| Error::ReviewSessionNotFound(session_id.to_string()))?;
+        session.reviews.push(result.review.clone());
+        session.cache_hits += result.cache_hits;
+        session.files_analyzed += result.analyzed.len();
+        session.updated_at = self.clock.now_rfc3339();
+        if result.cache_hits > 0 {
+            info!(
+                "Session {}: reused findings for {} unchanged file(s), analyzed {}",
+                session_id,
+                result.cache_hits,
+                result.analyzed.len()
+            );
         }
 
-        Ok(review)
+        Ok(result.review)
     }
 
     /// Complete a session.
@@ -126,7 +187,8 @@ impl ReactiveReviewManager {
             .ok_or_else(|| Error::ReviewSessionNotFound(id.to_string()))?;
 
         session.status = SessionStatus::Completed;
-        session.updated_at = chrono::Utc::now().to_rfc3339();
+        session.updated_at = self.clock.now_rfc3339();
+        self.file_caches.write().await.remove(id);
 
         info!("Completed review session: {}", id);
         Ok(session.clone())
@@ -140,7 +202,8 @@ impl ReactiveReviewManager {
             .ok_or_else(|| Error::ReviewSessionNotFound(id.to_string()))?;
 
         session.status = SessionStatus::Cancelled;
-        session.updated_at = chrono::Utc::now().to_rfc3339();
+        session.updated_at = self.clock.now_rfc3339();
+        self.file_caches.write().await.remove(id);
 
         info!("Cancelled review session: {}", id);
         Ok(session.clone())
@@ -159,6 +222,7 @@ impl ReactiveReviewManager {
                 SessionStatus::Paused => stats.paused += 1,
             }
             stats.total_reviews += session.reviews.len();
+            stats.cache_hits += session.cache_hits;
         }
         stats.total_sessions = sessions.len();
 
@@ -175,11 +239,15 @@ pub struct SessionStats {
     pub cancelled: usize,
     pub paused: usize,
     pub total_reviews: usize,
+    /// Files whose findings were reused across all sessions
+    #[serde(default)]
+    pub cache_hits: usize,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::FixedClock;
 
     #[test]
     fn test_session_status_serialization() {
@@ -203,6 +271,8 @@ mod tests {
             status: SessionStatus::Active,
             target: "feature/test-branch".to_string(),
             reviews: Vec::new(),
+            cache_hits: 0,
+            files_analyzed: 0,
             metadata: HashMap::new(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
@@ -219,6 +289,8 @@ mod tests {
             status: SessionStatus::Active,
             target: "main".to_string(),
             reviews: Vec::new(),
+            cache_hits: 0,
+            files_analyzed: 0,
             metadata: HashMap::new(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
@@ -252,6 +324,7 @@ mod tests {
             cancelled: 1,
             paused: 1,
             total_reviews: 25,
+            cache_hits: 4,
         };
 
         let json = serde_json::to_string(&stats).unwrap();
@@ -272,6 +345,8 @@ mod tests {
             status: SessionStatus::Active,
             target: "PR #123".to_string(),
             reviews: Vec::new(),
+            cache_hits: 0,
+            files_analyzed: 0,
             metadata,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
@@ -283,4 +358,79 @@ mod tests {
             &serde_json::json!(123)
         );
     }
+
+    #[tokio::test]
+    async fn test_ids_and_timestamps_are_predictable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = crate::config::Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..crate::config::Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let fixed = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let manager = ReactiveReviewManager::new(service, ReviewConfig::default())
+            .with_id_gen(Arc::new(crate::reviewer::CounterIdGen::new("id")))
+            .with_clock(Arc::new(FixedClock(fixed)));
+
+        let session = manager
+            .start_session("feature/x".to_string())
+            .await
+            .unwrap();
+        assert_eq!(session.id, "id-1");
+        assert_eq!(session.created_at, "2024-05-01T12:00:00+00:00");
+
+        let diff = "--- a/src/config.rs\n+++ b/src/config.rs\n+let password = \"hunter2\";\n";
+        let review = manager.add_review(&session.id, diff).await.unwrap();
+        assert_eq!(review.id, "id-2");
+        assert_eq!(review.created_at, fixed.to_rfc3339());
+        assert_eq!(review.findings.len(), 1);
+        assert_eq!(review.findings[0].id, "id-3");
+    }
+
+    #[tokio::test]
+    async fn test_readded_diff_only_reanalyzes_changed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = crate::config::Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..crate::config::Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let manager = ReactiveReviewManager::new(service, ReviewConfig::default());
+        let session = manager.start_session("PR #7".to_string()).await.unwrap();
+
+        let unchanged = "diff --git a/src/config.rs b/src/config.rs\n--- a/src/config.rs\n+++ b/src/config.rs\n@@ -1,1 +1,2 @@\n fn load() {}\n+let password = \"hunter2\";\n";
+        let first = format!(
+            "{}diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,2 @@\n fn a() {{}}\n+fn b() {{}}\n",
+            unchanged
+        );
+        manager.add_review(&session.id, &first).await.unwrap();
+        let session_state = manager.get_session(&session.id).await.unwrap();
+        assert_eq!(session_state.files_analyzed, 2);
+        assert_eq!(session_state.cache_hits, 0);
+
+        // Force-push: only src/lib.rs changed
+        let second = format!(
+            "{}diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,2 @@\n fn a() {{}}\n+fn c() {{}}\n",
+            unchanged
+        );
+        let review = manager.add_review(&session.id, &second).await.unwrap();
+        assert_eq!(review.files.len(), 2);
+        // The cached file keeps its finding
+        assert!(review
+            .findings
+            .iter()
+            .any(|f| f.file == "src/config.rs" && f.title == "Password in code"));
+        let session_state = manager.get_session(&session.id).await.unwrap();
+        assert_eq!(session_state.files_analyzed, 3); // just src/lib.rs again
+        assert_eq!(session_state.cache_hits, 1);
+        assert_eq!(manager.get_stats().await.cache_hits, 1);
+
+        // Caches are per session
+        let other = manager.start_session("PR #8".to_string()).await.unwrap();
+        manager.add_review(&other.id, &second).await.unwrap();
+        let other_state = manager.get_session(&other.id).await.unwrap();
+        assert_eq!((other_state.cache_hits, other_state.files_analyzed), (0, 2));
+    }
 }
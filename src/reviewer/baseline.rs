@@ -0,0 +1,179 @@
+//! Baseline of known findings, for adopting the reviewer on legacy code.
+//!
+//! A baseline is a set of finding fingerprints saved in
+//! `.context-engine/baseline.json`. Reviews drop findings whose fingerprint
+//! is in it, so only new issues surface. A fingerprint hashes the finding
+//! type, the file and the flagged line's text with whitespace collapsed;
+//! line numbers are left out, so a known issue stays suppressed when code
+//! above it moves. Findings without a file, such as the AI analysis, are
+//! never suppressed.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::service::persist::{load_json, write_atomic};
+use crate::types::review::Finding;
+
+/// Baseline location, relative to the workspace.
+pub const BASELINE_FILE: &str = ".context-engine/baseline.json";
+
+/// Hex characters of the SHA-256 kept in a fingerprint.
+const FINGERPRINT_LEN: usize = 32;
+
+/// Where the baseline of `workspace` is stored.
+pub fn baseline_path(workspace: &Path) -> PathBuf {
+    workspace.join(BASELINE_FILE)
+}
+
+/// The text of the flagged line in a finding's snippet, whitespace collapsed.
+///
+/// Snippets rendered with line numbers contribute only the marked line, so
+/// neither numbers nor neighbouring lines affect the fingerprint.
+fn normalized_snippet(snippet: &str) -> String {
+    let marked = snippet
+        .lines()
+        .find_map(|line| Some(line.strip_prefix("> ")?.split_once(" | ")?.1));
+    marked
+        .unwrap_or(snippet)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The stable fingerprint of `finding`, or `None` if it names no file.
+pub fn fingerprint(finding: &Finding) -> Option<String> {
+    if finding.file.is_empty() {
+        return None;
+    }
+    let finding_type = serde_json::to_string(&finding.finding_type).unwrap_or_default();
+    let snippet = finding
+        .code_snippet
+        .as_deref()
+        .map(normalized_snippet)
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    for part in [finding_type.as_str(), finding.file.as_str(), &snippet] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let mut fingerprint = hex::encode(hasher.finalize());
+    fingerprint.truncate(FINGERPRINT_LEN);
+    Some(fingerprint)
+}
+
+/// A set of known finding fingerprints.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub fingerprints: BTreeSet<String>,
+}
+
+impl Baseline {
+    /// A baseline of `findings`.
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        let mut baseline = Self::default();
+        baseline.extend(findings);
+        baseline
+    }
+
+    /// Add `findings` to the baseline.
+    pub fn extend(&mut self, findings: &[Finding]) {
+        self.fingerprints
+            .extend(findings.iter().filter_map(fingerprint));
+    }
+
+    /// Whether `finding` is a known one.
+    pub fn contains(&self, finding: &Finding) -> bool {
+        fingerprint(finding).is_some_and(|f| self.fingerprints.contains(&f))
+    }
+
+    /// Drop known findings, returning the rest and how many were dropped.
+    pub fn filter(&self, findings: Vec<Finding>) -> (Vec<Finding>, usize) {
+        let total = findings.len();
+        let kept: Vec<Finding> = findings.into_iter().filter(|f| !self.contains(f)).collect();
+        let suppressed = total - kept.len();
+        (kept, suppressed)
+    }
+
+    /// Load the baseline at `path`, if there is one.
+    pub async fn load(path: &Path) -> Result<Option<Self>> {
+        load_json(path).await
+    }
+
+    /// Save the baseline to `path`.
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, &serde_json::to_vec_pretty(self)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::review::{FindingType, Severity};
+
+    fn finding(file: &str, snippet: &str) -> Finding {
+        Finding {
+            id: "f".to_string(),
+            finding_type: FindingType::Security,
+            severity: Severity::Critical,
+            title: "Password in code".to_string(),
+            description: String::new(),
+            file: file.to_string(),
+            line: Some(3),
+            line_range: None,
+            suggestion: None,
+            code_snippet: Some(snippet.to_string()),
+            actionable: true,
+            category: None,
+            occurrences: 1,
+        }
+    }
+
+    #[test]
+    fn test_fingerprints_ignore_line_numbers_and_neighbours() {
+        let before = finding(
+            "src/db.rs",
+            "```\n  2 | let user = \"app\";\n> 3 |     let password = \"hunter2\";\n```",
+        );
+        let moved = finding(
+            "src/db.rs",
+            "```\n  40 | connect();\n> 41 | let  password = \"hunter2\";\n  42 | }\n```",
+        );
+        assert_eq!(fingerprint(&before), fingerprint(&moved));
+        assert_ne!(
+            fingerprint(&before),
+            fingerprint(&finding("src/api.rs", "let password = \"hunter2\";"))
+        );
+
+        let mut unlocated = finding("", "password = \"x\"");
+        assert_eq!(fingerprint(&unlocated), None);
+        unlocated.file = "a.py".to_string();
+        assert!(fingerprint(&unlocated).is_some());
+    }
+
+    #[test]
+    fn test_filter_keeps_new_findings() {
+        let known = finding("src/db.rs", "let password = \"hunter2\";");
+        let baseline = Baseline::from_findings(std::slice::from_ref(&known));
+        let new = finding("src/db.rs", "let token = \"abc\";");
+
+        let (kept, suppressed) = baseline.filter(vec![known, new.clone()]);
+        assert_eq!(suppressed, 1);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].code_snippet, new.code_snippet);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = baseline_path(temp.path());
+        assert_eq!(Baseline::load(&path).await.unwrap(), None);
+
+        let baseline = Baseline::from_findings(&[finding("a.rs", "x")]);
+        baseline.save(&path).await.unwrap();
+        assert_eq!(Baseline::load(&path).await.unwrap(), Some(baseline));
+    }
+}
@@ -0,0 +1,326 @@
+//! Compressed diffs and patches.
+//!
+//! Large patches are often passed around gzip-compressed or zipped. Before a
+//! diff is parsed, [`decompress`] sniffs its first bytes: gzip data
+//! (`1f 8b`) is inflated, a zip archive (`PK\x03\x04`) must hold a single
+//! file, which is read, and anything else is taken as plain text. Tool
+//! arguments are strings, so [`decode_diff_arg`] accepts compressed diffs
+//! encoded as base64 and leaves ordinary diffs untouched.
+//!
+//! Output is capped at [`MAX_DECOMPRESSED_SIZE`] so a small archive cannot
+//! expand into gigabytes.
+
+use base64::Engine;
+use tokio::io::AsyncReadExt;
+
+use crate::error::{Error, Result};
+
+/// Most bytes a compressed diff may expand to.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Base64 of the magic bytes, marking an encoded compressed diff.
+const GZIP_BASE64_PREFIX: &str = "H4sI";
+const ZIP_BASE64_PREFIX: &str = "UEsDB";
+
+const ZIP_END_MAGIC: &[u8] = b"PK\x05\x06";
+const ZIP_CENTRAL_MAGIC: &[u8] = b"PK\x01\x02";
+const ZIP_END_LEN: usize = 22;
+const ZIP_CENTRAL_LEN: usize = 46;
+const ZIP_LOCAL_LEN: usize = 30;
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+
+/// How a diff is compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zip,
+}
+
+impl Compression {
+    /// The compression of `bytes`, judged by their magic bytes.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if bytes.starts_with(ZIP_MAGIC) {
+            Self::Zip
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// The diff in `bytes`, decompressed if it is gzip or zip data.
+///
+/// Fails if the data expands past `limit` bytes, or if a zip archive does
+/// not hold exactly one file.
+pub async fn decompress(bytes: &[u8], limit: usize) -> Result<String> {
+    let text = match Compression::detect(bytes) {
+        Compression::None => return Ok(String::from_utf8_lossy(bytes).into_owned()),
+        Compression::Gzip => {
+            let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(bytes);
+            decoder.multiple_members(true);
+            read_capped(decoder, limit).await?
+        }
+        Compression::Zip => {
+            let entry = single_zip_entry(bytes)?;
+            if entry.size > limit {
+                return Err(too_large(limit));
+            }
+            match entry.method {
+                ZIP_STORED => entry.data.to_vec(),
+                ZIP_DEFLATED => {
+                    let decoder =
+                        async_compression::tokio::bufread::DeflateDecoder::new(entry.data);
+                    read_capped(decoder, limit).await?
+                }
+                method => {
+                    return Err(Error::InvalidToolArguments(format!(
+                        "Unsupported zip compression method {} for '{}'",
+                        method, entry.name
+                    )))
+                }
+            }
+        }
+    };
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+/// A `diff` tool argument, decompressed if it is base64-encoded gzip or zip
+/// data.
+pub async fn decode_diff_arg(diff: &str, limit: usize) -> Result<String> {
+    let trimmed = diff.trim_start();
+    if !trimmed.starts_with(GZIP_BASE64_PREFIX) && !trimmed.starts_with(ZIP_BASE64_PREFIX) {
+        return Ok(diff.to_string());
+    }
+    let encoded: String = trimmed.split_whitespace().collect();
+    match base64::engine::general_purpose::STANDARD.decode(encoded) {
+        Ok(bytes) => decompress(&bytes, limit).await,
+        Err(_) => Ok(diff.to_string()),
+    }
+}
+
+/// Everything `reader` yields, failing once it passes `limit` bytes.
+async fn read_capped<R: tokio::io::AsyncRead + Unpin>(reader: R, limit: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    reader
+        .take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .await
+        .map_err(|e| Error::InvalidToolArguments(format!("Invalid compressed diff: {}", e)))?;
+    if out.len() > limit {
+        return Err(too_large(limit));
+    }
+    Ok(out)
+}
+
+fn too_large(limit: usize) -> Error {
+    Error::InvalidToolArguments(format!(
+        "Compressed diff expands past the {} byte limit",
+        limit
+    ))
+}
+
+/// The one file of a zip archive.
+struct ZipEntry<'a> {
+    name: String,
+    method: u16,
+    /// Declared uncompressed size
+    size: usize,
+    /// The file's data as stored
+    data: &'a [u8],
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?) as usize)
+}
+
+/// The single file in the zip archive `bytes`, read through its central
+/// directory. Directory entries are ignored.
+fn single_zip_entry(bytes: &[u8]) -> Result<ZipEntry<'_>> {
+    let invalid = || Error::InvalidToolArguments("Invalid zip archive".to_string());
+    let end = (0..=bytes.len().saturating_sub(ZIP_END_LEN))
+        .rev()
+        .find(|&at| bytes[at..].starts_with(ZIP_END_MAGIC))
+        .ok_or_else(invalid)?;
+    let mut at = u32_at(bytes, end + 16).ok_or_else(invalid)?;
+    let entries = u16_at(bytes, end + 10).ok_or_else(invalid)?;
+
+    let mut files = Vec::new();
+    for _ in 0..entries {
+        if !bytes
+            .get(at..)
+            .is_some_and(|b| b.starts_with(ZIP_CENTRAL_MAGIC))
+        {
+            return Err(invalid());
+        }
+        let field = |offset| u16_at(bytes, at + offset).map(usize::from);
+        let (name_len, extra_len, comment_len) = (field(28), field(30), field(32));
+        let (Some(name_len), Some(extra_len), Some(comment_len)) =
+            (name_len, extra_len, comment_len)
+        else {
+            return Err(invalid());
+        };
+        let name = bytes
+            .get(at + ZIP_CENTRAL_LEN..at + ZIP_CENTRAL_LEN + name_len)
+            .ok_or_else(invalid)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        if !name.ends_with('/') {
+            files.push((name, at));
+        }
+        at += ZIP_CENTRAL_LEN + name_len + extra_len + comment_len;
+    }
+
+    let [(name, central)] = <[_; 1]>::try_from(files).map_err(|files| {
+        Error::InvalidToolArguments(format!(
+            "Zip archive holds {} files; expected a single diff or patch",
+            files.len()
+        ))
+    })?;
+    let method = u16_at(bytes, central + 10).ok_or_else(invalid)?;
+    let compressed = u32_at(bytes, central + 20).ok_or_else(invalid)?;
+    let size = u32_at(bytes, central + 24).ok_or_else(invalid)?;
+    let local = u32_at(bytes, central + 42).ok_or_else(invalid)?;
+    if [compressed, size, local].contains(&(u32::MAX as usize)) {
+        return Err(Error::InvalidToolArguments(
+            "Zip64 archives are not supported".to_string(),
+        ));
+    }
+    let name_len = u16_at(bytes, local + 26).ok_or_else(invalid)? as usize;
+    let extra_len = u16_at(bytes, local + 28).ok_or_else(invalid)? as usize;
+    let start = local + ZIP_LOCAL_LEN + name_len + extra_len;
+    let data = bytes.get(start..start + compressed).ok_or_else(invalid)?;
+    Ok(ZipEntry {
+        name,
+        method,
+        size,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::bufread::{DeflateEncoder, GzipEncoder};
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n fn main() {}\n+fn added() {}\n";
+
+    async fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        GzipEncoder::new(data).read_to_end(&mut out).await.unwrap();
+        out
+    }
+
+    /// A zip archive of deflated `files`.
+    async fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let (mut out, mut central) = (Vec::new(), Vec::new());
+        for (name, data) in files {
+            let mut deflated = Vec::new();
+            DeflateEncoder::new(*data)
+                .read_to_end(&mut deflated)
+                .await
+                .unwrap();
+            let offset = out.len() as u32;
+            let sizes = [deflated.len() as u32, data.len() as u32];
+
+            out.extend_from_slice(ZIP_MAGIC);
+            out.extend_from_slice(&[20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            sizes
+                .iter()
+                .for_each(|s| out.extend_from_slice(&s.to_le_bytes()));
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&[0, 0]);
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&deflated);
+
+            central.extend_from_slice(ZIP_CENTRAL_MAGIC);
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            sizes
+                .iter()
+                .for_each(|s| central.extend_from_slice(&s.to_le_bytes()));
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_offset = out.len() as u32;
+        let count = (files.len() as u16).to_le_bytes();
+        out.extend_from_slice(&central);
+        out.extend_from_slice(ZIP_END_MAGIC);
+        out.extend_from_slice(&[0, 0, 0, 0, count[0], count[1], count[0], count[1]]);
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&central_offset.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    #[tokio::test]
+    async fn test_gzipped_diff_is_decompressed() {
+        let compressed = gzip(DIFF.as_bytes()).await;
+        assert_eq!(Compression::detect(&compressed), Compression::Gzip);
+        assert_eq!(
+            decompress(&compressed, MAX_DECOMPRESSED_SIZE)
+                .await
+                .unwrap(),
+            DIFF
+        );
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&compressed);
+        assert_eq!(
+            decode_diff_arg(&encoded, MAX_DECOMPRESSED_SIZE)
+                .await
+                .unwrap(),
+            DIFF
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plain_diff_passes_through() {
+        assert_eq!(Compression::detect(DIFF.as_bytes()), Compression::None);
+        assert_eq!(
+            decompress(DIFF.as_bytes(), MAX_DECOMPRESSED_SIZE)
+                .await
+                .unwrap(),
+            DIFF
+        );
+        assert_eq!(
+            decode_diff_arg(DIFF, MAX_DECOMPRESSED_SIZE).await.unwrap(),
+            DIFF
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_file_zip_is_read() {
+        let archive = zip(&[("change.patch", DIFF.as_bytes())]).await;
+        assert_eq!(Compression::detect(&archive), Compression::Zip);
+        assert_eq!(
+            decompress(&archive, MAX_DECOMPRESSED_SIZE).await.unwrap(),
+            DIFF
+        );
+
+        let two = zip(&[("a.patch", DIFF.as_bytes()), ("b.patch", DIFF.as_bytes())]).await;
+        let err = decompress(&two, MAX_DECOMPRESSED_SIZE).await.unwrap_err();
+        assert!(err.to_string().contains("holds 2 files"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_decompression_is_capped() {
+        let bomb = gzip(&vec![b'+'; 1 << 20]).await;
+        assert!(bomb.len() < 4096);
+        let err = decompress(&bomb, 1024).await.unwrap_err();
+        assert!(err.to_string().contains("1024 byte limit"), "{}", err);
+
+        let archive = zip(&[("big.patch", &vec![b'+'; 4096])]).await;
+        assert!(decompress(&archive, 1024).await.is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Language-specific correctness heuristics for added lines.
+//!
+//! Each rule targets one well-known source of bugs in one language, chosen
+//! by the file extension:
+//!
+//! - Rust: `.unwrap()` and `.expect(..)` outside test code
+//! - Python: bare `except:`
+//! - JavaScript/TypeScript: `==` and `!=` instead of `===` and `!==`
+//! - Go: errors discarded with `_ =` or `x, _ :=`
+//!
+//! Rules match a single line, so they are heuristics: a hit deserves a
+//! look, not a block. Rust test code is recognized by path (`tests/`,
+//! `*_test.rs`, `tests.rs`) or by a `#[cfg(test)]` or `#[test]` line
+//! earlier in the file's diff.
+
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::reviewer::diff::{LineKind, ParsedFile};
+use crate::service::language::filename_to_language;
+
+/// One added line that looks like a bug.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// New-file line number, when the diff has hunk headers
+    pub line: Option<u32>,
+    /// Short rule name, e.g. `unwrap`
+    pub rule: &'static str,
+    pub title: &'static str,
+    pub suggestion: &'static str,
+}
+
+struct Rule {
+    rule: &'static str,
+    title: &'static str,
+    suggestion: &'static str,
+    pattern: Regex,
+}
+
+fn rule(rule: &'static str, title: &'static str, suggestion: &'static str, pattern: &str) -> Rule {
+    Rule {
+        rule,
+        title,
+        suggestion,
+        pattern: Regex::new(pattern).expect("correctness pattern is valid"),
+    }
+}
+
+static RUST: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![rule(
+        "unwrap",
+        "Unwrap in non-test code",
+        "Propagate the error with `?` or handle the `None`/`Err` case",
+        r"\.(?:unwrap|expect)\(",
+    )]
+});
+
+static PYTHON: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![rule(
+        "bare_except",
+        "Bare except",
+        "Catch specific exceptions, or at least `except Exception:`",
+        r"^\s*except\s*:",
+    )]
+});
+
+static JAVASCRIPT: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![rule(
+        "loose_equality",
+        "Loose equality",
+        "Use `===` or `!==`, which don't coerce types",
+        r"(?:^|[^=!<>])(?:==|!=)(?:[^=]|$)",
+    )]
+});
+
+static GO: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![rule(
+        "ignored_error",
+        "Ignored error",
+        "Check the error, or comment why it is safe to ignore",
+        r"(?:^\s*_|,\s*_)\s*:?=[^=]",
+    )]
+});
+
+fn rules_for(language: &str) -> Option<&'static [Rule]> {
+    let rules: &'static Vec<Rule> = match language {
+        "rust" => &RUST,
+        "python" => &PYTHON,
+        "javascript" | "typescript" | "jsx" | "tsx" => &JAVASCRIPT,
+        "go" => &GO,
+        _ => return None,
+    };
+    Some(rules)
+}
+
+/// Whether a Rust path holds only test code.
+fn is_rust_test_path(path: &str) -> bool {
+    path.starts_with("tests/")
+        || path.contains("/tests/")
+        || path.ends_with("_test.rs")
+        || path.ends_with("/tests.rs")
+        || path == "tests.rs"
+}
+
+/// Correctness issues in the added lines of `file`.
+pub fn check(file: &ParsedFile) -> Vec<Issue> {
+    if file.file.binary {
+        return Vec::new();
+    }
+    let Some(language) = filename_to_language(Path::new(&file.file.path)) else {
+        return Vec::new();
+    };
+    let Some(rules) = rules_for(language) else {
+        return Vec::new();
+    };
+    let rust = language == "rust";
+    if rust && is_rust_test_path(&file.file.path) {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    let mut in_tests = false;
+    for line in &file.lines {
+        if rust {
+            let trimmed = line.text.trim_start();
+            if trimmed.starts_with("#[cfg(test)]") || trimmed.starts_with("#[test]") {
+                in_tests = true;
+            }
+            if in_tests {
+                continue;
+            }
+        }
+        if line.kind != LineKind::Added {
+            continue;
+        }
+        for rule in rules.iter().filter(|r| r.pattern.is_match(line.text)) {
+            issues.push(Issue {
+                line: line.new_line,
+                rule: rule.rule,
+                title: rule.title,
+                suggestion: rule.suggestion,
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer::diff::ParsedDiff;
+
+    /// Rules fired by adding `added` to `path`.
+    fn rules_fired(path: &str, added: &str) -> Vec<&'static str> {
+        let diff = format!(
+            "--- a/{0}\n+++ b/{0}\n@@ -1 +1,2 @@\n first\n+{1}\n",
+            path, added
+        );
+        let parsed = ParsedDiff::parse(&diff);
+        check(&parsed.files[0])
+            .into_iter()
+            .map(|i| i.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_each_rule_fires_only_on_its_language() {
+        let cases = [
+            ("let port = env.parse().unwrap();", "unwrap"),
+            ("except:", "bare_except"),
+            ("if (count == 0) {", "loose_equality"),
+            ("_ = os.Remove(path)", "ignored_error"),
+        ];
+        let files = ["src/lib.rs", "app.py", "web/app.ts", "main.go"];
+
+        for (file, (line, expected)) in files.iter().zip(cases) {
+            for other in files {
+                let fired = rules_fired(other, line);
+                if other == *file {
+                    assert_eq!(fired, vec![expected], "{} in {}", line, other);
+                } else {
+                    assert!(
+                        !fired.contains(&expected),
+                        "{} fired in {}",
+                        expected,
+                        other
+                    );
+                }
+            }
+        }
+        assert!(rules_fired("README.md", "except:").is_empty());
+    }
+
+    #[test]
+    fn test_rules_skip_correct_code() {
+        assert!(rules_fired("src/lib.rs", "let port = env.parse()?;").is_empty());
+        assert!(rules_fired("app.py", "except ValueError:").is_empty());
+        assert!(rules_fired("app.js", "if (count === 0 && a !== b) {").is_empty());
+        assert!(rules_fired("main.go", "if err := run(); err != nil {").is_empty());
+        assert_eq!(
+            rules_fired("main.go", "data, _ := load()"),
+            vec!["ignored_error"]
+        );
+        assert_eq!(
+            rules_fired("app.js", "if (a != b) {"),
+            vec!["loose_equality"]
+        );
+    }
+
+    #[test]
+    fn test_rust_test_code_may_unwrap() {
+        assert!(rules_fired("tests/api.rs", "run().unwrap();").is_empty());
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,4 @@\n+let a = b.unwrap();\n #[cfg(test)]\n mod tests {\n+    let c = d.unwrap();\n";
+        let parsed = ParsedDiff::parse(diff);
+        let issues = check(&parsed.files[0]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(1));
+    }
+}
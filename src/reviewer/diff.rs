@@ -0,0 +1,538 @@
+//! Structured unified diffs.
+//!
+//! A diff is parsed once into a [`ParsedDiff`]: its files, their hunks, and
+//! every hunk line classified as context, added or removed with its old and
+//! new line numbers. Risk scoring, invariant checks and findings all read
+//! this structure rather than re-scanning the text. Lines borrow from the
+//! diff and keep their byte span in it, so a regex match on the raw text
+//! maps back to a file and line.
+
+use std::ops::Range;
+
+use crate::reviewer::snippet;
+use crate::types::review::{ChangeType, DiffHunk, ReviewFile};
+
+/// How a diff line changes the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Unchanged line shown for context
+    Context,
+    /// Line present only in the new file
+    Added,
+    /// Line present only in the old file
+    Removed,
+}
+
+/// One line of a diff body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine<'a> {
+    pub kind: LineKind,
+    /// Line number in the old file; `None` for added lines
+    pub old_line: Option<u32>,
+    /// Line number in the new file; `None` for removed lines
+    pub new_line: Option<u32>,
+    /// The line without its `+`, `-` or space marker
+    pub text: &'a str,
+    /// Byte range of the whole line, marker included, in the diff
+    pub span: Range<usize>,
+}
+
+/// One file of a diff with its classified lines.
+#[derive(Debug, Clone)]
+pub struct ParsedFile<'a> {
+    pub file: ReviewFile,
+    /// The part of the diff describing this file, from its first header line
+    /// up to the next file's
+    pub section: &'a str,
+    /// Hunk lines in order. Bare `+`/`-` lines of a diff without hunk
+    /// headers are kept too, without line numbers.
+    pub lines: Vec<DiffLine<'a>>,
+}
+
+impl ParsedFile<'_> {
+    /// The added lines, each followed by a newline.
+    pub fn added_text(&self) -> String {
+        let mut text = String::new();
+        for line in self.lines.iter().filter(|l| l.kind == LineKind::Added) {
+            text.push_str(line.text);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// A unified diff parsed into files, hunks and lines.
+#[derive(Debug, Clone)]
+pub struct ParsedDiff<'a> {
+    /// The raw diff; line spans index into it
+    pub text: &'a str,
+    pub files: Vec<ParsedFile<'a>>,
+}
+
+impl<'a> ParsedDiff<'a> {
+    /// Parse a unified diff.
+    ///
+    /// Git extended headers (`new file mode`, `deleted file mode`,
+    /// `rename from`/`rename to`, `copy from`/`copy to`) and `/dev/null`
+    /// paths set the change type. Binary files are flagged and have no line
+    /// counts. Hunk bodies are read by their header counts, so a removed
+    /// line starting with `--` isn't mistaken for a file header.
+    pub fn parse(diff: &'a str) -> Self {
+        let mut parser = Parser {
+            diff,
+            files: Vec::new(),
+            current: None,
+            remaining: (0, 0),
+            next: (0, 0),
+        };
+        for line in diff.lines() {
+            parser.line(line);
+        }
+        parser.finish()
+    }
+
+    /// The review files, dropping the line structure.
+    pub fn into_review_files(self) -> Vec<ReviewFile> {
+        self.files.into_iter().map(|f| f.file).collect()
+    }
+
+    /// The review files.
+    pub fn review_files(&self) -> impl Iterator<Item = &ReviewFile> {
+        self.files.iter().map(|f| &f.file)
+    }
+
+    /// The added lines of each file as `(path, content)` pairs.
+    ///
+    /// Deleted files have no added lines and are left out. Input without
+    /// any file header is treated as a single anonymous source holding the
+    /// whole text.
+    pub fn added_lines_by_file(&self) -> Vec<(String, String)> {
+        if self.files.is_empty() {
+            return vec![(String::new(), self.text.to_string())];
+        }
+        self.files
+            .iter()
+            .filter(|f| f.file.change_type != ChangeType::Deleted)
+            .map(|f| (f.file.path.clone(), f.added_text()))
+            .collect()
+    }
+
+    /// The file and new-file line of the diff line containing byte `offset`.
+    ///
+    /// The line is `None` for removed lines, header lines, and diffs
+    /// without hunk headers.
+    pub fn locate(&self, offset: usize) -> Option<(String, Option<u32>)> {
+        let file = self
+            .files
+            .iter()
+            .rev()
+            .find(|f| self.section_start(f) <= offset)?;
+        if offset >= self.section_start(file) + file.section.len() {
+            return None;
+        }
+        let at = file.lines.partition_point(|l| l.span.start <= offset);
+        let line = at
+            .checked_sub(1)
+            .map(|i| &file.lines[i])
+            .filter(|l| offset <= l.span.end)
+            .and_then(|l| l.new_line.filter(|_| l.kind != LineKind::Removed));
+        Some((file.file.path.clone(), line))
+    }
+
+    /// One diff per file, each over that file's section alone.
+    pub fn split(&self) -> Vec<ParsedDiff<'a>> {
+        self.files
+            .iter()
+            .map(|file| {
+                let start = self.section_start(file);
+                let mut file = file.clone();
+                for line in &mut file.lines {
+                    line.span = line.span.start - start..line.span.end - start;
+                }
+                ParsedDiff {
+                    text: file.section,
+                    files: vec![file],
+                }
+            })
+            .collect()
+    }
+
+    fn section_start(&self, file: &ParsedFile) -> usize {
+        offset_in(self.text, file.section)
+    }
+}
+
+/// Parse a unified diff into review files with change types, line counts
+/// and hunks. See [`ParsedDiff::parse`].
+pub fn parse_diff_files(diff: &str) -> Vec<ReviewFile> {
+    ParsedDiff::parse(diff).into_review_files()
+}
+
+/// Like [`parse_diff_files`], pairing each file with the part of `diff`
+/// that describes it, from its first header line up to the next file's.
+pub fn diff_sections(diff: &str) -> Vec<(ReviewFile, &str)> {
+    ParsedDiff::parse(diff)
+        .files
+        .into_iter()
+        .map(|f| (f.file, f.section))
+        .collect()
+}
+
+/// Strip the `a/` or `b/` prefix git puts on diff paths.
+fn diff_path(path: &str) -> &str {
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+/// Byte offset of `line`, a slice of `text`, within `text`.
+fn offset_in(text: &str, line: &str) -> usize {
+    line.as_ptr() as usize - text.as_ptr() as usize
+}
+
+/// A file being parsed, with its `---` path and whether `+++` was seen.
+struct ParsingFile<'a> {
+    file: ReviewFile,
+    start: usize,
+    lines: Vec<DiffLine<'a>>,
+    old_header: Option<String>,
+    saw_new_header: bool,
+}
+
+impl ParsingFile<'_> {
+    fn new(path: &str, start: usize) -> Self {
+        Self {
+            file: ReviewFile {
+                path: path.to_string(),
+                change_type: ChangeType::Modified,
+                old_path: None,
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                hunks: Vec::new(),
+                risk_score: 0,
+                findings: Vec::new(),
+            },
+            start,
+            lines: Vec::new(),
+            old_header: None,
+            saw_new_header: false,
+        }
+    }
+}
+
+/// Line-at-a-time state of [`ParsedDiff::parse`].
+struct Parser<'a> {
+    diff: &'a str,
+    files: Vec<ParsingFile<'a>>,
+    current: Option<ParsingFile<'a>>,
+    /// Old and new lines left in the current hunk
+    remaining: (u32, u32),
+    /// Old and new numbers of the next line in the current hunk
+    next: (u32, u32),
+}
+
+impl<'a> Parser<'a> {
+    fn line(&mut self, line: &'a str) {
+        let span = offset_in(self.diff, line)..offset_in(self.diff, line) + line.len();
+
+        // "\ No newline at end of file" follows the last line of a hunk
+        if line.starts_with('\\') {
+            if let Some(hunk) = self.current.as_mut().and_then(|p| p.file.hunks.last_mut()) {
+                hunk.content.push_str(line);
+                hunk.content.push('\n');
+            }
+            return;
+        }
+        if self.remaining != (0, 0) {
+            if let Some(parsing) = self.current.as_mut() {
+                let (old, new) = self.next;
+                let classified = match line.chars().next() {
+                    Some('+') => {
+                        parsing.file.additions += 1;
+                        self.remaining.1 = self.remaining.1.saturating_sub(1);
+                        self.next.1 += 1;
+                        Some((LineKind::Added, None, Some(new)))
+                    }
+                    Some('-') => {
+                        parsing.file.deletions += 1;
+                        self.remaining.0 = self.remaining.0.saturating_sub(1);
+                        self.next.0 += 1;
+                        Some((LineKind::Removed, Some(old), None))
+                    }
+                    Some(' ') | None => {
+                        self.remaining.0 = self.remaining.0.saturating_sub(1);
+                        self.remaining.1 = self.remaining.1.saturating_sub(1);
+                        self.next = (old + 1, new + 1);
+                        Some((LineKind::Context, Some(old), Some(new)))
+                    }
+                    _ => None,
+                };
+                if let Some((kind, old_line, new_line)) = classified {
+                    if let Some(hunk) = parsing.file.hunks.last_mut() {
+                        hunk.content.push_str(line);
+                        hunk.content.push('\n');
+                    }
+                    parsing.lines.push(DiffLine {
+                        kind,
+                        old_line,
+                        new_line,
+                        text: line.get(1..).unwrap_or_default(),
+                        span,
+                    });
+                    return;
+                }
+            }
+            self.remaining = (0, 0);
+        }
+
+        if let Some((old_start, old_lines, new_start, new_lines, header)) =
+            snippet::parse_hunk_header(line)
+        {
+            if let Some(parsing) = self.current.as_mut() {
+                parsing.file.hunks.push(DiffHunk {
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    content: String::new(),
+                    header,
+                });
+                self.remaining = (old_lines, new_lines);
+                self.next = (old_start, new_start);
+            }
+            return;
+        }
+
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths
+                .rsplit_once(" b/")
+                .map(|(_, new)| new)
+                .unwrap_or(paths);
+            self.start_file(path, span.start);
+            return;
+        }
+
+        if let Some(path) = line.strip_prefix("--- ") {
+            // Without `diff --git` lines, `---` starts the next file
+            if self.current.as_ref().is_none_or(|p| p.saw_new_header) {
+                self.start_file(diff_path(path), span.start);
+            }
+            if let Some(parsing) = self.current.as_mut() {
+                parsing.old_header = Some(path.to_string());
+            }
+            return;
+        }
+
+        if let Some(path) = line.strip_prefix("+++ ") {
+            if self.current.as_ref().is_none_or(|p| p.saw_new_header) {
+                self.start_file(diff_path(path), span.start);
+            }
+            let Some(parsing) = self.current.as_mut() else {
+                return;
+            };
+            parsing.saw_new_header = true;
+            let old = parsing.old_header.as_deref();
+            if path == "/dev/null" {
+                parsing.file.change_type = ChangeType::Deleted;
+                if let Some(old) = old {
+                    parsing.file.path = diff_path(old).to_string();
+                }
+            } else {
+                parsing.file.path = diff_path(path).to_string();
+                if old == Some("/dev/null") {
+                    parsing.file.change_type = ChangeType::Added;
+                }
+            }
+            return;
+        }
+
+        let Some(parsing) = self.current.as_mut() else {
+            return;
+        };
+        let file = &mut parsing.file;
+        if line.starts_with("new file mode") {
+            file.change_type = ChangeType::Added;
+        } else if line.starts_with("deleted file mode") {
+            file.change_type = ChangeType::Deleted;
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.change_type = ChangeType::Renamed;
+            file.old_path = Some(from.to_string());
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            file.change_type = ChangeType::Renamed;
+            file.path = to.to_string();
+        } else if let Some(from) = line.strip_prefix("copy from ") {
+            file.change_type = ChangeType::Copied;
+            file.old_path = Some(from.to_string());
+        } else if let Some(to) = line.strip_prefix("copy to ") {
+            file.change_type = ChangeType::Copied;
+            file.path = to.to_string();
+        } else if let Some(paths) = line
+            .strip_prefix("Binary files ")
+            .and_then(|rest| rest.strip_suffix(" differ"))
+        {
+            file.binary = true;
+            file.additions = 0;
+            file.deletions = 0;
+            parsing.lines.clear();
+            if paths.starts_with("/dev/null ") {
+                file.change_type = ChangeType::Added;
+            } else if paths.ends_with(" /dev/null") {
+                file.change_type = ChangeType::Deleted;
+            }
+        } else if line.starts_with("GIT binary patch") {
+            file.binary = true;
+        } else if file.hunks.is_empty() && !file.binary {
+            // Bare `+`/`-` lines without hunk headers still count
+            let kind = if line.starts_with('+') {
+                file.additions += 1;
+                LineKind::Added
+            } else if line.starts_with('-') {
+                file.deletions += 1;
+                LineKind::Removed
+            } else {
+                return;
+            };
+            parsing.lines.push(DiffLine {
+                kind,
+                old_line: None,
+                new_line: None,
+                text: &line[1..],
+                span,
+            });
+        }
+    }
+
+    fn start_file(&mut self, path: &str, start: usize) {
+        self.files.extend(self.current.take());
+        self.current = Some(ParsingFile::new(path, start));
+    }
+
+    fn finish(mut self) -> ParsedDiff<'a> {
+        self.files.extend(self.current.take());
+        let ends: Vec<usize> = self
+            .files
+            .iter()
+            .skip(1)
+            .map(|p| p.start)
+            .chain(std::iter::once(self.diff.len()))
+            .collect();
+        let files = self
+            .files
+            .into_iter()
+            .zip(ends)
+            .map(|(parsing, end)| ParsedFile {
+                file: parsing.file,
+                section: &self.diff[parsing.start..end],
+                lines: parsing.lines,
+            })
+            .collect();
+        ParsedDiff {
+            text: self.diff,
+            files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -3,4 +3,4 @@ fn lib() {\n keep\n-old\n+new\n+extra\n\n@@ -20,2 +20,1 @@\n-gone\n tail\n";
+
+    #[test]
+    fn test_lines_are_classified_and_numbered() {
+        let parsed = ParsedDiff::parse(DIFF);
+        assert_eq!(parsed.files.len(), 1);
+        let lines: Vec<(LineKind, Option<u32>, Option<u32>, &str)> = parsed.files[0]
+            .lines
+            .iter()
+            .map(|l| (l.kind, l.old_line, l.new_line, l.text))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (LineKind::Context, Some(3), Some(3), "keep"),
+                (LineKind::Removed, Some(4), None, "old"),
+                (LineKind::Added, None, Some(4), "new"),
+                (LineKind::Added, None, Some(5), "extra"),
+                // An empty line inside a hunk is context with its space stripped
+                (LineKind::Context, Some(5), Some(6), ""),
+                (LineKind::Removed, Some(20), None, "gone"),
+                (LineKind::Context, Some(21), Some(20), "tail"),
+            ]
+        );
+        let file = &parsed.files[0].file;
+        assert_eq!((file.additions, file.deletions), (2, 2));
+        assert_eq!(file.hunks.len(), 2);
+        assert_eq!(parsed.files[0].added_text(), "new\nextra\n");
+    }
+
+    #[test]
+    fn test_spans_point_into_the_diff() {
+        let parsed = ParsedDiff::parse(DIFF);
+        for line in &parsed.files[0].lines {
+            assert!(DIFF[line.span.clone()].ends_with(line.text));
+        }
+
+        let offset = DIFF.find("extra").unwrap();
+        assert_eq!(
+            parsed.locate(offset),
+            Some(("src/lib.rs".to_string(), Some(5)))
+        );
+        let removed = DIFF.find("gone").unwrap();
+        assert_eq!(
+            parsed.locate(removed),
+            Some(("src/lib.rs".to_string(), None))
+        );
+        let header = DIFF.find("@@ -20").unwrap();
+        assert_eq!(
+            parsed.locate(header),
+            Some(("src/lib.rs".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_bare_lines_and_split_sections() {
+        let diff =
+            "--- a/a.rs\n+++ b/a.rs\n-x\n+y\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1,2 @@\n z\n+w\n";
+        let parsed = ParsedDiff::parse(diff);
+        let kinds: Vec<(LineKind, Option<u32>)> = parsed.files[0]
+            .lines
+            .iter()
+            .map(|l| (l.kind, l.new_line))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(LineKind::Removed, None), (LineKind::Added, None)]
+        );
+        assert_eq!(
+            parsed.added_lines_by_file(),
+            vec![
+                ("a.rs".to_string(), "y\n".to_string()),
+                ("b.rs".to_string(), "w\n".to_string()),
+            ]
+        );
+
+        let split = parsed.split();
+        assert_eq!(
+            split[1].text,
+            "--- a/b.rs\n+++ b/b.rs\n@@ -1 +1,2 @@\n z\n+w\n"
+        );
+        let offset = split[1].text.find('w').unwrap();
+        assert_eq!(split[1].locate(offset), Some(("b.rs".to_string(), Some(2))));
+        assert_eq!(
+            ParsedDiff::parse("+TODO").added_lines_by_file(),
+            vec![(String::new(), "+TODO".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_added_lines_strip_one_prefix_and_skip_deleted_files() {
+        let diff = "--- a/b/x.rs\n+++ b/b/x.rs\n@@ -0,0 +1 @@\n+let a = 1;\n--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+        assert_eq!(
+            ParsedDiff::parse(diff).added_lines_by_file(),
+            vec![("b/x.rs".to_string(), "let a = 1;\n".to_string())]
+        );
+    }
+}
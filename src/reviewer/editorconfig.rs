@@ -0,0 +1,294 @@
+//! EditorConfig style checks for added lines.
+//!
+//! Reads the workspace root `.editorconfig` and checks added lines against
+//! the common whitespace keys: `indent_style`, `indent_size`,
+//! `trim_trailing_whitespace` and `insert_final_newline`. Sections apply in
+//! file order, so later sections override earlier ones. Section globs follow
+//! EditorConfig: a glob without `/` matches the file name in any directory.
+//!
+//! Only the root file is read; `.editorconfig` files in subdirectories are
+//! ignored.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+use tracing::debug;
+
+use crate::types::review::ReviewFile;
+
+/// File name of an EditorConfig file.
+pub const EDITORCONFIG_FILE: &str = ".editorconfig";
+
+/// Indentation style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Space,
+    Tab,
+}
+
+/// The properties that apply to one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Properties {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<usize>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+}
+
+impl Properties {
+    /// Apply a `key = value` pair; unknown keys and `unset` values are ignored.
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.to_lowercase();
+        let flag = match value.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+        match key {
+            "indent_style" => {
+                self.indent_style = match value.as_str() {
+                    "space" => Some(IndentStyle::Space),
+                    "tab" => Some(IndentStyle::Tab),
+                    _ => None,
+                }
+            }
+            "indent_size" => self.indent_size = value.parse().ok(),
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = flag,
+            "insert_final_newline" => self.insert_final_newline = flag,
+            _ => {}
+        }
+    }
+}
+
+/// A `[glob]` section.
+#[derive(Debug, Clone)]
+struct Section {
+    matcher: GlobMatcher,
+    pairs: Vec<(String, String)>,
+}
+
+/// A parsed `.editorconfig`.
+#[derive(Debug, Clone, Default)]
+pub struct EditorConfig {
+    sections: Vec<Section>,
+}
+
+/// One added line that breaks a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// New-file line number
+    pub line: u32,
+    /// The EditorConfig key that was violated
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl EditorConfig {
+    /// Parse `.editorconfig` text. Sections with invalid globs are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut sections = Vec::new();
+        let mut current: Option<Section> = None;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.extend(current.take());
+                current = section_matcher(glob).map(|matcher| Section {
+                    matcher,
+                    pairs: Vec::new(),
+                });
+                continue;
+            }
+            // Pairs before the first section (e.g. `root = true`) are preamble
+            if let (Some(section), Some((key, value))) = (current.as_mut(), line.split_once('=')) {
+                section
+                    .pairs
+                    .push((key.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+        sections.extend(current);
+        Self { sections }
+    }
+
+    /// Load `.editorconfig` from the workspace root, if there is one.
+    pub fn load(workspace: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(workspace.join(EDITORCONFIG_FILE))
+            .map_err(|e| debug!("No {}: {}", EDITORCONFIG_FILE, e))
+            .ok()?;
+        Some(Self::parse(&text))
+    }
+
+    /// The properties for the workspace-relative `path`.
+    pub fn properties_for(&self, path: &str) -> Properties {
+        let mut properties = Properties::default();
+        for section in self.sections.iter().filter(|s| s.matcher.is_match(path)) {
+            for (key, value) in &section.pairs {
+                properties.set(key, value);
+            }
+        }
+        properties
+    }
+
+    /// Check the added lines of `file` against its properties.
+    pub fn check(&self, file: &ReviewFile) -> Vec<Violation> {
+        if file.binary {
+            return Vec::new();
+        }
+        let properties = self.properties_for(&file.path);
+        let mut violations = Vec::new();
+
+        for hunk in &file.hunks {
+            let mut number = hunk.new_start;
+            let mut last_added: Option<u32> = None;
+            for line in hunk.content.lines() {
+                match line.chars().next() {
+                    Some('+') => {
+                        check_line(&line[1..], number, &properties, &mut violations);
+                        last_added = Some(number);
+                        number += 1;
+                        continue;
+                    }
+                    Some('\\') => {
+                        // "\ No newline at end of file" right after an added line
+                        if let (Some(line), Some(true)) =
+                            (last_added, properties.insert_final_newline)
+                        {
+                            violations.push(Violation {
+                                line,
+                                rule: "insert_final_newline",
+                                message: "File does not end with a newline".to_string(),
+                            });
+                        }
+                    }
+                    Some('-') => {}
+                    _ => number += 1,
+                }
+                last_added = None;
+            }
+        }
+        violations
+    }
+}
+
+/// Matcher for a section glob, relative to the workspace root.
+fn section_matcher(glob: &str) -> Option<GlobMatcher> {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if glob.contains('/') => glob.to_string(),
+        None => format!("**/{}", glob),
+    };
+    GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .map(|g| g.compile_matcher())
+        .map_err(|e| debug!("Skipping {} section [{}]: {}", EDITORCONFIG_FILE, glob, e))
+        .ok()
+}
+
+fn check_line(text: &str, line: u32, properties: &Properties, violations: &mut Vec<Violation>) {
+    let indent: &str = &text[..text.len() - text.trim_start_matches([' ', '\t']).len()];
+    let is_blank = indent.len() == text.len();
+
+    if !is_blank {
+        match properties.indent_style {
+            Some(IndentStyle::Space) if indent.contains('\t') => violations.push(Violation {
+                line,
+                rule: "indent_style",
+                message: "Indented with tabs; indent_style is space".to_string(),
+            }),
+            Some(IndentStyle::Tab) if indent.starts_with(' ') && !indent.contains('\t') => {
+                violations.push(Violation {
+                    line,
+                    rule: "indent_style",
+                    message: "Indented with spaces; indent_style is tab".to_string(),
+                })
+            }
+            Some(IndentStyle::Space) => {
+                if let Some(size) = properties.indent_size.filter(|s| *s > 0) {
+                    if !indent.len().is_multiple_of(size) {
+                        violations.push(Violation {
+                            line,
+                            rule: "indent_size",
+                            message: format!(
+                                "Indented by {} spaces; indent_size is {}",
+                                indent.len(),
+                                size
+                            ),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if properties.trim_trailing_whitespace == Some(true) && text.ends_with([' ', '\t']) {
+        violations.push(Violation {
+            line,
+            rule: "trim_trailing_whitespace",
+            message: "Trailing whitespace".to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer::parse_diff_files;
+
+    const CONFIG: &str = "root = true\n\n[*]\nindent_style = space\nindent_size = 4\ntrim_trailing_whitespace = true\ninsert_final_newline = true\n\n[Makefile]\nindent_style = tab\n\n[docs/*.md]\ntrim_trailing_whitespace = false\n";
+
+    #[test]
+    fn test_sections_apply_in_order() {
+        let config = EditorConfig::parse(CONFIG);
+        let rust = config.properties_for("src/lib.rs");
+        assert_eq!(rust.indent_style, Some(IndentStyle::Space));
+        assert_eq!(rust.indent_size, Some(4));
+
+        let make = config.properties_for("build/Makefile");
+        assert_eq!(make.indent_style, Some(IndentStyle::Tab));
+
+        assert_eq!(
+            config
+                .properties_for("docs/guide.md")
+                .trim_trailing_whitespace,
+            Some(false)
+        );
+        assert_eq!(
+            config
+                .properties_for("docs/nested/guide.md")
+                .trim_trailing_whitespace,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_tab_indent_violates_space_style() {
+        let config = EditorConfig::parse(CONFIG);
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,5 @@\n fn a() {\n+\tlet tabbed = 1;\n+    let fine = 2;\n+  let odd = 3; \n }\n\\ No newline at end of file\n";
+        let files = parse_diff_files(diff);
+
+        let violations = config.check(&files[0]);
+        let rules: Vec<_> = violations.iter().map(|v| (v.line, v.rule)).collect();
+        assert_eq!(
+            rules,
+            vec![
+                (2, "indent_style"),
+                (4, "indent_size"),
+                (4, "trim_trailing_whitespace"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_final_newline_on_added_line() {
+        let config = EditorConfig::parse(CONFIG);
+        let diff = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1,2 @@\n fn a() {}\n+fn b() {}\n\\ No newline at end of file\n";
+        let violations = config.check(&parse_diff_files(diff)[0]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "insert_final_newline");
+        assert_eq!(violations[0].line, 2);
+    }
+}
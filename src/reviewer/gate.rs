@@ -0,0 +1,164 @@
+//! Pass/fail gate over a completed review, for CI.
+//!
+//! A review fails the gate if any finding is at or above the severity
+//! threshold, or if its overall risk level is at or above the risk
+//! threshold. Every tripped condition is reported as a reason, so a CI log
+//! shows why a job failed without digging through the full review.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::review::{Review, RiskLevel, Severity};
+
+/// When a review fails the gate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateThresholds {
+    /// Fail if any finding is at or above this severity
+    pub fail_on_severity: Severity,
+    /// Fail if the review's risk level is at or above this level
+    pub fail_on_risk: RiskLevel,
+}
+
+impl Default for GateThresholds {
+    fn default() -> Self {
+        Self {
+            fail_on_severity: Severity::Critical,
+            fail_on_risk: RiskLevel::High,
+        }
+    }
+}
+
+/// The outcome of a gate check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GateResult {
+    pub review_id: String,
+    pub passed: bool,
+    /// Why the gate failed, one entry per tripped condition; empty on a pass
+    pub reasons: Vec<String>,
+    pub thresholds: GateThresholds,
+    pub risk_level: RiskLevel,
+    pub risk_score: u8,
+    /// Findings at or above `fail_on_severity`
+    pub blocking_findings: usize,
+}
+
+/// Check `review` against `thresholds`.
+pub fn evaluate_gate(review: &Review, thresholds: GateThresholds) -> GateResult {
+    let mut reasons = Vec::new();
+
+    let blocking: Vec<_> = review
+        .findings
+        .iter()
+        .filter(|f| f.severity >= thresholds.fail_on_severity)
+        .collect();
+    for finding in &blocking {
+        let location = match (finding.file.as_str(), finding.line) {
+            ("", _) => String::new(),
+            (file, Some(line)) => format!(" at {}:{}", file, line),
+            (file, None) => format!(" in {}", file),
+        };
+        reasons.push(format!(
+            "{} finding: {}{}",
+            severity_name(finding.severity),
+            finding.title,
+            location
+        ));
+    }
+
+    if review.risk_level >= thresholds.fail_on_risk {
+        reasons.push(format!(
+            "Risk level {} (score {}) is at or above {}",
+            risk_name(review.risk_level),
+            review.risk_score,
+            risk_name(thresholds.fail_on_risk)
+        ));
+    }
+
+    GateResult {
+        review_id: review.id.clone(),
+        passed: reasons.is_empty(),
+        reasons,
+        thresholds,
+        risk_level: review.risk_level,
+        risk_score: review.risk_score,
+        blocking_findings: blocking.len(),
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+        Severity::Critical => "critical",
+    }
+}
+
+fn risk_name(level: RiskLevel) -> &'static str {
+    match level {
+        RiskLevel::Low => "low",
+        RiskLevel::Medium => "medium",
+        RiskLevel::High => "high",
+        RiskLevel::Critical => "critical",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::review::{Finding, FindingType, ReviewStatus};
+    use std::collections::HashMap;
+
+    fn review(risk_level: RiskLevel, severities: &[Severity]) -> Review {
+        Review {
+            id: "r".to_string(),
+            title: "Code Review".to_string(),
+            status: ReviewStatus::Completed,
+            files: Vec::new(),
+            findings: severities
+                .iter()
+                .map(|&severity| Finding {
+                    id: "f".to_string(),
+                    finding_type: FindingType::Security,
+                    severity,
+                    title: "Hardcoded secret".to_string(),
+                    description: String::new(),
+                    file: "src/config.rs".to_string(),
+                    line: Some(7),
+                    line_range: None,
+                    suggestion: None,
+                    code_snippet: None,
+                    actionable: true,
+                    category: None,
+                    occurrences: 1,
+                })
+                .collect(),
+            risk_score: 60,
+            risk_level,
+            invariants: Vec::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_thresholds_are_inclusive() {
+        let review = review(RiskLevel::Medium, &[Severity::Warning, Severity::Error]);
+        assert!(evaluate_gate(&review, GateThresholds::default()).passed);
+
+        let strict = GateThresholds {
+            fail_on_severity: Severity::Error,
+            fail_on_risk: RiskLevel::Medium,
+        };
+        let result = evaluate_gate(&review, strict);
+        assert!(!result.passed);
+        assert_eq!(result.blocking_findings, 1);
+        assert_eq!(
+            result.reasons,
+            vec![
+                "error finding: Hardcoded secret at src/config.rs:7".to_string(),
+                "Risk level medium (score 60) is at or above medium".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,713 @@
+//! Git helpers for discovering the change set to review.
+//!
+//! Every command runs with `-C <workspace>` so results don't depend on the
+//! server's working directory.
+//!
+//! [`GitCache`] reuses the output of diffs whose result is fixed by the
+//! commits and the index, so agents polling the same change set don't
+//! shell out for every call.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Which changes to diff.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DiffTarget {
+    /// Staged and unstaged changes against `HEAD`
+    #[default]
+    WorkingTree,
+    /// Changes staged in the index
+    Staged,
+    /// Changes not yet staged
+    Unstaged,
+    /// The working tree against its merge-base with a branch or commit
+    Base(String),
+    /// The commits on `head` since it diverged from `base`
+    Range { base: String, head: String },
+}
+
+impl DiffTarget {
+    /// Parse a `target` argument (`working_tree`, `staged`, `unstaged`).
+    ///
+    /// A `base` ref takes precedence over `target`.
+    pub fn from_args(target: Option<&str>, base: Option<&str>) -> Result<Self> {
+        if let Some(base) = base.filter(|b| !b.trim().is_empty()) {
+            return Ok(Self::Base(check_ref(base)?));
+        }
+        match target.unwrap_or("working_tree") {
+            "working_tree" => Ok(Self::WorkingTree),
+            "staged" => Ok(Self::Staged),
+            "unstaged" => Ok(Self::Unstaged),
+            other => Err(Error::InvalidToolArguments(format!(
+                "Unknown diff target '{}': expected working_tree, staged, or unstaged",
+                other
+            ))),
+        }
+    }
+
+    /// The commits between `base` and `head`.
+    pub fn range(base: &str, head: &str) -> Result<Self> {
+        Ok(Self::Range {
+            base: check_ref(base)?,
+            head: check_ref(head)?,
+        })
+    }
+
+    /// Parse a `base..head` or `base...head` range; an empty head means
+    /// `HEAD`. Both forms diff `head` against its merge-base with `base`.
+    pub fn parse_range(spec: &str) -> Result<Self> {
+        let (base, head) = spec
+            .split_once("...")
+            .or_else(|| spec.split_once(".."))
+            .ok_or_else(|| {
+                Error::InvalidToolArguments(format!(
+                    "Invalid range '{}': expected base..head",
+                    spec
+                ))
+            })?;
+        let head = if head.trim().is_empty() { "HEAD" } else { head };
+        Self::range(base, head)
+    }
+
+    /// The last `count` commits on `HEAD`.
+    pub fn last_commits(count: u64) -> Result<Self> {
+        if count == 0 {
+            return Err(Error::InvalidToolArguments(
+                "last_commits must be at least 1".to_string(),
+            ));
+        }
+        Self::range(&format!("HEAD~{}", count), "HEAD")
+    }
+
+    /// Human-readable description, e.g. for messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::WorkingTree => "working tree vs HEAD".to_string(),
+            Self::Staged => "staged changes".to_string(),
+            Self::Unstaged => "unstaged changes".to_string(),
+            Self::Base(base) => format!("working tree vs {}", base),
+            Self::Range { base, head } => format!("{} -> {}", base, head),
+        }
+    }
+
+    /// Whether the diff is fixed by the commits and the index alone.
+    ///
+    /// Diffs against the working tree change with every edit to a file,
+    /// which neither moves `HEAD` nor touches the index, so they are never
+    /// cached.
+    fn is_cacheable(&self) -> bool {
+        matches!(self, Self::Staged | Self::Range { .. })
+    }
+
+    /// Revisions the diff depends on besides `HEAD` and the index.
+    ///
+    /// A range's ends may be branches that move without `HEAD` moving, e.g.
+    /// on `git fetch`, so their commits are part of the cache stamp.
+    fn revisions(&self) -> Vec<&str> {
+        match self {
+            Self::Range { base, head } => vec![base.as_str(), head.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn args(&self) -> Vec<&str> {
+        match self {
+            Self::WorkingTree => vec!["HEAD"],
+            Self::Staged => vec!["--cached"],
+            Self::Unstaged => vec![],
+            Self::Base(base) => vec!["--merge-base", base.as_str()],
+            // From the merge-base, so commits that landed on the base after
+            // the branch point don't show up as reverted
+            Self::Range { base, head } => vec!["--merge-base", base.as_str(), head.as_str()],
+        }
+    }
+}
+
+/// A trimmed ref, rejecting anything git could take for an option.
+fn check_ref(reference: &str) -> Result<String> {
+    let reference = reference.trim();
+    if reference.is_empty() || reference.starts_with('-') {
+        return Err(Error::InvalidToolArguments(format!(
+            "Invalid git ref '{}'",
+            reference
+        )));
+    }
+    Ok(reference.to_string())
+}
+
+/// Lines added and deleted in one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileStat {
+    pub path: String,
+    /// Previous path of a renamed file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub additions: u32,
+    pub deletions: u32,
+    /// Binary files have no line counts
+    pub binary: bool,
+}
+
+/// Per-file line counts of a diff, with totals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStat {
+    pub files: Vec<FileStat>,
+    pub files_changed: usize,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+impl DiffStat {
+    fn from_files(files: Vec<FileStat>) -> Self {
+        Self {
+            files_changed: files.len(),
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+        }
+    }
+}
+
+/// Parse `git diff --numstat -z` output.
+///
+/// Each record is `added<TAB>deleted<TAB>path<NUL>`, or for a rename
+/// `added<TAB>deleted<TAB><NUL>old<NUL>new<NUL>`. Binary files show `-` for
+/// both counts.
+pub fn parse_numstat(output: &str) -> DiffStat {
+    let mut files = Vec::new();
+    let mut fields = output.split('\0');
+    while let Some(record) = fields.next() {
+        let mut columns = record.trim_start_matches('\n').splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) =
+            (columns.next(), columns.next(), columns.next())
+        else {
+            continue;
+        };
+        let (path, old_path) = if path.is_empty() {
+            let old = fields.next().unwrap_or_default().to_string();
+            (fields.next().unwrap_or_default().to_string(), Some(old))
+        } else {
+            (path.to_string(), None)
+        };
+        let binary = added == "-" && deleted == "-";
+        files.push(FileStat {
+            path,
+            old_path,
+            additions: added.parse().unwrap_or(0),
+            deletions: deleted.parse().unwrap_or(0),
+            binary,
+        });
+    }
+    DiffStat::from_files(files)
+}
+
+/// Per-file line counts of `target`, limited to `paths` if any are given.
+///
+/// `paths` are passed to git as pathspecs relative to the workspace; callers
+/// are expected to have checked that they stay inside it.
+pub async fn git_diff_stat(
+    workspace: &Path,
+    target: &DiffTarget,
+    paths: &[String],
+) -> Result<DiffStat> {
+    let args = diff_args(&["--numstat", "-z"], target, paths);
+    Ok(parse_numstat(&run_git(workspace, &args).await?))
+}
+
+/// Unified diff of `target` in the repository at `workspace`, limited to
+/// `paths` if any are given.
+///
+/// As with [`git_diff_stat`], callers check that `paths` stay inside the
+/// workspace.
+pub async fn git_diff(workspace: &Path, target: &DiffTarget, paths: &[String]) -> Result<String> {
+    run_git(workspace, &diff_args(&[], target, paths)).await
+}
+
+/// Arguments of a `git diff` of `target` limited to `paths`, with `options`.
+fn diff_args<'a>(options: &[&'a str], target: &'a DiffTarget, paths: &'a [String]) -> Vec<&'a str> {
+    let mut args = vec!["diff"];
+    args.extend(options);
+    args.extend(["--no-color", "--no-ext-diff"]);
+    args.extend(target.args());
+    args.push("--");
+    args.extend(paths.iter().map(String::as_str));
+    args
+}
+
+/// How long [`GitCache`] reuses output by default.
+pub const GIT_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Commands whose output [`GitCache`] keeps at once.
+const GIT_CACHE_CAPACITY: usize = 64;
+
+/// What cached output was computed from: the `HEAD` commit, the time the
+/// index was last written, and the objects `revisions` pointed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RepoStamp {
+    head: String,
+    index_modified: Option<SystemTime>,
+    revisions: Vec<String>,
+}
+
+impl RepoStamp {
+    /// The stamp of the repository at `workspace` with `revisions` resolved,
+    /// or `None` if it has no commits, isn't a repository or a revision
+    /// doesn't resolve.
+    async fn read(workspace: &Path, revisions: &[&str]) -> Option<Self> {
+        let mut args = vec!["rev-parse", "--git-path", "index", "HEAD"];
+        args.extend(revisions);
+        let output = run_git(workspace, &args).await.ok()?;
+        let mut lines = output.lines().map(str::trim);
+        let index = workspace.join(PathBuf::from(lines.next()?));
+        let head = lines.next()?.to_string();
+        let revisions: Vec<String> = lines.map(str::to_string).collect();
+        if revisions.len() != args.len() - 4 {
+            return None;
+        }
+        let index_modified = tokio::fs::metadata(&index)
+            .await
+            .and_then(|m| m.modified())
+            .ok();
+        Some(Self {
+            head,
+            index_modified,
+            revisions,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CachedOutput {
+    output: String,
+    stamp: RepoStamp,
+    inserted: Instant,
+}
+
+/// Short-lived cache of git output, keyed by the command's arguments.
+///
+/// An entry is reused only within its time-to-live and while `HEAD` and the
+/// index are unchanged; a commit, checkout or `git add` invalidates it. A
+/// range diff is also rerun when either end resolves to a different commit,
+/// such as a branch updated by `git fetch`. The stamp costs one
+/// `git rev-parse`, much cheaper than the diff it saves.
+#[derive(Debug)]
+pub struct GitCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Vec<String>, CachedOutput>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for GitCache {
+    fn default() -> Self {
+        Self::new(GIT_CACHE_TTL)
+    }
+}
+
+impl GitCache {
+    /// A cache reusing output for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Commands answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Commands that ran git.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Like [`git_diff`], reusing fresh output for cacheable targets.
+    pub async fn diff(
+        &self,
+        workspace: &Path,
+        target: &DiffTarget,
+        paths: &[String],
+    ) -> Result<String> {
+        let args = diff_args(&[], target, paths);
+        if target.is_cacheable() {
+            self.run_at(workspace, &args, &target.revisions()).await
+        } else {
+            run_git(workspace, &args).await
+        }
+    }
+
+    /// Like [`git_diff_stat`], reusing fresh output for cacheable targets.
+    pub async fn diff_stat(
+        &self,
+        workspace: &Path,
+        target: &DiffTarget,
+        paths: &[String],
+    ) -> Result<DiffStat> {
+        let args = diff_args(&["--numstat", "-z"], target, paths);
+        let output = if target.is_cacheable() {
+            self.run_at(workspace, &args, &target.revisions()).await?
+        } else {
+            run_git(workspace, &args).await?
+        };
+        Ok(parse_numstat(&output))
+    }
+
+    /// Run git with `args` in `workspace`, or return the output of the same
+    /// command if it is still fresh and none of `revisions` resolves to a
+    /// different object.
+    ///
+    /// Only for commands whose output is fixed by `HEAD`, the index and
+    /// `revisions`; anything reading the working tree would go stale.
+    async fn run_at(&self, workspace: &Path, args: &[&str], revisions: &[&str]) -> Result<String> {
+        let Some(stamp) = RepoStamp::read(workspace, revisions).await else {
+            return run_git(workspace, args).await;
+        };
+        let key: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        {
+            let entries = self.entries.lock().unwrap();
+            if let Some(entry) = entries.get(&key) {
+                if entry.stamp == stamp && entry.inserted.elapsed() < self.ttl {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(entry.output.clone());
+                }
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let output = run_git(workspace, args).await?;
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.inserted.elapsed() < self.ttl);
+        if entries.len() >= GIT_CACHE_CAPACITY {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedOutput {
+                output: output.clone(),
+                stamp,
+                inserted: Instant::now(),
+            },
+        );
+        Ok(output)
+    }
+}
+
+/// The remote's default branch, e.g. `origin/main`, as recorded in
+/// `refs/remotes/origin/HEAD` when the repository was cloned.
+pub async fn detect_default_branch(workspace: &Path) -> Option<String> {
+    let args = [
+        "symbolic-ref",
+        "--quiet",
+        "--short",
+        "refs/remotes/origin/HEAD",
+    ];
+    let branch = run_git(workspace, &args).await.ok()?;
+    Some(branch.trim().to_string()).filter(|b| !b.is_empty())
+}
+
+/// The ref to compare against when a tool is given no base: `configured`
+/// if set, otherwise the remote's default branch, if it can be detected.
+pub async fn default_base_ref(
+    workspace: &Path,
+    configured: Option<&str>,
+) -> Result<Option<String>> {
+    match configured.filter(|r| !r.trim().is_empty()) {
+        Some(reference) => check_ref(reference).map(Some),
+        None => Ok(detect_default_branch(workspace).await),
+    }
+}
+
+/// Run git in `workspace`, returning stdout or the stderr of a failed run.
+async fn run_git(workspace: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| Error::Git(format!("failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(Error::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+pub(crate) mod test_repo {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Run git synchronously in `dir` with a fixed identity.
+    pub fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Initialize a repository with one committed file.
+    pub fn init(dir: &Path, file: &str, content: &str) {
+        git(dir, &["init", "-q"]);
+        std::fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "initial"]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_diff_targets() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\n");
+
+        assert!(git_diff(root, &DiffTarget::WorkingTree, &[])
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+        test_repo::git(root, &["add", "a.txt"]);
+        std::fs::write(root.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+
+        let staged = git_diff(root, &DiffTarget::Staged, &[]).await.unwrap();
+        assert!(staged.contains("+two") && !staged.contains("+three"));
+        let unstaged = git_diff(root, &DiffTarget::Unstaged, &[]).await.unwrap();
+        assert!(unstaged.contains("+three") && !unstaged.contains("+two"));
+        let all = git_diff(root, &DiffTarget::WorkingTree, &[]).await.unwrap();
+        assert!(all.contains("+two") && all.contains("+three"));
+
+        assert!(matches!(
+            git_diff(root, &DiffTarget::Base("no-such-branch".to_string()), &[]).await,
+            Err(Error::Git(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_base_ref_prefers_config_then_remote_head() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\n");
+        assert_eq!(default_base_ref(root, None).await.unwrap(), None);
+
+        test_repo::git(
+            root,
+            &[
+                "symbolic-ref",
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/trunk",
+            ],
+        );
+        assert_eq!(
+            default_base_ref(root, None).await.unwrap().as_deref(),
+            Some("origin/trunk")
+        );
+        assert_eq!(
+            default_base_ref(root, Some("develop"))
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("develop")
+        );
+        assert!(default_base_ref(root, Some("--exec=x")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_git_cache_reuses_output_until_head_moves() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\n");
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+        test_repo::git(root, &["commit", "-q", "-am", "two"]);
+
+        let cache = GitCache::default();
+        let last = DiffTarget::last_commits(1).unwrap();
+        let first = cache.diff(root, &last, &[]).await.unwrap();
+        assert!(first.contains("+two"));
+        assert_eq!(cache.diff(root, &last, &[]).await.unwrap(), first);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // A new commit moves HEAD, so HEAD~1..HEAD is run again
+        std::fs::write(root.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        test_repo::git(root, &["commit", "-q", "-am", "three"]);
+        let moved = cache.diff(root, &last, &[]).await.unwrap();
+        assert!(moved.contains("+three") && !moved.contains("+two"));
+        assert_eq!(cache.misses(), 2);
+
+        // Working tree diffs always run git
+        std::fs::write(root.join("a.txt"), "edited\n").unwrap();
+        let working = cache
+            .diff(root, &DiffTarget::WorkingTree, &[])
+            .await
+            .unwrap();
+        assert!(working.contains("+edited"));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // An expired entry is recomputed
+        let expired = GitCache::new(Duration::ZERO);
+        expired.diff(root, &last, &[]).await.unwrap();
+        expired.diff(root, &last, &[]).await.unwrap();
+        assert_eq!((expired.hits(), expired.misses()), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn test_git_cache_reruns_range_when_a_branch_moves() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\n");
+        test_repo::git(root, &["branch", "feature"]);
+        test_repo::git(root, &["checkout", "-q", "feature"]);
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+        test_repo::git(root, &["commit", "-q", "-am", "two"]);
+        test_repo::git(root, &["checkout", "-q", "-"]);
+
+        let cache = GitCache::default();
+        let range = DiffTarget::parse_range("HEAD..feature").unwrap();
+        let first = cache.diff(root, &range, &[]).await.unwrap();
+        assert!(first.contains("+two"));
+
+        // Moving the branch, as a fetch would, leaves HEAD and the index alone
+        test_repo::git(root, &["branch", "-f", "feature", "HEAD"]);
+        assert_eq!(cache.diff(root, &range, &[]).await.unwrap(), "");
+        assert_eq!((cache.hits(), cache.misses()), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn test_range_diffs_from_the_merge_base() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\n");
+        test_repo::git(root, &["branch", "-M", "main"]);
+        test_repo::git(root, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(root.join("feature.txt"), "branch work\n").unwrap();
+        test_repo::git(root, &["add", "."]);
+        test_repo::git(root, &["commit", "-q", "-m", "feature"]);
+
+        // The base moves on after the branch point
+        test_repo::git(root, &["checkout", "-q", "main"]);
+        std::fs::write(root.join("upstream.txt"), "landed later\n").unwrap();
+        test_repo::git(root, &["add", "."]);
+        test_repo::git(root, &["commit", "-q", "-m", "upstream"]);
+
+        for spec in ["main..feature", "main...feature"] {
+            let range = DiffTarget::parse_range(spec).unwrap();
+            let diff = git_diff(root, &range, &[]).await.unwrap();
+            assert!(diff.contains("+branch work"), "{}", spec);
+            assert!(!diff.contains("upstream.txt"), "{}", spec);
+            let stat = git_diff_stat(root, &range, &[]).await.unwrap();
+            assert_eq!(stat.files_changed, 1, "{}", spec);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_diff_stat_counts_text_and_binary_changes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        test_repo::init(root, "a.txt", "one\ntwo\n");
+        std::fs::write(root.join("logo.png"), [0u8, 159, 146, 150]).unwrap();
+        std::fs::write(root.join("old.txt"), "keep\nthese\nlines\n").unwrap();
+        test_repo::git(root, &["add", "."]);
+        test_repo::git(root, &["commit", "-q", "-m", "more"]);
+
+        std::fs::write(root.join("a.txt"), "one\n2\nthree\n").unwrap();
+        std::fs::write(root.join("logo.png"), [0u8, 1, 2, 3, 4]).unwrap();
+        test_repo::git(root, &["mv", "old.txt", "new.txt"]);
+
+        let stat = git_diff_stat(root, &DiffTarget::WorkingTree, &[])
+            .await
+            .unwrap();
+        assert_eq!(stat.files_changed, 3);
+        assert_eq!((stat.additions, stat.deletions), (2, 1));
+
+        let a = stat.files.iter().find(|f| f.path == "a.txt").unwrap();
+        assert_eq!((a.additions, a.deletions, a.binary), (2, 1, false));
+        let logo = stat.files.iter().find(|f| f.path == "logo.png").unwrap();
+        assert!(logo.binary);
+        assert_eq!((logo.additions, logo.deletions), (0, 0));
+        let renamed = stat.files.iter().find(|f| f.path == "new.txt").unwrap();
+        assert_eq!(renamed.old_path.as_deref(), Some("old.txt"));
+
+        let only_a = git_diff_stat(root, &DiffTarget::WorkingTree, &["a.txt".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(only_a.files_changed, 1);
+    }
+
+    #[test]
+    fn test_refs_that_look_like_options_are_rejected() {
+        assert!(DiffTarget::from_args(None, Some("--output=/tmp/x")).is_err());
+        assert!(DiffTarget::range("main", "-p").is_err());
+        assert_eq!(
+            DiffTarget::range(" main ", "HEAD").unwrap(),
+            DiffTarget::Range {
+                base: "main".to_string(),
+                head: "HEAD".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_range_specs() {
+        assert_eq!(
+            DiffTarget::parse_range("main..feature").unwrap(),
+            DiffTarget::range("main", "feature").unwrap()
+        );
+        assert_eq!(
+            DiffTarget::parse_range("HEAD~2..").unwrap(),
+            DiffTarget::range("HEAD~2", "HEAD").unwrap()
+        );
+        assert_eq!(
+            DiffTarget::parse_range("main...feature").unwrap(),
+            DiffTarget::range("main", "feature").unwrap()
+        );
+        assert!(DiffTarget::parse_range("main").is_err());
+        assert!(DiffTarget::parse_range("..--output=x").is_err());
+        assert_eq!(
+            DiffTarget::last_commits(3).unwrap(),
+            DiffTarget::range("HEAD~3", "HEAD").unwrap()
+        );
+        assert!(DiffTarget::last_commits(0).is_err());
+    }
+
+    #[test]
+    fn test_target_from_args() {
+        assert_eq!(
+            DiffTarget::from_args(None, None).unwrap(),
+            DiffTarget::WorkingTree
+        );
+        assert_eq!(
+            DiffTarget::from_args(Some("staged"), Some("main")).unwrap(),
+            DiffTarget::Base("main".to_string())
+        );
+        assert!(DiffTarget::from_args(Some("everything"), None).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Id generation for reviews, findings and sessions.
+//!
+//! Ids default to random v4 UUIDs. Swapping in [`CounterIdGen`] makes review
+//! output reproducible, which golden tests rely on.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Produces unique ids.
+pub trait IdGen: Debug + Send + Sync {
+    /// The next id.
+    fn next_id(&self) -> String;
+}
+
+/// Shared id generator handle.
+pub type SharedIdGen = Arc<dyn IdGen>;
+
+/// The default generator: random v4 UUIDs.
+pub fn default_id_gen() -> SharedIdGen {
+    Arc::new(UuidGen)
+}
+
+/// Random v4 UUIDs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidGen;
+
+impl IdGen for UuidGen {
+    fn next_id(&self) -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+}
+
+/// Sequential ids (`{prefix}-1`, `{prefix}-2`, ...).
+#[derive(Debug)]
+pub struct CounterIdGen {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl CounterIdGen {
+    /// Create a generator whose ids start at `{prefix}-1`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl IdGen for CounterIdGen {
+    fn next_id(&self) -> String {
+        let n = self.next.fetch_add(1, Ordering::SeqCst);
+        format!("{}-{}", self.prefix, n)
+    }
+}
@@ -2,13 +2,63 @@
 //!
 //! Multi-pass review system with risk scoring and invariant checking.
 
+pub mod baseline;
+pub mod compressed;
+pub mod correctness;
+pub mod diff;
+pub mod editorconfig;
+pub mod gate;
+pub mod git;
+pub mod ids;
+pub mod remote;
+pub mod risk;
+pub mod snippet;
+pub mod static_analysis;
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
+use crate::clock::{system_clock, SharedClock};
+use crate::config::Config;
 use crate::error::Result;
+use crate::service::secrets::SECRET_PATTERNS;
 use crate::service::ContextService;
 use crate::types::review::*;
+use crate::DEFAULT_TOKEN_BUDGET;
+
+pub use baseline::{baseline_path, fingerprint, Baseline};
+pub use diff::{diff_sections, parse_diff_files, DiffLine, LineKind, ParsedDiff, ParsedFile};
+pub use gate::{evaluate_gate, GateResult, GateThresholds};
+pub use ids::{default_id_gen, CounterIdGen, IdGen, SharedIdGen, UuidGen};
+pub use risk::{risk_breakdown, risk_summary, RiskBreakdown, RiskContribution, SizeBucket};
+
+/// Completed reviews by id, shared by the review tools.
+pub type ReviewStore = Arc<RwLock<HashMap<String, Review>>>;
+
+/// Findings of already-analyzed file diff sections, keyed by
+/// [`section_hash`].
+pub type FileFindingsCache = HashMap<String, Vec<Finding>>;
+
+/// A review built partly from cached per-file findings.
+#[derive(Debug, Clone)]
+pub struct IncrementalReview {
+    pub review: Review,
+    /// Files whose findings came from the cache
+    pub cache_hits: usize,
+    /// Files analyzed in this review, in diff order
+    pub analyzed: Vec<String>,
+}
+
+/// Content hash of one file's diff section.
+pub fn section_hash(section: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(section.as_bytes()))
+}
+
+/// Share of the review token budget spent on the AI analysis pass.
+const ANALYSIS_BUDGET_DIVISOR: usize = 4;
 
 /// Review configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,10 +69,31 @@ pub struct ReviewConfig {
     pub performance_checks: bool,
     /// Enable style checks
     pub style_checks: bool,
+    /// Enable language-specific correctness heuristics
+    #[serde(default = "default_correctness_checks")]
+    pub correctness_checks: bool,
     /// Custom invariants to check
     pub invariants: Vec<InvariantDefinition>,
     /// Risk thresholds
     pub risk_thresholds: RiskThresholds,
+    /// Token budget for a single review
+    #[serde(default = "default_token_budget")]
+    pub token_budget: usize,
+    /// Lines of diff context shown before and after a finding's line
+    #[serde(default = "default_context_lines")]
+    pub context_lines: usize,
+}
+
+fn default_correctness_checks() -> bool {
+    true
+}
+
+fn default_context_lines() -> usize {
+    snippet::DEFAULT_CONTEXT_LINES
+}
+
+fn default_token_budget() -> usize {
+    DEFAULT_TOKEN_BUDGET
 }
 
 impl Default for ReviewConfig {
@@ -31,10 +102,28 @@ impl Default for ReviewConfig {
             security_checks: true,
             performance_checks: true,
             style_checks: true,
+            correctness_checks: true,
             invariants: Vec::new(),
             risk_thresholds: RiskThresholds::default(),
+            token_budget: DEFAULT_TOKEN_BUDGET,
+            context_lines: snippet::DEFAULT_CONTEXT_LINES,
+        }
+    }
+}
+
+impl ReviewConfig {
+    /// Default review configuration using the server's token budget.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            token_budget: config.token_budget,
+            ..Self::default()
         }
     }
+
+    /// Token budget for the AI analysis pass.
+    pub fn analysis_budget(&self) -> usize {
+        (self.token_budget / ANALYSIS_BUDGET_DIVISOR).max(1)
+    }
 }
 
 /// Risk thresholds for categorization.
@@ -56,18 +145,196 @@ impl Default for RiskThresholds {
 }
 
 /// Invariant definition.
+///
+/// `pattern` is a regular expression; a pattern that fails to compile is
+/// matched literally instead.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvariantDefinition {
     pub name: String,
+    #[serde(default)]
     pub description: String,
     pub pattern: String,
+    #[serde(default = "default_invariant_severity")]
     pub severity: Severity,
 }
 
+fn default_invariant_severity() -> Severity {
+    Severity::Warning
+}
+
+/// Check invariants against the lines added by a unified diff.
+///
+/// Only added lines are inspected, so removing a violation never fails a check.
+pub fn check_invariants(invariants: &[InvariantDefinition], diff: &str) -> Vec<InvariantCheck> {
+    check_invariants_in_sources(invariants, &ParsedDiff::parse(diff).added_lines_by_file())
+}
+
+/// Check invariants against `(path, content)` pairs.
+///
+/// An empty path is allowed for anonymous content; it is never reported as an
+/// affected file.
+pub fn check_invariants_in_sources(
+    invariants: &[InvariantDefinition],
+    sources: &[(String, String)],
+) -> Vec<InvariantCheck> {
+    invariants
+        .iter()
+        .map(|invariant| {
+            let re = regex::Regex::new(&invariant.pattern).unwrap_or_else(|_| {
+                regex::Regex::new(&regex::escape(&invariant.pattern))
+                    .expect("escaped pattern is a valid regex")
+            });
+
+            let mut matches = 0usize;
+            let mut affected_files = Vec::new();
+            for (path, content) in sources {
+                let count = re.find_iter(content).count();
+                if count > 0 {
+                    matches += count;
+                    if !path.is_empty() && !affected_files.contains(path) {
+                        affected_files.push(path.clone());
+                    }
+                }
+            }
+
+            let passed = matches == 0;
+            InvariantCheck {
+                name: invariant.name.clone(),
+                description: invariant.description.clone(),
+                passed,
+                failure_message: if passed {
+                    None
+                } else {
+                    Some(format!(
+                        "Pattern '{}' matched {} time(s)",
+                        invariant.pattern, matches
+                    ))
+                },
+                affected_files,
+            }
+        })
+        .collect()
+}
+
+/// Merge findings that share `(finding_type, file, line, title)`.
+///
+/// The first finding of each group is kept, in original order, and its
+/// `occurrences` count absorbs the duplicates.
+pub fn dedup_findings(findings: Vec<Finding>) -> Vec<Finding> {
+    let mut merged: Vec<Finding> = Vec::with_capacity(findings.len());
+    let mut index: HashMap<(FindingType, String, Option<u32>, String), usize> = HashMap::new();
+
+    for finding in findings {
+        let key = (
+            finding.finding_type,
+            finding.file.clone(),
+            finding.line,
+            finding.title.clone(),
+        );
+        match index.get(&key) {
+            Some(&i) => merged[i].occurrences += finding.occurrences,
+            None => {
+                index.insert(key, merged.len());
+                merged.push(finding);
+            }
+        }
+    }
+
+    merged
+}
+
+impl Review {
+    /// Render findings as GitHub Actions workflow commands
+    /// (`::error file=...,line=...::message`).
+    pub fn to_github_annotations(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .map(|finding| {
+                let mut props = Vec::new();
+                if !finding.file.is_empty() {
+                    props.push(format!("file={}", escape_property(&finding.file)));
+                }
+                match (finding.line, finding.line_range) {
+                    (_, Some((start, end))) => {
+                        props.push(format!("line={}", start));
+                        props.push(format!("endLine={}", end));
+                    }
+                    (Some(line), None) => props.push(format!("line={}", line)),
+                    (None, None) => {}
+                }
+                props.push(format!("title={}", escape_property(&finding.title)));
+
+                format!(
+                    "::{} {}::{}",
+                    workflow_command_level(finding.severity),
+                    props.join(","),
+                    escape_data(&finding.description)
+                )
+            })
+            .collect()
+    }
+
+    /// Render findings in the GitHub Checks API `annotations` shape.
+    ///
+    /// Findings without a file are skipped since the API requires a path.
+    pub fn to_github_check_annotations(&self) -> Vec<serde_json::Value> {
+        self.findings
+            .iter()
+            .filter(|finding| !finding.file.is_empty())
+            .map(|finding| {
+                let (start, end) = finding
+                    .line_range
+                    .or(finding.line.map(|line| (line, line)))
+                    .unwrap_or((1, 1));
+                serde_json::json!({
+                    "path": finding.file,
+                    "start_line": start,
+                    "end_line": end,
+                    "annotation_level": check_annotation_level(finding.severity),
+                    "title": finding.title,
+                    "message": finding.description,
+                })
+            })
+            .collect()
+    }
+}
+
+fn workflow_command_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "notice",
+    }
+}
+
+fn check_annotation_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::Error => "failure",
+        Severity::Warning => "warning",
+        Severity::Info => "notice",
+    }
+}
+
+/// Escape a workflow command message.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
 /// Review pipeline for analyzing code changes.
 pub struct ReviewPipeline {
     context_service: Arc<ContextService>,
     config: ReviewConfig,
+    ids: SharedIdGen,
+    clock: SharedClock,
+    baseline: Option<Baseline>,
 }
 
 impl ReviewPipeline {
@@ -76,25 +343,76 @@ impl ReviewPipeline {
         Self {
             context_service,
             config,
+            ids: default_id_gen(),
+            clock: system_clock(),
+            baseline: None,
         }
     }
 
+    /// Generate review and finding ids with `ids` instead of random UUIDs.
+    pub fn with_id_gen(mut self, ids: SharedIdGen) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Stamp reviews with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Drop findings known to `baseline` from reviews.
+    pub fn with_baseline(mut self, baseline: Baseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Use the baseline saved in the workspace, if there is one.
+    pub async fn with_workspace_baseline(self) -> Result<Self> {
+        let path = baseline_path(self.context_service.workspace());
+        Ok(match Baseline::load(&path).await? {
+            Some(baseline) => self.with_baseline(baseline),
+            None => self,
+        })
+    }
+
+    /// Deduplicate `findings` and drop those in the baseline, returning the
+    /// rest with review metadata counting the dropped ones.
+    fn finish_findings(
+        &self,
+        findings: Vec<Finding>,
+    ) -> (Vec<Finding>, HashMap<String, serde_json::Value>) {
+        let findings = dedup_findings(findings);
+        let mut metadata = HashMap::new();
+        let Some(baseline) = &self.baseline else {
+            return (findings, metadata);
+        };
+        let (findings, suppressed) = baseline.filter(findings);
+        metadata.insert("suppressed_findings".to_string(), suppressed.into());
+        (findings, metadata)
+    }
+
     /// Review a diff.
+    ///
+    /// Findings in the pipeline's baseline, if any, are left out and counted
+    /// in the `suppressed_findings` metadata.
     pub async fn review_diff(&self, diff: &str, _context: Option<&str>) -> Result<Review> {
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().to_rfc3339();
+        let id = self.ids.next_id();
+        let now = self.clock.now_rfc3339();
 
-        // Parse the diff
-        let files = self.parse_diff(diff)?;
-
-        // Calculate risk score
-        let (risk_score, risk_level) = self.calculate_risk(&files);
+        // Parse the diff once; every pass reads the parsed form
+        let parsed = ParsedDiff::parse(diff);
 
         // Run invariant checks
-        let invariants = self.check_invariants(diff).await?;
+        let invariants =
+            check_invariants_in_sources(&self.config.invariants, &parsed.added_lines_by_file());
 
         // Generate findings
-        let findings = self.generate_findings(&files, diff).await?;
+        let (findings, metadata) = self.finish_findings(self.generate_findings(&parsed).await?);
+
+        // Calculate risk score
+        let mut files = parsed.into_review_files();
+        let (risk_score, risk_level) = self.calculate_risk(&mut files);
 
         Ok(Review {
             id,
@@ -107,139 +425,151 @@ impl ReviewPipeline {
             invariants,
             created_at: now.clone(),
             updated_at: now,
-            metadata: HashMap::new(),
+            metadata,
         })
     }
 
-    /// Parse a unified diff into review files.
-    fn parse_diff(&self, diff: &str) -> Result<Vec<ReviewFile>> {
-        let mut files = Vec::new();
-        let mut current_file: Option<ReviewFile> = None;
-
-        for line in diff.lines() {
-            if line.starts_with("diff --git")
-                || line.starts_with("--- ")
-                || line.starts_with("+++ ")
-            {
-                // Extract file path
-                if line.starts_with("+++ ") {
-                    let path = line.trim_start_matches("+++ ").trim_start_matches("b/");
-                    if let Some(ref mut file) = current_file {
-                        files.push(file.clone());
-                    }
-                    current_file = Some(ReviewFile {
-                        path: path.to_string(),
-                        change_type: ChangeType::Modified,
-                        additions: 0,
-                        deletions: 0,
-                        hunks: Vec::new(),
-                        risk_score: 0,
-                        findings: Vec::new(),
-                    });
-                }
-            } else if let Some(ref mut file) = current_file {
-                if line.starts_with('+') && !line.starts_with("+++") {
-                    file.additions += 1;
-                } else if line.starts_with('-') && !line.starts_with("---") {
-                    file.deletions += 1;
-                }
+    /// Review a diff one file at a time, reusing the findings in `cache`
+    /// for file sections analyzed before and adding the new ones to it.
+    ///
+    /// Risk and invariants still cover the whole diff; only the per-file
+    /// finding passes, including the AI analysis, are skipped on a hit.
+    pub async fn review_diff_incremental(
+        &self,
+        diff: &str,
+        cache: &mut FileFindingsCache,
+    ) -> Result<IncrementalReview> {
+        let id = self.ids.next_id();
+        let now = self.clock.now_rfc3339();
+        let parsed = ParsedDiff::parse(diff);
+        let mut findings = Vec::new();
+        let mut cache_hits = 0;
+        let mut analyzed = Vec::new();
+        for section in parsed.split() {
+            let key = section_hash(section.text);
+            if let Some(cached) = cache.get(&key) {
+                cache_hits += 1;
+                findings.extend(cached.iter().cloned());
+                continue;
             }
+            let file_findings = self.generate_findings(&section).await?;
+            cache.insert(key, file_findings.clone());
+            findings.extend(file_findings);
+            analyzed.extend(section.review_files().map(|f| f.path.clone()));
         }
 
-        if let Some(file) = current_file {
-            files.push(file);
-        }
-
-        Ok(files)
+        let invariants =
+            check_invariants_in_sources(&self.config.invariants, &parsed.added_lines_by_file());
+        let (findings, metadata) = self.finish_findings(findings);
+        let mut files = parsed.into_review_files();
+        let (risk_score, risk_level) = self.calculate_risk(&mut files);
+        let review = Review {
+            id,
+            title: "Code Review".to_string(),
+            status: ReviewStatus::Completed,
+            files,
+            findings,
+            risk_score,
+            risk_level,
+            invariants,
+            created_at: now.clone(),
+            updated_at: now,
+            metadata,
+        };
+        Ok(IncrementalReview {
+            review,
+            cache_hits,
+            analyzed,
+        })
     }
 
-    /// Calculate risk score for the review.
-    fn calculate_risk(&self, files: &[ReviewFile]) -> (u8, RiskLevel) {
-        let mut score = 0u8;
+    /// Summarize the top risks of `review` in prose, for PR summary comments.
+    ///
+    /// Asks the LLM to explain the risk, keeping it to the changed files.
+    /// Falls back to the heuristic [`risk_summary`] when the backend is
+    /// unavailable or returns nothing.
+    pub async fn explain_risk(&self, review: &Review) -> Result<String> {
+        let heuristic = risk_summary(review, &self.config.risk_thresholds);
+        if review.files.is_empty() {
+            return Ok(heuristic);
+        }
 
-        for file in files {
-            // High-risk file patterns
-            if file.path.contains("auth") || file.path.contains("security") {
-                score = score.saturating_add(20);
-            }
-            if file.path.contains("database") || file.path.contains("migration") {
-                score = score.saturating_add(15);
-            }
-            // Size-based risk
-            let changes = file.additions + file.deletions;
-            if changes > 100 {
-                score = score.saturating_add(10);
-            } else if changes > 50 {
-                score = score.saturating_add(5);
+        let changed: Vec<String> = review
+            .files
+            .iter()
+            .map(|f| {
+                format!(
+                    "- {} (+{}/-{}, risk {})",
+                    f.path, f.additions, f.deletions, f.risk_score
+                )
+            })
+            .collect();
+        let prompt = format!(
+            "Summarize the top risks of this code change in a short paragraph for a pull request comment. \
+             Only discuss the changed files listed below; do not speculate about other files.\n\n\
+             Changed files:\n{}\n\nHeuristic assessment:\n{}\n",
+            changed.join("\n"),
+            heuristic
+        );
+        let prompt = self
+            .context_service
+            .token_estimator()
+            .truncate(&prompt, self.config.analysis_budget());
+
+        match self.context_service.chat(prompt).await {
+            Ok(explanation) if !explanation.trim().is_empty() => Ok(explanation.trim().to_string()),
+            Ok(_) => Ok(heuristic),
+            Err(e) => {
+                tracing::debug!("Risk explanation unavailable, using heuristics: {}", e);
+                Ok(heuristic)
             }
         }
+    }
 
-        let level = if score > self.config.risk_thresholds.high_max {
-            RiskLevel::Critical
-        } else if score > self.config.risk_thresholds.medium_max {
-            RiskLevel::High
-        } else if score > self.config.risk_thresholds.low_max {
-            RiskLevel::Medium
-        } else {
-            RiskLevel::Low
+    /// Point `finding` at the diff line containing byte `offset`, with a
+    /// snippet of the surrounding hunk when the line is known.
+    fn locate_finding(&self, finding: &mut Finding, diff: &ParsedDiff, offset: usize) {
+        let Some((path, line)) = diff.locate(offset) else {
+            return;
         };
-
-        (score.min(100), level)
+        finding.line = line;
+        if let Some(snippet) = line.and_then(|line| {
+            let file = diff.review_files().find(|f| f.path == path)?;
+            snippet::hunk_snippet(file, line, self.config.context_lines)
+        }) {
+            finding.code_snippet = Some(snippet);
+        }
+        finding.file = path;
     }
 
-    /// Check invariants against the diff.
-    async fn check_invariants(&self, diff: &str) -> Result<Vec<InvariantCheck>> {
-        let mut results = Vec::new();
-
-        for invariant in &self.config.invariants {
-            let passed = !diff.contains(&invariant.pattern);
-
-            results.push(InvariantCheck {
-                name: invariant.name.clone(),
-                description: invariant.description.clone(),
-                passed,
-                failure_message: if passed {
-                    None
-                } else {
-                    Some(format!("Pattern '{}' found in diff", invariant.pattern))
-                },
-                affected_files: Vec::new(),
-            });
+    /// Calculate risk score for the review, recording per-file scores.
+    fn calculate_risk(&self, files: &mut [ReviewFile]) -> (u8, RiskLevel) {
+        let breakdown = risk_breakdown(files, &self.config.risk_thresholds);
+        for (file, risk) in files.iter_mut().zip(&breakdown.files) {
+            file.risk_score = risk.score;
         }
-
-        Ok(results)
+        (breakdown.score, breakdown.level)
     }
 
     /// Generate findings from the diff using context service for AI analysis.
-    async fn generate_findings(&self, files: &[ReviewFile], diff: &str) -> Result<Vec<Finding>> {
+    async fn generate_findings(&self, parsed: &ParsedDiff<'_>) -> Result<Vec<Finding>> {
         let mut findings = Vec::new();
+        let diff = parsed.text;
+        let files: Vec<&ReviewFile> = parsed.review_files().collect();
 
-        // Security patterns to check
-        let security_patterns = [
-            (
-                "API key exposure",
-                r#"(?i)(api[_-]?key|apikey)\s*[:=]\s*['"]?[a-zA-Z0-9]{20,}"#,
-            ),
-            (
-                "Password in code",
-                r#"(?i)(password|passwd|pwd)\s*[:=]\s*['"]"#,
-            ),
-            (
-                "SQL injection risk",
-                r#"(?i)(execute|query)\s*\(\s*['"].*\+"#,
-            ),
-            (
-                "Hardcoded secret",
-                r#"(?i)(secret|token|credential)\s*[:=]\s*['"]"#,
-            ),
-        ];
+        // Security patterns to check: the shared secret patterns, plus
+        // checks that only make sense on changed code
+        let security_patterns = SECRET_PATTERNS.iter().copied().chain([(
+            "SQL injection risk",
+            r#"(?i)(execute|query)\s*\(\s*['"].*\+"#,
+        )]);
 
         if self.config.security_checks {
-            for (name, pattern) in &security_patterns {
+            for (name, pattern) in security_patterns {
                 if let Ok(re) = regex::Regex::new(pattern) {
                     for cap in re.find_iter(diff) {
-                        findings.push(Finding {
-                            id: uuid::Uuid::new_v4().to_string(),
+                        let mut finding = Finding {
+                            id: self.ids.next_id(),
                             finding_type: FindingType::Security,
                             severity: Severity::Critical,
                             title: name.to_string(),
@@ -257,12 +587,74 @@ impl ReviewPipeline {
                             code_snippet: Some(cap.as_str().to_string()),
                             actionable: true,
                             category: Some("security".to_string()),
+                            occurrences: 1,
+                        };
+                        self.locate_finding(&mut finding, parsed, cap.start());
+                        findings.push(finding);
+                    }
+                }
+            }
+        }
+
+        // Whitespace rules from .editorconfig
+        if self.config.style_checks {
+            if let Some(editorconfig) =
+                editorconfig::EditorConfig::load(self.context_service.workspace())
+            {
+                for file in &files {
+                    for violation in editorconfig.check(file) {
+                        findings.push(Finding {
+                            id: self.ids.next_id(),
+                            finding_type: FindingType::Style,
+                            severity: Severity::Info,
+                            title: format!("EditorConfig: {}", violation.rule),
+                            description: violation.message,
+                            file: file.path.clone(),
+                            line: Some(violation.line),
+                            line_range: None,
+                            suggestion: Some(format!(
+                                "Follow the {} rule in .editorconfig",
+                                violation.rule
+                            )),
+                            code_snippet: snippet::hunk_snippet(
+                                file,
+                                violation.line,
+                                self.config.context_lines,
+                            ),
+                            actionable: true,
+                            category: Some("editorconfig".to_string()),
+                            occurrences: 1,
                         });
                     }
                 }
             }
         }
 
+        // Language-specific correctness heuristics
+        if self.config.correctness_checks {
+            for file in &parsed.files {
+                for issue in correctness::check(file) {
+                    findings.push(Finding {
+                        id: self.ids.next_id(),
+                        finding_type: FindingType::Bug,
+                        severity: Severity::Warning,
+                        title: issue.title.to_string(),
+                        description: format!("Possible bug ({})", issue.rule),
+                        file: file.file.path.clone(),
+                        line: issue.line,
+                        line_range: None,
+                        suggestion: Some(issue.suggestion.to_string()),
+                        code_snippet: issue.line.and_then(|line| {
+                            snippet::hunk_snippet(&file.file, line, self.config.context_lines)
+                        }),
+                        actionable: true,
+                        category: Some("correctness".to_string()),
+                        occurrences: 1,
+                    });
+                }
+            }
+        }
+
         // Performance patterns
         if self.config.performance_checks {
             let perf_patterns = [
@@ -275,9 +667,9 @@ impl ReviewPipeline {
 
             for (name, pattern) in &perf_patterns {
                 if let Ok(re) = regex::Regex::new(pattern) {
-                    if re.is_match(diff) {
-                        findings.push(Finding {
-                            id: uuid::Uuid::new_v4().to_string(),
+                    if let Some(m) = re.find(diff) {
+                        let mut finding = Finding {
+                            id: self.ids.next_id(),
                             finding_type: FindingType::Performance,
                             severity: Severity::Warning,
                             title: name.to_string(),
@@ -289,7 +681,10 @@ impl ReviewPipeline {
                             code_snippet: None,
                             actionable: true,
                             category: Some("performance".to_string()),
-                        });
+                            occurrences: 1,
+                        };
+                        self.locate_finding(&mut finding, parsed, m.start());
+                        findings.push(finding);
                     }
                 }
             }
@@ -297,19 +692,26 @@ impl ReviewPipeline {
 
         // Use context service for semantic analysis if files are changed
         if !files.is_empty() {
-            let query = format!(
-                "Analyze these code changes for potential issues:\n\nChanged files: {}\n\nDiff summary: {} lines added, {} lines removed",
+            let budget = self.config.analysis_budget();
+            let header = format!(
+                "Analyze these code changes for potential issues:\n\nChanged files: {}\n\nDiff summary: {} lines added, {} lines removed\n\n",
                 files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "),
                 files.iter().map(|f| f.additions as usize).sum::<usize>(),
                 files.iter().map(|f| f.deletions as usize).sum::<usize>()
             );
+            let assembled = format!("{}{}", header, diff);
+            let query = self
+                .context_service
+                .token_estimator()
+                .truncate(&assembled, budget);
 
             // Try to get AI-powered analysis
-            match self.context_service.search(&query, Some(2000)).await {
+            match self.context_service.search(query, Some(budget)).await {
                 Ok(analysis) => {
+                    let analysis = analysis.text;
                     if !analysis.is_empty() && analysis.len() > 50 {
                         findings.push(Finding {
-                            id: uuid::Uuid::new_v4().to_string(),
+                            id: self.ids.next_id(),
                             finding_type: FindingType::BestPractice,
                             severity: Severity::Info,
                             title: "AI Analysis".to_string(),
@@ -321,6 +723,7 @@ impl ReviewPipeline {
                             code_snippet: None,
                             actionable: false,
                             category: Some("ai-analysis".to_string()),
+                            occurrences: 1,
                         });
                     }
                 }
@@ -343,8 +746,11 @@ mod tests {
             security_checks: true,
             performance_checks: true,
             style_checks: true,
+            correctness_checks: true,
             invariants: Vec::new(),
             risk_thresholds: RiskThresholds::default(),
+            token_budget: DEFAULT_TOKEN_BUDGET,
+            context_lines: 2,
         }
     }
 
@@ -400,6 +806,73 @@ mod tests {
         assert!(diff.contains("+ "));
     }
 
+    #[test]
+    fn test_parse_added_file() {
+        let diff = "diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\nindex 0000000..e69de29\n--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1,2 @@\n+fn new() {}\n+-- not a header\n";
+        let files = parse_diff_files(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/new.rs");
+        assert_eq!(files[0].change_type, ChangeType::Added);
+        assert_eq!((files[0].additions, files[0].deletions), (2, 0));
+    }
+
+    #[test]
+    fn test_parse_deleted_file() {
+        let diff = "diff --git a/old.rs b/old.rs\ndeleted file mode 100644\n--- a/old.rs\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-fn old() {}\n--- removed comment\ndiff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let files = parse_diff_files(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "old.rs");
+        assert_eq!(files[0].change_type, ChangeType::Deleted);
+        assert_eq!((files[0].additions, files[0].deletions), (0, 2));
+        assert_eq!(files[1].path, "lib.rs");
+        assert_eq!(files[1].change_type, ChangeType::Modified);
+        assert_eq!((files[1].additions, files[1].deletions), (1, 1));
+    }
+
+    #[test]
+    fn test_diff_sections_cover_each_file() {
+        let diff = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n--- a/b.rs\n+++ b/b.rs\n+z\n";
+        let sections = diff_sections(diff);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0.path, "a.rs");
+        assert_eq!(
+            sections[0].1,
+            "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n"
+        );
+        assert_eq!(sections[1].1, "--- a/b.rs\n+++ b/b.rs\n+z\n");
+        assert_ne!(section_hash(sections[0].1), section_hash(sections[1].1));
+    }
+
+    #[test]
+    fn test_parse_renamed_file() {
+        let diff = "diff --git a/src/before.rs b/src/after.rs\nsimilarity index 90%\nrename from src/before.rs\nrename to src/after.rs\n--- a/src/before.rs\n+++ b/src/after.rs\n@@ -1 +1 @@\n-fn before() {}\n+fn after() {}\n";
+        let files = parse_diff_files(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/after.rs");
+        assert_eq!(files[0].old_path.as_deref(), Some("src/before.rs"));
+        assert_eq!(files[0].change_type, ChangeType::Renamed);
+        assert_eq!((files[0].additions, files[0].deletions), (1, 1));
+
+        // A pure rename has no hunks at all
+        let diff = "diff --git a/a.txt b/b.txt\nsimilarity index 100%\nrename from a.txt\nrename to b.txt\n";
+        let files = parse_diff_files(diff);
+        assert_eq!(files[0].path, "b.txt");
+        assert_eq!(files[0].change_type, ChangeType::Renamed);
+    }
+
+    #[test]
+    fn test_parse_binary_file() {
+        let diff = "diff --git a/logo.png b/logo.png\nindex 1234567..89abcde 100644\nBinary files a/logo.png and b/logo.png differ\ndiff --git a/icon.png b/icon.png\nnew file mode 100644\nBinary files /dev/null and b/icon.png differ\n";
+        let files = parse_diff_files(diff);
+        assert_eq!(files.len(), 2);
+        assert!(files[0].binary);
+        assert_eq!(files[0].change_type, ChangeType::Modified);
+        assert_eq!((files[0].additions, files[0].deletions), (0, 0));
+        assert_eq!(files[1].path, "icon.png");
+        assert!(files[1].binary);
+        assert_eq!(files[1].change_type, ChangeType::Added);
+    }
+
     /// Helper to calculate risk score using the same logic as ReviewPipeline
     fn calculate_test_risk(files: &[ReviewFile], thresholds: &RiskThresholds) -> (u8, RiskLevel) {
         let mut score = 0u8;
@@ -442,6 +915,8 @@ mod tests {
             change_type: ChangeType::Modified,
             additions: 10,
             deletions: 5,
+            old_path: None,
+            binary: false,
             hunks: Vec::new(),
             risk_score: 0,
             findings: Vec::new(),
@@ -462,6 +937,8 @@ mod tests {
             change_type: ChangeType::Modified,
             additions: 80,
             deletions: 30,
+            old_path: None,
+            binary: false,
             hunks: Vec::new(),
             risk_score: 0,
             findings: Vec::new(),
@@ -483,6 +960,8 @@ mod tests {
             change_type: ChangeType::Modified,
             additions: 100,
             deletions: 50,
+            old_path: None,
+            binary: false,
             hunks: Vec::new(),
             risk_score: 0,
             findings: Vec::new(),
@@ -504,6 +983,8 @@ mod tests {
                 change_type: ChangeType::Modified,
                 additions: 200,
                 deletions: 100,
+                old_path: None,
+                binary: false,
                 hunks: Vec::new(),
                 risk_score: 0,
                 findings: Vec::new(),
@@ -513,6 +994,8 @@ mod tests {
                 change_type: ChangeType::Modified,
                 additions: 150,
                 deletions: 50,
+                old_path: None,
+                binary: false,
                 hunks: Vec::new(),
                 risk_score: 0,
                 findings: Vec::new(),
@@ -522,6 +1005,8 @@ mod tests {
                 change_type: ChangeType::Added,
                 additions: 150,
                 deletions: 0,
+                old_path: None,
+                binary: false,
                 hunks: Vec::new(),
                 risk_score: 0,
                 findings: Vec::new(),
@@ -531,6 +1016,8 @@ mod tests {
                 change_type: ChangeType::Modified,
                 additions: 100,
                 deletions: 50,
+                old_path: None,
+                binary: false,
                 hunks: Vec::new(),
                 risk_score: 0,
                 findings: Vec::new(),
@@ -576,6 +1063,8 @@ mod tests {
             change_type: ChangeType::Added,
             additions: 50,
             deletions: 0,
+            old_path: None,
+            binary: false,
             hunks: Vec::new(),
             risk_score: 10,
             findings: Vec::new(),
@@ -600,18 +1089,274 @@ mod tests {
             code_snippet: Some("api_key = \"...\"".to_string()),
             actionable: true,
             category: Some("security".to_string()),
+            occurrences: 1,
         };
 
         assert_eq!(finding.severity, Severity::Critical);
         assert!(finding.actionable);
     }
 
+    #[test]
+    fn test_check_invariants_only_inspects_added_lines() {
+        let invariants = vec![InvariantDefinition {
+            name: "No TODO".to_string(),
+            description: String::new(),
+            pattern: r"TODO\b".to_string(),
+            severity: Severity::Warning,
+        }];
+        let diff = "--- a/a.rs\n+++ b/a.rs\n-// TODO remove\n+fn a() {}\n--- a/b.rs\n+++ b/b.rs\n+// TODO later\n";
+
+        let checks = check_invariants(&invariants, diff);
+        assert!(!checks[0].passed);
+        assert_eq!(checks[0].affected_files, vec!["b.rs".to_string()]);
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let finding = |severity, file: &str, line| Finding {
+            id: "f".to_string(),
+            finding_type: FindingType::Security,
+            severity,
+            title: "Hardcoded secret".to_string(),
+            description: "Found key\nin config".to_string(),
+            file: file.to_string(),
+            line,
+            line_range: None,
+            suggestion: None,
+            code_snippet: None,
+            actionable: true,
+            category: None,
+            occurrences: 1,
+        };
+        let review = Review {
+            id: "r".to_string(),
+            title: "Code Review".to_string(),
+            status: ReviewStatus::Completed,
+            files: Vec::new(),
+            findings: vec![
+                finding(Severity::Critical, "src/config.rs", Some(42)),
+                finding(Severity::Info, "", None),
+            ],
+            risk_score: 0,
+            risk_level: RiskLevel::Low,
+            invariants: Vec::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            metadata: HashMap::new(),
+        };
+
+        let commands = review.to_github_annotations();
+        assert_eq!(
+            commands[0],
+            "::error file=src/config.rs,line=42,title=Hardcoded secret::Found key%0Ain config"
+        );
+        assert_eq!(
+            commands[1],
+            "::notice title=Hardcoded secret::Found key%0Ain config"
+        );
+
+        let annotations = review.to_github_check_annotations();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0]["annotation_level"], "failure");
+        assert_eq!(annotations[0]["start_line"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_analysis_budget_derives_from_config() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+
+        let config = Config {
+            token_budget: 12_000,
+            ..backend.config(temp_dir.path())
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        service.index_workspace().await.unwrap();
+
+        let pipeline = ReviewPipeline::new(service, ReviewConfig::from_config(&config));
+        let diff = "--- a/lib.rs\n+++ b/lib.rs\n+fn added() {}\n";
+        pipeline.review_diff(diff, None).await.unwrap();
+
+        let requests = backend.requests("agents/codebase-retrieval");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["max_output_length"], 3_000);
+        assert!(requests[0]["information_request"]
+            .as_str()
+            .unwrap()
+            .contains("+fn added() {}"));
+    }
+
+    #[tokio::test]
+    async fn test_findings_carry_hunk_context() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = backend.config(temp_dir.path());
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let pipeline = ReviewPipeline::new(
+            service,
+            ReviewConfig {
+                context_lines: 1,
+                ..ReviewConfig::from_config(&config)
+            },
+        );
+
+        let diff = "--- a/src/db.rs\n+++ b/src/db.rs\n@@ -1,3 +1,4 @@\n fn connect() {\n     let user = \"app\";\n+    let password = \"hunter2\";\n     open(user, password)\n";
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+        let finding = review
+            .findings
+            .iter()
+            .find(|f| f.title == "Password in code")
+            .unwrap();
+        assert_eq!(finding.file, "src/db.rs");
+        assert_eq!(finding.line, Some(3));
+        assert_eq!(
+            finding.code_snippet.as_deref(),
+            Some("```\n  2 |     let user = \"app\";\n> 3 |     let password = \"hunter2\";\n  4 |     open(user, password)\n```")
+        );
+        assert_eq!(review.files[0].hunks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_editorconfig_findings_follow_style_checks() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(".editorconfig"),
+            "root = true\n\n[*.rs]\nindent_style = space\n",
+        )
+        .unwrap();
+        let config = backend.config(temp_dir.path());
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,3 @@\n fn a() {\n+\tlet tabbed = 1;\n }\n";
+
+        let pipeline = ReviewPipeline::new(service.clone(), ReviewConfig::from_config(&config));
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+        let finding = review
+            .findings
+            .iter()
+            .find(|f| f.category.as_deref() == Some("editorconfig"))
+            .unwrap();
+        assert_eq!(finding.title, "EditorConfig: indent_style");
+        assert_eq!(finding.severity, Severity::Info);
+        assert_eq!(
+            (finding.file.as_str(), finding.line),
+            ("src/lib.rs", Some(2))
+        );
+
+        let pipeline = ReviewPipeline::new(
+            service,
+            ReviewConfig {
+                style_checks: false,
+                ..ReviewConfig::from_config(&config)
+            },
+        );
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+        assert!(review
+            .findings
+            .iter()
+            .all(|f| f.category.as_deref() != Some("editorconfig")));
+    }
+
+    #[tokio::test]
+    async fn test_correctness_findings_follow_flag() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = backend.config(temp_dir.path());
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let diff = "--- a/app.py\n+++ b/app.py\n@@ -1,2 +1,3 @@\n try:\n     run()\n+except:\n";
+
+        let pipeline = ReviewPipeline::new(service.clone(), ReviewConfig::from_config(&config));
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+        let finding = review
+            .findings
+            .iter()
+            .find(|f| f.category.as_deref() == Some("correctness"))
+            .unwrap();
+        assert_eq!(finding.title, "Bare except");
+        assert_eq!(finding.finding_type, FindingType::Bug);
+        assert_eq!(finding.severity, Severity::Warning);
+        assert_eq!((finding.file.as_str(), finding.line), ("app.py", Some(3)));
+
+        let pipeline = ReviewPipeline::new(
+            service,
+            ReviewConfig {
+                correctness_checks: false,
+                ..ReviewConfig::from_config(&config)
+            },
+        );
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+        assert!(review
+            .findings
+            .iter()
+            .all(|f| f.category.as_deref() != Some("correctness")));
+    }
+
+    #[tokio::test]
+    async fn test_explain_risk_prompts_with_changed_files() {
+        let backend = crate::sdk::mock_backend::MockBackend::start().await;
+        backend.set_chat("The auth change is the main risk.");
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+        let config = backend.config(temp_dir.path());
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+
+        let pipeline = ReviewPipeline::new(service, ReviewConfig::from_config(&config));
+        let diff = "--- a/src/auth/login.rs\n+++ b/src/auth/login.rs\n+fn login() {}\n--- a/lib.rs\n+++ b/lib.rs\n+fn added() {}\n";
+        let review = pipeline.review_diff(diff, None).await.unwrap();
+
+        let explanation = pipeline.explain_risk(&review).await.unwrap();
+        assert_eq!(explanation, "The auth change is the main risk.");
+        let requests = backend.requests("chat-stream");
+        assert_eq!(requests.len(), 1);
+        let prompt = requests[0]["prompt"].as_str().unwrap();
+        assert!(prompt.contains("- src/auth/login.rs (+1/-0"), "{}", prompt);
+        assert!(prompt.contains("- lib.rs (+1/-0"), "{}", prompt);
+
+        // Backend down: the heuristic summary comes back instead
+        backend.fail_next("chat-stream", 503, 1);
+        let fallback = pipeline.explain_risk(&review).await.unwrap();
+        assert!(fallback.starts_with("Risk is"), "{}", fallback);
+        assert!(fallback.contains("src/auth/login.rs: touches authentication"));
+    }
+
+    #[test]
+    fn test_dedup_findings_merges_identical_matches() {
+        let finding = |title: &str, line| Finding {
+            id: uuid::Uuid::new_v4().to_string(),
+            finding_type: FindingType::Security,
+            severity: Severity::Critical,
+            title: title.to_string(),
+            description: "Potential security issue detected".to_string(),
+            file: "src/config.rs".to_string(),
+            line,
+            line_range: None,
+            suggestion: None,
+            code_snippet: None,
+            actionable: true,
+            category: Some("security".to_string()),
+            occurrences: 1,
+        };
+
+        let findings = dedup_findings(vec![
+            finding("Password in code", Some(3)),
+            finding("Hardcoded secret", Some(3)),
+            finding("Password in code", Some(3)),
+        ]);
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].title, "Password in code");
+        assert_eq!(findings[0].occurrences, 2);
+        assert_eq!(findings[1].occurrences, 1);
+    }
+
     #[test]
     fn test_config_with_invariants() {
         let config = ReviewConfig {
             security_checks: true,
             performance_checks: false,
             style_checks: false,
+            correctness_checks: false,
             invariants: vec![InvariantDefinition {
                 name: "No TODO".to_string(),
                 description: "Code should not contain TODO comments".to_string(),
@@ -619,6 +1364,8 @@ mod tests {
                 severity: Severity::Warning,
             }],
             risk_thresholds: RiskThresholds::default(),
+            token_budget: DEFAULT_TOKEN_BUDGET,
+            context_lines: 2,
         };
 
         assert_eq!(config.invariants.len(), 1);
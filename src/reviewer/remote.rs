@@ -0,0 +1,657 @@
+//! Fetching diffs for pull/merge requests hosted on GitHub or GitLab.
+//!
+//! Review tools otherwise need a local checkout. Here a PR/MR URL is turned
+//! into a unified diff through the host's API:
+//!
+//! - `https://github.com/{owner}/{repo}/pull/{n}` (optionally with a
+//!   `.diff`/`.patch` suffix) is read from the pull request files endpoint,
+//!   authenticated with `GITHUB_TOKEN` (or `GH_TOKEN`) when set.
+//! - `https://{gitlab-host}/{group}/{project}/-/merge_requests/{n}` is read
+//!   from the merge request diffs endpoint of that host. `GITLAB_TOKEN` is
+//!   only sent to the instance at `GITLAB_URL` (default `https://gitlab.com`);
+//!   any other host is asked without credentials, since the URL may come
+//!   from untrusted text.
+//! - Any other URL ending in `.diff` or `.patch` is downloaded as-is, as is
+//!   one ending in `.gz` or `.zip`, which is decompressed. Downloads are
+//!   capped at [`MAX_DOWNLOAD_SIZE`].
+//!
+//! The API endpoints are paginated, so large changes are fetched page by
+//! page instead of hitting the size limit of the single-document diff view.
+
+use reqwest::header::{HeaderMap, ACCEPT, AUTHORIZATION, LINK};
+use reqwest::Url;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{Error, Result};
+use crate::reviewer::compressed::{decompress, MAX_DECOMPRESSED_SIZE};
+use crate::VERSION;
+
+/// Public GitHub API.
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// GitLab instance `GITLAB_TOKEN` belongs to unless `GITLAB_URL` is set.
+pub const GITLAB_URL: &str = "https://gitlab.com";
+
+/// Largest `.diff`/`.patch` download accepted, before any decompression.
+pub const MAX_DOWNLOAD_SIZE: usize = MAX_DECOMPRESSED_SIZE;
+
+/// Files (GitHub) or diffs (GitLab) requested per page.
+const PER_PAGE: usize = 100;
+
+/// Pages fetched before giving up; GitHub lists at most 3000 files.
+const MAX_PAGES: usize = 30;
+
+/// Timeout for each request to the hosting service.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a diff URL points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteDiff {
+    /// A GitHub pull request
+    GitHub {
+        owner: String,
+        repo: String,
+        number: u64,
+    },
+    /// A GitLab merge request; `base` is the instance URL
+    GitLab {
+        base: String,
+        project: String,
+        iid: u64,
+    },
+    /// A `.diff`/`.patch` download, possibly gzipped or zipped
+    Raw(Url),
+}
+
+impl RemoteDiff {
+    /// Work out how to fetch the diff for `url`.
+    pub fn parse(url: &str) -> Result<Self> {
+        let parsed = Url::parse(url.trim())
+            .map_err(|e| Error::InvalidToolArguments(format!("Invalid URL '{}': {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(Error::InvalidToolArguments(format!(
+                "Unsupported URL scheme '{}': expected http or https",
+                parsed.scheme()
+            )));
+        }
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        if parsed.host_str() == Some("github.com") {
+            if let [owner, repo, "pull", number, ..] = segments.as_slice() {
+                if let Some(number) = parse_number(number) {
+                    return Ok(Self::GitHub {
+                        owner: owner.to_string(),
+                        repo: repo.to_string(),
+                        number,
+                    });
+                }
+            }
+        }
+
+        if let Some(dash) = segments.iter().position(|s| *s == "-") {
+            if let ["merge_requests", iid, ..] = &segments[dash + 1..] {
+                if let Some(iid) = parse_number(iid).filter(|_| dash > 0) {
+                    let mut base = parsed.clone();
+                    base.set_path("");
+                    return Ok(Self::GitLab {
+                        base: base.as_str().trim_end_matches('/').to_string(),
+                        project: segments[..dash].join("/"),
+                        iid,
+                    });
+                }
+            }
+        }
+
+        if [".diff", ".patch", ".gz", ".zip"]
+            .iter()
+            .any(|suffix| parsed.path().ends_with(suffix))
+        {
+            return Ok(Self::Raw(parsed));
+        }
+
+        Err(Error::InvalidToolArguments(format!(
+            "Unsupported diff URL '{}': expected a GitHub pull request, a GitLab merge request, or a .diff/.patch URL (optionally .gz or .zip)",
+            url
+        )))
+    }
+}
+
+/// `42`, `42.diff` or `42.patch` as a number.
+fn parse_number(segment: &str) -> Option<u64> {
+    segment
+        .trim_end_matches(".diff")
+        .trim_end_matches(".patch")
+        .parse()
+        .ok()
+}
+
+/// One changed file as reported by the hosting service.
+#[derive(Debug)]
+struct FileChange {
+    old_path: String,
+    new_path: String,
+    added: bool,
+    deleted: bool,
+    /// Hunks without file headers; `None` for binary or oversized files
+    patch: Option<String>,
+}
+
+impl FileChange {
+    /// The change as a `diff --git` section.
+    fn render(&self) -> String {
+        let mut out = format!("diff --git a/{} b/{}\n", self.old_path, self.new_path);
+        if self.added {
+            out.push_str("new file mode 100644\n");
+        } else if self.deleted {
+            out.push_str("deleted file mode 100644\n");
+        } else if self.old_path != self.new_path {
+            out.push_str(&format!(
+                "rename from {}\nrename to {}\n",
+                self.old_path, self.new_path
+            ));
+        }
+
+        let old = if self.added {
+            "/dev/null".to_string()
+        } else {
+            format!("a/{}", self.old_path)
+        };
+        let new = if self.deleted {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", self.new_path)
+        };
+        match self.patch.as_deref().filter(|p| !p.is_empty()) {
+            Some(patch) => {
+                out.push_str(&format!("--- {}\n+++ {}\n{}", old, new, patch));
+                if !patch.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None if self.old_path == self.new_path || self.added || self.deleted => {
+                out.push_str(&format!("Binary files {} and {} differ\n", old, new));
+            }
+            None => {}
+        }
+        out
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubFile {
+    filename: String,
+    #[serde(default)]
+    previous_filename: Option<String>,
+    status: String,
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+impl From<GitHubFile> for FileChange {
+    fn from(file: GitHubFile) -> Self {
+        Self {
+            old_path: file
+                .previous_filename
+                .unwrap_or_else(|| file.filename.clone()),
+            new_path: file.filename,
+            added: file.status == "added",
+            deleted: file.status == "removed",
+            patch: file.patch,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabDiff {
+    old_path: String,
+    new_path: String,
+    #[serde(default)]
+    new_file: bool,
+    #[serde(default)]
+    deleted_file: bool,
+    #[serde(default)]
+    diff: Option<String>,
+}
+
+impl From<GitLabDiff> for FileChange {
+    fn from(diff: GitLabDiff) -> Self {
+        Self {
+            old_path: diff.old_path,
+            new_path: diff.new_path,
+            added: diff.new_file,
+            deleted: diff.deleted_file,
+            patch: diff.diff,
+        }
+    }
+}
+
+/// Downloads PR/MR diffs.
+#[derive(Debug, Clone)]
+pub struct RemoteDiffClient {
+    http: reqwest::Client,
+    github_api: String,
+    github_token: Option<String>,
+    gitlab_url: String,
+    gitlab_token: Option<String>,
+    max_download: usize,
+}
+
+impl RemoteDiffClient {
+    /// Client using the public GitHub API and tokens from the environment.
+    pub fn from_env() -> Self {
+        let token = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| std::env::var(name).ok())
+                .filter(|t| !t.trim().is_empty())
+        };
+        Self {
+            http: reqwest::Client::builder()
+                .user_agent(format!("context-engine/{}", VERSION))
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            github_api: GITHUB_API_URL.to_string(),
+            github_token: token(&["GITHUB_TOKEN", "GH_TOKEN"]),
+            gitlab_url: token(&["GITLAB_URL"])
+                .unwrap_or_else(|| GITLAB_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            gitlab_token: token(&["GITLAB_TOKEN"]),
+            max_download: MAX_DOWNLOAD_SIZE,
+        }
+    }
+
+    /// Use a different GitHub API base URL (e.g. GitHub Enterprise).
+    pub fn with_github_api(mut self, url: impl Into<String>) -> Self {
+        self.github_api = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Override the GitHub token.
+    pub fn with_github_token(mut self, token: Option<String>) -> Self {
+        self.github_token = token;
+        self
+    }
+
+    /// Use a different GitLab instance (e.g. self-hosted) for the token.
+    pub fn with_gitlab_url(mut self, url: impl Into<String>) -> Self {
+        self.gitlab_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Override the GitLab token.
+    pub fn with_gitlab_token(mut self, token: Option<String>) -> Self {
+        self.gitlab_token = token;
+        self
+    }
+
+    /// Cap `.diff`/`.patch` downloads at `limit` bytes.
+    pub fn with_max_download(mut self, limit: usize) -> Self {
+        self.max_download = limit;
+        self
+    }
+
+    /// The GitLab token, if `base` is the configured instance.
+    fn gitlab_token_for(&self, base: &str) -> Option<&str> {
+        let trusted = Url::parse(&self.gitlab_url).ok()?;
+        let host = Url::parse(base).ok()?;
+        if host.origin() != trusted.origin() {
+            debug!(
+                "Not sending GITLAB_TOKEN to {}: not {}",
+                base, self.gitlab_url
+            );
+            return None;
+        }
+        self.gitlab_token.as_deref()
+    }
+
+    /// Fetch the unified diff for a PR/MR or `.diff`/`.patch` URL.
+    pub async fn fetch(&self, url: &str) -> Result<String> {
+        match RemoteDiff::parse(url)? {
+            RemoteDiff::GitHub {
+                owner,
+                repo,
+                number,
+            } => {
+                let first = format!(
+                    "{}/repos/{}/{}/pulls/{}/files?per_page={}",
+                    self.github_api, owner, repo, number, PER_PAGE
+                );
+                self.fetch_github(first).await
+            }
+            RemoteDiff::GitLab { base, project, iid } => {
+                self.fetch_gitlab(&base, &project, iid).await
+            }
+            RemoteDiff::Raw(url) => {
+                let response = check(self.http.get(url).send().await?).await?;
+                let body = read_capped(response, self.max_download).await?;
+                decompress(&body, MAX_DECOMPRESSED_SIZE).await
+            }
+        }
+    }
+
+    async fn fetch_github(&self, first_page: String) -> Result<String> {
+        let mut diff = String::new();
+        let mut next = Some(first_page);
+        let mut pages = 0;
+        while let Some(url) = next.take() {
+            pages += 1;
+            if pages > MAX_PAGES {
+                debug!("Stopping after {} pages of pull request files", MAX_PAGES);
+                break;
+            }
+            let mut request = self
+                .http
+                .get(&url)
+                .header(ACCEPT, "application/vnd.github+json");
+            if let Some(token) = &self.github_token {
+                request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+            }
+            let response = check(request.send().await?).await?;
+            next = next_link(response.headers());
+            let files: Vec<GitHubFile> = response.json().await?;
+            for file in files {
+                diff.push_str(&FileChange::from(file).render());
+            }
+        }
+        Ok(diff)
+    }
+
+    async fn fetch_gitlab(&self, base: &str, project: &str, iid: u64) -> Result<String> {
+        let endpoint = format!(
+            "{}/api/v4/projects/{}/merge_requests/{}/diffs",
+            base,
+            project.replace('/', "%2F"),
+            iid
+        );
+        let mut diff = String::new();
+        let mut page = Some("1".to_string());
+        let mut pages = 0;
+        while let Some(current) = page.take() {
+            pages += 1;
+            if pages > MAX_PAGES {
+                debug!("Stopping after {} pages of merge request diffs", MAX_PAGES);
+                break;
+            }
+            let mut request = self.http.get(format!(
+                "{}?per_page={}&page={}",
+                endpoint, PER_PAGE, current
+            ));
+            if let Some(token) = self.gitlab_token_for(base) {
+                request = request.header("PRIVATE-TOKEN", token);
+            }
+            let response = check(request.send().await?).await?;
+            page = response
+                .headers()
+                .get("x-next-page")
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string);
+            let diffs: Vec<GitLabDiff> = response.json().await?;
+            for file in diffs {
+                diff.push_str(&FileChange::from(file).render());
+            }
+        }
+        Ok(diff)
+    }
+}
+
+/// Turn a non-success response into [`Error::Api`].
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let hint = match status.as_u16() {
+        401 | 403 | 404 => " (private repositories need GITHUB_TOKEN or GITLAB_TOKEN)",
+        _ => "",
+    };
+    Err(Error::api(
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown"),
+        format!("{}{}", body.trim(), hint),
+    ))
+}
+
+/// The body of `response`, failing once it passes `limit` bytes.
+async fn read_capped(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let too_large =
+        || Error::InvalidToolArguments(format!("Diff download exceeds the {} byte limit", limit));
+    if response
+        .content_length()
+        .is_some_and(|len| len > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// The `rel="next"` URL of a `Link` header.
+fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Query;
+    use axum::http::{HeaderMap as AxumHeaders, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+    use std::collections::HashMap;
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_parse_urls() {
+        assert_eq!(
+            RemoteDiff::parse("https://github.com/acme/widgets/pull/42.diff").unwrap(),
+            RemoteDiff::GitHub {
+                owner: "acme".to_string(),
+                repo: "widgets".to_string(),
+                number: 42,
+            }
+        );
+        assert_eq!(
+            RemoteDiff::parse("https://gitlab.example.com/team/sub/app/-/merge_requests/7")
+                .unwrap(),
+            RemoteDiff::GitLab {
+                base: "https://gitlab.example.com".to_string(),
+                project: "team/sub/app".to_string(),
+                iid: 7,
+            }
+        );
+        assert!(matches!(
+            RemoteDiff::parse("https://example.com/changes.patch").unwrap(),
+            RemoteDiff::Raw(_)
+        ));
+        assert!(matches!(
+            RemoteDiff::parse("https://example.com/changes.patch.gz").unwrap(),
+            RemoteDiff::Raw(_)
+        ));
+        assert!(RemoteDiff::parse("https://github.com/acme/widgets").is_err());
+        assert!(RemoteDiff::parse("file:///tmp/x.diff").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_github_pages_are_joined_with_auth() {
+        let app = Router::new().route(
+            "/repos/acme/widgets/pulls/42/files",
+            get(
+                |headers: AxumHeaders, Query(query): Query<HashMap<String, String>>| async move {
+                    if headers.get("authorization").and_then(|v| v.to_str().ok())
+                        != Some("Bearer secret")
+                    {
+                        return StatusCode::UNAUTHORIZED.into_response();
+                    }
+                    let host = headers["host"].to_str().unwrap().to_string();
+                    match query.get("page").map(String::as_str) {
+                        None => (
+                            [(
+                                "link",
+                                format!(
+                                    "<http://{}/repos/acme/widgets/pulls/42/files?page=2>; rel=\"next\"",
+                                    host
+                                ),
+                            )],
+                            axum::Json(serde_json::json!([{
+                                "filename": "src/lib.rs",
+                                "status": "modified",
+                                "patch": "@@ -1 +1 @@\n-old\n+new"
+                            }])),
+                        )
+                            .into_response(),
+                        _ => axum::Json(serde_json::json!([{
+                            "filename": "src/new.rs",
+                            "previous_filename": "src/old.rs",
+                            "status": "renamed",
+                            "patch": "@@ -1 +1 @@\n-a\n+b"
+                        }]))
+                        .into_response(),
+                    }
+                },
+            ),
+        );
+        let api = serve(app).await;
+
+        let client = RemoteDiffClient::from_env().with_github_api(api);
+        let err = client
+            .clone()
+            .with_github_token(None)
+            .fetch("https://github.com/acme/widgets/pull/42")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Api { status: 401, .. }), "{:?}", err);
+
+        let diff = client
+            .with_github_token(Some("secret".to_string()))
+            .fetch("https://github.com/acme/widgets/pull/42.patch")
+            .await
+            .unwrap();
+        assert!(diff.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n"));
+        assert!(diff.contains("rename from src/old.rs\nrename to src/new.rs\n"));
+        assert!(diff.contains("+++ b/src/new.rs\n@@ -1 +1 @@\n-a\n+b\n"));
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_and_raw_diffs() {
+        let app = Router::new()
+            .route(
+                "/api/v4/projects/team%2Fapp/merge_requests/7/diffs",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    let (next, path) = match query.get("page").map(String::as_str) {
+                        Some("1") => ("2", "README.md"),
+                        _ => ("", "docs/new.md"),
+                    };
+                    (
+                        [("x-next-page", next)],
+                        axum::Json(serde_json::json!([{
+                            "old_path": path,
+                            "new_path": path,
+                            "new_file": path == "docs/new.md",
+                            "deleted_file": false,
+                            "diff": "@@ -0,0 +1 @@\n+hello\n"
+                        }])),
+                    )
+                }),
+            )
+            .route(
+                "/changes.diff",
+                get(|| async { "diff --git a/x b/x\n--- a/x\n+++ b/x\n" }),
+            );
+        let base = serve(app).await;
+        let client = RemoteDiffClient::from_env().with_max_download(1024);
+
+        let diff = client
+            .fetch(&format!("{}/team/app/-/merge_requests/7", base))
+            .await
+            .unwrap();
+        assert!(diff.contains("diff --git a/README.md b/README.md\n"));
+        assert!(diff.contains("new file mode 100644\n--- /dev/null\n+++ b/docs/new.md\n"));
+
+        let raw = client
+            .fetch(&format!("{}/changes.diff", base))
+            .await
+            .unwrap();
+        assert_eq!(raw, "diff --git a/x b/x\n--- a/x\n+++ b/x\n");
+
+        let err = client
+            .with_max_download(8)
+            .fetch(&format!("{}/changes.diff", base))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("8 byte limit"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_token_only_goes_to_configured_instance() {
+        let app = Router::new().route(
+            "/api/v4/projects/a/merge_requests/1/diffs",
+            get(|headers: AxumHeaders| async move {
+                let token = headers
+                    .get("private-token")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("none")
+                    .to_string();
+                axum::Json(serde_json::json!([{
+                    "old_path": token,
+                    "new_path": token,
+                    "diff": "@@ -1 +1 @@\n-a\n+b\n"
+                }]))
+            }),
+        );
+        let host = serve(app).await;
+        let url = format!("{}/a/-/merge_requests/1", host);
+        let client = RemoteDiffClient::from_env().with_gitlab_token(Some("secret".to_string()));
+
+        // An unconfigured host is asked without the token
+        let diff = client
+            .clone()
+            .with_gitlab_url(GITLAB_URL)
+            .fetch(&url)
+            .await
+            .unwrap();
+        assert!(diff.starts_with("diff --git a/none b/none\n"), "{}", diff);
+        assert!(!diff.contains("secret"));
+
+        let diff = client.with_gitlab_url(host).fetch(&url).await.unwrap();
+        assert!(
+            diff.starts_with("diff --git a/secret b/secret\n"),
+            "{}",
+            diff
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Risk scoring for a change set.
+//!
+//! Scores are the sum of per-file contributions from path and size
+//! heuristics. The breakdown keeps every contribution so callers can show
+//! why a change scored the way it did, not just the total.
+
+use serde::Serialize;
+
+use super::RiskThresholds;
+use crate::types::review::{Review, ReviewFile, RiskLevel};
+
+/// Most risk signals and findings listed in a summary.
+const SUMMARY_ITEMS: usize = 5;
+
+/// Path heuristics: (rule, substrings, points, reason).
+const PATH_RULES: &[(&str, &[&str], u8, &str)] = &[
+    (
+        "sensitive_path",
+        &["auth", "security", "crypto"],
+        20,
+        "touches authentication or security code",
+    ),
+    (
+        "data_path",
+        &["database", "migration", "schema"],
+        15,
+        "touches database or schema code",
+    ),
+    (
+        "config_path",
+        &["config", ".env"],
+        10,
+        "touches configuration",
+    ),
+];
+
+/// Changed lines above which a file is a medium change.
+const MEDIUM_CHANGE_LINES: u32 = 50;
+
+/// Changed lines above which a file is a large change.
+const LARGE_CHANGE_LINES: u32 = 100;
+
+/// Size of a change by added plus deleted lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeBucket {
+    Small,
+    Medium,
+    Large,
+}
+
+impl SizeBucket {
+    /// Bucket for a number of changed lines.
+    pub fn for_lines(lines: u32) -> Self {
+        if lines > LARGE_CHANGE_LINES {
+            Self::Large
+        } else if lines > MEDIUM_CHANGE_LINES {
+            Self::Medium
+        } else {
+            Self::Small
+        }
+    }
+
+    /// Lowercase name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+        }
+    }
+
+    fn points(self) -> u8 {
+        match self {
+            Self::Small => 0,
+            Self::Medium => 5,
+            Self::Large => 10,
+        }
+    }
+}
+
+/// One heuristic that fired for a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RiskContribution {
+    /// File the rule fired for
+    pub file: String,
+    /// Rule identifier, e.g. `sensitive_path` or `size_large`
+    pub rule: String,
+    /// Points added to the score
+    pub points: u8,
+    /// Why the rule applies
+    pub reason: String,
+}
+
+/// Risk of a single file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRisk {
+    pub path: String,
+    pub score: u8,
+    pub changed_lines: u32,
+    pub size_bucket: SizeBucket,
+}
+
+/// Full risk assessment for a change set.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskBreakdown {
+    /// Overall score (0-100)
+    pub score: u8,
+    /// Level derived from the score and thresholds
+    pub level: RiskLevel,
+    /// Size of the whole change set
+    pub size_bucket: SizeBucket,
+    /// Per-file scores
+    pub files: Vec<FileRisk>,
+    /// Every heuristic that fired, in file order
+    pub contributions: Vec<RiskContribution>,
+}
+
+/// Score `files` and explain the result.
+pub fn risk_breakdown(files: &[ReviewFile], thresholds: &RiskThresholds) -> RiskBreakdown {
+    let mut contributions = Vec::new();
+    let mut file_risks = Vec::with_capacity(files.len());
+    let mut score = 0u8;
+    let mut total_lines = 0u32;
+
+    for file in files {
+        let path = file.path.to_lowercase();
+        let mut fired: Vec<(String, u8, String)> = PATH_RULES
+            .iter()
+            .filter(|(_, needles, _, _)| needles.iter().any(|n| path.contains(n)))
+            .map(|(rule, _, points, reason)| (rule.to_string(), *points, reason.to_string()))
+            .collect();
+
+        let changed_lines = file.additions + file.deletions;
+        total_lines = total_lines.saturating_add(changed_lines);
+        let size_bucket = SizeBucket::for_lines(changed_lines);
+        if size_bucket != SizeBucket::Small {
+            fired.push((
+                format!("size_{}", size_bucket.as_str()),
+                size_bucket.points(),
+                format!("{} lines changed", changed_lines),
+            ));
+        }
+
+        let mut file_score = 0u8;
+        for (rule, points, reason) in fired {
+            file_score = file_score.saturating_add(points);
+            contributions.push(RiskContribution {
+                file: file.path.clone(),
+                rule,
+                points,
+                reason,
+            });
+        }
+        score = score.saturating_add(file_score);
+        file_risks.push(FileRisk {
+            path: file.path.clone(),
+            score: file_score.min(100),
+            changed_lines,
+            size_bucket,
+        });
+    }
+
+    RiskBreakdown {
+        score: score.min(100),
+        level: risk_level(score, thresholds),
+        size_bucket: SizeBucket::for_lines(total_lines),
+        files: file_risks,
+        contributions,
+    }
+}
+
+/// Plain-text summary of the top risks in `review`, from the heuristics alone.
+pub fn risk_summary(review: &Review, thresholds: &RiskThresholds) -> String {
+    let breakdown = risk_breakdown(&review.files, thresholds);
+    let mut summary = format!(
+        "Risk is {} ({}/100) across {} changed file(s).",
+        format!("{:?}", review.risk_level).to_lowercase(),
+        review.risk_score,
+        review.files.len()
+    );
+
+    let mut contributions: Vec<_> = breakdown.contributions.iter().collect();
+    contributions.sort_by_key(|c| std::cmp::Reverse(c.points));
+    let mut findings: Vec<_> = review.findings.iter().collect();
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity as u8));
+
+    if contributions.is_empty() && findings.is_empty() {
+        summary.push_str("\n\nNo risk signals were found.");
+        return summary;
+    }
+    summary.push_str("\n\nTop risks:");
+    for c in contributions.into_iter().take(SUMMARY_ITEMS) {
+        summary.push_str(&format!("\n- {}: {}", c.file, c.reason));
+    }
+    for f in findings.into_iter().take(SUMMARY_ITEMS) {
+        let location = if f.file.is_empty() {
+            String::new()
+        } else {
+            format!(" in {}", f.file)
+        };
+        let severity = format!("{:?}", f.severity).to_lowercase();
+        summary.push_str(&format!("\n- [{}] {}{}", severity, f.title, location));
+    }
+    summary
+}
+
+/// Map a score onto a level using `thresholds`.
+pub fn risk_level(score: u8, thresholds: &RiskThresholds) -> RiskLevel {
+    if score > thresholds.high_max {
+        RiskLevel::Critical
+    } else if score > thresholds.medium_max {
+        RiskLevel::High
+    } else if score > thresholds.low_max {
+        RiskLevel::Medium
+    } else {
+        RiskLevel::Low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::review::ChangeType;
+
+    fn file(path: &str, additions: u32, deletions: u32) -> ReviewFile {
+        ReviewFile {
+            path: path.to_string(),
+            change_type: ChangeType::Modified,
+            additions,
+            deletions,
+            old_path: None,
+            binary: false,
+            hunks: Vec::new(),
+            risk_score: 0,
+            findings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_breakdown_explains_score() {
+        let breakdown = risk_breakdown(
+            &[
+                file("src/auth/login.rs", 100, 50),
+                file("src/util.rs", 40, 20),
+            ],
+            &RiskThresholds::default(),
+        );
+
+        let rules: Vec<(&str, &str, u8)> = breakdown
+            .contributions
+            .iter()
+            .map(|c| (c.file.as_str(), c.rule.as_str(), c.points))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("src/auth/login.rs", "sensitive_path", 20),
+                ("src/auth/login.rs", "size_large", 10),
+                ("src/util.rs", "size_medium", 5),
+            ]
+        );
+        assert_eq!(breakdown.score, 35);
+        assert_eq!(breakdown.level, RiskLevel::Medium);
+        assert_eq!(breakdown.size_bucket, SizeBucket::Large);
+        assert_eq!(breakdown.files[0].score, 30);
+        assert_eq!(breakdown.files[1].size_bucket, SizeBucket::Medium);
+    }
+
+    #[test]
+    fn test_small_ordinary_change_is_low() {
+        let breakdown = risk_breakdown(&[file("src/util.rs", 3, 1)], &RiskThresholds::default());
+        assert!(breakdown.contributions.is_empty());
+        assert_eq!(breakdown.score, 0);
+        assert_eq!(breakdown.level, RiskLevel::Low);
+        assert_eq!(breakdown.size_bucket, SizeBucket::Small);
+    }
+}
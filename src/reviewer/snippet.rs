@@ -0,0 +1,129 @@
+//! Code context for findings.
+//!
+//! Findings point at a line of the new file. The snippet shows that line
+//! with a few neighbours taken from the diff hunk containing it, so review
+//! output reads on its own without the checkout. Context never crosses a
+//! hunk boundary, and removed lines are left out since they have no
+//! new-file line number.
+
+use crate::reviewer::diff::ParsedDiff;
+use crate::types::review::ReviewFile;
+
+/// Default lines of context shown before and after a finding's line.
+pub const DEFAULT_CONTEXT_LINES: usize = 2;
+
+/// Parse a hunk header `@@ -a,b +c,d @@ header` into
+/// `(old_start, old_lines, new_start, new_lines, header)`.
+pub fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32, Option<String>)> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, header) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let range = |spec: &str| -> Option<(u32, u32)> {
+        match spec.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((spec.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(new.strip_prefix('+')?)?;
+    let header = header.trim();
+    Some((
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        (!header.is_empty()).then(|| header.to_string()),
+    ))
+}
+
+/// The file and new-file line of the diff line containing byte `offset`.
+///
+/// The line is `None` for removed lines and for diffs without hunk headers.
+/// Callers holding a [`ParsedDiff`] should use [`ParsedDiff::locate`].
+pub fn locate(diff: &str, offset: usize) -> Option<(String, Option<u32>)> {
+    ParsedDiff::parse(diff).locate(offset)
+}
+
+/// A fenced, line-numbered snippet of `line` in `file` with up to `context`
+/// lines either side, or `None` if no hunk covers the line.
+pub fn hunk_snippet(file: &ReviewFile, line: u32, context: usize) -> Option<String> {
+    let hunk = file
+        .hunks
+        .iter()
+        .find(|h| line >= h.new_start && line < h.new_start + h.new_lines.max(1))?;
+
+    let lines: Vec<(u32, &str)> = hunk
+        .content
+        .lines()
+        .filter(|l| !l.starts_with('-') && !l.starts_with('\\'))
+        .zip(hunk.new_start..)
+        .map(|(l, number)| (number, l.get(1..).unwrap_or("")))
+        .collect();
+    let at = lines.iter().position(|(number, _)| *number == line)?;
+    let shown = &lines[at.saturating_sub(context)..(at + context + 1).min(lines.len())];
+
+    let width = shown
+        .last()
+        .map_or(1, |(number, _)| number.to_string().len());
+    let mut snippet = String::from("```\n");
+    for (number, text) in shown {
+        let marker = if *number == line { '>' } else { ' ' };
+        snippet.push_str(&format!("{} {:>width$} | {}\n", marker, number, text));
+    }
+    snippet.push_str("```");
+    Some(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer::parse_diff_files;
+
+    const DIFF: &str = "--- a/src/config.rs\n+++ b/src/config.rs\n@@ -10,6 +10,7 @@ fn load() {\n let a = 1;\n let b = 2;\n-let old = 0;\n let c = 3;\n+let password = \"hunter2\";\n let d = 4;\n let e = 5;\n let f = 6;\n";
+
+    #[test]
+    fn test_parse_hunk_header() {
+        assert_eq!(
+            parse_hunk_header("@@ -10,6 +10,7 @@ fn load() {"),
+            Some((10, 6, 10, 7, Some("fn load() {".to_string())))
+        );
+        assert_eq!(parse_hunk_header("@@ -1 +1 @@"), Some((1, 1, 1, 1, None)));
+        assert_eq!(parse_hunk_header("+++ b/x"), None);
+    }
+
+    #[test]
+    fn test_locate_maps_offsets_to_new_lines() {
+        let offset = DIFF.find("let password").unwrap();
+        assert_eq!(
+            locate(DIFF, offset),
+            Some(("src/config.rs".to_string(), Some(13)))
+        );
+        let removed = DIFF.find("let old").unwrap();
+        assert_eq!(
+            locate(DIFF, removed),
+            Some(("src/config.rs".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_snippet_includes_line_and_neighbours() {
+        let files = parse_diff_files(DIFF);
+        let snippet = hunk_snippet(&files[0], 13, 2).unwrap();
+        assert_eq!(
+            snippet,
+            "```\n  11 | let b = 2;\n  12 | let c = 3;\n> 13 | let password = \"hunter2\";\n  14 | let d = 4;\n  15 | let e = 5;\n```"
+        );
+        assert!(!snippet.contains("let old"));
+
+        // Clamped at the start of the hunk
+        let snippet = hunk_snippet(&files[0], 10, 2).unwrap();
+        assert!(
+            snippet.starts_with("```\n> 10 | let a = 1;\n"),
+            "{}",
+            snippet
+        );
+        assert_eq!(snippet.lines().count(), 5);
+
+        assert_eq!(hunk_snippet(&files[0], 40, 2), None);
+    }
+}
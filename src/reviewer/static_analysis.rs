@@ -0,0 +1,518 @@
+//! External linter integration.
+//!
+//! Runs installed language linters and converts their diagnostics into
+//! review [`Finding`]s. Every linter is opt-in; a linter whose binary is not
+//! installed is reported as skipped rather than failing the whole run.
+//!
+//! File arguments follow a `--` separator so none can be read as an option,
+//! and a linter still running after its timeout is killed.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+use crate::reviewer::IdGen;
+use crate::types::review::{Finding, FindingType, Severity};
+
+/// How long a linter may run before it is killed.
+pub const LINTER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Supported linters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Linter {
+    Clippy,
+    Ruff,
+    Flake8,
+    Eslint,
+}
+
+impl Linter {
+    /// Parse a linter name as accepted by the `run_static_analysis` tool.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "clippy" => Some(Self::Clippy),
+            "ruff" => Some(Self::Ruff),
+            "flake8" => Some(Self::Flake8),
+            "eslint" => Some(Self::Eslint),
+            _ => None,
+        }
+    }
+
+    /// Linter name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Clippy => "clippy",
+            Self::Ruff => "ruff",
+            Self::Flake8 => "flake8",
+            Self::Eslint => "eslint",
+        }
+    }
+
+    /// File extensions this linter understands.
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Clippy => &["rs"],
+            Self::Ruff | Self::Flake8 => &["py", "pyi"],
+            Self::Eslint => &["js", "jsx", "mjs", "cjs", "ts", "tsx"],
+        }
+    }
+
+    /// Whether the workspace (or the requested files) contain this linter's language.
+    pub fn applies_to(&self, workspace: &Path, files: &[String]) -> bool {
+        if !files.is_empty() {
+            return files.iter().any(|f| {
+                Path::new(f)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| self.extensions().contains(&e))
+            });
+        }
+        match self {
+            Self::Clippy => workspace.join("Cargo.toml").exists(),
+            Self::Ruff | Self::Flake8 => {
+                workspace.join("pyproject.toml").exists()
+                    || workspace.join("setup.py").exists()
+                    || workspace.join("requirements.txt").exists()
+            }
+            Self::Eslint => workspace.join("package.json").exists(),
+        }
+    }
+
+    /// Program and arguments used to invoke the linter.
+    fn command(&self, files: &[String]) -> (&'static str, Vec<String>) {
+        let targets = || {
+            let matching: Vec<String> = files
+                .iter()
+                .filter(|f| {
+                    Path::new(f)
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| self.extensions().contains(&e))
+                })
+                .cloned()
+                .collect();
+            let mut targets = vec!["--".to_string()];
+            if matching.is_empty() {
+                targets.push(".".to_string());
+            } else {
+                targets.extend(matching);
+            }
+            targets
+        };
+
+        match self {
+            // Clippy always checks the whole crate; findings are filtered afterwards.
+            Self::Clippy => (
+                "cargo",
+                vec![
+                    "clippy".to_string(),
+                    "--message-format=json".to_string(),
+                    "--quiet".to_string(),
+                ],
+            ),
+            Self::Ruff => {
+                let mut args = vec!["check".to_string(), "--output-format=json".to_string()];
+                args.extend(targets());
+                ("ruff", args)
+            }
+            Self::Flake8 => ("flake8", targets()),
+            Self::Eslint => {
+                let mut args = vec!["-f".to_string(), "json".to_string()];
+                args.extend(targets());
+                ("eslint", args)
+            }
+        }
+    }
+
+    /// Parse the linter's raw output into findings, taking ids from `ids`.
+    pub fn parse_output(&self, output: &str, workspace: &Path, ids: &dyn IdGen) -> Vec<Finding> {
+        let mut findings = match self {
+            Self::Clippy => parse_clippy(output, workspace),
+            Self::Ruff => parse_ruff(output, workspace),
+            Self::Flake8 => parse_flake8(output, workspace),
+            Self::Eslint => parse_eslint(output, workspace),
+        };
+        for finding in &mut findings {
+            finding.id = ids.next_id();
+        }
+        findings
+    }
+}
+
+/// Outcome of running a single linter.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinterRun {
+    pub linter: Linter,
+    pub status: LinterRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub findings: usize,
+}
+
+/// Status of a linter run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinterRunStatus {
+    Completed,
+    NotInstalled,
+    NotApplicable,
+    Failed,
+}
+
+/// Run a linter in `workspace`, returning its findings and run summary.
+/// Finding ids come from `ids`.
+///
+/// Missing binaries and unparseable output never produce an error; they are
+/// recorded in the returned [`LinterRun`] instead. A linter still running
+/// after `timeout` is killed and fails the call with
+/// [`Error::ToolExecutionFailed`].
+pub async fn run_linter(
+    linter: Linter,
+    workspace: &Path,
+    files: &[String],
+    timeout: Duration,
+    ids: &dyn IdGen,
+) -> Result<(Vec<Finding>, LinterRun)> {
+    let run = |status, message: Option<String>, findings| LinterRun {
+        linter,
+        status,
+        message,
+        findings,
+    };
+
+    if !linter.applies_to(workspace, files) {
+        return Ok((
+            Vec::new(),
+            run(
+                LinterRunStatus::NotApplicable,
+                Some("No matching files for this linter".to_string()),
+                0,
+            ),
+        ));
+    }
+
+    let (program, args) = linter.command(files);
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(workspace)
+        .kill_on_drop(true)
+        .output();
+    let output = match tokio::time::timeout(timeout, output).await {
+        Err(_) => {
+            return Err(Error::ToolExecutionFailed(format!(
+                "{} did not finish within {} seconds",
+                linter.name(),
+                timeout.as_secs()
+            )))
+        }
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok((
+                Vec::new(),
+                run(
+                    LinterRunStatus::NotInstalled,
+                    Some(format!("'{}' is not installed", program)),
+                    0,
+                ),
+            ));
+        }
+        Ok(Err(e)) => {
+            return Ok((
+                Vec::new(),
+                run(LinterRunStatus::Failed, Some(e.to_string()), 0),
+            ));
+        }
+    };
+
+    // Linters exit non-zero when they report issues, so the status code is
+    // not treated as a failure.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut findings = linter.parse_output(&stdout, workspace, ids);
+    if linter == Linter::Clippy && !files.is_empty() {
+        findings.retain(|f| files.iter().any(|path| path == &f.file));
+    }
+
+    if findings.is_empty() && !output.status.success() && stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().last().unwrap_or("linter failed").to_string();
+        return Ok((Vec::new(), run(LinterRunStatus::Failed, Some(message), 0)));
+    }
+
+    let count = findings.len();
+    Ok((findings, run(LinterRunStatus::Completed, None, count)))
+}
+
+/// Make a linter-reported path relative to the workspace.
+fn relative_path(path: &str, workspace: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(workspace)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.trim_start_matches("./").to_string())
+}
+
+fn lint_finding(
+    linter: Linter,
+    rule: &str,
+    severity: Severity,
+    message: &str,
+    file: String,
+    line: Option<u32>,
+    end_line: Option<u32>,
+) -> Finding {
+    Finding {
+        id: String::new(),
+        finding_type: FindingType::Style,
+        severity,
+        title: if rule.is_empty() {
+            linter.name().to_string()
+        } else {
+            rule.to_string()
+        },
+        description: message.to_string(),
+        file,
+        line,
+        line_range: match (line, end_line) {
+            (Some(start), Some(end)) if end > start => Some((start, end)),
+            _ => None,
+        },
+        suggestion: None,
+        code_snippet: None,
+        actionable: true,
+        category: Some(linter.name().to_string()),
+        occurrences: 1,
+    }
+}
+
+/// Parse `cargo clippy --message-format=json` output (one JSON object per line).
+fn parse_clippy(output: &str, workspace: &Path) -> Vec<Finding> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-message")
+        .filter_map(|msg| {
+            let message = &msg["message"];
+            let severity = match message["level"].as_str()? {
+                "error" => Severity::Error,
+                "warning" => Severity::Warning,
+                _ => return None,
+            };
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|s| s["is_primary"].as_bool().unwrap_or(false))?;
+            let rule = message["code"]["code"].as_str().unwrap_or_default();
+            Some(lint_finding(
+                Linter::Clippy,
+                rule,
+                severity,
+                message["message"].as_str().unwrap_or_default(),
+                relative_path(span["file_name"].as_str().unwrap_or_default(), workspace),
+                span["line_start"].as_u64().map(|l| l as u32),
+                span["line_end"].as_u64().map(|l| l as u32),
+            ))
+        })
+        .collect()
+}
+
+/// Parse `ruff check --output-format=json` output.
+fn parse_ruff(output: &str, workspace: &Path) -> Vec<Finding> {
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(output) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .map(|item| {
+            lint_finding(
+                Linter::Ruff,
+                item["code"].as_str().unwrap_or_default(),
+                Severity::Warning,
+                item["message"].as_str().unwrap_or_default(),
+                relative_path(item["filename"].as_str().unwrap_or_default(), workspace),
+                item["location"]["row"].as_u64().map(|l| l as u32),
+                item["end_location"]["row"].as_u64().map(|l| l as u32),
+            )
+        })
+        .collect()
+}
+
+/// Parse flake8's default `path:row:col: CODE message` output.
+fn parse_flake8(output: &str, workspace: &Path) -> Vec<Finding> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ':');
+            let path = parts.next()?;
+            let row = parts.next()?.trim().parse::<u32>().ok()?;
+            let _col = parts.next()?;
+            let (code, message) = parts.next()?.trim().split_once(' ')?;
+            // E9xx are syntax/IO errors and F8xx undefined names; the rest is style.
+            let severity = if code.starts_with("E9") || code.starts_with("F8") {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+            Some(lint_finding(
+                Linter::Flake8,
+                code,
+                severity,
+                message,
+                relative_path(path, workspace),
+                Some(row),
+                None,
+            ))
+        })
+        .collect()
+}
+
+/// Parse `eslint -f json` output.
+fn parse_eslint(output: &str, workspace: &Path) -> Vec<Finding> {
+    let Ok(Value::Array(files)) = serde_json::from_str::<Value>(output) else {
+        return Vec::new();
+    };
+    files
+        .iter()
+        .flat_map(|file| {
+            let path = relative_path(file["filePath"].as_str().unwrap_or_default(), workspace);
+            file["messages"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |msg| {
+                    let severity = if msg["severity"].as_u64() == Some(2) {
+                        Severity::Error
+                    } else {
+                        Severity::Warning
+                    };
+                    lint_finding(
+                        Linter::Eslint,
+                        msg["ruleId"].as_str().unwrap_or_default(),
+                        severity,
+                        msg["message"].as_str().unwrap_or_default(),
+                        path.clone(),
+                        msg["line"].as_u64().map(|l| l as u32),
+                        msg["endLine"].as_u64().map(|l| l as u32),
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer::{CounterIdGen, UuidGen};
+
+    #[test]
+    fn test_linter_from_name() {
+        assert_eq!(Linter::from_name("Clippy"), Some(Linter::Clippy));
+        assert_eq!(Linter::from_name("eslint"), Some(Linter::Eslint));
+        assert_eq!(Linter::from_name("pylint"), None);
+    }
+
+    #[test]
+    fn test_files_follow_option_separator() {
+        let files = ["--fix.py".to_string(), "app/main.py".to_string()];
+        let (program, args) = Linter::Ruff.command(&files);
+        assert_eq!(program, "ruff");
+        assert_eq!(
+            args,
+            vec![
+                "check",
+                "--output-format=json",
+                "--",
+                "--fix.py",
+                "app/main.py"
+            ]
+        );
+        assert_eq!(Linter::Flake8.command(&[]).1, vec!["--", "."]);
+    }
+
+    #[test]
+    fn test_parse_clippy_output() {
+        let output = r#"{"reason":"compiler-artifact","target":{"name":"demo"}}
+{"reason":"compiler-message","message":{"level":"warning","message":"redundant clone","code":{"code":"clippy::redundant_clone"},"spans":[{"file_name":"src/lib.rs","line_start":12,"line_end":12,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"level":"note","message":"note only","code":null,"spans":[]}}
+{"reason":"build-finished","success":true}"#;
+
+        let findings =
+            Linter::Clippy.parse_output(output, Path::new("/ws"), &CounterIdGen::new("lint"));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].title, "clippy::redundant_clone");
+        assert_eq!(findings[0].file, "src/lib.rs");
+        assert_eq!(findings[0].line, Some(12));
+        assert_eq!(findings[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_parse_ruff_output() {
+        let output = r#"[{"code":"F401","message":"`os` imported but unused","filename":"/ws/app/main.py","location":{"row":1,"column":8},"end_location":{"row":1,"column":10}}]"#;
+
+        let findings =
+            Linter::Ruff.parse_output(output, Path::new("/ws"), &CounterIdGen::new("lint"));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].title, "F401");
+        assert_eq!(findings[0].file, "app/main.py");
+        assert_eq!(findings[0].line, Some(1));
+        assert_eq!(findings[0].category.as_deref(), Some("ruff"));
+    }
+
+    #[test]
+    fn test_parse_flake8_output() {
+        let output = "./app/main.py:3:1: F821 undefined name 'foo'\n./app/main.py:9:80: E501 line too long (91 > 79 characters)\n";
+
+        let findings =
+            Linter::Flake8.parse_output(output, Path::new("/ws"), &CounterIdGen::new("lint"));
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].file, "app/main.py");
+        assert_eq!(findings[0].line, Some(3));
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[1].title, "E501");
+        assert_eq!(findings[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_parse_eslint_output() {
+        let output = r#"[{"filePath":"/ws/src/index.ts","messages":[{"ruleId":"no-unused-vars","severity":2,"message":"'x' is defined but never used.","line":4,"column":7,"endLine":4},{"ruleId":"eqeqeq","severity":1,"message":"Expected '===' and instead saw '=='.","line":8,"column":10,"endLine":9}]},{"filePath":"/ws/src/clean.ts","messages":[]}]"#;
+
+        let findings =
+            Linter::Eslint.parse_output(output, Path::new("/ws"), &CounterIdGen::new("lint"));
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].id, "lint-1");
+        assert_eq!(findings[1].id, "lint-2");
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[0].file, "src/index.ts");
+        assert_eq!(findings[1].line_range, Some((8, 9)));
+    }
+
+    #[test]
+    fn test_unparseable_output_yields_no_findings() {
+        assert!(Linter::Ruff
+            .parse_output("not json", Path::new("/ws"), &CounterIdGen::new("lint"))
+            .is_empty());
+        assert!(Linter::Eslint
+            .parse_output("", Path::new("/ws"), &CounterIdGen::new("lint"))
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_linter_skipped_without_matching_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let (findings, run) = run_linter(
+            Linter::Eslint,
+            temp_dir.path(),
+            &[],
+            LINTER_TIMEOUT,
+            &UuidGen,
+        )
+        .await
+        .unwrap();
+        assert!(findings.is_empty());
+        assert_eq!(run.status, LinterRunStatus::NotApplicable);
+    }
+}
@@ -6,9 +6,13 @@
 use reqwest::{Client, Response};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::error::{Error, Result};
+use crate::sdk::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::sdk::retry::{retry_api, BackoffParams};
 use crate::sdk::types::*;
 use crate::VERSION;
@@ -18,22 +22,85 @@ fn user_agent() -> String {
     format!("augment.sdk.context/{} (rust)", VERSION)
 }
 
+/// Default timeout for a single backend request.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout for establishing a connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default number of idle connections kept open to the backend.
+pub const DEFAULT_POOL_MAX_IDLE: usize = 32;
+
+/// How long an idle pooled connection is kept before being closed.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// TCP keep-alive interval for pooled connections.
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Transport settings for [`ApiClient`].
+#[derive(Debug, Clone)]
+pub struct ApiClientOptions {
+    /// Timeout for a whole request, from send until the body is read
+    pub request_timeout: Duration,
+    /// Timeout for establishing the TCP/TLS connection
+    pub connect_timeout: Duration,
+    /// Maximum idle connections kept in the pool
+    pub pool_max_idle: usize,
+    /// Backoff for retried requests
+    pub retry: BackoffParams,
+    /// When to stop calling a failing backend
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for ApiClientOptions {
+    fn default() -> Self {
+        Self {
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            pool_max_idle: DEFAULT_POOL_MAX_IDLE,
+            retry: BackoffParams::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
 /// API client for Augment backend.
+///
+/// Holds a single pooled `reqwest::Client`; clones share the same
+/// connection pool and circuit breaker, so create one client and clone it
+/// rather than constructing a new one per call.
 #[derive(Debug, Clone)]
 pub struct ApiClient {
     client: Client,
     api_url: String,
     api_key: String,
     session_id: String,
+    request_timeout: Duration,
+    retry: BackoffParams,
+    breaker: Arc<CircuitBreaker>,
     debug: bool,
 }
 
 impl ApiClient {
-    /// Create a new API client.
+    /// Create a new API client with default transport settings.
     pub fn new(api_url: String, api_key: String, debug: bool) -> Result<Self> {
+        Self::with_options(api_url, api_key, debug, ApiClientOptions::default())
+    }
+
+    /// Create a new API client with explicit transport settings.
+    pub fn with_options(
+        api_url: String,
+        api_key: String,
+        debug: bool,
+        options: ApiClientOptions,
+    ) -> Result<Self> {
         let client = Client::builder()
             .user_agent(user_agent())
-            .timeout(std::time::Duration::from_secs(300))
+            .timeout(options.request_timeout)
+            .connect_timeout(options.connect_timeout.min(options.request_timeout))
+            .pool_max_idle_per_host(options.pool_max_idle)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(TCP_KEEPALIVE)
             .build()
             .map_err(|e| Error::Internal(format!("Failed to create HTTP client: {}", e)))?;
 
@@ -42,6 +109,9 @@ impl ApiClient {
             api_url,
             api_key,
             session_id: Uuid::new_v4().to_string(),
+            request_timeout: options.request_timeout,
+            retry: options.retry,
+            breaker: Arc::new(CircuitBreaker::new(options.circuit_breaker)),
             debug,
         })
     }
@@ -57,11 +127,16 @@ impl ApiClient {
         endpoint: &str,
         body: &T,
     ) -> Result<R> {
+        let response = self.send(endpoint, body).await?;
+        self.handle_response(response).await
+    }
+
+    /// Send an authenticated API request and return the response unread.
+    async fn send<T: Serialize>(&self, endpoint: &str, body: &T) -> Result<Response> {
         let url = format!("{}/{}", self.api_url.trim_end_matches('/'), endpoint);
         let request_id = Uuid::new_v4().to_string();
 
-        let response = self
-            .client
+        self.client
             .post(&url)
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -69,46 +144,87 @@ impl ApiClient {
             .header("X-Request-Id", &request_id)
             .json(body)
             .send()
-            .await?;
+            .await
+            .map_err(|e| self.transport_error(e))
+    }
 
-        self.handle_response(response).await
+    /// Convert a reqwest error, surfacing timeouts as [`Error::Timeout`].
+    fn transport_error(&self, error: reqwest::Error) -> Error {
+        if error.is_timeout() {
+            Error::Timeout {
+                seconds: self.request_timeout.as_secs_f64().ceil() as u64,
+            }
+        } else {
+            Error::Http(error)
+        }
     }
 
-    /// Handle API response, extracting errors.
-    async fn handle_response<R: DeserializeOwned>(&self, response: Response) -> Result<R> {
+    /// Turn a non-success response into an [`Error::Api`].
+    async fn check_status(response: Response) -> Result<Response> {
         let status = response.status();
-
-        if !status.is_success() {
-            let status_text = status.canonical_reason().unwrap_or("Unknown");
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::api(status.as_u16(), status_text, body));
+        if status.is_success() {
+            return Ok(response);
         }
+        let status_text = status.canonical_reason().unwrap_or("Unknown");
+        let body = response.text().await.unwrap_or_default();
+        Err(Error::api(status.as_u16(), status_text, body))
+    }
 
-        response
+    /// Handle API response, extracting errors.
+    async fn handle_response<R: DeserializeOwned>(&self, response: Response) -> Result<R> {
+        Self::check_status(response)
+            .await?
             .json()
             .await
-            .map_err(|e| Error::Internal(format!("Failed to parse response: {}", e)))
+            .map_err(|e| {
+                if e.is_timeout() {
+                    self.transport_error(e)
+                } else {
+                    Error::Internal(format!("Failed to parse response: {}", e))
+                }
+            })
+    }
+
+    /// The circuit breaker guarding backend calls.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
     }
 
     /// Make an API request with retry logic.
+    ///
+    /// The whole retried call goes through the circuit breaker, so it counts
+    /// as a single failure once retries are exhausted.
     async fn call_api_with_retry<T: Serialize + Clone, R: DeserializeOwned>(
         &self,
         endpoint: &str,
         body: &T,
     ) -> Result<R> {
-        let params = BackoffParams::default();
         let body = body.clone();
 
-        retry_api(
-            || async { self.request(endpoint, &body).await },
-            &params,
-            self.debug,
-        )
-        .await
+        self.breaker
+            .call(|| {
+                retry_api(
+                    || async { self.request(endpoint, &body).await },
+                    &self.retry,
+                    self.debug,
+                )
+            })
+            .await
     }
 
     // ===== API Endpoints =====
 
+    /// Check that the backend is reachable and accepts our credentials.
+    ///
+    /// Sends an empty `find-missing` request without retries.
+    pub async fn ping(&self) -> Result<()> {
+        let request = FindMissingRequest {
+            mem_object_names: Vec::new(),
+        };
+        let _: FindMissingResponse = self.request("find-missing", &request).await?;
+        Ok(())
+    }
+
     /// Find which blobs are unknown or not indexed.
     pub async fn find_missing(&self, blob_names: Vec<String>) -> Result<FindMissingResponse> {
         let request = FindMissingRequest {
@@ -141,6 +257,10 @@ impl ApiClient {
     }
 
     /// Perform semantic codebase retrieval.
+    ///
+    /// Timed-out attempts are retried like any other request. If every
+    /// attempt times out, the whole snippets received by the attempt that
+    /// got furthest are returned with `truncated` set instead of the error.
     pub async fn agent_codebase_retrieval(
         &self,
         query: &str,
@@ -158,8 +278,66 @@ impl ApiClient {
             request.max_output_length = Some(len);
         }
 
-        self.call_api_with_retry("agents/codebase-retrieval", &request)
-            .await
+        let partial = Mutex::new(None::<String>);
+        let result = self
+            .breaker
+            .call(|| {
+                retry_api(
+                    || self.retrieval_attempt(&request, &partial),
+                    &self.retry,
+                    self.debug,
+                )
+            })
+            .await;
+
+        match (result, partial.into_inner().unwrap()) {
+            (Err(Error::Timeout { seconds }), Some(text)) => {
+                warn!(
+                    "Codebase retrieval timed out after {}s; returning partial results",
+                    seconds
+                );
+                Ok(CodebaseRetrievalResponse {
+                    formatted_retrieval: text,
+                    truncated: true,
+                })
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// One retrieval request, reading the body as it arrives.
+    ///
+    /// On a timeout, the whole snippets received so far are kept in
+    /// `partial` if they are more than an earlier attempt got.
+    async fn retrieval_attempt(
+        &self,
+        request: &CodebaseRetrievalRequest,
+        partial: &Mutex<Option<String>>,
+    ) -> Result<CodebaseRetrievalResponse> {
+        let response = self.send("agents/codebase-retrieval", request).await?;
+        let mut response = Self::check_status(response).await?;
+
+        let mut body = Vec::new();
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(e) => {
+                    if e.is_timeout() {
+                        if let Some(text) = salvage_retrieval(&body) {
+                            let mut best = partial.lock().unwrap();
+                            if best.as_ref().is_none_or(|b| b.len() < text.len()) {
+                                *best = Some(text);
+                            }
+                        }
+                    }
+                    return Err(self.transport_error(e));
+                }
+            }
+        }
+
+        serde_json::from_slice(&body)
+            .map_err(|e| Error::Internal(format!("Failed to parse response: {}", e)))
     }
 
     /// Chat with the AI using SSE streaming.
@@ -183,7 +361,8 @@ impl ApiClient {
             .header("X-Request-Id", &request_id)
             .json(&request)
             .send()
-            .await?;
+            .await
+            .map_err(|e| self.transport_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -193,7 +372,7 @@ impl ApiClient {
         }
 
         // Parse SSE stream and collect response
-        let body = response.text().await?;
+        let body = response.text().await.map_err(|e| self.transport_error(e))?;
         let mut result = String::new();
 
         for line in body.lines() {
@@ -212,3 +391,252 @@ impl ApiClient {
         Ok(result)
     }
 }
+
+/// The whole snippets in a retrieval response body that was cut off.
+///
+/// Closes the unterminated `formatted_retrieval` string and drops its last
+/// snippet, which the cut left incomplete. `None` if no whole snippet
+/// arrived.
+fn salvage_retrieval(body: &[u8]) -> Option<String> {
+    let body = match std::str::from_utf8(body) {
+        Ok(body) => body,
+        Err(e) => std::str::from_utf8(&body[..e.valid_up_to()]).ok()?,
+    };
+    let key = "\"formatted_retrieval\"";
+    let value = body[body.find(key)? + key.len()..]
+        .trim_start()
+        .strip_prefix(':')?
+        .trim_start();
+    if !value.starts_with('"') {
+        return None;
+    }
+
+    // The string itself arrived whole; only later fields were cut
+    let mut whole = serde_json::Deserializer::from_str(value).into_iter::<String>();
+    if let Some(Ok(text)) = whole.next() {
+        return (!text.is_empty()).then_some(text);
+    }
+
+    // Close the string, backing off over an escape sequence cut in half
+    let text = (0..=6)
+        .filter_map(|cut| {
+            value
+                .len()
+                .checked_sub(cut)
+                .and_then(|end| value.get(..end))
+        })
+        .find_map(|prefix| serde_json::from_str::<String>(&format!("{}\"", prefix)).ok())?;
+    let last = text
+        .rmatch_indices("Path:")
+        .map(|(at, _)| at)
+        .find(|&at| at == 0 || text[..at].ends_with('\n'))?;
+    let kept = text[..last].trim_end();
+    (!kept.is_empty()).then(|| kept.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::mock_backend::MockBackend;
+
+    #[tokio::test]
+    async fn test_request_timeout_surfaces_timeout_error() {
+        let backend = MockBackend::start().await;
+        backend.set_delay("find-missing", Duration::from_secs(2));
+
+        let client = ApiClient::with_options(
+            backend.url().to_string(),
+            "test-key".to_string(),
+            false,
+            ApiClientOptions {
+                request_timeout: Duration::from_millis(200),
+                ..ApiClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client.ping().await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { seconds: 1 }), "{:?}", err);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_salvage_retrieval_keeps_whole_snippets() {
+        let body = br#"{"formatted_retrieval": "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {\n    \u00e9t"#;
+        assert_eq!(
+            salvage_retrieval(body).as_deref(),
+            Some("Path: a.rs\nfn a() {}")
+        );
+        // Cut inside an escape sequence
+        let body = br#"{"formatted_retrieval": "Path: a.rs\nfn a() {}\nPath: b.rs\n\u00"#;
+        assert_eq!(
+            salvage_retrieval(body).as_deref(),
+            Some("Path: a.rs\nfn a() {}")
+        );
+        // The string arrived whole
+        let body = br#"{"formatted_retrieval": "Path: a.rs\nfn a() {}", "extra": [1,"#;
+        assert_eq!(
+            salvage_retrieval(body).as_deref(),
+            Some("Path: a.rs\nfn a() {}")
+        );
+        // Only the first snippet had started
+        assert_eq!(
+            salvage_retrieval(br#"{"formatted_retrieval": "Path: a.rs\nfn"#),
+            None
+        );
+        assert_eq!(salvage_retrieval(br#"{"formatt"#), None);
+    }
+
+    #[tokio::test]
+    async fn test_retrieval_timeout_returns_partial_results() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval("Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}");
+        backend.stall_after("agents/codebase-retrieval", 60, Duration::from_secs(2));
+
+        let client = ApiClient::with_options(
+            backend.url().to_string(),
+            "test-key".to_string(),
+            false,
+            ApiClientOptions {
+                request_timeout: Duration::from_millis(200),
+                retry: BackoffParams {
+                    initial_ms: 1,
+                    max_tries: Some(2),
+                    ..BackoffParams::default()
+                },
+                ..ApiClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        let blobs = Blobs {
+            checkpoint_id: None,
+            added_blobs: Vec::new(),
+            deleted_blobs: Vec::new(),
+        };
+        let response = client
+            .agent_codebase_retrieval("where is a", blobs, None)
+            .await
+            .unwrap();
+        assert!(response.truncated);
+        assert_eq!(response.formatted_retrieval, "Path: a.rs\nfn a() {}");
+        // The timeout was retried before falling back
+        assert_eq!(backend.requests("agents/codebase-retrieval").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let backend = MockBackend::start().await;
+        let client =
+            ApiClient::new(backend.url().to_string(), "test-key".to_string(), false).unwrap();
+
+        backend.fail_next("find-missing", 401, 5);
+        let err = client.find_missing(Vec::new()).await.unwrap_err();
+        assert!(matches!(err, Error::Api { status: 401, .. }), "{:?}", err);
+        assert_eq!(backend.requests("find-missing").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let backend = MockBackend::start().await;
+        let client =
+            ApiClient::new(backend.url().to_string(), "test-key".to_string(), false).unwrap();
+
+        backend.fail_next("find-missing", 503, 1);
+        client.find_missing(Vec::new()).await.unwrap();
+        assert_eq!(backend.requests("find-missing").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_settings_from_config_cap_attempts() {
+        let backend = MockBackend::start().await;
+        let config = crate::config::Config {
+            retry_max_attempts: 3,
+            retry_base_delay_ms: 1,
+            retry_max_delay_ms: 5,
+            ..crate::config::Config::default()
+        };
+        let client = ApiClient::with_options(
+            backend.url().to_string(),
+            "test-key".to_string(),
+            false,
+            ApiClientOptions {
+                retry: config.backoff(),
+                ..ApiClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        backend.fail_next("find-missing", 503, 10);
+        let started = std::time::Instant::now();
+        let err = client.find_missing(Vec::new()).await.unwrap_err();
+        assert!(matches!(err, Error::Api { status: 503, .. }), "{:?}", err);
+        assert_eq!(backend.requests("find-missing").len(), 3);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast_during_outage() {
+        let backend = MockBackend::start().await;
+        let client = ApiClient::with_options(
+            backend.url().to_string(),
+            "test-key".to_string(),
+            false,
+            ApiClientOptions {
+                retry: BackoffParams {
+                    max_tries: Some(1),
+                    ..BackoffParams::default()
+                },
+                circuit_breaker: CircuitBreakerConfig {
+                    failure_threshold: 2,
+                    cooldown: Duration::from_millis(50),
+                },
+                ..ApiClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        backend.fail_next("find-missing", 503, 2);
+        for _ in 0..2 {
+            client.find_missing(Vec::new()).await.unwrap_err();
+        }
+        let err = client.clone().find_missing(Vec::new()).await.unwrap_err();
+        assert!(matches!(err, Error::BackendUnavailable { .. }), "{:?}", err);
+        assert_eq!(backend.requests("find-missing").len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        client.find_missing(Vec::new()).await.unwrap();
+        assert_eq!(
+            client.circuit_breaker().state(),
+            crate::sdk::CircuitState::Closed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_reuse_pooled_connections() {
+        const CONCURRENCY: usize = 8;
+        const ROUNDS: usize = 5;
+
+        let backend = MockBackend::start().await;
+        let client =
+            ApiClient::new(backend.url().to_string(), "test-key".to_string(), false).unwrap();
+
+        for _ in 0..ROUNDS {
+            let calls = (0..CONCURRENCY).map(|_| {
+                let client = client.clone();
+                async move { client.ping().await }
+            });
+            for result in futures::future::join_all(calls).await {
+                result.unwrap();
+            }
+        }
+
+        assert_eq!(backend.requests("find-missing").len(), CONCURRENCY * ROUNDS);
+        assert!(
+            backend.connections() <= CONCURRENCY,
+            "opened {} connections",
+            backend.connections()
+        );
+    }
+}
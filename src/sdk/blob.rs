@@ -63,6 +63,25 @@ impl BlobNameCalculator {
             .expect("calculate_no_throw should never fail")
     }
 
+    /// Number of content bytes that participate in a blob.
+    ///
+    /// Content longer than the maximum blob size is truncated to exactly
+    /// `max_blob_size` bytes; shorter content (including empty content) is
+    /// used as-is.
+    pub fn blob_size(&self, content: &[u8]) -> usize {
+        content.len().min(self.max_blob_size)
+    }
+
+    /// Blob name for `content` at `path`, never failing.
+    ///
+    /// The name is the lowercase hex SHA256 of the path followed by the first
+    /// [`blob_size`](Self::blob_size) bytes of content. Because the path is
+    /// part of the hash, identical content at different paths gets different
+    /// names; content differing only past the size limit gets the same name.
+    pub fn blob_name(&self, path: &str, content: &[u8]) -> String {
+        self.hash(path, &content[..self.blob_size(content)])
+    }
+
     /// Calculate the blob name from a string.
     pub fn calculate_from_str(&self, path: &str, contents: &str) -> Option<String> {
         self.calculate(path, contents.as_bytes())
@@ -130,6 +149,57 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_blob_size_empty() {
+        let calc = BlobNameCalculator::new(10);
+        assert_eq!(calc.blob_size(b""), 0);
+
+        let name = calc.blob_name("empty.txt", b"");
+        assert_eq!(name, calc.calculate("empty.txt", b"").unwrap());
+        assert_eq!(name.len(), 64);
+    }
+
+    #[test]
+    fn test_blob_size_exactly_at_limit() {
+        let calc = BlobNameCalculator::new(10);
+        let content = b"0123456789";
+
+        assert_eq!(calc.blob_size(content), 10);
+        assert_eq!(
+            calc.blob_name("at_limit.txt", content),
+            calc.calculate("at_limit.txt", content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_blob_size_over_limit_truncates() {
+        let calc = BlobNameCalculator::new(10);
+        let content = b"0123456789 and more";
+
+        assert_eq!(calc.blob_size(content), 10);
+        assert_eq!(
+            calc.blob_name("big.txt", content),
+            calc.blob_name("big.txt", b"0123456789")
+        );
+        assert_eq!(
+            calc.blob_name("big.txt", content),
+            calc.blob_name("big.txt", b"0123456789 different tail")
+        );
+    }
+
+    #[test]
+    fn test_blob_name_includes_path() {
+        let calc = BlobNameCalculator::default();
+        assert_ne!(
+            calc.blob_name("a/lib.rs", b"same"),
+            calc.blob_name("b/lib.rs", b"same")
+        );
+        assert_eq!(
+            calc.blob_name("a/lib.rs", b"same"),
+            calc.blob_name("a/lib.rs", b"same")
+        );
+    }
+
     #[test]
     fn test_blob_no_throw() {
         let calc = BlobNameCalculator::new(10);
@@ -0,0 +1,279 @@
+//! Circuit breaker for backend calls.
+//!
+//! After `failure_threshold` consecutive transient failures the breaker
+//! opens and calls fail immediately with [`Error::BackendUnavailable`]
+//! instead of waiting on a backend that is down. Once `cooldown` has passed
+//! it half-opens and lets a single probe call through: success closes the
+//! breaker, failure opens it for another cooldown.
+//!
+//! The current state is published as the `context_engine_backend_circuit_state`
+//! gauge (0 closed, 1 half-open, 2 open), and each trip increments the
+//! `context_engine_backend_circuit_opened_total` counter.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::error::{Error, Result};
+use crate::sdk::retry::is_retryable;
+
+/// Default consecutive failures before the breaker opens.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Default time the breaker stays open before probing the backend.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Breaker thresholds.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker (0 disables it)
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before half-opening
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+/// Breaker state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// One probe call is allowed through to test recovery
+    HalfOpen,
+    /// Calls fail fast
+    Open,
+}
+
+impl CircuitState {
+    /// Value of the state gauge.
+    fn gauge_value(self) -> f64 {
+        match self {
+            Self::Closed => 0.0,
+            Self::HalfOpen => 1.0,
+            Self::Open => 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+}
+
+/// Tracks backend health and short-circuits calls while it is down.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<Inner>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_in_flight: false,
+            }),
+        }
+    }
+
+    /// Current state; an open breaker whose cooldown has passed reports half-open.
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Run `call` through the breaker.
+    ///
+    /// Only transient failures (see [`is_retryable`]) count against the
+    /// backend; a 4xx means it answered, so it resets the failure count.
+    pub async fn call<F, Fut, T>(&self, call: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.acquire()?;
+        let result = call().await;
+        match &result {
+            Err(e) if is_retryable(e) => self.record_failure(),
+            _ => self.record_success(),
+        }
+        result
+    }
+
+    /// Check whether a call may proceed, claiming the probe slot when half-open.
+    pub fn acquire(&self) -> Result<()> {
+        if self.config.failure_threshold == 0 {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::HalfOpen if !inner.probe_in_flight => {
+                inner.probe_in_flight = true;
+                Ok(())
+            }
+            _ => Err(Error::BackendUnavailable {
+                retry_after_secs: self.remaining_cooldown(&inner).as_secs().max(1),
+            }),
+        }
+    }
+
+    /// Record a successful call, closing the breaker.
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        inner.probe_in_flight = false;
+        if inner.state != CircuitState::Closed {
+            info!("Backend recovered, closing circuit breaker");
+            self.transition(&mut inner, CircuitState::Closed);
+        }
+    }
+
+    /// Record a transient failure, opening the breaker at the threshold.
+    pub fn record_failure(&self) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        let probe_failed = inner.state == CircuitState::HalfOpen;
+        inner.probe_in_flight = false;
+        if probe_failed || inner.consecutive_failures >= self.config.failure_threshold {
+            warn!(
+                "Opening circuit breaker after {} consecutive backend failures; retrying in {:?}",
+                inner.consecutive_failures, self.config.cooldown
+            );
+            inner.opened_at = Some(Instant::now());
+            self.transition(&mut inner, CircuitState::Open);
+            metrics::counter!("context_engine_backend_circuit_opened_total").increment(1);
+        }
+    }
+
+    /// Move an open breaker to half-open once the cooldown has passed.
+    fn refresh(&self, inner: &mut Inner) {
+        if inner.state == CircuitState::Open && self.remaining_cooldown(inner).is_zero() {
+            self.transition(inner, CircuitState::HalfOpen);
+        }
+    }
+
+    fn remaining_cooldown(&self, inner: &Inner) -> Duration {
+        inner
+            .opened_at
+            .map(|at| self.config.cooldown.saturating_sub(at.elapsed()))
+            .unwrap_or_default()
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        inner.state = state;
+        metrics::gauge!("context_engine_backend_circuit_state").set(state.gauge_value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown_ms: u64) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(cooldown_ms),
+        })
+    }
+
+    async fn fail(breaker: &CircuitBreaker) -> Result<()> {
+        breaker
+            .call(|| async { Err::<(), _>(Error::api(503, "Service Unavailable", "")) })
+            .await
+    }
+
+    async fn succeed(breaker: &CircuitBreaker) -> Result<()> {
+        breaker.call(|| async { Ok(()) }).await
+    }
+
+    #[tokio::test]
+    async fn test_open_half_open_closed() {
+        let breaker = breaker(50);
+        assert!(fail(&breaker).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(fail(&breaker).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Open: fails fast without running the call
+        let mut ran = false;
+        let err = breaker
+            .call(|| async {
+                ran = true;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BackendUnavailable { .. }), "{:?}", err);
+        assert!(!ran);
+
+        // Half-open after the cooldown; one probe at a time
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_err());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        succeed(&breaker).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_reopens() {
+        let breaker = breaker(30);
+        let _ = fail(&breaker).await;
+        let _ = fail(&breaker).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let _ = fail(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_and_disabled_breaker_do_not_trip() {
+        let breaker = breaker(1000);
+        for _ in 0..3 {
+            let _ = breaker
+                .call(|| async { Err::<(), _>(Error::api(401, "Unauthorized", "")) })
+                .await;
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let disabled = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 0,
+            ..CircuitBreakerConfig::default()
+        });
+        for _ in 0..10 {
+            let _ = fail(&disabled).await;
+        }
+        assert!(succeed(&disabled).await.is_ok());
+    }
+}
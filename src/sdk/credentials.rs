@@ -1,9 +1,12 @@
 //! Credential resolution for Augment API.
 //!
-//! Credentials are resolved in order:
-//! 1. Explicit options
+//! Each value is resolved independently, first match wins:
+//! 1. Explicit options (CLI `--api-key` / `--api-url`)
 //! 2. Environment variables (AUGMENT_API_TOKEN, AUGMENT_API_URL)
 //! 3. Session file (~/.augment/session.json)
+//!
+//! Empty or whitespace-only values are treated as absent, so an empty
+//! `AUGMENT_API_TOKEN` does not shadow a valid session file.
 
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -21,7 +24,7 @@ pub struct Credentials {
 }
 
 /// Session file structure.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct SessionFile {
     #[serde(rename = "accessToken")]
     access_token: Option<String>,
@@ -29,6 +32,13 @@ struct SessionFile {
     tenant_url: Option<String>,
 }
 
+/// One source of credentials, as read before precedence is applied.
+#[derive(Debug, Default)]
+struct CredentialSource {
+    api_key: Option<String>,
+    api_url: Option<String>,
+}
+
 /// Get the path to the session file.
 fn session_file_path() -> Option<PathBuf> {
     dirs::home_dir().map(|h| h.join(".augment").join("session.json"))
@@ -50,50 +60,57 @@ async fn read_session_file() -> Option<SessionFile> {
 ///
 /// # Errors
 ///
-/// Returns an error if credentials cannot be resolved from any source.
+/// Returns [`Error::MissingCredentials`] if the API key or URL cannot be
+/// resolved to a non-empty value from any source.
 pub async fn resolve_credentials(
     api_key: Option<&str>,
     api_url: Option<&str>,
 ) -> Result<Credentials> {
-    // Try options first
-    let mut resolved_key = api_key.map(String::from);
-    let mut resolved_url = api_url.map(String::from);
-
-    // Try environment variables
-    if resolved_key.is_none() {
-        resolved_key = std::env::var("AUGMENT_API_TOKEN").ok();
-    }
-    if resolved_url.is_none() {
-        resolved_url = std::env::var("AUGMENT_API_URL").ok();
-    }
+    let options = CredentialSource {
+        api_key: api_key.map(String::from),
+        api_url: api_url.map(String::from),
+    };
+    let env = CredentialSource {
+        api_key: std::env::var("AUGMENT_API_TOKEN").ok(),
+        api_url: std::env::var("AUGMENT_API_URL").ok(),
+    };
+    let session = read_session_file().await.unwrap_or_default();
+    let session = CredentialSource {
+        api_key: session.access_token,
+        api_url: session.tenant_url,
+    };
+
+    resolve_from_sources(options, env, session)
+}
 
-    // Try session file
-    if resolved_key.is_none() || resolved_url.is_none() {
-        if let Some(session) = read_session_file().await {
-            if resolved_key.is_none() {
-                resolved_key = session.access_token;
-            }
-            if resolved_url.is_none() {
-                resolved_url = session.tenant_url;
-            }
-        }
+/// Apply precedence across sources, ignoring empty values.
+fn resolve_from_sources(
+    options: CredentialSource,
+    env: CredentialSource,
+    session: CredentialSource,
+) -> Result<Credentials> {
+    fn first(values: [Option<String>; 3]) -> Option<String> {
+        values
+            .into_iter()
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .find(|v| !v.is_empty())
     }
 
-    // Validate
-    let api_key = resolved_key.ok_or_else(|| {
-        Error::CredentialsNotFound(
+    let api_key = first([options.api_key, env.api_key, session.api_key]).ok_or_else(|| {
+        Error::MissingCredentials(
             "API key is required. Provide it via:\n\
-             1. options.api_key parameter\n\
+             1. --api-key flag\n\
              2. AUGMENT_API_TOKEN environment variable\n\
              3. Run 'auggie login' to create ~/.augment/session.json"
                 .to_string(),
         )
     })?;
 
-    let api_url = resolved_url.ok_or_else(|| {
-        Error::CredentialsNotFound(
+    let api_url = first([options.api_url, env.api_url, session.api_url]).ok_or_else(|| {
+        Error::MissingCredentials(
             "API URL is required. Provide it via:\n\
-             1. options.api_url parameter\n\
+             1. --api-url flag\n\
              2. AUGMENT_API_URL environment variable\n\
              3. Run 'auggie login' to create ~/.augment/session.json"
                 .to_string(),
@@ -128,7 +145,62 @@ mod tests {
         // Should fail if no session file exists
         // (This test may pass if session file exists on the system)
         if let Err(e) = result {
-            assert!(matches!(e, Error::CredentialsNotFound(_)));
+            assert!(matches!(e, Error::MissingCredentials(_)));
+        }
+    }
+
+    fn source(key: Option<&str>, url: Option<&str>) -> CredentialSource {
+        CredentialSource {
+            api_key: key.map(String::from),
+            api_url: url.map(String::from),
         }
     }
+
+    #[test]
+    fn test_options_win_over_env_and_session() {
+        let creds = resolve_from_sources(
+            source(Some("cli-key"), Some("https://cli")),
+            source(Some("env-key"), Some("https://env")),
+            source(Some("session-key"), Some("https://session")),
+        )
+        .unwrap();
+        assert_eq!(creds.api_key, "cli-key");
+        assert_eq!(creds.api_url, "https://cli");
+    }
+
+    #[test]
+    fn test_env_wins_over_session() {
+        let creds = resolve_from_sources(
+            source(None, None),
+            source(Some("env-key"), Some("https://env")),
+            source(Some("session-key"), Some("https://session")),
+        )
+        .unwrap();
+        assert_eq!(creds.api_key, "env-key");
+        assert_eq!(creds.api_url, "https://env");
+    }
+
+    #[test]
+    fn test_session_used_as_fallback() {
+        let creds = resolve_from_sources(
+            source(None, Some("https://cli")),
+            source(Some("   "), None),
+            source(Some("session-key"), Some("https://session")),
+        )
+        .unwrap();
+        assert_eq!(creds.api_key, "session-key");
+        assert_eq!(creds.api_url, "https://cli");
+    }
+
+    #[test]
+    fn test_all_sources_missing() {
+        let err = resolve_from_sources(
+            source(Some(""), None),
+            source(None, None),
+            source(None, Some("https://session")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::MissingCredentials(_)));
+        assert!(err.to_string().contains("AUGMENT_API_TOKEN"));
+    }
 }
@@ -39,7 +39,12 @@ impl DirectContext {
         let credentials =
             resolve_credentials(options.api_key.as_deref(), options.api_url.as_deref()).await?;
 
-        let api_client = ApiClient::new(credentials.api_url, credentials.api_key, options.debug)?;
+        let api_client = ApiClient::with_options(
+            credentials.api_url,
+            credentials.api_key,
+            options.debug,
+            options.client,
+        )?;
 
         let max_file_size = options.max_file_size.unwrap_or(DEFAULT_MAX_BLOB_SIZE);
         let blob_calculator = BlobNameCalculator::new(max_file_size);
@@ -53,12 +58,16 @@ impl DirectContext {
     }
 
     /// Add files to the index.
+    ///
+    /// The state lock is only held while blob names are recorded, not during
+    /// uploads, so several calls can upload concurrently.
     pub async fn add_to_index(&self, files: Vec<crate::types::File>) -> Result<IndexingResult> {
         let mut indexed = 0;
         let mut skipped = 0;
         let mut errors = Vec::new();
 
         let mut state = self.state.write().await;
+        let mut upload_batches: Vec<Vec<BlobEntry>> = Vec::new();
         let mut blobs_to_upload: Vec<BlobEntry> = Vec::new();
         let mut current_batch_size = 0;
 
@@ -78,8 +87,8 @@ impl DirectContext {
                 }
             };
 
-            // Check if already indexed
-            if let Some(existing) = state.blob_map.get(&file.path) {
+            // Check if already indexed (including changes not yet checkpointed)
+            if let Some(existing) = state.client_blob_map.get(&file.path) {
                 if existing == &blob_name {
                     skipped += 1;
                     continue;
@@ -100,13 +109,9 @@ impl DirectContext {
             if current_batch_size + content_size > MAX_BATCH_CONTENT_BYTES
                 || blobs_to_upload.len() >= MAX_BATCH_UPLOAD_SIZE
             {
-                // Upload current batch
+                // Start a new upload batch
                 if !blobs_to_upload.is_empty() {
-                    match self.upload_batch(&blobs_to_upload).await {
-                        Ok(_) => indexed += blobs_to_upload.len(),
-                        Err(e) => errors.push(format!("Batch upload failed: {}", e)),
-                    }
-                    blobs_to_upload.clear();
+                    upload_batches.push(std::mem::take(&mut blobs_to_upload));
                     current_batch_size = 0;
                 }
             }
@@ -119,17 +124,21 @@ impl DirectContext {
             current_batch_size += content_size;
         }
 
-        // Upload remaining batch
         if !blobs_to_upload.is_empty() {
-            match self.upload_batch(&blobs_to_upload).await {
-                Ok(_) => indexed += blobs_to_upload.len(),
+            upload_batches.push(blobs_to_upload);
+        }
+        drop(state); // Release lock before uploading
+
+        for blobs in upload_batches {
+            match self.upload_batch(&blobs).await {
+                Ok(_) => indexed += blobs.len(),
                 Err(e) => errors.push(format!("Batch upload failed: {}", e)),
             }
         }
 
         // Create checkpoint if needed
-        if state.pending_added.len() >= CHECKPOINT_THRESHOLD {
-            drop(state); // Release lock before checkpoint
+        let needs_checkpoint = self.state.read().await.pending_added.len() >= CHECKPOINT_THRESHOLD;
+        if needs_checkpoint {
             self.create_checkpoint().await?;
         }
 
@@ -194,7 +203,14 @@ impl DirectContext {
     }
 
     /// Perform semantic search against the indexed codebase.
-    pub async fn search(&self, query: &str, max_output_length: Option<usize>) -> Result<String> {
+    ///
+    /// A search the backend could not finish in time comes back truncated
+    /// rather than failing; see [`ApiClient::agent_codebase_retrieval`].
+    pub async fn search(
+        &self,
+        query: &str,
+        max_output_length: Option<usize>,
+    ) -> Result<crate::types::Retrieval> {
         // Ensure we have a checkpoint
         self.create_checkpoint().await?;
 
@@ -217,7 +233,10 @@ impl DirectContext {
             .agent_codebase_retrieval(query, blobs, max_output_length)
             .await?;
 
-        Ok(response.formatted_retrieval)
+        Ok(crate::types::Retrieval {
+            text: response.formatted_retrieval,
+            truncated: response.truncated,
+        })
     }
 
     /// Remove files from the index.
@@ -276,6 +295,17 @@ impl DirectContext {
         self.state.read().await.client_blob_map.len()
     }
 
+    /// Paths of the indexed files, in no particular order.
+    pub async fn indexed_paths(&self) -> Vec<String> {
+        self.state
+            .read()
+            .await
+            .client_blob_map
+            .keys()
+            .cloned()
+            .collect()
+    }
+
     /// Check if a file is indexed.
     pub async fn is_indexed(&self, path: &str) -> bool {
         self.state.read().await.client_blob_map.contains_key(path)
@@ -301,6 +331,11 @@ impl DirectContext {
         Ok(())
     }
 
+    /// Check that the backend is reachable.
+    pub async fn ping(&self) -> Result<()> {
+        self.api_client.ping().await
+    }
+
     /// Chat with the AI using the indexed codebase context.
     pub async fn chat(&self, prompt: &str) -> Result<String> {
         // Ensure we have a checkpoint
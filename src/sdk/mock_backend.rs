@@ -0,0 +1,234 @@
+//! In-process fake of the Augment backend for tests.
+//!
+//! Serves the endpoints used by [`ApiClient`](super::ApiClient) on a random
+//! local port and records every request body so tests can assert on what
+//! the client sent.
+
+use axum::body::Bytes;
+use axum::extract::ConnectInfo;
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::Config;
+
+/// Shared, mutable behaviour of the fake backend.
+#[derive(Default)]
+struct MockState {
+    requests: Mutex<Vec<(String, Value)>>,
+    retrieval: Mutex<String>,
+    chat: Mutex<String>,
+    delays: Mutex<HashMap<String, Duration>>,
+    stalls: Mutex<HashMap<String, (usize, Duration)>>,
+    failures: Mutex<HashMap<String, (StatusCode, usize)>>,
+    peers: Mutex<HashSet<SocketAddr>>,
+    checkpoints: AtomicUsize,
+}
+
+/// A running fake backend.
+#[derive(Clone)]
+pub(crate) struct MockBackend {
+    url: String,
+    state: Arc<MockState>,
+}
+
+impl MockBackend {
+    /// Start the fake backend on an ephemeral port.
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState::default());
+        *state.retrieval.lock().unwrap() = "Path: src/lib.rs\nfn example() {}".to_string();
+
+        let app = Router::new().fallback({
+            let state = state.clone();
+            move |ConnectInfo(peer): ConnectInfo<SocketAddr>, uri: Uri, body: Bytes| {
+                handle(state.clone(), peer, uri, body)
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        Self {
+            url: format!("http://{}", addr),
+            state,
+        }
+    }
+
+    /// A config pointing at this backend for the given workspace.
+    pub fn config(&self, workspace: &Path) -> Config {
+        Config {
+            workspace: workspace.to_path_buf(),
+            api_key: Some("test-key".to_string()),
+            api_url: Some(self.url.clone()),
+            ..Config::default()
+        }
+    }
+
+    /// Base URL of the backend.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Set the text streamed back by chat.
+    pub fn set_chat(&self, text: &str) {
+        *self.state.chat.lock().unwrap() = text.to_string();
+    }
+
+    /// Set the text returned by codebase retrieval.
+    pub fn set_retrieval(&self, text: &str) {
+        *self.state.retrieval.lock().unwrap() = text.to_string();
+    }
+
+    /// Delay every response from `endpoint`.
+    pub fn set_delay(&self, endpoint: &str, delay: Duration) {
+        self.state
+            .delays
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), delay);
+    }
+
+    /// Send only the first `bytes` of each response body from `endpoint`,
+    /// then wait `stall` before sending the rest.
+    pub fn stall_after(&self, endpoint: &str, bytes: usize, stall: Duration) {
+        self.state
+            .stalls
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), (bytes, stall));
+    }
+
+    /// Answer the next `count` requests to `endpoint` with `status`.
+    pub fn fail_next(&self, endpoint: &str, status: u16, count: usize) {
+        let status = StatusCode::from_u16(status).unwrap();
+        self.state
+            .failures
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), (status, count));
+    }
+
+    /// Number of distinct client connections seen so far.
+    pub fn connections(&self) -> usize {
+        self.state.peers.lock().unwrap().len()
+    }
+
+    /// Request bodies received by `endpoint`, in arrival order.
+    pub fn requests(&self, endpoint: &str) -> Vec<Value> {
+        self.state
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(e, _)| e == endpoint)
+            .map(|(_, body)| body.clone())
+            .collect()
+    }
+}
+
+async fn handle(state: Arc<MockState>, peer: SocketAddr, uri: Uri, body: Bytes) -> Response {
+    state.peers.lock().unwrap().insert(peer);
+    let endpoint = uri.path().trim_start_matches('/').to_string();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push((endpoint.clone(), body.clone()));
+
+    let delay = state.delays.lock().unwrap().get(&endpoint).copied();
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let failure = match state.failures.lock().unwrap().get_mut(&endpoint) {
+        Some((status, remaining)) if *remaining > 0 => {
+            *remaining -= 1;
+            Some(*status)
+        }
+        _ => None,
+    };
+    if let Some(status) = failure {
+        return (status, "injected failure").into_response();
+    }
+
+    let names = |items: &Value, key: &str| -> Vec<Value> {
+        items
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .map(|item| {
+                        if key.is_empty() {
+                            item.clone()
+                        } else {
+                            item[key].clone()
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let response = match endpoint.as_str() {
+        "find-missing" => axum::Json(json!({
+            "unknown_memory_names": names(&body["mem_object_names"], ""),
+            "nonindexed_blob_names": []
+        }))
+        .into_response(),
+        "batch-upload" => axum::Json(json!({
+            "blob_names": names(&body["blobs"], "blob_name")
+        }))
+        .into_response(),
+        "checkpoint-blobs" => {
+            let n = state.checkpoints.fetch_add(1, Ordering::SeqCst) + 1;
+            axum::Json(json!({ "new_checkpoint_id": format!("checkpoint-{}", n) })).into_response()
+        }
+        "agents/codebase-retrieval" => {
+            let retrieval = state.retrieval.lock().unwrap().clone();
+            axum::Json(json!({ "formatted_retrieval": retrieval })).into_response()
+        }
+        "chat-stream" => {
+            let content = state.chat.lock().unwrap().clone();
+            let event = json!({ "content": content });
+            format!("data: {}\n\ndata: [DONE]\n\n", event).into_response()
+        }
+        _ => (StatusCode::NOT_FOUND, "unknown endpoint").into_response(),
+    };
+
+    let stall = state.stalls.lock().unwrap().get(&endpoint).copied();
+    match stall {
+        Some((bytes, stall)) => stalled(response, bytes, stall).await,
+        None => response,
+    }
+}
+
+/// `response` with its body split after `bytes`, the rest sent after `stall`.
+async fn stalled(response: Response, bytes: usize, stall: Duration) -> Response {
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    let (head, tail) = body.split_at(bytes.min(body.len()));
+    let (head, tail) = (Bytes::copy_from_slice(head), Bytes::copy_from_slice(tail));
+    let chunks = futures::stream::once(async move { Ok::<_, std::io::Error>(head) }).chain(
+        futures::stream::once(async move {
+            tokio::time::sleep(stall).await;
+            Ok(tail)
+        }),
+    );
+    Response::from_parts(parts, axum::body::Body::from_stream(chunks))
+}
@@ -7,6 +7,7 @@
 //!
 //! - `api_client` - HTTP client for Augment backend API
 //! - `blob` - Blob naming and size calculations
+//! - `circuit_breaker` - Fast-fail while the backend is down
 //! - `credentials` - Authentication resolution
 //! - `direct_context` - Main context management class
 //! - `retry` - Retry logic with exponential backoff
@@ -14,13 +15,17 @@
 
 pub mod api_client;
 pub mod blob;
+pub mod circuit_breaker;
 pub mod credentials;
 pub mod direct_context;
+#[cfg(test)]
+pub(crate) mod mock_backend;
 pub mod retry;
 pub mod types;
 
-pub use api_client::ApiClient;
+pub use api_client::{ApiClient, ApiClientOptions};
 pub use blob::BlobNameCalculator;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
 pub use credentials::{resolve_credentials, Credentials};
 pub use direct_context::DirectContext;
 pub use types::*;
@@ -58,6 +58,29 @@ impl BackoffParams {
     }
 }
 
+/// Whether a failed backend call is worth retrying.
+///
+/// Server errors (5xx), timeouts and connection failures are transient.
+/// Client errors (4xx) are permanent, except 408 (request timeout), 429
+/// (rate limited) and 499 (request cancelled upstream); retrying a 401 just
+/// repeats the auth failure and can get the key locked out.
+pub fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::Api { status, .. } => is_retryable_status(*status),
+        Error::Http(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|s| is_retryable_status(s.as_u16()))
+        }
+        Error::Timeout { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 499 | 500..=599)
+}
+
 /// Retry a function with exponential backoff.
 ///
 /// # Arguments
@@ -143,7 +166,7 @@ where
     F: FnMut() -> Fut,
     Fut: Future<Output = crate::Result<T>>,
 {
-    retry_with_backoff(f, |e: &Error| e.is_retriable(), params, debug).await
+    retry_with_backoff(f, is_retryable, params, debug).await
 }
 
 /// Retry with chat-specific retriable error check.
@@ -154,3 +177,36 @@ where
 {
     retry_with_backoff(f, |e: &Error| e.is_chat_retriable(), params, debug).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable_classification() {
+        for status in [500, 502, 503, 504, 408, 429, 499] {
+            assert!(is_retryable(&Error::api(status, "", "")), "{}", status);
+        }
+        for status in [400, 401, 403, 404, 409, 422] {
+            assert!(!is_retryable(&Error::api(status, "", "")), "{}", status);
+        }
+        assert!(is_retryable(&Error::Timeout { seconds: 30 }));
+        assert!(!is_retryable(&Error::Auth("invalid".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_permanent_error_short_circuits() {
+        let mut calls = 0;
+        let result: crate::Result<()> = retry_api(
+            || {
+                calls += 1;
+                async { Err(Error::api(401, "Unauthorized", "")) }
+            },
+            &BackoffParams::default(),
+            false,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
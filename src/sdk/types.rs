@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::sdk::api_client::ApiClientOptions;
+
 /// Options for creating a DirectContext.
 #[derive(Debug, Clone, Default)]
 pub struct DirectContextOptions {
@@ -14,6 +16,8 @@ pub struct DirectContextOptions {
     pub debug: bool,
     /// Maximum file size in bytes (default: 1MB)
     pub max_file_size: Option<usize>,
+    /// HTTP transport settings for the API client
+    pub client: ApiClientOptions,
 }
 
 /// Blob information for tracking.
@@ -137,6 +141,9 @@ pub struct CodebaseRetrievalRequest {
 #[derive(Debug, Clone, Deserialize)]
 pub struct CodebaseRetrievalResponse {
     pub formatted_retrieval: String,
+    /// Set by the client when the response was cut off by a timeout
+    #[serde(skip)]
+    pub truncated: bool,
 }
 
 /// Chat message for streaming.
@@ -0,0 +1,191 @@
+//! Token-budgeted context bundles.
+//!
+//! Retrieval returns more text than fits in a prompt. A bundle ranks the
+//! retrieved snippets by relevance and packs them greedily until the token
+//! budget is spent, recording which files made it in and which didn't.
+
+use serde::Serialize;
+
+use crate::service::tokens::TokenEstimator;
+
+/// A retrieved snippet that may go into a bundle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    /// Workspace-relative file path
+    pub path: String,
+    /// Snippet text
+    pub content: String,
+    /// Relevance score (0-1), higher is better
+    pub relevance: f64,
+}
+
+impl Snippet {
+    /// The snippet as it appears in a bundle.
+    pub fn render(&self) -> String {
+        format!("Path: {}\n{}", self.path, self.content)
+    }
+}
+
+/// Snippets packed under a token budget.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContextBundle {
+    /// Included snippets, most relevant first
+    pub snippets: Vec<Snippet>,
+    /// Files with at least one included snippet, in relevance order
+    pub included_files: Vec<String>,
+    /// Files none of whose snippets fit, in relevance order
+    pub excluded_files: Vec<String>,
+    /// Snippets left out for budget or file-count reasons
+    pub excluded_snippets: usize,
+    /// Estimated tokens used by the included snippets
+    pub estimated_tokens: usize,
+    /// The budget the bundle was packed against
+    pub token_budget: usize,
+}
+
+impl ContextBundle {
+    /// Pack `candidates` under `token_budget`, drawing from at most `max_files` files.
+    ///
+    /// Candidates are taken in descending relevance (ties keep retrieval
+    /// order). A snippet that doesn't fit is skipped rather than ending the
+    /// pack, so a smaller, less relevant one can still use the space left.
+    pub fn pack(
+        mut candidates: Vec<Snippet>,
+        token_budget: usize,
+        max_files: usize,
+        tokens: &dyn TokenEstimator,
+    ) -> Self {
+        candidates.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+
+        let mut bundle = Self {
+            token_budget,
+            ..Self::default()
+        };
+        let mut seen = Vec::new();
+        for snippet in candidates {
+            if !seen.contains(&snippet.path) {
+                seen.push(snippet.path.clone());
+            }
+            let new_file = !bundle.included_files.contains(&snippet.path);
+            let cost = tokens.estimate(&snippet.render());
+            if (new_file && bundle.included_files.len() >= max_files)
+                || bundle.estimated_tokens + cost > token_budget
+            {
+                bundle.excluded_snippets += 1;
+                continue;
+            }
+            if new_file {
+                bundle.included_files.push(snippet.path.clone());
+            }
+            bundle.estimated_tokens += cost;
+            bundle.snippets.push(snippet);
+        }
+
+        bundle.excluded_files = seen
+            .into_iter()
+            .filter(|path| !bundle.included_files.contains(path))
+            .collect();
+        bundle
+    }
+
+    /// The included snippets joined for use in a prompt.
+    pub fn render(&self) -> String {
+        self.snippets
+            .iter()
+            .map(Snippet::render)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Split a formatted retrieval result into snippets.
+///
+/// Each snippet starts at a `Path: ...` line. The backend returns results
+/// best first, so relevance falls off linearly with rank.
+pub fn parse_snippets(text: &str) -> Vec<Snippet> {
+    let mut parsed: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in text.lines() {
+        match line.trim().strip_prefix("Path:").map(str::trim) {
+            Some(path) if !path.is_empty() => parsed.push((path.to_string(), Vec::new())),
+            _ => {
+                if let Some((_, lines)) = parsed.last_mut() {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    let count = parsed.len();
+    parsed
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (path, lines))| Snippet {
+            path,
+            content: lines.join("\n").trim().to_string(),
+            relevance: 1.0 - rank as f64 / count as f64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tokens::CharHeuristic;
+
+    fn snippet(path: &str, chars: usize, relevance: f64) -> Snippet {
+        Snippet {
+            path: path.to_string(),
+            content: "x".repeat(chars),
+            relevance,
+        }
+    }
+
+    #[test]
+    fn test_pack_stays_under_budget_and_reports_exclusions() {
+        let candidates = vec![
+            snippet("src/low.rs", 80, 0.2),
+            snippet("src/big.rs", 400, 0.9),
+            snippet("src/top.rs", 200, 1.0),
+            snippet("src/mid.rs", 80, 0.5),
+        ];
+        let tokens = CharHeuristic::default();
+        let bundle = ContextBundle::pack(candidates, 100, 10, &tokens);
+
+        assert!(bundle.estimated_tokens <= 100);
+        assert_eq!(bundle.included_files, vec!["src/top.rs", "src/mid.rs"]);
+        assert_eq!(bundle.excluded_files, vec!["src/big.rs", "src/low.rs"]);
+        assert_eq!(bundle.excluded_snippets, 2);
+        assert_eq!(
+            bundle.estimated_tokens,
+            bundle
+                .snippets
+                .iter()
+                .map(|s| tokens.estimate(&s.render()))
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_pack_caps_files_but_not_snippets_per_file() {
+        let candidates = vec![
+            snippet("a.rs", 8, 1.0),
+            snippet("b.rs", 8, 0.8),
+            snippet("a.rs", 8, 0.6),
+        ];
+        let bundle = ContextBundle::pack(candidates, 1000, 1, &CharHeuristic::default());
+        assert_eq!(bundle.snippets.len(), 2);
+        assert_eq!(bundle.included_files, vec!["a.rs"]);
+        assert_eq!(bundle.excluded_files, vec!["b.rs"]);
+    }
+
+    #[test]
+    fn test_parse_snippets_ranks_by_position() {
+        let snippets =
+            parse_snippets("Path: src/lib.rs\nfn a() {}\n\nPath: src/cli.rs\nfn main() {}\n");
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].path, "src/lib.rs");
+        assert_eq!(snippets[0].content, "fn a() {}");
+        assert_eq!(snippets[1].content, "fn main() {}");
+        assert!(snippets[0].relevance > snippets[1].relevance);
+    }
+}
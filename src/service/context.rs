@@ -1,55 +1,83 @@
 //! Context service - main service for code context operations.
 
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+use crate::clock::{system_clock, SharedClock};
 use crate::config::Config;
 use crate::error::{Error, Result};
-use crate::sdk::{DirectContext, DirectContextOptions};
-use crate::types::{IndexState, IndexStatus};
-
-/// Patterns to ignore when indexing.
-const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
-    "node_modules",
-    ".git",
-    ".svn",
-    ".hg",
-    "target",
-    "dist",
-    "build",
-    "__pycache__",
-    ".pytest_cache",
-    ".mypy_cache",
-    "*.pyc",
-    "*.pyo",
-    "*.so",
-    "*.dylib",
-    "*.dll",
-    "*.exe",
-    "*.o",
-    "*.a",
-    "*.lib",
-    ".DS_Store",
-    "Thumbs.db",
-    "*.log",
-    "*.lock",
-    "package-lock.json",
-    "yarn.lock",
-    "pnpm-lock.yaml",
-    "Cargo.lock",
-];
+use crate::reviewer::git::GitCache;
+use crate::sdk::{ApiClientOptions, DirectContext, DirectContextOptions, IndexingResult};
+use crate::service::bundle::{parse_snippets, ContextBundle, Snippet};
+use crate::service::exclude::ExcludeMatcher;
+use crate::service::explain::{explain, query_terms};
+use crate::service::grep::{Grep, GrepMatch, GrepOptions, GrepResult};
+use crate::service::language;
+use crate::service::search_cache::SearchCache;
+use crate::service::secrets::{self, EntropyOptions, SecretScan};
+use crate::service::snippets::SnippetStore;
+use crate::service::stats::{StatsCache, WorkspaceStats};
+use crate::service::tokens::{CharHeuristic, TokenEstimator};
+use crate::service::tree::{build_tree, Tree, TreeOptions};
+use crate::types::{IndexState, IndexStatus, Retrieval, SearchResult};
+
+/// Number of files and content bytes in an indexing batch.
+fn batch_size(batch: &[crate::types::File]) -> (usize, u64) {
+    (
+        batch.len(),
+        batch.iter().map(|f| f.contents.len() as u64).sum(),
+    )
+}
+
+/// A finished indexing batch.
+struct BatchOutcome {
+    files: usize,
+    bytes: u64,
+    result: Result<IndexingResult>,
+}
+
+/// Send one batch of files to the backend.
+async fn index_batch(ctx: &DirectContext, batch: Vec<crate::types::File>) -> BatchOutcome {
+    let (files, bytes) = batch_size(&batch);
+    BatchOutcome {
+        files,
+        bytes,
+        result: ctx.add_to_index(batch).await,
+    }
+}
+
+/// Whether a file's first lines name its language (shebang or modeline).
+async fn has_language_header(path: &Path) -> bool {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = [0u8; 512];
+    let n = match fs::File::open(path).await {
+        Ok(mut file) => file.read(&mut head).await.unwrap_or(0),
+        Err(_) => return false,
+    };
+    let head = String::from_utf8_lossy(&head[..n]);
+    language::detect_language_from_content(&head).is_some()
+}
 
 /// Context service for managing code indexing and retrieval.
 pub struct ContextService {
     config: Config,
     context: Arc<RwLock<Option<DirectContext>>>,
     workspace: PathBuf,
-    ignore_patterns: HashSet<String>,
+    excludes: ExcludeMatcher,
     state: Arc<RwLock<ServiceState>>,
+    ready: AtomicBool,
+    tokens: Arc<dyn TokenEstimator>,
+    clock: SharedClock,
+    stats: Arc<std::sync::Mutex<StatsCache>>,
+    search_cache: std::sync::Mutex<SearchCache>,
+    snippets: std::sync::Mutex<SnippetStore>,
+    git_cache: GitCache,
 }
 
 /// Internal service state.
@@ -59,6 +87,16 @@ struct ServiceState {
     file_count: usize,
     last_indexed: Option<String>,
     last_error: Option<String>,
+    progress: IndexProgress,
+}
+
+/// Progress of the current (or last) indexing run.
+#[derive(Debug, Default, Clone)]
+struct IndexProgress {
+    files_total: usize,
+    files_done: usize,
+    bytes_indexed: u64,
+    started_at: Option<String>,
 }
 
 impl ContextService {
@@ -67,34 +105,65 @@ impl ContextService {
         let workspace = config.workspace.clone();
         let config = config.clone();
 
-        // Initialize ignore patterns
-        let mut ignore_patterns: HashSet<String> = DEFAULT_IGNORE_PATTERNS
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-
-        // Load .gitignore if present
+        // Defaults, then .gitignore if present, then configured globs; later
+        // patterns win, so configured `!` rules can force-include paths
+        let mut exclude_patterns = Vec::new();
         let gitignore_path = workspace.join(".gitignore");
         if gitignore_path.exists() {
             if let Ok(content) = fs::read_to_string(&gitignore_path).await {
-                for line in content.lines() {
-                    let line = line.trim();
-                    if !line.is_empty() && !line.starts_with('#') {
-                        ignore_patterns.insert(line.to_string());
-                    }
-                }
+                exclude_patterns.extend(content.lines().map(str::to_string));
             }
         }
+        exclude_patterns.extend(config.exclude_globs.iter().cloned());
+        let excludes = ExcludeMatcher::with_defaults(exclude_patterns);
+        let stats = StatsCache::new(config.stats_change_detection);
+        let search_cache =
+            SearchCache::new(config.search_cache_capacity, config.search_cache_ttl());
 
         Ok(Self {
             config,
             context: Arc::new(RwLock::new(None)),
             workspace,
-            ignore_patterns,
+            excludes,
             state: Arc::new(RwLock::new(ServiceState::default())),
+            ready: AtomicBool::new(false),
+            tokens: Arc::new(CharHeuristic::default()),
+            clock: system_clock(),
+            stats: Arc::new(std::sync::Mutex::new(stats)),
+            search_cache: std::sync::Mutex::new(search_cache),
+            snippets: std::sync::Mutex::new(SnippetStore::default()),
+            git_cache: GitCache::default(),
         })
     }
 
+    /// Stamp index runs with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Count tokens with `estimator` instead of the default character heuristic.
+    pub fn with_token_estimator(mut self, estimator: Arc<dyn TokenEstimator>) -> Self {
+        self.tokens = estimator;
+        self
+    }
+
+    /// Cache search results in `cache` instead of one built from the config.
+    pub fn with_search_cache(mut self, cache: SearchCache) -> Self {
+        self.search_cache = std::sync::Mutex::new(cache);
+        self
+    }
+
+    /// Drop cached search results, e.g. after the index changed.
+    fn invalidate_search_cache(&self) {
+        self.search_cache.lock().unwrap().clear();
+    }
+
+    /// The estimator used for token budgets.
+    pub fn token_estimator(&self) -> &dyn TokenEstimator {
+        self.tokens.as_ref()
+    }
+
     /// Initialize the context (lazy initialization).
     pub async fn initialize(&self) -> Result<()> {
         let mut context_guard = self.context.write().await;
@@ -108,6 +177,13 @@ impl ContextService {
             api_url: self.config.api_url.clone(),
             debug: self.config.debug,
             max_file_size: Some(self.config.max_file_size),
+            client: ApiClientOptions {
+                request_timeout: self.config.request_timeout(),
+                pool_max_idle: self.config.pool_size,
+                retry: self.config.backoff(),
+                circuit_breaker: self.config.circuit_breaker(),
+                ..ApiClientOptions::default()
+            },
         };
 
         let context = DirectContext::create(options).await?;
@@ -120,11 +196,33 @@ impl ContextService {
         Ok(())
     }
 
+    /// Whether a full workspace index has completed at least once.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    /// Check that the backend is reachable.
+    pub async fn ping(&self) -> Result<()> {
+        let context = self.context.read().await;
+        let ctx = context.as_ref().ok_or(Error::IndexNotInitialized)?;
+        ctx.ping().await
+    }
+
+    /// Get the service configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Get the workspace path.
     pub fn workspace(&self) -> &Path {
         &self.workspace
     }
 
+    /// Cache of git output for the workspace repository.
+    pub fn git_cache(&self) -> &GitCache {
+        &self.git_cache
+    }
+
     /// Get the workspace path (alias for compatibility).
     pub fn workspace_path(&self) -> &Path {
         &self.workspace
@@ -148,40 +246,238 @@ impl ContextService {
             file_count,
             is_stale: false,
             last_error: state.last_error.clone(),
+            indexing: state.status == IndexState::Indexing,
+            files_total: state.progress.files_total,
+            files_done: state.progress.files_done,
+            bytes_indexed: state.progress.bytes_indexed,
+            started_at: state.progress.started_at.clone(),
         }
     }
 
-    /// Check if a path should be ignored.
-    fn should_ignore(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
-
-        for pattern in &self.ignore_patterns {
-            if pattern.contains('*') {
-                // Simple glob matching
-                let pattern = pattern.replace("*", "");
-                if path_str.contains(&pattern) {
-                    return true;
+    /// The workspace followed by the configured additional roots.
+    pub fn roots(&self) -> Vec<&Path> {
+        std::iter::once(self.workspace.as_path())
+            .chain(self.config.roots.iter().map(PathBuf::as_path))
+            .collect()
+    }
+
+    /// Look up a registered root by its path or its directory name.
+    ///
+    /// `None` selects the workspace; an unregistered root fails with
+    /// [`Error::InvalidPath`] rather than being read as an arbitrary path.
+    pub fn root(&self, root: Option<&str>) -> Result<&Path> {
+        let Some(name) = root else {
+            return Ok(&self.workspace);
+        };
+        let roots = self.roots();
+        roots
+            .iter()
+            .find(|r| r.as_os_str() == name)
+            .or_else(|| {
+                roots
+                    .iter()
+                    .find(|r| r.file_name().is_some_and(|n| n == name))
+            })
+            .copied()
+            .ok_or_else(|| {
+                let known: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+                Error::InvalidPath(format!(
+                    "Unknown root '{}': expected one of {}",
+                    name,
+                    known.join(", ")
+                ))
+            })
+    }
+
+    /// Remember a search hit so it can be read back by id, returning the id.
+    pub fn remember_snippet(&self, snippet: &Snippet) -> String {
+        self.snippets.lock().unwrap().insert(snippet)
+    }
+
+    /// A remembered search hit.
+    pub fn snippet(&self, id: &str) -> Option<Snippet> {
+        self.snippets.lock().unwrap().get(id).cloned()
+    }
+
+    /// Remembered search hits with their ids, most recent first.
+    pub fn recent_snippets(&self) -> Vec<(String, Snippet)> {
+        self.snippets.lock().unwrap().recent()
+    }
+
+    /// Resolve a workspace-relative path, rejecting paths that escape the workspace.
+    ///
+    /// Absolute paths and `..` components that climb above the root are
+    /// rejected outright. The path, or for a path that does not exist yet its
+    /// nearest existing parent directory, is then canonicalized so a symlink
+    /// cannot point outside the workspace. The path itself need not exist.
+    pub fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        self.resolve_path_in(None, path)
+    }
+
+    /// Like [`Self::resolve_path`], relative to the registered root `root`
+    /// (see [`Self::root`]) instead of the workspace.
+    pub fn resolve_path_in(&self, root: Option<&str>, path: &str) -> Result<PathBuf> {
+        let base = self.root(root)?;
+        let relative = Path::new(path);
+        if path.is_empty() || relative.is_absolute() {
+            return Err(Error::InvalidPath(format!(
+                "'{}' must be a non-empty path relative to the workspace",
+                path
+            )));
+        }
+
+        let mut depth = 0usize;
+        for component in relative.components() {
+            match component {
+                Component::Normal(_) => depth += 1,
+                Component::CurDir => {}
+                Component::ParentDir if depth > 0 => depth -= 1,
+                _ => {
+                    return Err(Error::InvalidPath(format!(
+                        "'{}' escapes the workspace",
+                        path
+                    )))
                 }
-            } else if path_str.contains(pattern) {
-                return true;
             }
         }
 
-        false
+        let full_path = base.join(relative);
+        let existing = full_path
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+            .and_then(|ancestor| ancestor.canonicalize().ok());
+        if let (Ok(root), Some(target)) = (base.canonicalize(), existing) {
+            if !target.starts_with(&root) {
+                return Err(Error::InvalidPath(format!(
+                    "'{}' escapes the workspace",
+                    path
+                )));
+            }
+        }
+        Ok(full_path)
+    }
+
+    /// Read a workspace file as text, enforcing the path and size limits.
+    ///
+    /// Escaping paths fail with [`Error::InvalidPath`], missing files with
+    /// [`Error::FileNotFound`] and unreadable ones with
+    /// [`Error::PermissionDenied`].
+    pub async fn read_file(&self, path: &str) -> Result<String> {
+        self.read_file_in(None, path).await
+    }
+
+    /// Like [`Self::read_file`], relative to the registered root `root`.
+    pub async fn read_file_in(&self, root: Option<&str>, path: &str) -> Result<String> {
+        let full_path = self.resolve_path_in(root, path)?;
+        let metadata = fs::metadata(&full_path)
+            .await
+            .map_err(|e| Error::file_io(path, e))?;
+        if metadata.len() > self.config.max_file_size as u64 {
+            return Err(Error::FileTooLarge {
+                path: path.to_string(),
+                size: metadata.len() as usize,
+            });
+        }
+
+        fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| Error::file_io(path, e))
+    }
+
+    /// Check if a workspace-relative path should be ignored.
+    fn should_ignore(&self, path: &Path) -> bool {
+        self.excludes.is_excluded(path)
     }
 
     /// Perform semantic search.
-    pub async fn search(&self, query: &str, max_tokens: Option<usize>) -> Result<String> {
+    ///
+    /// Results are cached by query and token budget until the cache TTL
+    /// passes or the index changes. If the backend times out, the snippets
+    /// received before it did are returned with `truncated` set; those are
+    /// not cached, so asking again retries the full search.
+    pub async fn search(&self, query: &str, max_tokens: Option<usize>) -> Result<Retrieval> {
         self.initialize().await?;
 
         let context = self.context.read().await;
         let ctx = context.as_ref().ok_or(Error::IndexNotInitialized)?;
 
-        ctx.search(query, max_tokens).await
+        if let Some(text) = self.search_cache.lock().unwrap().get(query, max_tokens) {
+            debug!("Search cache hit: {}", query);
+            return Ok(Retrieval {
+                text,
+                truncated: false,
+            });
+        }
+        let result = ctx.search(query, max_tokens).await?;
+        if !result.truncated {
+            self.search_cache
+                .lock()
+                .unwrap()
+                .insert(query, max_tokens, result.text.clone());
+        }
+        Ok(result)
+    }
+
+    /// Why each of `snippets` matched `query`; see [`crate::service::explain`].
+    ///
+    /// Files that can't be read just leave their hits without line numbers.
+    pub async fn explain_hits(&self, query: &str, snippets: &[Snippet]) -> Vec<SearchResult> {
+        let terms = query_terms(query);
+        let mut hits = Vec::with_capacity(snippets.len());
+        for snippet in snippets {
+            let file = self.read_file(&snippet.path).await.ok();
+            hits.push(explain(&terms, snippet, file.as_deref()));
+        }
+        hits
+    }
+
+    /// Retrieve context for `query`, packed to fit `token_budget`.
+    ///
+    /// Snippets are drawn from at most `max_files` files; see
+    /// [`ContextBundle::pack`] for how they are chosen.
+    pub async fn bundle_context(
+        &self,
+        query: &str,
+        token_budget: usize,
+        max_files: usize,
+    ) -> Result<ContextBundle> {
+        let retrieved = self.search(query, Some(token_budget)).await?;
+        Ok(ContextBundle::pack(
+            parse_snippets(&retrieved.text),
+            token_budget,
+            max_files,
+            self.token_estimator(),
+        ))
     }
 
     /// Index the entire workspace.
     pub async fn index_workspace(&self) -> Result<crate::types::IndexResult> {
+        self.index_files_under(&self.workspace, false).await
+    }
+
+    /// Index only the workspace-relative directory `path`.
+    ///
+    /// The path is checked like [`Self::resolve_path`] and must be a
+    /// directory. Its files are merged into the existing index: files indexed
+    /// from elsewhere stay searchable, and the reported file count is that of
+    /// the whole index.
+    pub async fn index_directory(&self, path: &str) -> Result<crate::types::IndexResult> {
+        let dir = self.resolve_path(path.trim_end_matches('/'))?;
+        let metadata = fs::metadata(&dir)
+            .await
+            .map_err(|e| Error::file_io(path, e))?;
+        if !metadata.is_dir() {
+            return Err(Error::InvalidPath(format!("'{}' is not a directory", path)));
+        }
+        self.index_files_under(&dir, true).await
+    }
+
+    /// Index the files under `dir`, merging into the index if `scoped`.
+    async fn index_files_under(
+        &self,
+        dir: &Path,
+        scoped: bool,
+    ) -> Result<crate::types::IndexResult> {
         self.initialize().await?;
 
         // Update status to indexing
@@ -189,28 +485,37 @@ impl ContextService {
             let mut state = self.state.write().await;
             state.status = IndexState::Indexing;
             state.last_error = None;
+            state.progress = IndexProgress {
+                started_at: Some(self.clock.now_rfc3339()),
+                ..IndexProgress::default()
+            };
         }
 
-        info!("Starting workspace indexing: {:?}", self.workspace);
+        info!("Starting workspace indexing: {:?}", dir);
 
         // Discover all files
-        let files = self.discover_files(&self.workspace).await?;
+        let files = self.discover_files(dir).await?;
         let file_count = files.len();
+        self.state.write().await.progress.files_total = file_count;
 
         info!("Discovered {} files to index", file_count);
 
-        // Index files in batches
-        let mut indexed = 0;
-        let mut skipped = 0;
-        let mut errors = Vec::new();
+        // Index files in batches, with a bounded number of batches in flight
+        let mut result = crate::types::IndexResult {
+            indexed: 0,
+            skipped: 0,
+            errors: Vec::new(),
+            duration: 0,
+        };
         let start_time = std::time::Instant::now();
 
         let context = self.context.read().await;
         let ctx = context.as_ref().ok_or(Error::IndexNotInitialized)?;
 
-        // Collect files into batches
-        const BATCH_SIZE: usize = 100;
-        let mut batch: Vec<crate::types::File> = Vec::with_capacity(BATCH_SIZE);
+        let batch_files = self.config.index_batch_size.max(1);
+        let concurrency = self.config.index_concurrency.max(1);
+        let mut in_flight = FuturesUnordered::new();
+        let mut batch: Vec<crate::types::File> = Vec::with_capacity(batch_files);
 
         for file_path in files {
             let relative_path = file_path
@@ -228,7 +533,8 @@ impl ContextService {
                             relative_path,
                             contents.len()
                         );
-                        skipped += 1;
+                        result.skipped += 1;
+                        self.record_progress(1, 0).await;
                         continue;
                     }
 
@@ -237,72 +543,373 @@ impl ContextService {
                         contents,
                     });
 
-                    // Process batch when full
-                    if batch.len() >= BATCH_SIZE {
-                        match ctx.add_to_index(std::mem::take(&mut batch)).await {
-                            Ok(result) => {
-                                indexed += result.indexed;
-                                skipped += result.skipped;
-                                errors.extend(result.errors);
-                            }
-                            Err(e) => {
-                                warn!("Batch indexing failed: {}", e);
-                                errors.push(format!("Batch error: {}", e));
+                    // Dispatch batch when full, waiting for a slot if needed
+                    if batch.len() >= batch_files {
+                        if in_flight.len() >= concurrency {
+                            if let Some(outcome) = in_flight.next().await {
+                                self.absorb_batch(&mut result, outcome).await;
                             }
                         }
+                        in_flight.push(index_batch(ctx, std::mem::take(&mut batch)));
                     }
                 }
                 Err(e) => {
                     debug!("Failed to read {}: {}", relative_path, e);
-                    skipped += 1;
+                    result.skipped += 1;
+                    self.record_progress(1, 0).await;
                 }
             }
         }
 
-        // Process remaining files
+        // Dispatch remaining files and wait for everything in flight
         if !batch.is_empty() {
-            match ctx.add_to_index(batch).await {
-                Ok(result) => {
-                    indexed += result.indexed;
-                    skipped += result.skipped;
-                    errors.extend(result.errors);
-                }
-                Err(e) => {
-                    warn!("Final batch indexing failed: {}", e);
-                    errors.push(format!("Batch error: {}", e));
-                }
-            }
+            in_flight.push(index_batch(ctx, batch));
+        }
+        while let Some(outcome) = in_flight.next().await {
+            self.absorb_batch(&mut result, outcome).await;
         }
+        let index_size = if scoped {
+            ctx.file_count().await
+        } else {
+            result.indexed
+        };
+        self.invalidate_search_cache();
 
-        let duration = start_time.elapsed().as_millis() as u64;
+        result.duration = start_time.elapsed().as_millis() as u64;
 
         // Update status
         {
             let mut state = self.state.write().await;
             state.status = IndexState::Idle;
-            state.file_count = indexed;
-            state.last_indexed = Some(chrono::Utc::now().to_rfc3339());
+            state.file_count = index_size;
+            state.last_indexed = Some(self.clock.now_rfc3339());
+        }
+        if !scoped {
+            self.ready.store(true, Ordering::SeqCst);
         }
 
         info!(
             "Indexing complete: {} indexed, {} skipped in {}ms",
-            indexed, skipped, duration
+            result.indexed, result.skipped, result.duration
+        );
+
+        Ok(result)
+    }
+
+    /// Fold a finished batch into the running totals and progress.
+    async fn absorb_batch(&self, result: &mut crate::types::IndexResult, outcome: BatchOutcome) {
+        match outcome.result {
+            Ok(batch) => {
+                result.indexed += batch.indexed;
+                result.skipped += batch.skipped;
+                result.errors.extend(batch.errors);
+            }
+            Err(e) => {
+                warn!("Batch indexing failed: {}", e);
+                result.errors.push(format!("Batch error: {}", e));
+            }
+        }
+        self.record_progress(outcome.files, outcome.bytes).await;
+    }
+
+    /// Re-index a single workspace file, e.g. right after it was edited.
+    ///
+    /// Unchanged content is reported as skipped without an upload.
+    pub async fn reindex_file(&self, path: &str) -> Result<crate::types::IndexResult> {
+        let full_path = self.resolve_path(path)?;
+        let start_time = std::time::Instant::now();
+
+        let contents = fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| Error::file_io(path, e))?;
+        if contents.len() > self.config.max_file_size {
+            return Err(Error::FileTooLarge {
+                path: path.to_string(),
+                size: contents.len(),
+            });
+        }
+
+        self.initialize().await?;
+        let context = self.context.read().await;
+        let ctx = context.as_ref().ok_or(Error::IndexNotInitialized)?;
+
+        let relative_path = full_path
+            .strip_prefix(&self.workspace)
+            .unwrap_or(&full_path)
+            .to_string_lossy()
+            .to_string();
+        let result = ctx
+            .add_to_index(vec![crate::types::File {
+                path: relative_path,
+                contents,
+            }])
+            .await?;
+        if result.indexed > 0 {
+            self.invalidate_search_cache();
+        }
+
+        debug!(
+            "Reindexed {}: {} indexed, {} skipped",
+            path, result.indexed, result.skipped
         );
 
         Ok(crate::types::IndexResult {
-            indexed,
-            skipped,
-            errors,
-            duration,
+            indexed: result.indexed,
+            skipped: result.skipped,
+            errors: result.errors,
+            duration: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Exact text search over indexable workspace files, without the backend.
+    pub async fn grep(&self, pattern: &str, options: GrepOptions) -> Result<GrepResult> {
+        self.grep_into(pattern, options, None).await
+    }
+
+    /// Like [`grep`](Self::grep), but each file's matches are sent to `sink`
+    /// as soon as the file is scanned instead of being kept in the result.
+    ///
+    /// The result counts them in `streamed`. Scanning stops early if the
+    /// receiver is dropped.
+    pub async fn grep_streaming(
+        &self,
+        pattern: &str,
+        options: GrepOptions,
+        sink: tokio::sync::mpsc::Sender<GrepMatch>,
+    ) -> Result<GrepResult> {
+        self.grep_into(pattern, options, Some(sink)).await
+    }
+
+    async fn grep_into(
+        &self,
+        pattern: &str,
+        options: GrepOptions,
+        sink: Option<tokio::sync::mpsc::Sender<GrepMatch>>,
+    ) -> Result<GrepResult> {
+        let grep = Grep::new(pattern, options)?;
+        let mut files = self.discover_files(&self.workspace).await?;
+        files.sort();
+        let workspace = self.workspace.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut result = GrepResult::default();
+            for path in files {
+                if grep.remaining(&result) == 0 {
+                    result.truncated = true;
+                    break;
+                }
+                let file = match std::fs::File::open(&path) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        debug!("Failed to open {:?}: {}", path, e);
+                        continue;
+                    }
+                };
+                let relative = path
+                    .strip_prefix(&workspace)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+                grep.scan(&relative, std::io::BufReader::new(file), &mut result);
+                if let Some(sink) = &sink {
+                    for found in std::mem::take(&mut result.matches) {
+                        if sink.blocking_send(found).is_err() {
+                            return result;
+                        }
+                        result.streamed += 1;
+                    }
+                }
+            }
+            result
+        })
+        .await
+        .map_err(|e| Error::Internal(format!("Grep task failed: {}", e)))
+    }
+
+    /// Scan every non-excluded workspace file for secrets.
+    ///
+    /// Unlike indexing this is not limited to source files, so `.env` and
+    /// config files are covered. High-entropy tokens are reported too, with
+    /// the configured threshold and minimum length. Files larger than `max_file_size` or that
+    /// look binary are skipped. The scan stops after `max_files` files or
+    /// `max_matches` matches, marking the result truncated.
+    pub async fn scan_secrets(&self, max_files: usize, max_matches: usize) -> Result<SecretScan> {
+        let mut files = self.walk_files(&self.workspace, false).await?;
+        files.sort();
+        let workspace = self.workspace.clone();
+        let max_file_size = self.config.max_file_size as u64;
+        let entropy = EntropyOptions::from_config(&self.config);
+
+        tokio::task::spawn_blocking(move || {
+            let mut scan = SecretScan::default();
+            for path in files {
+                if scan.files_scanned >= max_files || scan.matches.len() >= max_matches {
+                    scan.truncated = true;
+                    break;
+                }
+                let small = std::fs::metadata(&path).is_ok_and(|m| m.len() <= max_file_size);
+                let bytes = small
+                    .then(|| std::fs::read(&path).ok())
+                    .flatten()
+                    .filter(|bytes| !secrets::is_binary(bytes));
+                let Some(bytes) = bytes else {
+                    debug!("Skipping secret scan of {:?}", path);
+                    scan.files_skipped += 1;
+                    continue;
+                };
+
+                let relative = path
+                    .strip_prefix(&workspace)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+                scan.files_scanned += 1;
+                let found =
+                    secrets::scan_text(&relative, &String::from_utf8_lossy(&bytes), entropy);
+                let room = max_matches - scan.matches.len();
+                if found.len() > room {
+                    scan.truncated = true;
+                }
+                scan.matches.extend(found.into_iter().take(room));
+            }
+            scan
         })
+        .await
+        .map_err(|e| Error::Internal(format!("Secret scan task failed: {}", e)))
+    }
+
+    /// File, line and byte counts per language for indexable workspace files.
+    ///
+    /// Only files changed since the previous call are read again; see
+    /// [`ChangeDetection`](crate::service::ChangeDetection).
+    pub async fn workspace_stats(&self) -> Result<WorkspaceStats> {
+        let files: Vec<(String, PathBuf)> = self
+            .discover_files(&self.workspace)
+            .await?
+            .into_iter()
+            .map(|path| {
+                let relative = path
+                    .strip_prefix(&self.workspace)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+                (relative, path)
+            })
+            .collect();
+        let cache = self.stats.clone();
+
+        tokio::task::spawn_blocking(move || cache.lock().unwrap().refresh(&files))
+            .await
+            .map_err(|e| Error::Internal(format!("Stats task failed: {}", e)))
+    }
+
+    /// Workspace-relative paths completing `prefix`, sorted.
+    ///
+    /// Lists the directory named by everything up to the last `/` and keeps
+    /// entries starting with the rest. Excluded paths are skipped, as are
+    /// hidden entries unless the typed name starts with `.`. Directories get
+    /// a trailing `/` so clients can drill in.
+    pub async fn complete_path(&self, prefix: &str) -> Result<Vec<String>> {
+        let (dir, name) = match prefix.rfind('/') {
+            Some(i) => (&prefix[..=i], &prefix[i + 1..]),
+            None => ("", prefix),
+        };
+        let dir_path = if dir.is_empty() {
+            self.workspace.clone()
+        } else {
+            self.resolve_path(dir)?
+        };
+
+        let mut entries = match fs::read_dir(&dir_path).await {
+            Ok(entries) => entries,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut completions = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                continue;
+            }
+            let candidate = format!("{}{}", dir, file_name);
+            if self.should_ignore(Path::new(&candidate)) {
+                continue;
+            }
+            if entry.path().is_dir() {
+                completions.push(format!("{}/", candidate));
+            } else {
+                completions.push(candidate);
+            }
+        }
+        completions.sort();
+        Ok(completions)
+    }
+
+    /// The directory tree under the workspace-relative `dir` (the workspace
+    /// root if empty), skipping excluded paths.
+    pub async fn list_tree(&self, dir: &str, options: TreeOptions) -> Result<Tree> {
+        let path = if dir.trim_matches('/').is_empty() {
+            self.workspace.clone()
+        } else {
+            self.resolve_path(dir.trim_end_matches('/'))?
+        };
+        let metadata = fs::metadata(&path)
+            .await
+            .map_err(|e| Error::file_io(dir, e))?;
+        if !metadata.is_dir() {
+            return Err(Error::InvalidPath(format!("'{}' is not a directory", dir)));
+        }
+
+        let workspace = self.workspace.clone();
+        let excludes = self.excludes.clone();
+        let options = TreeOptions {
+            follow_symlinks: self.config.follow_symlinks,
+            ..options
+        };
+        tokio::task::spawn_blocking(move || build_tree(&workspace, &path, &excludes, options))
+            .await
+            .map_err(|e| Error::Internal(format!("Tree task failed: {}", e)))
+    }
+
+    /// Advance the indexing progress counters.
+    async fn record_progress(&self, files_done: usize, bytes: u64) {
+        let mut state = self.state.write().await;
+        state.progress.files_done += files_done;
+        state.progress.bytes_indexed += bytes;
     }
 
     /// Discover all indexable files in a directory.
+    ///
+    /// Symlinks are skipped unless `follow_symlinks` is set, and even then
+    /// only followed when their target is inside the workspace. Directories
+    /// are visited once by canonical path, so symlink cycles terminate.
     async fn discover_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        self.walk_files(dir, true).await
+    }
+
+    /// Like [`Self::discover_files`], keeping every non-excluded file unless
+    /// `indexable_only` is set.
+    async fn walk_files(&self, dir: &Path, indexable_only: bool) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
         let mut dirs_to_visit = vec![dir.to_path_buf()];
+        let root = fs::canonicalize(&self.workspace)
+            .await
+            .unwrap_or_else(|_| self.workspace.clone());
+        let mut visited = std::collections::HashSet::new();
 
         while let Some(current_dir) = dirs_to_visit.pop() {
+            match fs::canonicalize(&current_dir).await {
+                Ok(canonical) => {
+                    if !visited.insert(canonical) {
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    debug!("Cannot resolve directory {:?}: {}", current_dir, e);
+                    continue;
+                }
+            }
             let mut entries = match fs::read_dir(&current_dir).await {
                 Ok(e) => e,
                 Err(e) => {
@@ -321,11 +928,37 @@ impl ContextService {
                     continue;
                 }
 
+                let is_symlink = entry.file_type().await.is_ok_and(|t| t.is_symlink());
+                if is_symlink {
+                    if !self.config.follow_symlinks {
+                        debug!("Skipping symlink: {:?}", relative);
+                        continue;
+                    }
+                    match fs::canonicalize(&path).await {
+                        Ok(target) if target.starts_with(&root) => {}
+                        Ok(target) => {
+                            debug!(
+                                "Skipping symlink out of workspace: {:?} -> {:?}",
+                                relative, target
+                            );
+                            continue;
+                        }
+                        Err(e) => {
+                            debug!("Skipping broken symlink {:?}: {}", relative, e);
+                            continue;
+                        }
+                    }
+                }
+
                 if path.is_dir() {
                     dirs_to_visit.push(path);
                 } else if path.is_file() {
-                    // Check if it's a text file we should index
-                    if self.should_index_file(&path) {
+                    // Check if it's a text file we should index; extensionless
+                    // scripts are recognized by their shebang or modeline
+                    if !indexable_only
+                        || self.should_index_file(&path)
+                        || (path.extension().is_none() && has_language_header(&path).await)
+                    {
                         files.push(path);
                     }
                 }
@@ -441,17 +1074,90 @@ impl ContextService {
         state.file_count = 0;
         state.last_indexed = None;
         state.last_error = None;
+        state.progress = IndexProgress::default();
+        self.invalidate_search_cache();
 
         info!("Index cleared");
     }
 
-    /// Enhance a prompt with codebase context using AI.
-    pub async fn enhance_prompt(&self, prompt: &str) -> Result<String> {
+    /// Remove the indexed files under the workspace-relative `path`, leaving
+    /// the rest of the index intact. Returns how many files were removed.
+    ///
+    /// The path is checked like [`Self::resolve_path`]; it may name a single
+    /// file or a directory, and need not exist any more.
+    pub async fn clear_path(&self, path: &str) -> Result<usize> {
+        self.resolve_path(path.trim_end_matches('/'))?;
+        // The check above guarantees `..` never climbs above the workspace
+        let mut prefix = PathBuf::new();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(part) => prefix.push(part),
+                Component::ParentDir => {
+                    prefix.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let context = self.context.read().await;
+        let Some(ctx) = context.as_ref() else {
+            return Ok(0);
+        };
+        // Compare by path components so `src` does not match `src2/a.rs`
+        let doomed: Vec<String> = ctx
+            .indexed_paths()
+            .await
+            .into_iter()
+            .filter(|indexed| Path::new(indexed).starts_with(&prefix))
+            .collect();
+        let removed = ctx.remove_from_index(doomed).await?;
+        let remaining = ctx.file_count().await;
+        drop(context);
+
+        self.state.write().await.file_count = remaining;
+        self.invalidate_search_cache();
+        info!("Cleared {} indexed files under {}", removed, path);
+        Ok(removed)
+    }
+
+    /// Send `prompt` to the LLM with the indexed codebase as context.
+    pub async fn chat(&self, prompt: &str) -> Result<String> {
         self.initialize().await?;
 
         let context = self.context.read().await;
         let ctx = context.as_ref().ok_or(Error::IndexNotInitialized)?;
+        ctx.chat(prompt).await
+    }
 
+    /// `prompt` followed by the workspace code most relevant to it.
+    ///
+    /// The prompt itself is the search query. Up to `max_snippets` of the
+    /// retrieved snippets are packed under `token_budget`, which covers the
+    /// snippets only, and appended between `--- BEGIN CODEBASE CONTEXT ---`
+    /// and `--- END CODEBASE CONTEXT ---` markers, each under its file path.
+    /// With nothing retrieved, the prompt is returned as is.
+    pub async fn augment_prompt(
+        &self,
+        prompt: &str,
+        token_budget: usize,
+        max_snippets: usize,
+    ) -> Result<String> {
+        let mut bundle = self
+            .bundle_context(prompt, token_budget, usize::MAX)
+            .await?;
+        bundle.snippets.truncate(max_snippets);
+        if bundle.snippets.is_empty() {
+            return Ok(prompt.to_string());
+        }
+        Ok(format!(
+            "{}\n\n--- BEGIN CODEBASE CONTEXT ---\n{}\n--- END CODEBASE CONTEXT ---\n",
+            prompt.trim_end(),
+            bundle.render()
+        ))
+    }
+
+    /// Enhance a prompt with codebase context using AI.
+    pub async fn enhance_prompt(&self, prompt: &str) -> Result<String> {
         // Use the chat stream to enhance the prompt
         let enhancement_prompt = format!(
             r#"You are a prompt enhancement assistant. Given the following simple prompt,
@@ -467,6 +1173,552 @@ Enhanced prompt:"#,
             prompt
         );
 
-        ctx.chat(&enhancement_prompt).await
+        self.chat(&enhancement_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::mock_backend::MockBackend;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Counts one token per line.
+    #[derive(Debug)]
+    struct LineTokenizer;
+
+    impl TokenEstimator for LineTokenizer {
+        fn estimate(&self, text: &str) -> usize {
+            text.lines().count()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bundle_context_uses_plugged_estimator() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}\n\nPath: c.rs\nfn c() {}",
+        );
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap()
+            .with_token_estimator(Arc::new(LineTokenizer));
+        service.index_workspace().await.unwrap();
+
+        // Each snippet renders as two lines, so a budget of 4 fits two of them
+        let bundle = service.bundle_context("query", 4, 10).await.unwrap();
+        assert_eq!(bundle.estimated_tokens, 4);
+        assert_eq!(bundle.included_files, vec!["a.rs", "b.rs"]);
+        assert_eq!(bundle.excluded_files, vec!["c.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_augment_prompt_appends_snippets_within_budget() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}\n\nPath: c.rs\nfn c() {}",
+        );
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap()
+            .with_token_estimator(Arc::new(LineTokenizer));
+        service.index_workspace().await.unwrap();
+
+        let augmented = service.augment_prompt("Fix a\n", 4, 5).await.unwrap();
+        assert_eq!(
+            augmented,
+            "Fix a\n\n--- BEGIN CODEBASE CONTEXT ---\n\
+             Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}\n\
+             --- END CODEBASE CONTEXT ---\n"
+        );
+        let context = augmented
+            .split_once("--- BEGIN CODEBASE CONTEXT ---\n")
+            .and_then(|(_, rest)| rest.split_once("--- END"))
+            .unwrap()
+            .0;
+        // The budget covers the snippets, not the blank lines between them
+        assert!(LineTokenizer.estimate(&context.replace("\n\n", "\n")) <= 4);
+
+        let one = service.augment_prompt("Fix a", 100, 1).await.unwrap();
+        assert!(one.contains("Path: a.rs\nfn a() {}"));
+        assert!(!one.contains("b.rs"));
+        assert_eq!(
+            service.augment_prompt("Fix a", 1, 5).await.unwrap(),
+            "Fix a"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_progress_advances() {
+        let backend = MockBackend::start().await;
+        backend.set_delay("batch-upload", Duration::from_millis(50));
+
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..250 {
+            std::fs::write(
+                temp_dir.path().join(format!("file_{}.rs", i)),
+                format!("fn f{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+
+        // One batch at a time so progress advances in observable steps
+        let config = Config {
+            index_concurrency: 1,
+            ..backend.config(temp_dir.path())
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let indexer = {
+            let service = service.clone();
+            tokio::spawn(async move { service.index_workspace().await })
+        };
+
+        let mut observed = Vec::new();
+        while !indexer.is_finished() {
+            let status = service.status().await;
+            if status.indexing {
+                observed.push(status.files_done);
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        indexer.await.unwrap().unwrap();
+
+        assert!(
+            observed.iter().any(|&done| done > 0 && done < 250),
+            "expected intermediate progress, saw {:?}",
+            observed
+        );
+        assert!(observed.windows(2).all(|w| w[0] <= w[1]));
+
+        let status = service.status().await;
+        assert!(!status.indexing);
+        assert_eq!(status.files_total, 250);
+        assert_eq!(status.files_done, 250);
+        assert!(status.bytes_indexed > 0);
+        assert!(status.started_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_index_sends_one_upload_per_batch() {
+        let backend = MockBackend::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..25 {
+            std::fs::write(
+                temp_dir.path().join(format!("file_{}.rs", i)),
+                format!("fn f{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+
+        let config = Config {
+            index_batch_size: 10,
+            index_concurrency: 2,
+            ..backend.config(temp_dir.path())
+        };
+        let service = ContextService::new(&config).await.unwrap();
+        let result = service.index_workspace().await.unwrap();
+        assert_eq!(result.indexed, 25);
+        assert!(result.errors.is_empty());
+
+        let uploads = backend.requests("batch-upload");
+        assert_eq!(uploads.len(), 3); // ceil(25 / 10)
+
+        let mut uploaded: Vec<String> = uploads
+            .iter()
+            .flat_map(|body| body["blobs"].as_array().unwrap().clone())
+            .map(|blob| blob["path"].as_str().unwrap().to_string())
+            .collect();
+        uploaded.sort();
+        uploaded.dedup();
+        assert_eq!(uploaded.len(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_file_uploads_only_target() {
+        let backend = MockBackend::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(temp_dir.path().join("b.rs"), "fn b() {}").unwrap();
+
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap();
+        service.index_workspace().await.unwrap();
+        let uploads_before = backend.requests("batch-upload").len();
+
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() { changed() }").unwrap();
+        let result = service.reindex_file("a.rs").await.unwrap();
+        assert_eq!(result.indexed, 1);
+
+        let uploads = backend.requests("batch-upload");
+        assert_eq!(uploads.len(), uploads_before + 1);
+        let blobs = uploads.last().unwrap()["blobs"].as_array().unwrap().clone();
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0]["path"], "a.rs");
+        assert_eq!(blobs[0]["content"], "fn a() { changed() }");
+
+        // Unchanged content is not uploaded again
+        let result = service.reindex_file("b.rs").await.unwrap();
+        assert_eq!(result.skipped, 1);
+        assert_eq!(backend.requests("batch-upload").len(), uploads_before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_index_directory_merges_only_that_subtree() {
+        let backend = MockBackend::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        for (path, contents) in [
+            ("src/lib.rs", "pub mod a;"),
+            ("src/a/mod.rs", "fn a() {}"),
+            ("docs/guide.md", "# Guide"),
+            ("build.rs", "fn main() {}"),
+        ] {
+            let path = temp_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap();
+        let result = service.index_directory("src").await.unwrap();
+        assert_eq!(result.indexed, 2);
+
+        let context = service.context.read().await;
+        let ctx = context.as_ref().unwrap();
+        assert!(ctx.is_indexed("src/lib.rs").await);
+        assert!(ctx.is_indexed("src/a/mod.rs").await);
+        assert!(!ctx.is_indexed("docs/guide.md").await);
+        assert!(!ctx.is_indexed("build.rs").await);
+        drop(context);
+
+        // A second scope is merged into the index rather than replacing it
+        service.index_directory("docs/").await.unwrap();
+        assert_eq!(service.status().await.file_count, 3);
+
+        for escaping in ["../outside", "/etc"] {
+            let err = service.index_directory(escaping).await.unwrap_err();
+            assert_eq!(err.code(), "invalid_path", "{}", escaping);
+        }
+        let err = service.index_directory("build.rs").await.unwrap_err();
+        assert_eq!(err.code(), "invalid_path");
+    }
+
+    #[tokio::test]
+    async fn test_clear_path_leaves_other_entries() {
+        let backend = MockBackend::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        for path in ["src/a.rs", "src/b/c.rs", "src2/d.rs", "main.rs"] {
+            let path = temp_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "fn f() {}").unwrap();
+        }
+
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap();
+        service.index_workspace().await.unwrap();
+        assert_eq!(service.clear_path("src/").await.unwrap(), 2);
+        assert_eq!(service.status().await.file_count, 2);
+
+        let context = service.context.read().await;
+        let ctx = context.as_ref().unwrap();
+        assert!(!ctx.is_indexed("src/a.rs").await);
+        assert!(!ctx.is_indexed("src/b/c.rs").await);
+        assert!(ctx.is_indexed("src2/d.rs").await);
+        assert!(ctx.is_indexed("main.rs").await);
+        drop(context);
+
+        assert_eq!(service.clear_path("./src2/../main.rs").await.unwrap(), 1);
+        let err = service.clear_path("../elsewhere").await.unwrap_err();
+        assert_eq!(err.code(), "invalid_path");
+    }
+
+    #[tokio::test]
+    async fn test_search_cache_skips_backend_until_ttl_expires() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval("Path: a.rs\nfn a() {}");
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+
+        let service = ContextService::new(&backend.config(temp_dir.path()))
+            .await
+            .unwrap()
+            .with_search_cache(SearchCache::new(8, Duration::from_millis(200)));
+        service.index_workspace().await.unwrap();
+        let retrievals = || backend.requests("agents/codebase-retrieval").len();
+
+        let first = service.search("where is a", Some(100)).await.unwrap();
+        assert_eq!(retrievals(), 1);
+        let second = service.search("where is a", Some(100)).await.unwrap();
+        assert_eq!(second, first);
+        assert_eq!(retrievals(), 1);
+
+        // A different budget is a different query
+        service.search("where is a", Some(200)).await.unwrap();
+        assert_eq!(retrievals(), 2);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        service.search("where is a", Some(100)).await.unwrap();
+        assert_eq!(retrievals(), 3);
+
+        // Reindexing a changed file invalidates the cache
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() { changed() }").unwrap();
+        service.reindex_file("a.rs").await.unwrap();
+        service.search("where is a", Some(100)).await.unwrap();
+        assert_eq!(retrievals(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_file_rejects_escape_and_large_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("big.txt"), "x".repeat(64)).unwrap();
+        let config = Config {
+            workspace: temp_dir.path().to_path_buf(),
+            max_file_size: 16,
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        assert!(matches!(
+            service.reindex_file("../outside.rs").await,
+            Err(Error::InvalidPath(_))
+        ));
+        assert!(matches!(
+            service.reindex_file("/etc/passwd").await,
+            Err(Error::InvalidPath(_))
+        ));
+        assert!(matches!(
+            service.reindex_file("big.txt").await,
+            Err(Error::FileTooLarge { size: 64, .. })
+        ));
+        assert!(matches!(
+            service.reindex_file("missing.rs").await,
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_file_distinguishes_missing_and_escaping_paths() {
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("linked")).unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("secret.txt"),
+            temp_dir.path().join("src/secret.txt"),
+        )
+        .unwrap();
+        let config = Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        let missing = service.read_file("src/missing.rs").await.unwrap_err();
+        assert_eq!(missing.code(), "file_not_found");
+        assert_eq!(missing.to_string(), "File not found: src/missing.rs");
+        assert!(matches!(
+            service.read_file("nested/dir/missing.rs").await,
+            Err(Error::FileNotFound(_))
+        ));
+
+        // Symlinks out of the workspace are escapes, whether or not the
+        // target file exists
+        for path in ["src/secret.txt", "linked/secret.txt", "linked/missing.rs"] {
+            let error = service.read_file(path).await.unwrap_err();
+            assert_eq!(error.code(), "invalid_path", "{}", path);
+        }
+        assert!(matches!(
+            service.read_file("../secret.txt").await,
+            Err(Error::InvalidPath(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exclude_globs_skip_matching_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("vendor/lib")).unwrap();
+        std::fs::create_dir_all(root.join("docs/generated")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("vendor/lib/dep.js"), "x").unwrap();
+        std::fs::write(root.join("docs/generated/api.md"), "x").unwrap();
+        std::fs::write(root.join("docs/guide.md"), "x").unwrap();
+        std::fs::write(root.join("src/app.min.js"), "x").unwrap();
+        std::fs::write(root.join("src/app.js"), "x").unwrap();
+
+        let config = Config {
+            workspace: root.to_path_buf(),
+            exclude_globs: vec![
+                "vendor/".to_string(),
+                "*.min.js".to_string(),
+                "docs/generated/".to_string(),
+            ],
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        let mut files: Vec<String> = service
+            .discover_files(root)
+            .await
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+
+        assert_eq!(files, vec!["docs/guide.md", "src/app.js"]);
+    }
+
+    #[tokio::test]
+    async fn test_complete_path_skips_ignored_and_marks_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/service")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join(".gitignore"), "secrets.txt\n").unwrap();
+        std::fs::write(root.join("secrets.txt"), "x").unwrap();
+        std::fs::write(root.join("src/main.rs"), "x").unwrap();
+        std::fs::write(root.join("src/service/mod.rs"), "x").unwrap();
+        std::fs::write(root.join("setup.py"), "x").unwrap();
+
+        let config = Config {
+            workspace: root.to_path_buf(),
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        assert_eq!(
+            service.complete_path("s").await.unwrap(),
+            vec!["setup.py", "src/"]
+        );
+        assert_eq!(
+            service.complete_path("").await.unwrap(),
+            vec!["setup.py", "src/"]
+        );
+        assert_eq!(
+            service.complete_path("src/").await.unwrap(),
+            vec!["src/main.rs", "src/service/"]
+        );
+        assert_eq!(
+            service.complete_path(".git").await.unwrap(),
+            vec![".gitignore"]
+        );
+        assert!(service.complete_path("../").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_negated_glob_reincludes_gitignored_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("dist")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.gen.rs\n").unwrap();
+        std::fs::write(root.join("src/api.gen.rs"), "x").unwrap();
+        std::fs::write(root.join("src/schema.gen.rs"), "x").unwrap();
+        std::fs::write(root.join("dist/bundle.js"), "x").unwrap();
+
+        let config = Config {
+            workspace: root.to_path_buf(),
+            exclude_globs: vec!["!src/schema.gen.rs".to_string(), "!dist/".to_string()],
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        let mut files: Vec<String> = service
+            .discover_files(root)
+            .await
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec![".gitignore", "dist/bundle.js", "src/schema.gen.rs"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_loops_terminate_without_escaping() {
+        use std::os::unix::fs::symlink;
+
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.rs"), "fn secret() {}").unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "fn a() {}").unwrap();
+        symlink(root, root.join("src/loop")).unwrap();
+        symlink(root.join("src"), root.join("alias")).unwrap();
+        symlink(outside.path(), root.join("out")).unwrap();
+        symlink(outside.path().join("secret.rs"), root.join("src/secret.rs")).unwrap();
+
+        let discover = |follow_symlinks: bool| async move {
+            let config = Config {
+                workspace: root.to_path_buf(),
+                follow_symlinks,
+                ..Config::default()
+            };
+            let service = ContextService::new(&config).await.unwrap();
+            let mut files: Vec<String> = service
+                .discover_files(root)
+                .await
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+                .collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(discover(false).await, vec!["src/a.rs"]);
+
+        // Each directory is walked once, whichever path reaches it first
+        let followed = discover(true).await;
+        assert_eq!(followed.len(), 1, "{:?}", followed);
+        assert!(followed[0] == "src/a.rs" || followed[0] == "alias/a.rs");
+    }
+
+    #[tokio::test]
+    async fn test_extensionless_scripts_detected_by_header() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::write(
+            root.join("bin/deploy"),
+            "#!/usr/bin/env python3\nprint(1)\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("bin/setup"), "# vim: set ft=ruby :\nputs 1\n").unwrap();
+        std::fs::write(root.join("bin/notes"), "just some text\n").unwrap();
+
+        let config = Config {
+            workspace: root.to_path_buf(),
+            ..Config::default()
+        };
+        let service = ContextService::new(&config).await.unwrap();
+
+        let mut files: Vec<String> = service
+            .discover_files(root)
+            .await
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+
+        assert_eq!(files, vec!["bin/deploy", "bin/setup"]);
     }
 }
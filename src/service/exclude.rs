@@ -0,0 +1,198 @@
+//! Path exclusion rules shared by everything that walks the workspace.
+//!
+//! Patterns use gitignore-style semantics compiled into a single
+//! [`globset::GlobSet`]:
+//!
+//! - a pattern without a `/` matches at any depth (`*.min.js`, `vendor`)
+//! - a leading `/` or a `/` in the middle anchors it to the workspace root
+//! - a matching directory excludes everything beneath it
+//! - `*` does not cross directory separators; `**` does
+//! - blank lines and `#` comments are ignored
+//! - a leading `!` re-includes paths excluded by an earlier pattern
+//!
+//! Patterns are applied in order and the last one that matches decides, so
+//! later sources (config) can override earlier ones (defaults, `.gitignore`).
+//! As with gitignore, a file inside an excluded directory can't be
+//! re-included without also re-including the directory, since the walk never
+//! descends into it.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+use tracing::warn;
+
+/// Patterns excluded unless the user configures otherwise.
+pub const DEFAULT_EXCLUDE_GLOBS: &[&str] = &[
+    "node_modules",
+    ".git",
+    ".svn",
+    ".hg",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    ".pytest_cache",
+    ".mypy_cache",
+    "*.pyc",
+    "*.pyo",
+    "*.so",
+    "*.dylib",
+    "*.dll",
+    "*.exe",
+    "*.o",
+    "*.a",
+    "*.lib",
+    ".DS_Store",
+    "Thumbs.db",
+    "*.log",
+    "*.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+];
+
+/// Compiled set of exclusion patterns.
+#[derive(Debug, Clone)]
+pub struct ExcludeMatcher {
+    set: GlobSet,
+    /// Whether each glob in `set` came from a `!` pattern, by glob index
+    negated: Vec<bool>,
+}
+
+impl ExcludeMatcher {
+    /// Build a matcher from gitignore-style patterns.
+    ///
+    /// Invalid patterns are logged and skipped.
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builder = GlobSetBuilder::new();
+        let mut negated = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+            let (negate, pattern) = match pattern.strip_prefix('!') {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, pattern),
+            };
+            if pattern.is_empty() {
+                continue;
+            }
+            for glob in to_globs(pattern) {
+                match GlobBuilder::new(&glob).literal_separator(true).build() {
+                    Ok(glob) => {
+                        builder.add(glob);
+                        negated.push(negate);
+                    }
+                    Err(e) => warn!("Ignoring invalid exclude pattern '{}': {}", pattern, e),
+                }
+            }
+        }
+
+        let (set, negated) = match builder.build() {
+            Ok(set) => (set, negated),
+            Err(e) => {
+                warn!("Failed to build exclude patterns: {}", e);
+                (GlobSet::empty(), Vec::new())
+            }
+        };
+        Self { set, negated }
+    }
+
+    /// Defaults plus `extra` patterns (e.g. from config or `.gitignore`).
+    pub fn with_defaults<I, S>(extra: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let extra: Vec<String> = extra.into_iter().map(|s| s.as_ref().to_string()).collect();
+        Self::new(
+            DEFAULT_EXCLUDE_GLOBS
+                .iter()
+                .copied()
+                .chain(extra.iter().map(String::as_str)),
+        )
+    }
+
+    /// Whether a workspace-relative path is excluded.
+    ///
+    /// The last matching pattern wins; a `!` pattern means included.
+    pub fn is_excluded(&self, relative: &Path) -> bool {
+        self.set
+            .matches(relative)
+            .into_iter()
+            .max()
+            .is_some_and(|i| !self.negated[i])
+    }
+}
+
+/// Translate one gitignore-style pattern into equivalent globs.
+fn to_globs(pattern: &str) -> Vec<String> {
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.starts_with('/') || pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+
+    let base = if anchored || pattern.starts_with("**/") {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+
+    vec![base.clone(), format!("{}/**", base)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unanchored_pattern_matches_at_any_depth() {
+        let matcher = ExcludeMatcher::new(["*.min.js", "vendor"]);
+        assert!(matcher.is_excluded(Path::new("app.min.js")));
+        assert!(matcher.is_excluded(Path::new("web/static/app.min.js")));
+        assert!(matcher.is_excluded(Path::new("vendor/lib/a.go")));
+        assert!(matcher.is_excluded(Path::new("pkg/vendor/a.go")));
+        assert!(!matcher.is_excluded(Path::new("src/vendored.rs")));
+    }
+
+    #[test]
+    fn test_anchored_pattern_matches_from_root() {
+        let matcher = ExcludeMatcher::new(["docs/generated/", "/build"]);
+        assert!(matcher.is_excluded(Path::new("docs/generated/api.md")));
+        assert!(!matcher.is_excluded(Path::new("other/docs/generated/api.md")));
+        assert!(matcher.is_excluded(Path::new("build/out.txt")));
+        assert!(!matcher.is_excluded(Path::new("src/build/mod.rs")));
+    }
+
+    #[test]
+    fn test_defaults_no_longer_match_substrings() {
+        let matcher = ExcludeMatcher::with_defaults(Vec::<String>::new());
+        assert!(matcher.is_excluded(Path::new("target/debug/app")));
+        assert!(matcher.is_excluded(Path::new("web/node_modules/x/index.js")));
+        assert!(!matcher.is_excluded(Path::new("src/targeting.rs")));
+    }
+
+    #[test]
+    fn test_negation_reincludes_and_last_match_wins() {
+        let matcher = ExcludeMatcher::with_defaults(["*.gen.rs", "!keep.gen.rs", "!dist/"]);
+        assert!(matcher.is_excluded(Path::new("src/api.gen.rs")));
+        assert!(!matcher.is_excluded(Path::new("src/keep.gen.rs")));
+        assert!(!matcher.is_excluded(Path::new("dist")));
+        assert!(!matcher.is_excluded(Path::new("dist/app.js")));
+
+        // A later exclude overrides an earlier negation
+        let matcher = ExcludeMatcher::new(["*.log", "!debug.log", "logs/"]);
+        assert!(!matcher.is_excluded(Path::new("debug.log")));
+        assert!(matcher.is_excluded(Path::new("logs/debug.log")));
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines_are_ignored() {
+        let matcher = ExcludeMatcher::new(["# comment", "", "  "]);
+        assert!(!matcher.is_excluded(Path::new("src/main.rs")));
+    }
+}
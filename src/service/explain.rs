@@ -0,0 +1,131 @@
+//! Explanations of why a semantic search hit matched.
+//!
+//! The backend returns ranked snippets without spans or scores, so hits are
+//! explained locally. The score is the backend's rank scaled to 0-1, as in
+//! [`parse_snippets`](crate::service::bundle::parse_snippets). The snippet is
+//! located in the file on disk to give its line range, and its best line is
+//! the one containing the most distinct query terms.
+
+use std::collections::BTreeSet;
+
+use crate::service::bundle::Snippet;
+use crate::types::search::{MatchType, SearchResult};
+
+/// Words too common to explain a match.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "do", "does", "for", "from", "how", "in",
+    "is", "it", "of", "on", "or", "the", "to", "what", "where", "which", "who", "why", "with",
+];
+
+/// The distinct lowercase terms of `query`, in order.
+///
+/// Terms are split on anything but letters and digits, so `parse_config`
+/// yields `parse` and `config`. Single characters and stopwords are dropped.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|term| term.chars().count() > 1 && !STOPWORDS.contains(&term.as_str()))
+        .filter(|term| seen.insert(term.clone()))
+        .collect()
+}
+
+/// Index of the first line of `file` where `snippet` starts, comparing lines
+/// without surrounding whitespace.
+fn locate(snippet: &[&str], file: &str) -> Option<usize> {
+    let file: Vec<&str> = file.lines().map(str::trim).collect();
+    if snippet.is_empty() || snippet.len() > file.len() {
+        return None;
+    }
+    (0..=file.len() - snippet.len()).find(|&start| {
+        snippet
+            .iter()
+            .zip(&file[start..])
+            .all(|(a, b)| a.trim() == *b)
+    })
+}
+
+/// `snippet` explained against `terms`, with line numbers if `file` holds
+/// its text.
+pub fn explain(terms: &[String], snippet: &Snippet, file: Option<&str>) -> SearchResult {
+    let lines: Vec<&str> = snippet.content.lines().collect();
+    let start = file.and_then(|file| locate(&lines, file));
+
+    let terms_in = |text: &str| {
+        let text = text.to_lowercase();
+        terms
+            .iter()
+            .filter(|term| text.contains(term.as_str()))
+            .count()
+    };
+    let best = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| (index, terms_in(line)))
+        .filter(|&(_, hits)| hits > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+    let content = snippet.content.to_lowercase();
+    let matched_terms: Vec<String> = terms
+        .iter()
+        .filter(|term| content.contains(term.as_str()))
+        .cloned()
+        .collect();
+
+    SearchResult {
+        path: snippet.path.clone(),
+        content: snippet.content.clone(),
+        score: Some(snippet.relevance),
+        lines: start.map(|start| format!("{}-{}", start + 1, start + lines.len().max(1))),
+        relevance_score: (!terms.is_empty())
+            .then(|| matched_terms.len() as f64 / terms.len() as f64),
+        match_type: Some(MatchType::Semantic),
+        retrieved_at: None,
+        chunk_id: None,
+        best_line: best.and_then(|(index, _)| start.map(|start| (start + index + 1) as u32)),
+        best_line_text: best.map(|(index, _)| lines[index].trim().to_string()),
+        matched_terms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_terms_split_identifiers_and_drop_stopwords() {
+        assert_eq!(
+            query_terms("Where is parse_config called? parse it"),
+            vec!["parse", "config", "called"]
+        );
+    }
+
+    #[test]
+    fn test_explain_finds_range_and_best_line() {
+        let file = "use std::fs;\n\nfn load() {}\n\npub fn parse_config(path: &str) {\n    let text = fs::read(path);\n}\n";
+        let snippet = Snippet {
+            path: "src/config.rs".to_string(),
+            content: "pub fn parse_config(path: &str) {\n    let text = fs::read(path);\n}"
+                .to_string(),
+            relevance: 0.5,
+        };
+        let terms = query_terms("config parsing from a path");
+
+        let hit = explain(&terms, &snippet, Some(file));
+        assert_eq!(hit.lines.as_deref(), Some("5-7"));
+        assert_eq!(hit.best_line, Some(5));
+        assert_eq!(
+            hit.best_line_text.as_deref(),
+            Some("pub fn parse_config(path: &str) {")
+        );
+        assert_eq!(hit.matched_terms, vec!["config", "path"]);
+        assert_eq!(hit.score, Some(0.5));
+        assert_eq!(hit.relevance_score, Some(2.0 / 3.0));
+
+        // Without the file there are no line numbers, but still a best line
+        let unlocated = explain(&terms, &snippet, None);
+        assert_eq!(unlocated.lines, None);
+        assert_eq!(unlocated.best_line, None);
+        assert!(unlocated.best_line_text.is_some());
+    }
+}
@@ -0,0 +1,254 @@
+//! Exact (non-semantic) text search over workspace files.
+//!
+//! Files are scanned line by line without loading them fully into memory,
+//! so large files cost little beyond the read itself. No backend calls are
+//! made, which makes this usable offline.
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use crate::error::{Error, Result};
+
+/// Default number of matches returned before the scan stops.
+pub const DEFAULT_MAX_MATCHES: usize = 100;
+
+/// Options controlling a grep run.
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    /// Treat the pattern as a regular expression instead of a literal
+    pub regex: bool,
+    /// Match case exactly
+    pub case_sensitive: bool,
+    /// Lines of context to include before and after each match
+    pub context_lines: usize,
+    /// Stop after this many matches
+    pub max_matches: usize,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: true,
+            context_lines: 0,
+            max_matches: DEFAULT_MAX_MATCHES,
+        }
+    }
+}
+
+/// A single matching line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrepMatch {
+    /// Workspace-relative file path
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The matching line
+    pub text: String,
+    /// Lines preceding the match
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Lines following the match
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+/// Result of a grep run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GrepResult {
+    /// Matches in file order
+    pub matches: Vec<GrepMatch>,
+    /// Number of files scanned
+    pub files_scanned: usize,
+    /// Whether the scan stopped at `max_matches`
+    pub truncated: bool,
+    /// Matches handed to a stream instead of kept in `matches`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub streamed: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// A compiled grep pattern.
+#[derive(Debug, Clone)]
+pub struct Grep {
+    matcher: Regex,
+    options: GrepOptions,
+}
+
+impl Grep {
+    /// Compile `pattern` according to `options`.
+    pub fn new(pattern: &str, options: GrepOptions) -> Result<Self> {
+        if pattern.is_empty() {
+            return Err(Error::InvalidToolArguments(
+                "pattern must not be empty".to_string(),
+            ));
+        }
+        let source = if options.regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        let matcher = RegexBuilder::new(&source)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| Error::InvalidToolArguments(format!("Invalid regex: {}", e)))?;
+        Ok(Self { matcher, options })
+    }
+
+    /// Matches still allowed before `max_matches` is reached.
+    pub fn remaining(&self, result: &GrepResult) -> usize {
+        self.options
+            .max_matches
+            .saturating_sub(result.matches.len() + result.streamed)
+    }
+
+    /// Scan one file's contents, appending matches to `result`.
+    ///
+    /// Files containing NUL bytes are treated as binary and skipped.
+    pub fn scan<R: BufRead>(&self, path: &str, mut reader: R, result: &mut GrepResult) {
+        let context = self.options.context_lines;
+        let mut before: VecDeque<String> = VecDeque::with_capacity(context);
+        let mut open: Vec<GrepMatch> = Vec::new();
+        let mut found: Vec<GrepMatch> = Vec::new();
+        let mut remaining = self.remaining(result);
+        let mut buf = Vec::new();
+        let mut line_no = 0;
+
+        result.files_scanned += 1;
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if buf.contains(&0) {
+                return;
+            }
+            line_no += 1;
+            let text = String::from_utf8_lossy(&buf)
+                .trim_end_matches(['\n', '\r'])
+                .to_string();
+
+            // Feed trailing context to earlier matches
+            for m in open.iter_mut() {
+                m.after.push(text.clone());
+            }
+            let (done, pending): (Vec<_>, Vec<_>) =
+                open.drain(..).partition(|m| m.after.len() >= context);
+            found.extend(done);
+            open = pending;
+
+            if remaining > 0 && self.matcher.is_match(&text) {
+                remaining -= 1;
+                let m = GrepMatch {
+                    path: path.to_string(),
+                    line: line_no,
+                    text: text.clone(),
+                    before: before.iter().cloned().collect(),
+                    after: Vec::new(),
+                };
+                if context == 0 {
+                    found.push(m);
+                } else {
+                    open.push(m);
+                }
+            } else if remaining == 0 && open.is_empty() {
+                result.truncated = true;
+                break;
+            }
+
+            if context > 0 {
+                if before.len() == context {
+                    before.pop_front();
+                }
+                before.push_back(text);
+            }
+        }
+
+        found.extend(open);
+        found.sort_by_key(|m| m.line);
+        result.matches.extend(found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn main() {\n    let x = foo(1);\n    bar();\n    let y = foo(2);\n}\n";
+
+    fn run(pattern: &str, options: GrepOptions) -> GrepResult {
+        let grep = Grep::new(pattern, options).unwrap();
+        let mut result = GrepResult::default();
+        grep.scan("src/main.rs", SOURCE.as_bytes(), &mut result);
+        result
+    }
+
+    #[test]
+    fn test_literal_matches_with_context() {
+        let result = run(
+            "foo(",
+            GrepOptions {
+                context_lines: 1,
+                ..GrepOptions::default()
+            },
+        );
+        assert_eq!(result.matches.len(), 2);
+        assert_eq!(result.matches[0].line, 2);
+        assert_eq!(result.matches[0].before, vec!["fn main() {"]);
+        assert_eq!(result.matches[0].after, vec!["    bar();"]);
+        assert_eq!(result.matches[1].line, 4);
+        assert_eq!(result.matches[1].after, vec!["}"]);
+    }
+
+    #[test]
+    fn test_regex_and_case_insensitive() {
+        let result = run(
+            r"LET \w = FOO\(\d\)",
+            GrepOptions {
+                regex: true,
+                case_sensitive: false,
+                ..GrepOptions::default()
+            },
+        );
+        assert_eq!(
+            result.matches.iter().map(|m| m.line).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+    }
+
+    #[test]
+    fn test_match_cap_truncates() {
+        let result = run(
+            "let",
+            GrepOptions {
+                max_matches: 1,
+                ..GrepOptions::default()
+            },
+        );
+        assert_eq!(result.matches.len(), 1);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_invalid_regex_and_binary() {
+        assert!(Grep::new(
+            "(",
+            GrepOptions {
+                regex: true,
+                ..GrepOptions::default()
+            }
+        )
+        .is_err());
+
+        let grep = Grep::new("a", GrepOptions::default()).unwrap();
+        let mut result = GrepResult::default();
+        grep.scan("bin", &b"a\0b\n"[..], &mut result);
+        assert!(result.matches.is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! Language detection for workspace files.
+//!
+//! The extension is checked first, then well-known file names, and finally
+//! the first lines of the content: a `#!` shebang or a Vim/Emacs modeline.
+//! Names match common syntax-highlighting identifiers (`python`, `bash`, ...).
+
+use std::path::Path;
+
+/// Number of leading lines inspected for shebangs and modelines.
+pub const HEADER_LINES: usize = 5;
+
+/// Language for a file extension (case-insensitive).
+pub fn extension_to_language(ext: &str) -> Option<&'static str> {
+    let language = match ext.to_lowercase().as_str() {
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" => "python",
+        "rb" => "ruby",
+        "go" => "go",
+        "rs" => "rust",
+        "java" => "java",
+        "kt" => "kotlin",
+        "cs" => "csharp",
+        "cpp" | "cc" | "cxx" | "hpp" => "cpp",
+        "c" | "h" => "c",
+        "swift" => "swift",
+        "php" => "php",
+        "pl" | "pm" => "perl",
+        "lua" => "lua",
+        "sql" => "sql",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "toml" => "toml",
+        "xml" => "xml",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" => "powershell",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Language from a path's extension or well-known file name.
+pub fn filename_to_language(path: &Path) -> Option<&'static str> {
+    if let Some(language) = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(extension_to_language)
+    {
+        return Some(language);
+    }
+
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    let language = match name.as_str() {
+        "dockerfile" => "dockerfile",
+        "makefile" | "gnumakefile" => "makefile",
+        "rakefile" | "gemfile" | "brewfile" => "ruby",
+        ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" => "bash",
+        ".editorconfig" => "editorconfig",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Language from a shebang or Vim/Emacs modeline in the first lines.
+///
+/// Recognizes `#!/usr/bin/env python3`, `#!/bin/bash`, `# vim: set ft=ruby:`
+/// and `-*- mode: perl -*-` style headers.
+pub fn detect_language_from_content(first_lines: &str) -> Option<&'static str> {
+    let mut lines = first_lines.lines().take(HEADER_LINES);
+    let first = lines.next()?;
+    if let Some(language) = first.strip_prefix("#!").and_then(shebang_language) {
+        return Some(language);
+    }
+    std::iter::once(first)
+        .chain(lines)
+        .find_map(|line| vim_modeline(line).or_else(|| emacs_modeline(line)))
+}
+
+/// Language from the path, falling back to the content header.
+pub fn detect_language(path: &Path, content: &str) -> Option<&'static str> {
+    filename_to_language(path).or_else(|| detect_language_from_content(content))
+}
+
+/// Whether `language` is the one a user-supplied `hint` names.
+///
+/// The hint may be a language name (`rust`) or an extension with or without
+/// its `.` or `*.` prefix (`rs`, `.rs`, `*.rs`), in any case.
+pub fn language_matches_hint(language: &str, hint: &str) -> bool {
+    let hint = hint.trim();
+    let hint = hint
+        .strip_prefix("*.")
+        .or_else(|| hint.strip_prefix('.'))
+        .unwrap_or(hint);
+    hint.eq_ignore_ascii_case(language) || extension_to_language(hint) == Some(language)
+}
+
+/// Interpreter named by a shebang (the text after `#!`).
+fn shebang_language(shebang: &str) -> Option<&'static str> {
+    let mut words = shebang.split_whitespace();
+    let mut program = basename(words.next()?);
+    if program == "env" {
+        // Skip `env` flags (`-S`) and variable assignments
+        program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+    }
+    interpreter_language(program)
+}
+
+fn basename(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+/// Language for an interpreter name, ignoring version suffixes (`python3.11`).
+fn interpreter_language(program: &str) -> Option<&'static str> {
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = match name {
+        "python" | "pypy" => "python",
+        "sh" | "bash" | "dash" | "ksh" | "zsh" => "bash",
+        "node" | "nodejs" | "deno" | "bun" => "javascript",
+        "ts-node" | "tsx" => "typescript",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "php" => "php",
+        "lua" => "lua",
+        "fish" => "fish",
+        "Rscript" => "r",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// `vim: set ft=ruby:` / `vi: filetype=python` / `vim: syntax=sh`.
+fn vim_modeline(line: &str) -> Option<&'static str> {
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .find_map(|marker| line.find(marker).map(|i| i + marker.len()))?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syntax").then_some(value)
+        })
+        .and_then(modeline_language)
+}
+
+/// `-*- mode: ruby -*-` or the short form `-*- ruby -*-`.
+fn emacs_modeline(line: &str) -> Option<&'static str> {
+    let start = line.find("-*-")? + 3;
+    let inner = &line[start..line[start..].find("-*-")? + start];
+    let mode = if inner.contains(':') {
+        inner.split(';').find_map(|var| {
+            let (key, value) = var.split_once(':')?;
+            key.trim().eq_ignore_ascii_case("mode").then_some(value)
+        })?
+    } else {
+        inner
+    };
+    modeline_language(mode.trim())
+}
+
+/// Normalize a modeline language name.
+fn modeline_language(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_suffix("-mode").unwrap_or(&name);
+    interpreter_language(name).or_else(|| {
+        let language = match name {
+            "js" | "javascript" => "javascript",
+            "typescript" => "typescript",
+            "rust" => "rust",
+            "golang" => "go",
+            "markdown" => "markdown",
+            "shell-script" => "bash",
+            _ => return extension_to_language(name),
+        };
+        Some(language)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shebangs() {
+        assert_eq!(
+            detect_language_from_content("#!/usr/bin/env python3\nprint('hi')\n"),
+            Some("python")
+        );
+        assert_eq!(detect_language_from_content("#!/bin/bash\n"), Some("bash"));
+        assert_eq!(
+            detect_language_from_content("#!/usr/bin/env -S node --no-warnings\n"),
+            Some("javascript")
+        );
+        assert_eq!(
+            detect_language_from_content("#!/usr/bin/perl -w\n"),
+            Some("perl")
+        );
+        assert_eq!(
+            detect_language_from_content("#!/usr/bin/ruby\n"),
+            Some("ruby")
+        );
+        assert_eq!(detect_language_from_content("#!/opt/unknown\n"), None);
+    }
+
+    #[test]
+    fn test_modelines() {
+        assert_eq!(
+            detect_language_from_content("# vim: set ft=ruby :\nputs 1\n"),
+            Some("ruby")
+        );
+        assert_eq!(
+            detect_language_from_content("// vi: filetype=javascript\n"),
+            Some("javascript")
+        );
+        assert_eq!(
+            detect_language_from_content("# -*- mode: python; coding: utf-8 -*-\n"),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language_from_content("; -*- perl -*-\n"),
+            Some("perl")
+        );
+        assert_eq!(detect_language_from_content("plain text\n"), None);
+    }
+
+    #[test]
+    fn test_language_hints() {
+        assert!(language_matches_hint("rust", "Rust"));
+        assert!(language_matches_hint("rust", "rs"));
+        assert!(language_matches_hint("rust", "*.rs"));
+        assert!(language_matches_hint("typescript", ".ts"));
+        assert!(!language_matches_hint("tsx", "ts"));
+        assert!(!language_matches_hint("python", "rust"));
+    }
+
+    #[test]
+    fn test_path_lookup_comes_first() {
+        assert_eq!(
+            detect_language(Path::new("build.py"), "#!/bin/bash\n"),
+            Some("python")
+        );
+        assert_eq!(detect_language(Path::new("Gemfile"), ""), Some("ruby"));
+        assert_eq!(
+            detect_language(Path::new("bin/deploy"), "#!/bin/sh\n"),
+            Some("bash")
+        );
+    }
+}
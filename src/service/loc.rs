@@ -0,0 +1,286 @@
+//! Code, comment and blank line classification.
+//!
+//! Each language maps to its comment markers. A line is blank if it is only
+//! whitespace, a comment if it holds nothing but comments, and code
+//! otherwise, so a line with code followed by a trailing comment counts as
+//! code. Languages without known markers count every non-blank line as code.
+//!
+//! [`LineClassifier`] carries state across lines, so every line of a
+//! multi-line `/* ... */` or `<!-- ... -->` block, or of a Python docstring,
+//! counts as a comment. A triple quote only opens a docstring at the start of
+//! a line; after code it opens a multi-line string, whose lines count as code.
+//! String literals are otherwise not tracked, so a comment opener inside a
+//! string is taken at face value.
+
+/// Comment markers for a language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommentSyntax {
+    /// Markers starting a comment that runs to the end of the line
+    pub line: &'static [&'static str],
+    /// Block comment openers and their closers
+    pub block: &'static [(&'static str, &'static str)],
+    /// Delimiters that open and close a docstring
+    pub docstring: &'static [&'static str],
+}
+
+const C_STYLE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    docstring: &[],
+};
+
+const HASH: CommentSyntax = CommentSyntax {
+    line: &["#"],
+    block: &[],
+    docstring: &[],
+};
+
+const DASHES: CommentSyntax = CommentSyntax {
+    line: &["--"],
+    block: &[],
+    docstring: &[],
+};
+
+const MARKUP: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: &[("<!--", "-->")],
+    docstring: &[],
+};
+
+/// Comment markers for a language name from [`crate::service::language`].
+pub fn comment_syntax(language: &str) -> CommentSyntax {
+    match language {
+        "rust" | "c" | "cpp" | "csharp" | "java" | "kotlin" | "go" | "swift" | "javascript"
+        | "jsx" | "typescript" | "tsx" | "scss" => C_STYLE,
+        "php" => CommentSyntax {
+            line: &["//", "#"],
+            ..C_STYLE
+        },
+        "css" => CommentSyntax {
+            line: &[],
+            ..C_STYLE
+        },
+        "python" => CommentSyntax {
+            docstring: &["\"\"\"", "'''"],
+            ..HASH
+        },
+        "ruby" | "perl" | "bash" | "powershell" | "yaml" | "toml" | "makefile" | "dockerfile" => {
+            HASH
+        }
+        "editorconfig" => CommentSyntax {
+            line: &["#", ";"],
+            ..HASH
+        },
+        "sql" | "lua" => DASHES,
+        "html" | "xml" | "markdown" | "vue" | "svelte" => MARKUP,
+        _ => CommentSyntax::default(),
+    }
+}
+
+/// What a line holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Code,
+    Comment,
+    Blank,
+}
+
+/// A marker found on a line.
+#[derive(Debug, Clone, Copy)]
+enum Marker {
+    Line,
+    Block(&'static str, &'static str),
+    Docstring(&'static str),
+}
+
+/// A block, docstring or string still waiting for its closer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Open {
+    close: &'static str,
+    comment: bool,
+}
+
+/// Classifies lines in order, tracking comments that span lines.
+#[derive(Debug, Clone)]
+pub struct LineClassifier {
+    syntax: CommentSyntax,
+    open: Option<Open>,
+}
+
+impl LineClassifier {
+    /// Create a classifier for `language`.
+    pub fn new(language: &str) -> Self {
+        Self::with_syntax(comment_syntax(language))
+    }
+
+    /// Create a classifier using `syntax`.
+    pub fn with_syntax(syntax: CommentSyntax) -> Self {
+        Self { syntax, open: None }
+    }
+
+    /// Whether the last line left a block comment or docstring open.
+    pub fn in_comment(&self) -> bool {
+        self.open.is_some_and(|open| open.comment)
+    }
+
+    /// Classify the next line.
+    pub fn classify(&mut self, line: &str) -> LineKind {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            return LineKind::Blank;
+        }
+
+        let mut code = false;
+        loop {
+            if let Some(open) = self.open {
+                code |= !open.comment;
+                match rest.find(open.close) {
+                    Some(end) => {
+                        rest = &rest[end + open.close.len()..];
+                        self.open = None;
+                    }
+                    None => break,
+                }
+                continue;
+            }
+
+            rest = rest.trim_start();
+            let Some((pos, marker)) = self.next_marker(rest) else {
+                code |= !rest.is_empty();
+                break;
+            };
+            code |= pos > 0;
+            match marker {
+                Marker::Line => break,
+                Marker::Block(open, close) => {
+                    self.open = Some(Open {
+                        close,
+                        comment: true,
+                    });
+                    rest = &rest[pos + open.len()..];
+                }
+                Marker::Docstring(quote) => {
+                    // After code this is a string literal, not a docstring
+                    self.open = Some(Open {
+                        close: quote,
+                        comment: !code,
+                    });
+                    rest = &rest[pos + quote.len()..];
+                }
+            }
+        }
+
+        if code {
+            LineKind::Code
+        } else {
+            LineKind::Comment
+        }
+    }
+
+    /// The earliest comment marker in `text` and its byte offset.
+    fn next_marker(&self, text: &str) -> Option<(usize, Marker)> {
+        let line = self.syntax.line.iter().map(|m| (*m, Marker::Line));
+        let block = self
+            .syntax
+            .block
+            .iter()
+            .map(|&(o, c)| (o, Marker::Block(o, c)));
+        let docstring = self
+            .syntax
+            .docstring
+            .iter()
+            .map(|&q| (q, Marker::Docstring(q)));
+        line.chain(block)
+            .chain(docstring)
+            .filter_map(|(pattern, marker)| text.find(pattern).map(|pos| (pos, marker)))
+            .min_by_key(|(pos, _)| *pos)
+    }
+}
+
+/// Line totals by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+/// Count code, comment and blank lines in `text` written in `language`.
+pub fn count_lines(text: &str, language: &str) -> LineCounts {
+    let mut classifier = LineClassifier::new(language);
+    let mut counts = LineCounts::default();
+    for line in text.lines() {
+        match classifier.classify(line) {
+            LineKind::Code => counts.code += 1,
+            LineKind::Comment => counts.comment += 1,
+            LineKind::Blank => counts.blank += 1,
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_line_and_block_comments() {
+        let text = "//! Crate docs.\n\
+                    \n\
+                    /* Licensed under MIT */\n\
+                    use std::io;\n\
+                    \n\
+                    /// Entry point.\n\
+                    fn main() {\n    \
+                        // say hi\n    \
+                        println!(\"hi\"); // trailing\n\
+                    }\n";
+        let counts = count_lines(text, "rust");
+        assert_eq!(
+            counts,
+            LineCounts {
+                code: 4,
+                comment: 4,
+                blank: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_multi_line_block_comment() {
+        let text = "/*\n * Licensed under MIT.\n\n */\nint x; /* starts here\n   and ends here */\nint y;\n";
+        let mut classifier = LineClassifier::new("c");
+        let kinds: Vec<_> = text.lines().map(|l| classifier.classify(l)).collect();
+        use LineKind::*;
+        assert_eq!(
+            kinds,
+            vec![Comment, Comment, Blank, Comment, Code, Comment, Code]
+        );
+        assert!(!classifier.in_comment());
+
+        let html = "<p>hi</p>\n<!--\n  <p>old</p>\n-->\n";
+        let counts = count_lines(html, "html");
+        assert_eq!((counts.code, counts.comment), (1, 3));
+    }
+
+    #[test]
+    fn test_python_docstrings() {
+        let text = "def f():\n    \"\"\"Do the thing.\n\n    Really.\n    \"\"\"\n    x = '''not\n    a docstring'''\n    '''One line.'''\n    return x  # done\n";
+        let mut classifier = LineClassifier::new("python");
+        let kinds: Vec<_> = text.lines().map(|l| classifier.classify(l)).collect();
+        use LineKind::*;
+        assert_eq!(
+            kinds,
+            vec![Code, Comment, Blank, Comment, Comment, Code, Code, Comment, Code]
+        );
+    }
+
+    #[test]
+    fn test_markers_depend_on_language() {
+        let text = "# heading\n-- note\n// slash\n";
+        assert_eq!(count_lines(text, "python").comment, 1);
+        assert_eq!(count_lines(text, "sql").comment, 1);
+        assert_eq!(count_lines(text, "go").comment, 1);
+        assert_eq!(count_lines(text, "json").code, 3);
+    }
+}
@@ -1,17 +1,18 @@
 //! Memory service for persistent agent memory.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
 use tracing::info;
 
-use crate::error::Result;
+use crate::clock::{system_clock, SharedClock};
+use crate::error::{Error, Result};
+use crate::service::persist::{load_json, write_atomic};
 
 /// A memory entry.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MemoryEntry {
     /// Entry key
     pub key: String,
@@ -35,52 +36,182 @@ struct MemoryStore {
     entries: HashMap<String, MemoryEntry>,
 }
 
+/// Namespace used when none is given.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Longest accepted namespace name.
+const MAX_NAMESPACE_LEN: usize = 64;
+
+/// Check that `name` is a usable namespace.
+///
+/// Names become directory names, so only ASCII letters, digits, `-`, `_`
+/// and `.` are allowed, and `.`/`..` are rejected.
+pub fn validate_namespace(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAMESPACE_LEN
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidNamespace(format!(
+            "'{}': use up to {} letters, digits, '-', '_' or '.'",
+            name, MAX_NAMESPACE_LEN
+        )))
+    }
+}
+
+/// Identifies a memory export document.
+pub const EXPORT_FORMAT: &str = "context-engine-memory";
+
+/// Version of the export document written by [`MemoryService::export`].
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Memories serialized as one document, for moving agent state between
+/// machines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryExport {
+    /// Always [`EXPORT_FORMAT`]
+    pub format: String,
+    /// Format version; imports reject versions newer than [`EXPORT_VERSION`]
+    pub version: u32,
+    pub exported_at: String,
+    /// Entries by namespace, sorted by key
+    pub namespaces: BTreeMap<String, Vec<MemoryEntry>>,
+}
+
+/// What an import does with a key that already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep the existing entry
+    #[default]
+    Skip,
+    /// Replace the existing entry
+    Overwrite,
+    /// Keep whichever entry was updated last, adding metadata keys it lacks
+    /// from the other
+    Merge,
+}
+
+/// Counts from an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    /// New keys added
+    pub imported: usize,
+    /// Existing keys left as they were
+    pub skipped: usize,
+    /// Existing keys replaced
+    pub overwritten: usize,
+    /// Existing keys merged
+    pub merged: usize,
+}
+
 /// Memory service for persistent storage.
+///
+/// Memories are partitioned into namespaces, so agents or projects sharing
+/// a workspace don't see or overwrite each other's entries. Each namespace
+/// is stored in its own directory under `.context-engine/memory/` and
+/// loaded on first use.
 pub struct MemoryService {
-    store: Arc<RwLock<MemoryStore>>,
-    storage_path: PathBuf,
+    stores: RwLock<HashMap<String, MemoryStore>>,
+    storage_dir: PathBuf,
+    /// Pre-namespace store, migrated into the default namespace
+    legacy_path: PathBuf,
+    clock: SharedClock,
 }
 
 impl MemoryService {
     /// Create a new memory service.
     pub async fn new(workspace: &Path) -> Result<Self> {
-        let storage_path = workspace.join(".context-engine").join("memory.json");
+        let base = workspace.join(".context-engine");
+        let storage_dir = base.join("memory");
+        fs::create_dir_all(&storage_dir).await?;
 
-        // Create directory if needed
-        if let Some(parent) = storage_path.parent() {
-            fs::create_dir_all(parent).await?;
+        Ok(Self {
+            stores: RwLock::new(HashMap::new()),
+            storage_dir,
+            legacy_path: base.join("memory.json"),
+            clock: system_clock(),
+        })
+    }
+
+    /// Stamp entries with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// File holding the entries of `namespace`.
+    fn storage_path(&self, namespace: &str) -> PathBuf {
+        self.storage_dir.join(namespace).join("memory.json")
+    }
+
+    /// Validate `namespace` and load it from disk if not loaded yet.
+    async fn load(&self, namespace: &str) -> Result<()> {
+        validate_namespace(namespace)?;
+        if self.stores.read().await.contains_key(namespace) {
+            return Ok(());
         }
 
-        // Load existing store
-        let store = if storage_path.exists() {
-            let content = fs::read_to_string(&storage_path).await?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            MemoryStore::default()
-        };
+        let mut path = self.storage_path(namespace);
+        if namespace == DEFAULT_NAMESPACE && !path.exists() && self.legacy_path.exists() {
+            path = self.legacy_path.clone();
+        }
+        let store = load_json(&path).await?.unwrap_or_default();
 
-        Ok(Self {
-            store: Arc::new(RwLock::new(store)),
-            storage_path,
-        })
+        self.stores
+            .write()
+            .await
+            .entry(namespace.to_string())
+            .or_insert(store);
+        Ok(())
     }
 
-    /// Save the store to disk.
-    async fn save(&self) -> Result<()> {
-        let store = self.store.read().await;
-        let content = serde_json::to_string_pretty(&*store)?;
-        fs::write(&self.storage_path, content).await?;
+    /// Save `namespace` to disk.
+    async fn save(&self, namespace: &str) -> Result<()> {
+        let content = {
+            let stores = self.stores.read().await;
+            serde_json::to_string_pretty(&stores[namespace])?
+        };
+        write_atomic(&self.storage_path(namespace), content.as_bytes()).await?;
+
+        // The default namespace now lives in its own directory
+        if namespace == DEFAULT_NAMESPACE && self.legacy_path.exists() {
+            fs::remove_file(&self.legacy_path).await?;
+        }
         Ok(())
     }
 
-    /// Store a memory entry.
+    /// Entries of `namespace` matching `filter`.
+    async fn entries(
+        &self,
+        namespace: &str,
+        filter: impl Fn(&MemoryEntry) -> bool,
+    ) -> Result<Vec<MemoryEntry>> {
+        self.load(namespace).await?;
+        let stores = self.stores.read().await;
+        Ok(stores[namespace]
+            .entries
+            .values()
+            .filter(|e| filter(e))
+            .cloned()
+            .collect())
+    }
+
+    /// Store a memory entry in `namespace`.
     pub async fn store(
         &self,
+        namespace: &str,
         key: String,
         value: String,
         entry_type: Option<String>,
     ) -> Result<MemoryEntry> {
-        let now = chrono::Utc::now().to_rfc3339();
+        self.load(namespace).await?;
+        let now = self.clock.now_rfc3339();
 
         let entry = MemoryEntry {
             key: key.clone(),
@@ -92,78 +223,190 @@ impl MemoryService {
         };
 
         {
-            let mut store = self.store.write().await;
-            store.entries.insert(key, entry.clone());
+            let mut stores = self.stores.write().await;
+            if let Some(store) = stores.get_mut(namespace) {
+                store.entries.insert(key, entry.clone());
+            }
         }
 
-        self.save().await?;
-        info!("Stored memory entry: {}", entry.key);
+        self.save(namespace).await?;
+        info!("Stored memory entry: {}/{}", namespace, entry.key);
 
         Ok(entry)
     }
 
-    /// Retrieve a memory entry.
-    pub async fn retrieve(&self, key: &str) -> Option<MemoryEntry> {
-        let store = self.store.read().await;
-        store.entries.get(key).cloned()
+    /// Retrieve a memory entry from `namespace`.
+    pub async fn retrieve(&self, namespace: &str, key: &str) -> Result<Option<MemoryEntry>> {
+        self.load(namespace).await?;
+        let stores = self.stores.read().await;
+        Ok(stores[namespace].entries.get(key).cloned())
     }
 
-    /// List all memory entries.
-    pub async fn list(&self, entry_type: Option<&str>) -> Vec<MemoryEntry> {
-        let store = self.store.read().await;
-
-        store
-            .entries
-            .values()
-            .filter(|e| entry_type.is_none_or(|t| e.entry_type == t))
-            .cloned()
-            .collect()
+    /// List the memory entries in `namespace`.
+    pub async fn list(
+        &self,
+        namespace: &str,
+        entry_type: Option<&str>,
+    ) -> Result<Vec<MemoryEntry>> {
+        self.entries(namespace, |e| entry_type.is_none_or(|t| e.entry_type == t))
+            .await
     }
 
-    /// Delete a memory entry.
-    pub async fn delete(&self, key: &str) -> Result<bool> {
+    /// Delete a memory entry from `namespace`.
+    pub async fn delete(&self, namespace: &str, key: &str) -> Result<bool> {
+        self.load(namespace).await?;
         let removed = {
-            let mut store = self.store.write().await;
-            store.entries.remove(key).is_some()
+            let mut stores = self.stores.write().await;
+            stores
+                .get_mut(namespace)
+                .is_some_and(|store| store.entries.remove(key).is_some())
         };
 
         if removed {
-            self.save().await?;
-            info!("Deleted memory entry: {}", key);
+            self.save(namespace).await?;
+            info!("Deleted memory entry: {}/{}", namespace, key);
         }
 
         Ok(removed)
     }
 
-    /// Clear all memory entries.
-    pub async fn clear(&self) -> Result<usize> {
+    /// Clear all memory entries in `namespace`.
+    pub async fn clear(&self, namespace: &str) -> Result<usize> {
+        self.load(namespace).await?;
         let count = {
-            let mut store = self.store.write().await;
-            let count = store.entries.len();
-            store.entries.clear();
-            count
+            let mut stores = self.stores.write().await;
+            stores.get_mut(namespace).map_or(0, |store| {
+                let count = store.entries.len();
+                store.entries.clear();
+                count
+            })
         };
 
-        self.save().await?;
-        info!("Cleared {} memory entries", count);
+        self.save(namespace).await?;
+        info!("Cleared {} memory entries in {}", count, namespace);
 
         Ok(count)
     }
 
-    /// Search memory entries by value.
-    pub async fn search(&self, query: &str) -> Vec<MemoryEntry> {
-        let store = self.store.read().await;
-        let query_lower = query.to_lowercase();
+    /// Namespaces that hold or have held memories, sorted.
+    pub async fn namespaces(&self) -> Result<Vec<String>> {
+        let mut names: BTreeSet<String> = self.stores.read().await.keys().cloned().collect();
+        let mut dirs = fs::read_dir(&self.storage_dir).await?;
+        while let Some(dir) = dirs.next_entry().await? {
+            let name = dir.file_name().to_string_lossy().to_string();
+            if validate_namespace(&name).is_ok() && dir.path().join("memory.json").exists() {
+                names.insert(name);
+            }
+        }
+        if self.legacy_path.exists() {
+            names.insert(DEFAULT_NAMESPACE.to_string());
+        }
+        Ok(names.into_iter().collect())
+    }
 
-        store
-            .entries
-            .values()
-            .filter(|e| {
-                e.key.to_lowercase().contains(&query_lower)
-                    || e.value.to_lowercase().contains(&query_lower)
-            })
-            .cloned()
-            .collect()
+    /// Export `namespace`, or every namespace if `None`.
+    ///
+    /// Empty namespaces are left out.
+    pub async fn export(&self, namespace: Option<&str>) -> Result<MemoryExport> {
+        let names = match namespace {
+            Some(namespace) => vec![namespace.to_string()],
+            None => self.namespaces().await?,
+        };
+
+        let mut namespaces = BTreeMap::new();
+        for name in names {
+            let mut entries = self.list(&name, None).await?;
+            if entries.is_empty() {
+                continue;
+            }
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+            namespaces.insert(name, entries);
+        }
+
+        Ok(MemoryExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: self.clock.now_rfc3339(),
+            namespaces,
+        })
+    }
+
+    /// Load the memories in `export`, resolving existing keys by `policy`.
+    ///
+    /// Every namespace name is checked before anything is written.
+    pub async fn import(
+        &self,
+        export: MemoryExport,
+        policy: ConflictPolicy,
+    ) -> Result<ImportSummary> {
+        if export.format != EXPORT_FORMAT {
+            return Err(Error::InvalidToolArguments(format!(
+                "Not a memory export: format is '{}', expected '{}'",
+                export.format, EXPORT_FORMAT
+            )));
+        }
+        if export.version > EXPORT_VERSION {
+            return Err(Error::InvalidToolArguments(format!(
+                "Unsupported memory export version {} (newest supported: {})",
+                export.version, EXPORT_VERSION
+            )));
+        }
+        for namespace in export.namespaces.keys() {
+            self.load(namespace).await?;
+        }
+
+        let mut summary = ImportSummary::default();
+        for (namespace, entries) in export.namespaces {
+            {
+                let mut stores = self.stores.write().await;
+                let Some(store) = stores.get_mut(&namespace) else {
+                    continue;
+                };
+                for entry in entries {
+                    let Some(existing) = store.entries.get_mut(&entry.key) else {
+                        summary.imported += 1;
+                        store.entries.insert(entry.key.clone(), entry);
+                        continue;
+                    };
+                    match policy {
+                        ConflictPolicy::Skip => summary.skipped += 1,
+                        ConflictPolicy::Overwrite => {
+                            *existing = entry;
+                            summary.overwritten += 1;
+                        }
+                        ConflictPolicy::Merge => {
+                            let (mut newer, older) = if entry.updated_at > existing.updated_at {
+                                (entry, existing.clone())
+                            } else {
+                                (existing.clone(), entry)
+                            };
+                            for (key, value) in older.metadata {
+                                newer.metadata.entry(key).or_insert(value);
+                            }
+                            *existing = newer;
+                            summary.merged += 1;
+                        }
+                    }
+                }
+            }
+            self.save(&namespace).await?;
+        }
+
+        info!(
+            "Imported memories: {} new, {} skipped, {} overwritten, {} merged",
+            summary.imported, summary.skipped, summary.overwritten, summary.merged
+        );
+        Ok(summary)
+    }
+
+    /// Search memory entries in `namespace` by key and value.
+    pub async fn search(&self, namespace: &str, query: &str) -> Result<Vec<MemoryEntry>> {
+        let query_lower = query.to_lowercase();
+        self.entries(namespace, |e| {
+            e.key.to_lowercase().contains(&query_lower)
+                || e.value.to_lowercase().contains(&query_lower)
+        })
+        .await
     }
 }
 
@@ -184,6 +427,7 @@ mod tests {
 
         let entry = service
             .store(
+                DEFAULT_NAMESPACE,
                 "test-key".to_string(),
                 "test-value".to_string(),
                 Some("test-type".to_string()),
@@ -195,14 +439,21 @@ mod tests {
         assert_eq!(entry.value, "test-value");
         assert_eq!(entry.entry_type, "test-type");
 
-        let retrieved = service.retrieve("test-key").await.unwrap();
+        let retrieved = service
+            .retrieve(DEFAULT_NAMESPACE, "test-key")
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(retrieved.value, "test-value");
     }
 
     #[tokio::test]
     async fn test_retrieve_nonexistent() {
         let (service, _temp) = create_test_service().await;
-        let result = service.retrieve("nonexistent").await;
+        let result = service
+            .retrieve(DEFAULT_NAMESPACE, "nonexistent")
+            .await
+            .unwrap();
         assert!(result.is_none());
     }
 
@@ -211,15 +462,25 @@ mod tests {
         let (service, _temp) = create_test_service().await;
 
         service
-            .store("key1".to_string(), "value1".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "key1".to_string(),
+                "value1".to_string(),
+                None,
+            )
             .await
             .unwrap();
         service
-            .store("key2".to_string(), "value2".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "key2".to_string(),
+                "value2".to_string(),
+                None,
+            )
             .await
             .unwrap();
 
-        let all = service.list(None).await;
+        let all = service.list(DEFAULT_NAMESPACE, None).await.unwrap();
         assert_eq!(all.len(), 2);
     }
 
@@ -229,6 +490,7 @@ mod tests {
 
         service
             .store(
+                DEFAULT_NAMESPACE,
                 "key1".to_string(),
                 "value1".to_string(),
                 Some("type-a".to_string()),
@@ -237,6 +499,7 @@ mod tests {
             .unwrap();
         service
             .store(
+                DEFAULT_NAMESPACE,
                 "key2".to_string(),
                 "value2".to_string(),
                 Some("type-b".to_string()),
@@ -245,6 +508,7 @@ mod tests {
             .unwrap();
         service
             .store(
+                DEFAULT_NAMESPACE,
                 "key3".to_string(),
                 "value3".to_string(),
                 Some("type-a".to_string()),
@@ -252,10 +516,16 @@ mod tests {
             .await
             .unwrap();
 
-        let type_a = service.list(Some("type-a")).await;
+        let type_a = service
+            .list(DEFAULT_NAMESPACE, Some("type-a"))
+            .await
+            .unwrap();
         assert_eq!(type_a.len(), 2);
 
-        let type_b = service.list(Some("type-b")).await;
+        let type_b = service
+            .list(DEFAULT_NAMESPACE, Some("type-b"))
+            .await
+            .unwrap();
         assert_eq!(type_b.len(), 1);
     }
 
@@ -264,20 +534,39 @@ mod tests {
         let (service, _temp) = create_test_service().await;
 
         service
-            .store("to-delete".to_string(), "value".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "to-delete".to_string(),
+                "value".to_string(),
+                None,
+            )
             .await
             .unwrap();
-        assert!(service.retrieve("to-delete").await.is_some());
+        assert!(service
+            .retrieve(DEFAULT_NAMESPACE, "to-delete")
+            .await
+            .unwrap()
+            .is_some());
 
-        let deleted = service.delete("to-delete").await.unwrap();
+        let deleted = service
+            .delete(DEFAULT_NAMESPACE, "to-delete")
+            .await
+            .unwrap();
         assert!(deleted);
-        assert!(service.retrieve("to-delete").await.is_none());
+        assert!(service
+            .retrieve(DEFAULT_NAMESPACE, "to-delete")
+            .await
+            .unwrap()
+            .is_none());
     }
 
     #[tokio::test]
     async fn test_delete_nonexistent() {
         let (service, _temp) = create_test_service().await;
-        let deleted = service.delete("nonexistent").await.unwrap();
+        let deleted = service
+            .delete(DEFAULT_NAMESPACE, "nonexistent")
+            .await
+            .unwrap();
         assert!(!deleted);
     }
 
@@ -286,18 +575,28 @@ mod tests {
         let (service, _temp) = create_test_service().await;
 
         service
-            .store("key1".to_string(), "value1".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "key1".to_string(),
+                "value1".to_string(),
+                None,
+            )
             .await
             .unwrap();
         service
-            .store("key2".to_string(), "value2".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "key2".to_string(),
+                "value2".to_string(),
+                None,
+            )
             .await
             .unwrap();
 
-        let cleared = service.clear().await.unwrap();
+        let cleared = service.clear(DEFAULT_NAMESPACE).await.unwrap();
         assert_eq!(cleared, 2);
 
-        let all = service.list(None).await;
+        let all = service.list(DEFAULT_NAMESPACE, None).await.unwrap();
         assert!(all.is_empty());
     }
 
@@ -307,6 +606,7 @@ mod tests {
 
         service
             .store(
+                DEFAULT_NAMESPACE,
                 "config".to_string(),
                 "database connection string".to_string(),
                 None,
@@ -315,6 +615,7 @@ mod tests {
             .unwrap();
         service
             .store(
+                DEFAULT_NAMESPACE,
                 "note".to_string(),
                 "remember to check database".to_string(),
                 None,
@@ -322,14 +623,19 @@ mod tests {
             .await
             .unwrap();
         service
-            .store("other".to_string(), "unrelated content".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "other".to_string(),
+                "unrelated content".to_string(),
+                None,
+            )
             .await
             .unwrap();
 
-        let results = service.search("database").await;
+        let results = service.search(DEFAULT_NAMESPACE, "database").await.unwrap();
         assert_eq!(results.len(), 2);
 
-        let results = service.search("CONFIG").await; // Case insensitive
+        let results = service.search(DEFAULT_NAMESPACE, "CONFIG").await.unwrap(); // Case insensitive
         assert_eq!(results.len(), 1);
     }
 
@@ -338,7 +644,12 @@ mod tests {
         let (service, _temp) = create_test_service().await;
 
         let entry = service
-            .store("key".to_string(), "value".to_string(), None)
+            .store(
+                DEFAULT_NAMESPACE,
+                "key".to_string(),
+                "value".to_string(),
+                None,
+            )
             .await
             .unwrap();
         assert_eq!(entry.entry_type, "general");
@@ -352,7 +663,12 @@ mod tests {
         {
             let service = MemoryService::new(temp_dir.path()).await.unwrap();
             service
-                .store("persistent".to_string(), "data".to_string(), None)
+                .store(
+                    DEFAULT_NAMESPACE,
+                    "persistent".to_string(),
+                    "data".to_string(),
+                    None,
+                )
                 .await
                 .unwrap();
         }
@@ -360,12 +676,236 @@ mod tests {
         // Reload and verify
         {
             let service = MemoryService::new(temp_dir.path()).await.unwrap();
-            let entry = service.retrieve("persistent").await;
+            let entry = service
+                .retrieve(DEFAULT_NAMESPACE, "persistent")
+                .await
+                .unwrap();
             assert!(entry.is_some());
             assert_eq!(entry.unwrap().value, "data");
         }
     }
 
+    #[tokio::test]
+    async fn test_namespaces_are_isolated() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let service = MemoryService::new(temp_dir.path()).await.unwrap();
+            service
+                .store("agent-a", "plan".to_string(), "from a".to_string(), None)
+                .await
+                .unwrap();
+            service
+                .store("agent-b", "plan".to_string(), "from b".to_string(), None)
+                .await
+                .unwrap();
+            service
+                .store("agent-b", "notes".to_string(), "b only".to_string(), None)
+                .await
+                .unwrap();
+
+            let a = service.retrieve("agent-a", "plan").await.unwrap().unwrap();
+            assert_eq!(a.value, "from a");
+            assert_eq!(service.list("agent-a", None).await.unwrap().len(), 1);
+            assert!(service
+                .retrieve("agent-a", "notes")
+                .await
+                .unwrap()
+                .is_none());
+            assert!(service
+                .list(DEFAULT_NAMESPACE, None)
+                .await
+                .unwrap()
+                .is_empty());
+            assert!(service
+                .search("agent-a", "b only")
+                .await
+                .unwrap()
+                .is_empty());
+
+            assert!(service.delete("agent-a", "plan").await.unwrap());
+            let b = service.retrieve("agent-b", "plan").await.unwrap().unwrap();
+            assert_eq!(b.value, "from b");
+        }
+
+        // One directory per namespace, reloaded separately
+        let dir = temp_dir.path().join(".context-engine/memory");
+        assert!(dir.join("agent-b/memory.json").exists());
+        let service = MemoryService::new(temp_dir.path()).await.unwrap();
+        assert!(service.list("agent-a", None).await.unwrap().is_empty());
+        assert_eq!(service.list("agent-b", None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_file_is_quarantined_and_the_rest_loads() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let service = MemoryService::new(temp_dir.path()).await.unwrap();
+            for namespace in ["intact", "broken"] {
+                service
+                    .store(namespace, "k".to_string(), "v".to_string(), None)
+                    .await
+                    .unwrap();
+            }
+        }
+        let dir = temp_dir.path().join(".context-engine/memory");
+        let broken = dir.join("broken/memory.json");
+        let content = std::fs::read_to_string(&broken).unwrap();
+        std::fs::write(&broken, &content[..content.len() / 2]).unwrap();
+
+        let service = MemoryService::new(temp_dir.path()).await.unwrap();
+        assert_eq!(service.list("intact", None).await.unwrap().len(), 1);
+        assert!(service.list("broken", None).await.unwrap().is_empty());
+
+        // The damaged file is kept aside, not overwritten by the next save
+        service
+            .store("broken", "new".to_string(), "v".to_string(), None)
+            .await
+            .unwrap();
+        let names: Vec<String> = std::fs::read_dir(dir.join("broken"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().any(|n| n.starts_with("memory.json.corrupt-")));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_namespace_is_rejected() {
+        let (service, _temp) = create_test_service().await;
+        for name in ["", "..", "a/b", "a\\b", "../escape", "with space"] {
+            let err = service.list(name, None).await.unwrap_err();
+            assert_eq!(err.code(), "invalid_namespace", "{:?}", name);
+        }
+        assert!(validate_namespace("project.v2_agent-1").is_ok());
+        assert!(validate_namespace(&"x".repeat(65)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_store_migrates_to_default_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join(".context-engine");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(
+            base.join("memory.json"),
+            r#"{"entries":{"old":{"key":"old","value":"kept","created_at":"","updated_at":""}}}"#,
+        )
+        .unwrap();
+
+        let service = MemoryService::new(temp_dir.path()).await.unwrap();
+        let entry = service.retrieve(DEFAULT_NAMESPACE, "old").await.unwrap();
+        assert_eq!(entry.unwrap().value, "kept");
+
+        service
+            .store(DEFAULT_NAMESPACE, "new".to_string(), "v".to_string(), None)
+            .await
+            .unwrap();
+        assert!(!base.join("memory.json").exists());
+        assert!(base.join("memory/default/memory.json").exists());
+        assert_eq!(
+            service.list(DEFAULT_NAMESPACE, None).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let (source, _source_dir) = create_test_service().await;
+        for (namespace, key) in [("default", "a"), ("default", "b"), ("agent-x", "c")] {
+            source
+                .store(namespace, key.to_string(), format!("value {}", key), None)
+                .await
+                .unwrap();
+        }
+        let export = source.export(None).await.unwrap();
+        assert_eq!(export.version, EXPORT_VERSION);
+        assert_eq!(
+            export.namespaces.keys().collect::<Vec<_>>(),
+            vec!["agent-x", "default"]
+        );
+
+        // Through JSON, as the document would travel between machines
+        let json = serde_json::to_string(&export).unwrap();
+        let (target, _target_dir) = create_test_service().await;
+        let summary = target
+            .import(serde_json::from_str(&json).unwrap(), ConflictPolicy::Skip)
+            .await
+            .unwrap();
+        assert_eq!(summary.imported, 3);
+        let c = target.retrieve("agent-x", "c").await.unwrap().unwrap();
+        assert_eq!(c.value, "value c");
+        assert_eq!(target.list(DEFAULT_NAMESPACE, None).await.unwrap().len(), 2);
+        assert_eq!(
+            target.export(None).await.unwrap().namespaces,
+            export.namespaces
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_policies() {
+        let (service, _temp) = create_test_service().await;
+        let entry = |value: &str, updated_at: &str, meta: &str| MemoryEntry {
+            key: "k".to_string(),
+            value: value.to_string(),
+            entry_type: "general".to_string(),
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+            metadata: HashMap::from([(meta.to_string(), serde_json::json!(true))]),
+        };
+        let export = |entry: MemoryEntry| MemoryExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: String::new(),
+            namespaces: BTreeMap::from([(DEFAULT_NAMESPACE.to_string(), vec![entry])]),
+        };
+        let current = || async {
+            service
+                .retrieve(DEFAULT_NAMESPACE, "k")
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        service
+            .import(
+                export(entry("local", "2024-02-01T00:00:00Z", "local")),
+                ConflictPolicy::Skip,
+            )
+            .await
+            .unwrap();
+
+        let older = entry("older", "2024-01-01T00:00:00Z", "older");
+        let summary = service
+            .import(export(older.clone()), ConflictPolicy::Skip)
+            .await
+            .unwrap();
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(current().await.value, "local");
+
+        // Merge keeps the newer entry but picks up the older one's metadata
+        let summary = service
+            .import(export(older.clone()), ConflictPolicy::Merge)
+            .await
+            .unwrap();
+        assert_eq!(summary.merged, 1);
+        let merged = current().await;
+        assert_eq!(merged.value, "local");
+        assert!(merged.metadata.contains_key("older"));
+
+        service
+            .import(export(older), ConflictPolicy::Overwrite)
+            .await
+            .unwrap();
+        assert_eq!(current().await.value, "older");
+
+        let mut future = export(entry("x", "", "x"));
+        future.version = EXPORT_VERSION + 1;
+        let err = service
+            .import(future, ConflictPolicy::Skip)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "invalid_arguments");
+    }
+
     #[test]
     fn test_memory_entry_serialization() {
         let entry = MemoryEntry {
@@ -3,10 +3,35 @@
 //! This module provides the business logic layer that wraps the SDK
 //! and provides higher-level operations for the MCP tools.
 
+pub mod bundle;
 pub mod context;
+pub mod exclude;
+pub mod explain;
+pub mod grep;
+pub mod language;
+pub mod loc;
 pub mod memory;
+pub mod outline;
+pub mod persist;
 pub mod planning;
+pub mod search_cache;
+pub mod search_filter;
+pub mod secrets;
+pub mod snippets;
+pub mod stats;
+pub mod tokens;
+pub mod tree;
 
+pub use bundle::ContextBundle;
 pub use context::ContextService;
-pub use memory::MemoryService;
+pub use exclude::ExcludeMatcher;
+pub use grep::{GrepMatch, GrepOptions, GrepResult};
+pub use memory::{ConflictPolicy, ImportSummary, MemoryExport, MemoryService, DEFAULT_NAMESPACE};
 pub use planning::PlanningService;
+pub use search_cache::SearchCache;
+pub use search_filter::SearchFilter;
+pub use secrets::{SecretMatch, SecretScan};
+pub use snippets::SnippetStore;
+pub use stats::{ChangeDetection, LanguageStats, WorkspaceStats};
+pub use tokens::{CharHeuristic, TokenEstimator};
+pub use tree::{Tree, TreeNode, TreeOptions};
@@ -0,0 +1,689 @@
+//! Symbol outlines extracted with per-language line patterns.
+//!
+//! This is deliberately lightweight: no parser, just anchored regexes over
+//! each line. It finds top-level and nested declarations in the common
+//! formatting styles, which is enough for navigation and summaries.
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+use crate::service::loc::{comment_syntax, CommentSyntax, LineClassifier, LineKind};
+
+/// Kind of a declared symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Module,
+    Struct,
+    Enum,
+    Trait,
+    Interface,
+    Class,
+    Type,
+    Impl,
+    Const,
+    Function,
+}
+
+impl SymbolKind {
+    /// Every detectable kind.
+    pub const ALL: &'static [SymbolKind] = &[
+        Self::Module,
+        Self::Struct,
+        Self::Enum,
+        Self::Trait,
+        Self::Interface,
+        Self::Class,
+        Self::Type,
+        Self::Impl,
+        Self::Const,
+        Self::Function,
+    ];
+
+    /// Lowercase name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Module => "module",
+            Self::Struct => "struct",
+            Self::Enum => "enum",
+            Self::Trait => "trait",
+            Self::Interface => "interface",
+            Self::Class => "class",
+            Self::Type => "type",
+            Self::Impl => "impl",
+            Self::Const => "const",
+            Self::Function => "function",
+        }
+    }
+
+    /// Parse a kind name (case-insensitive).
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.iter().copied().find(|k| k.as_str() == name)
+    }
+}
+
+/// A declaration found in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 1-based line of the declaration
+    pub line: usize,
+    /// The declaration line, trimmed
+    pub signature: String,
+    /// Type whose `impl` block declares this method (Rust only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+impl Symbol {
+    /// The name qualified by its parent, e.g. `Server::new`.
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}::{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A pattern whose `name` group captures the symbol name.
+struct Rule {
+    kind: SymbolKind,
+    pattern: Regex,
+}
+
+fn rules(specs: &[(SymbolKind, &str)]) -> Vec<Rule> {
+    specs
+        .iter()
+        .map(|(kind, pattern)| Rule {
+            kind: *kind,
+            pattern: Regex::new(pattern).expect("outline pattern is valid"),
+        })
+        .collect()
+}
+
+const RUST_VIS: &str = r"^\s*(?:pub(?:\([^)]*\))?\s+)?";
+
+static RUST: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Function,
+            &format!(
+                r#"{}(?:(?:const|async|unsafe|extern(?:\s+"[^"]*")?)\s+)*fn\s+(?P<name>\w+)"#,
+                RUST_VIS
+            ),
+        ),
+        (
+            SymbolKind::Struct,
+            &format!(r"{}struct\s+(?P<name>\w+)", RUST_VIS),
+        ),
+        (
+            SymbolKind::Enum,
+            &format!(r"{}enum\s+(?P<name>\w+)", RUST_VIS),
+        ),
+        (
+            SymbolKind::Trait,
+            &format!(r"{}(?:unsafe\s+)?trait\s+(?P<name>\w+)", RUST_VIS),
+        ),
+        (
+            SymbolKind::Impl,
+            r"^\s*(?:unsafe\s+)?impl(?:<[^>]*>)?\s+(?P<name>[^{]+?)\s*(?:\{.*|where.*)?$",
+        ),
+        (
+            SymbolKind::Type,
+            &format!(r"{}type\s+(?P<name>\w+)", RUST_VIS),
+        ),
+        (
+            SymbolKind::Const,
+            &format!(
+                r"{}(?:const|static)\s+(?:mut\s+)?(?P<name>\w+)\s*:",
+                RUST_VIS
+            ),
+        ),
+        (
+            SymbolKind::Module,
+            &format!(r"{}mod\s+(?P<name>\w+)", RUST_VIS),
+        ),
+    ])
+});
+
+static PYTHON: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Function,
+            r"^\s*(?:async\s+)?def\s+(?P<name>\w+)",
+        ),
+        (SymbolKind::Class, r"^\s*class\s+(?P<name>\w+)"),
+    ])
+});
+
+const JS_EXPORT: &str = r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?";
+
+static JAVASCRIPT: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Function,
+            &format!(r"{}(?:async\s+)?function\*?\s+(?P<name>\w+)", JS_EXPORT),
+        ),
+        (
+            SymbolKind::Function,
+            r"^\s*(?:export\s+)?(?:const|let|var)\s+(?P<name>\w+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|\w+)\s*(?::[^=]+)?=>",
+        ),
+        (
+            SymbolKind::Class,
+            &format!(r"{}(?:abstract\s+)?class\s+(?P<name>\w+)", JS_EXPORT),
+        ),
+        (
+            SymbolKind::Interface,
+            &format!(r"{}interface\s+(?P<name>\w+)", JS_EXPORT),
+        ),
+        (
+            SymbolKind::Enum,
+            &format!(r"{}(?:const\s+)?enum\s+(?P<name>\w+)", JS_EXPORT),
+        ),
+        (
+            SymbolKind::Type,
+            &format!(r"{}type\s+(?P<name>\w+)(?:<[^>]*>)?\s*=", JS_EXPORT),
+        ),
+    ])
+});
+
+static GO: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Function,
+            r"^func\s+(?:\([^)]*\)\s*)?(?P<name>\w+)",
+        ),
+        (SymbolKind::Struct, r"^type\s+(?P<name>\w+)\s+struct\b"),
+        (
+            SymbolKind::Interface,
+            r"^type\s+(?P<name>\w+)\s+interface\b",
+        ),
+        (SymbolKind::Type, r"^type\s+(?P<name>\w+)\s"),
+    ])
+});
+
+static RUBY: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Function,
+            r"^\s*def\s+(?P<name>(?:self\.)?[\w?!=]+)",
+        ),
+        (SymbolKind::Class, r"^\s*class\s+(?P<name>[\w:]+)"),
+        (SymbolKind::Module, r"^\s*module\s+(?P<name>[\w:]+)"),
+    ])
+});
+
+/// Leading annotations (`@Override`, `@Get("/x")`) and modifiers.
+const JVM_MODIFIERS: &str = r"^\s*(?:@[\w.]+(?:\([^)]*\))?\s+)*(?:(?:public|private|protected|internal|static|abstract|final|sealed|data|open|partial|synchronized|native|default|override|virtual|async)\s+)*";
+
+static JVM: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    rules(&[
+        (
+            SymbolKind::Class,
+            &format!(r"{}class\s+(?P<name>\w+)", JVM_MODIFIERS),
+        ),
+        (
+            SymbolKind::Interface,
+            &format!(r"{}interface\s+(?P<name>\w+)", JVM_MODIFIERS),
+        ),
+        (
+            SymbolKind::Enum,
+            &format!(r"{}enum\s+(?:class\s+)?(?P<name>\w+)", JVM_MODIFIERS),
+        ),
+        (
+            SymbolKind::Function,
+            &format!(
+                r"{}fun\s+(?:<[^>]*>\s*)?(?:\w+\.)?(?P<name>\w+)",
+                JVM_MODIFIERS
+            ),
+        ),
+        // Java/C# methods: `<T> List<T> name(`; `ret` is checked against keywords
+        (
+            SymbolKind::Function,
+            &format!(
+                r"{}(?:<[^>]+>\s+)?(?P<ret>[\w.?]+(?:<[^()]*?>)?(?:\[\])*)\s+(?P<name>\w+)\s*\(",
+                JVM_MODIFIERS
+            ),
+        ),
+    ])
+});
+
+/// Words that can precede `name(` in statements but never in declarations.
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "return", "new", "throw", "else", "if", "while", "for", "switch", "catch", "await", "yield",
+    "case", "goto",
+];
+
+/// Maximum physical lines joined into one logical declaration line.
+const MAX_CONTINUATION_LINES: usize = 8;
+
+fn rules_for(language: &str) -> Option<&'static [Rule]> {
+    let rules: &'static Vec<Rule> = match language {
+        "rust" => &RUST,
+        "python" => &PYTHON,
+        "javascript" | "typescript" | "jsx" | "tsx" => &JAVASCRIPT,
+        "go" => &GO,
+        "ruby" => &RUBY,
+        "java" | "kotlin" | "csharp" => &JVM,
+        _ => return None,
+    };
+    Some(rules)
+}
+
+/// Whether outlines are supported for `language`.
+pub fn supports_language(language: &str) -> bool {
+    rules_for(language).is_some()
+}
+
+/// Declarations in `content`, in line order.
+///
+/// A line with unbalanced parentheses is joined with the following lines
+/// (up to [`MAX_CONTINUATION_LINES`]) so signatures whose parameters wrap
+/// are matched as one logical line. Unsupported languages yield no symbols.
+///
+/// Rust methods get the type of their enclosing `impl` block as parent.
+pub fn extract_symbols(language: &str, content: &str) -> Vec<Symbol> {
+    let Some(rules) = rules_for(language) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut symbols: Vec<Symbol> = (0..lines.len())
+        .filter_map(|index| {
+            let logical = logical_line(&lines[index..]);
+            rules.iter().find_map(|rule| {
+                let captures = rule.pattern.captures(&logical)?;
+                let name = captures.name("name")?.as_str();
+                // Rust rules anchor on a declaration keyword, so `fn new` is a
+                // constructor rather than a `new` expression
+                let is_statement =
+                    |word: &str| language != "rust" && STATEMENT_KEYWORDS.contains(&word);
+                if is_statement(name)
+                    || captures
+                        .name("ret")
+                        .is_some_and(|r| is_statement(r.as_str()))
+                {
+                    return None;
+                }
+                Some(Symbol {
+                    name: name.trim().to_string(),
+                    kind: rule.kind,
+                    line: index + 1,
+                    signature: logical.trim().to_string(),
+                    parent: None,
+                })
+            })
+        })
+        .collect();
+    if language == "rust" {
+        attach_impl_methods(&lines, &mut symbols);
+    }
+    symbols
+}
+
+/// Set the parent of functions declared directly inside an `impl` block.
+///
+/// Scopes are tracked by brace depth. Braces after `//` are ignored, but
+/// braces inside string and char literals are counted.
+fn attach_impl_methods(lines: &[&str], symbols: &mut [Symbol]) {
+    // Self type and brace depth of each open impl body, innermost last
+    let mut scopes: Vec<(String, i32)> = Vec::new();
+    let mut pending: Option<String> = None;
+    let mut depth = 0i32;
+    let mut symbols = symbols.iter_mut().peekable();
+
+    for (index, line) in lines.iter().enumerate() {
+        while let Some(symbol) = symbols.next_if(|s| s.line == index + 1) {
+            match symbol.kind {
+                SymbolKind::Impl => pending = Some(impl_self_type(&symbol.name)),
+                SymbolKind::Function => {
+                    if let Some((self_type, _)) = scopes.last().filter(|(_, d)| *d == depth) {
+                        symbol.parent = Some(self_type.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let code = line.split("//").next().unwrap_or_default();
+        for c in code.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    if let Some(self_type) = pending.take() {
+                        scopes.push((self_type, depth));
+                    }
+                }
+                '}' => {
+                    if scopes.last().is_some_and(|(_, d)| *d == depth) {
+                        scopes.pop();
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The implementing type of an impl header: `Server` for `Server<T>` and
+/// for `Display for Server`.
+fn impl_self_type(header: &str) -> String {
+    let self_type = header.rsplit(" for ").next().unwrap_or(header);
+    self_type
+        .split('<')
+        .next()
+        .unwrap_or(self_type)
+        .trim()
+        .to_string()
+}
+
+/// The documentation comment at the top of `content`, without its markers.
+///
+/// Takes the first comment block before any code, skipping a shebang and
+/// license headers. In Rust only inner docs (`//!`, `/*!`) and plain
+/// comments count, since a `///` comment documents the item below it.
+pub fn module_doc(language: &str, content: &str) -> Option<String> {
+    let syntax = comment_syntax(language);
+    let mut classifier = LineClassifier::with_syntax(syntax);
+    let mut blocks: Vec<Vec<String>> = vec![Vec::new()];
+
+    for (index, line) in content.lines().enumerate() {
+        if index == 0 && line.starts_with("#!") {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if language == "rust"
+            && !classifier.in_comment()
+            && (trimmed.starts_with("///") || trimmed.starts_with("/**"))
+        {
+            break;
+        }
+        let in_comment = classifier.in_comment();
+        let block = blocks.last_mut().expect("at least one block");
+        match classifier.classify(line) {
+            LineKind::Comment => block.push(strip_comment_markers(line, &syntax)),
+            LineKind::Blank if in_comment => block.push(String::new()),
+            LineKind::Blank if !block.is_empty() => blocks.push(Vec::new()),
+            LineKind::Blank => {}
+            LineKind::Code => break,
+        }
+    }
+
+    blocks
+        .into_iter()
+        .map(|lines| lines.join("\n").trim().to_string())
+        .find(|doc| !doc.is_empty() && !is_license_header(doc))
+}
+
+/// `line` without its leading and trailing comment markers.
+fn strip_comment_markers(line: &str, syntax: &CommentSyntax) -> String {
+    let mut text = line.trim();
+    let openers = syntax
+        .line
+        .iter()
+        .chain(syntax.block.iter().map(|(open, _)| open))
+        .chain(syntax.docstring);
+    for opener in openers {
+        if let Some(rest) = text.strip_prefix(opener) {
+            text = rest;
+            break;
+        }
+    }
+    let closers = syntax
+        .block
+        .iter()
+        .map(|(_, close)| close)
+        .chain(syntax.docstring);
+    for closer in closers {
+        if let Some(rest) = text.strip_suffix(closer) {
+            text = rest;
+            break;
+        }
+    }
+    // `//!` and `/*!` inner docs, and `*` on block comment continuation lines
+    text.trim_start_matches(['!', '*']).trim().to_string()
+}
+
+/// Whether a comment block is a copyright or license header.
+fn is_license_header(doc: &str) -> bool {
+    let doc = doc.to_lowercase();
+    doc.starts_with("copyright")
+        || doc.contains("spdx-license-identifier")
+        || doc.contains("all rights reserved")
+        || doc.contains("licensed under the")
+}
+
+/// The first line of `lines`, joined with continuation lines while its
+/// parentheses are unbalanced.
+fn logical_line(lines: &[&str]) -> String {
+    let depth = |text: &str| {
+        text.chars().fold(0i32, |depth, c| match c {
+            '(' => depth + 1,
+            ')' => depth - 1,
+            _ => depth,
+        })
+    };
+
+    let mut logical = lines[0].to_string();
+    let mut open = depth(lines[0]);
+    for next in lines.iter().skip(1).take(MAX_CONTINUATION_LINES - 1) {
+        if open <= 0 {
+            break;
+        }
+        logical.push(' ');
+        logical.push_str(next.trim());
+        open += depth(next);
+    }
+    logical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(language: &str, content: &str) -> Vec<(SymbolKind, String, usize)> {
+        extract_symbols(language, content)
+            .into_iter()
+            .map(|s| (s.kind, s.name, s.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_symbols() {
+        let source = r#"
+pub mod api;
+pub(crate) const LIMIT: usize = 10;
+pub struct Server {
+    port: u16,
+}
+// fn commented_out() {}
+impl Server {
+    pub async fn start(&self) {}
+}
+impl<T: Clone> From<T> for Server where T: Into<u16> {
+}
+pub enum Mode { A }
+pub trait Handler {}
+type Alias = u8;
+"#;
+        assert_eq!(
+            outline("rust", source),
+            vec![
+                (SymbolKind::Module, "api".to_string(), 2),
+                (SymbolKind::Const, "LIMIT".to_string(), 3),
+                (SymbolKind::Struct, "Server".to_string(), 4),
+                (SymbolKind::Impl, "Server".to_string(), 8),
+                (SymbolKind::Function, "start".to_string(), 9),
+                (SymbolKind::Impl, "From<T> for Server".to_string(), 11),
+                (SymbolKind::Enum, "Mode".to_string(), 13),
+                (SymbolKind::Trait, "Handler".to_string(), 14),
+                (SymbolKind::Type, "Alias".to_string(), 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_typescript_and_go_symbols() {
+        assert_eq!(
+            outline(
+                "python",
+                "class Repo:\n    async def fetch(self):\n        pass\n# def nope():\n"
+            ),
+            vec![
+                (SymbolKind::Class, "Repo".to_string(), 1),
+                (SymbolKind::Function, "fetch".to_string(), 2),
+            ]
+        );
+        assert_eq!(
+            outline(
+                "typescript",
+                "export interface Props {}\nexport const render = async (p: Props) => {};\nexport default class App {}\n"
+            ),
+            vec![
+                (SymbolKind::Interface, "Props".to_string(), 1),
+                (SymbolKind::Function, "render".to_string(), 2),
+                (SymbolKind::Class, "App".to_string(), 3),
+            ]
+        );
+        assert_eq!(
+            outline(
+                "go",
+                "type Store struct {\n}\nfunc (s *Store) Get() {}\ntype ID string\n"
+            ),
+            vec![
+                (SymbolKind::Struct, "Store".to_string(), 1),
+                (SymbolKind::Function, "Get".to_string(), 3),
+                (SymbolKind::Type, "ID".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrapped_typescript_signature() {
+        let source = "export const handler = async (\n  event: Event,\n  context: Context,\n): Promise<void> => {\n};\nexport function render(\n  props: Props,\n) {}\n";
+        let symbols = extract_symbols("typescript", source);
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|s| (s.name.as_str(), s.line))
+                .collect::<Vec<_>>(),
+            vec![("handler", 1), ("render", 6)]
+        );
+        assert_eq!(
+            symbols[0].signature,
+            "export const handler = async ( event: Event, context: Context, ): Promise<void> => {"
+        );
+    }
+
+    #[test]
+    fn test_annotated_java_method() {
+        let source = r#"@Service
+public class UserService {
+    @Override
+    @Transactional(readOnly = true)
+    public Map<String, List<User>> findAll(
+            int page,
+            int size) {
+        List<User> users = repo.findAll(page);
+        return group(users);
+    }
+
+    @GetMapping("/health") public String health() { return "ok"; }
+}
+"#;
+        assert_eq!(
+            outline("java", source),
+            vec![
+                (SymbolKind::Class, "UserService".to_string(), 2),
+                (SymbolKind::Function, "findAll".to_string(), 5),
+                (SymbolKind::Function, "health".to_string(), 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_methods_attach_to_impl() {
+        let source = r#"
+pub struct Server;
+pub struct Client<T>(T);
+
+impl Server {
+    pub fn new() -> Self {
+        let handler = || { Server };
+        handler()
+    }
+
+    fn port(&self) -> u16 { 80 }
+}
+
+fn free_standing() {}
+
+impl<T: Clone> Client<T>
+where
+    T: Send,
+{
+    pub async fn connect(&self) {
+        fn nested() {}
+    }
+}
+
+impl std::fmt::Display for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { Ok(()) }
+}
+"#;
+        let functions: Vec<(String, usize)> = extract_symbols("rust", source)
+            .into_iter()
+            .filter(|s| s.kind == SymbolKind::Function)
+            .map(|s| (s.qualified_name(), s.line))
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                ("Server::new".to_string(), 6),
+                ("Server::port".to_string(), 11),
+                ("free_standing".to_string(), 14),
+                ("Client::connect".to_string(), 20),
+                ("nested".to_string(), 21),
+                ("Server::fmt".to_string(), 26),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_doc() {
+        let rust = "// Copyright 2024 Example Corp. All rights reserved.\n\n//! Request routing.\n//!\n//! Maps paths to handlers.\n\nuse std::io;\n";
+        assert_eq!(
+            module_doc("rust", rust).as_deref(),
+            Some("Request routing.\n\nMaps paths to handlers.")
+        );
+        // An item doc is not a module doc
+        assert_eq!(
+            module_doc("rust", "/// A server.\npub struct Server;\n"),
+            None
+        );
+
+        let python = "#!/usr/bin/env python3\n\"\"\"Command line entry point.\n\nParses arguments.\n\"\"\"\nimport sys\n";
+        assert_eq!(
+            module_doc("python", python).as_deref(),
+            Some("Command line entry point.\n\nParses arguments.")
+        );
+
+        let go = "/*\n * Package cache keeps hot blobs.\n */\npackage cache\n";
+        assert_eq!(
+            module_doc("go", go).as_deref(),
+            Some("Package cache keeps hot blobs.")
+        );
+        assert_eq!(module_doc("json", "{}"), None);
+    }
+
+    #[test]
+    fn test_kind_parsing_and_unsupported_language() {
+        assert_eq!(SymbolKind::parse("Function"), Some(SymbolKind::Function));
+        assert_eq!(SymbolKind::parse("widget"), None);
+        assert!(extract_symbols("markdown", "# Title\n").is_empty());
+    }
+}
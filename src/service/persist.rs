@@ -0,0 +1,122 @@
+//! Crash-safe JSON persistence for the on-disk stores.
+//!
+//! Writes go to a temporary file in the target's directory, which is synced
+//! and then renamed over the target, so a crash leaves either the old or
+//! the new contents and never a half-written file. Files that fail to parse
+//! are renamed aside with a `.corrupt-<timestamp>` suffix and treated as
+//! missing, so one bad file doesn't stop startup and isn't overwritten by
+//! the next save.
+
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+use crate::error::Result;
+
+/// Replace `path` with `contents` atomically, creating parent directories.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).await?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()));
+
+    let written = async {
+        let mut file = fs::File::create(&temp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+        fs::rename(&temp, path).await
+    }
+    .await;
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp).await;
+        return Err(e.into());
+    }
+
+    // Persist the rename itself; not every platform can sync a directory
+    if let Ok(dir) = fs::File::open(dir).await {
+        let _ = dir.sync_all().await;
+    }
+    Ok(())
+}
+
+/// Read and parse the JSON file at `path`.
+///
+/// Returns `None` if the file doesn't exist, or if it can't be parsed, in
+/// which case it is quarantined first.
+pub async fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let bytes = match fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            let quarantined = quarantine_path(path);
+            warn!(
+                "Unreadable store {:?} ({}); moved to {:?} and starting empty",
+                path, e, quarantined
+            );
+            fs::rename(path, &quarantined).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Where a corrupt file at `path` is moved.
+fn quarantine_path(path: &Path) -> PathBuf {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{}", stamp));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_write_atomic_replaces_without_leftovers() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested/store.json");
+
+        write_atomic(&path, b"{\"a\":1}").await.unwrap();
+        write_atomic(&path, b"{\"a\":2}").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"a\":2}");
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["store.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_load_json_quarantines_corrupt_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("store.json");
+
+        let missing: Option<HashMap<String, u32>> = load_json(&path).await.unwrap();
+        assert!(missing.is_none());
+
+        std::fs::write(&path, "{\"a\": 1, \"b\"").unwrap();
+        let corrupt: Option<HashMap<String, u32>> = load_json(&path).await.unwrap();
+        assert!(corrupt.is_none());
+        assert!(!path.exists());
+        let quarantined: Vec<String> = std::fs::read_dir(temp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].starts_with("store.json.corrupt-"));
+    }
+}
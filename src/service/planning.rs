@@ -1,6 +1,6 @@
 //! Planning service for AI-powered task planning.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
@@ -8,19 +8,84 @@ use tokio::sync::RwLock;
 use tracing::info;
 use uuid::Uuid;
 
+use crate::clock::{system_clock, SharedClock};
 use crate::error::{Error, Result};
+use crate::service::persist::{load_json, write_atomic};
 use crate::types::planning::*;
 
+/// The dependency loop `step` would close in `steps`, if any, as step ids
+/// starting and ending with `step`.
+fn dependency_cycle(steps: &[Step], step: &Step) -> Option<Vec<u32>> {
+    fn path_to(
+        graph: &HashMap<u32, &[u32]>,
+        from: u32,
+        target: u32,
+        visited: &mut HashSet<u32>,
+        path: &mut Vec<u32>,
+    ) -> bool {
+        path.push(from);
+        if from == target {
+            return true;
+        }
+        if visited.insert(from) {
+            let next = graph.get(&from).copied().unwrap_or_default();
+            if next
+                .iter()
+                .any(|&n| path_to(graph, n, target, visited, path))
+            {
+                return true;
+            }
+        }
+        path.pop();
+        false
+    }
+
+    let mut graph: HashMap<u32, &[u32]> = steps
+        .iter()
+        .map(|s| (s.id, s.dependencies.as_slice()))
+        .collect();
+    graph.insert(step.id, &step.dependencies);
+
+    let mut visited = HashSet::new();
+    let mut path = vec![step.id];
+    step.dependencies
+        .iter()
+        .any(|&d| path_to(&graph, d, step.id, &mut visited, &mut path))
+        .then_some(path)
+}
+
 /// Storage for plans.
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 struct PlanStore {
     plans: HashMap<String, Plan>,
+    /// Snapshots of each plan's definition, oldest first
+    #[serde(default)]
+    versions: HashMap<String, Vec<PlanVersion>>,
+}
+
+impl PlanStore {
+    /// Record the current definition of `plan_id` as its next version.
+    fn snapshot(&mut self, plan_id: &str, now: &str) {
+        let Some(plan) = self.plans.get(plan_id) else {
+            return;
+        };
+        let versions = self.versions.entry(plan_id.to_string()).or_default();
+        let version = versions.last().map_or(1, |v| v.version + 1);
+        versions.push(PlanVersion {
+            version,
+            created_at: now.to_string(),
+            title: plan.title.clone(),
+            description: plan.description.clone(),
+            steps: plan.steps.clone(),
+        });
+    }
 }
 
 /// Planning service for managing plans.
 pub struct PlanningService {
     store: Arc<RwLock<PlanStore>>,
     storage_path: PathBuf,
+    clock: SharedClock,
 }
 
 impl PlanningService {
@@ -34,30 +99,36 @@ impl PlanningService {
         }
 
         // Load existing store
-        let store = if storage_path.exists() {
-            let content = fs::read_to_string(&storage_path).await?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            PlanStore::default()
-        };
+        let store = load_json(&storage_path).await?.unwrap_or_default();
 
         Ok(Self {
             store: Arc::new(RwLock::new(store)),
             storage_path,
+            clock: system_clock(),
         })
     }
 
+    /// Stamp entries with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The clock entries are stamped with.
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
     /// Save the store to disk.
     async fn save(&self) -> Result<()> {
         let store = self.store.read().await;
         let content = serde_json::to_string_pretty(&*store)?;
-        fs::write(&self.storage_path, content).await?;
-        Ok(())
+        write_atomic(&self.storage_path, content.as_bytes()).await
     }
 
     /// Create a new plan.
     pub async fn create_plan(&self, title: String, description: String) -> Result<Plan> {
-        let now = chrono::Utc::now().to_rfc3339();
+        let now = self.clock.now_rfc3339();
         let id = Uuid::new_v4().to_string();
 
         let plan = Plan {
@@ -67,9 +138,10 @@ impl PlanningService {
             status: PlanStatus::Draft,
             steps: Vec::new(),
             created_at: now.clone(),
-            updated_at: now,
+            updated_at: now.clone(),
             metadata: HashMap::new(),
             risk_assessment: None,
+            approvals: Vec::new(),
             estimated_duration: None,
             tags: Vec::new(),
         };
@@ -77,6 +149,7 @@ impl PlanningService {
         {
             let mut store = self.store.write().await;
             store.plans.insert(id.clone(), plan.clone());
+            store.snapshot(&id, &now);
         }
 
         self.save().await?;
@@ -104,7 +177,13 @@ impl PlanningService {
     }
 
     /// Add a step to a plan.
-    pub async fn add_step(&self, plan_id: &str, step: Step) -> Result<Plan> {
+    ///
+    /// A step with id 0 gets the next free id. Every dependency must name a
+    /// step already in the plan, else [`Error::UnknownDependency`], and the
+    /// step must not close a loop, else [`Error::DependencyCycle`]. Only
+    /// paths through the new step are searched, since the rest of the graph
+    /// was checked when its steps were added.
+    pub async fn add_step(&self, plan_id: &str, mut step: Step) -> Result<Plan> {
         let mut store = self.store.write().await;
 
         let plan = store
@@ -112,10 +191,34 @@ impl PlanningService {
             .get_mut(plan_id)
             .ok_or_else(|| Error::PlanNotFound(plan_id.to_string()))?;
 
+        if step.id == 0 {
+            step.id = plan.steps.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        } else if plan.steps.iter().any(|s| s.id == step.id) {
+            return Err(Error::InvalidToolArguments(format!(
+                "Step {} already exists in plan {}",
+                step.id, plan_id
+            )));
+        }
+        if let Some(&dependency) = step
+            .dependencies
+            .iter()
+            .find(|&&d| d != step.id && !plan.steps.iter().any(|s| s.id == d))
+        {
+            return Err(Error::UnknownDependency {
+                step: step.id,
+                dependency,
+            });
+        }
+        if let Some(cycle) = dependency_cycle(&plan.steps, &step) {
+            return Err(Error::DependencyCycle(cycle));
+        }
+
+        let now = self.clock.now_rfc3339();
         plan.steps.push(step);
-        plan.updated_at = chrono::Utc::now().to_rfc3339();
+        plan.updated_at = now.clone();
 
         let plan = plan.clone();
+        store.snapshot(plan_id, &now);
         drop(store);
 
         self.save().await?;
@@ -123,6 +226,11 @@ impl PlanningService {
     }
 
     /// Update step status.
+    ///
+    /// Moves must follow [`StepStatus::can_transition_to`]; others fail with
+    /// [`Error::InvalidStepTransition`]. The check and the update happen
+    /// under one write lock, so of two concurrent moves from the same
+    /// status only the first succeeds.
     pub async fn update_step_status(
         &self,
         plan_id: &str,
@@ -142,12 +250,32 @@ impl PlanningService {
             .find(|s| s.id == step_id)
             .ok_or_else(|| Error::StepNotFound(step_id))?;
 
+        if !step.status.can_transition_to(status) {
+            return Err(Error::InvalidStepTransition {
+                step: step_id,
+                from: step.status.as_str(),
+                to: status.as_str(),
+            });
+        }
         step.status = status;
-        if status == StepStatus::Completed {
-            step.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        let now = self.clock.now();
+        match status {
+            StepStatus::InProgress => step.started_at = Some(now.to_rfc3339()),
+            StepStatus::Completed => {
+                step.completed_at = Some(now.to_rfc3339());
+                if let Some(started) = step
+                    .started_at
+                    .as_deref()
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                {
+                    let seconds = (now - started.with_timezone(&chrono::Utc)).num_seconds();
+                    step.actual_duration = u32::try_from((seconds.max(0) as u64).div_ceil(60)).ok();
+                }
+            }
+            _ => {}
         }
 
-        plan.updated_at = chrono::Utc::now().to_rfc3339();
+        plan.updated_at = self.clock.now_rfc3339();
 
         let plan = plan.clone();
         drop(store);
@@ -156,10 +284,177 @@ impl PlanningService {
         Ok(plan)
     }
 
+    /// Ask for approval of `plan_id`, or of `step_ids` within it.
+    ///
+    /// The request is saved with the plan, so pending approvals survive a
+    /// restart.
+    pub async fn request_approval(
+        &self,
+        plan_id: &str,
+        step_ids: Vec<u32>,
+        requested_by: Option<String>,
+    ) -> Result<ApprovalRequest> {
+        let mut store = self.store.write().await;
+        let plan = store
+            .plans
+            .get_mut(plan_id)
+            .ok_or_else(|| Error::PlanNotFound(plan_id.to_string()))?;
+        if let Some(&missing) = step_ids
+            .iter()
+            .find(|id| !plan.steps.iter().any(|s| s.id == **id))
+        {
+            return Err(Error::StepNotFound(missing));
+        }
+
+        let now = self.clock.now_rfc3339();
+        let request = ApprovalRequest {
+            id: Uuid::new_v4().to_string(),
+            plan_id: plan_id.to_string(),
+            step_ids,
+            state: ApprovalState::Pending,
+            requested_by,
+            requested_at: now.clone(),
+            responded_by: None,
+            responded_at: None,
+            comment: None,
+        };
+        plan.approvals.push(request.clone());
+        plan.updated_at = now;
+        drop(store);
+
+        self.save().await?;
+        info!("Requested approval {} for plan {}", request.id, plan_id);
+        Ok(request)
+    }
+
+    /// Approve or reject the pending request `request_id`.
+    ///
+    /// The covered steps record the decision in their `approval`. Answering
+    /// a request twice fails.
+    pub async fn respond_approval(
+        &self,
+        request_id: &str,
+        approve: bool,
+        responded_by: Option<String>,
+        comment: Option<String>,
+    ) -> Result<ApprovalRequest> {
+        let mut store = self.store.write().await;
+        let plan = store
+            .plans
+            .values_mut()
+            .find(|p| p.approvals.iter().any(|a| a.id == request_id))
+            .ok_or_else(|| Error::ApprovalNotFound(request_id.to_string()))?;
+        let now = self.clock.now_rfc3339();
+        let request = plan
+            .approvals
+            .iter_mut()
+            .find(|a| a.id == request_id)
+            .ok_or_else(|| Error::ApprovalNotFound(request_id.to_string()))?;
+        if request.state != ApprovalState::Pending {
+            return Err(Error::InvalidToolArguments(format!(
+                "Approval request {} is already {}",
+                request_id,
+                request.state.as_str()
+            )));
+        }
+
+        request.state = if approve {
+            ApprovalState::Approved
+        } else {
+            ApprovalState::Rejected
+        };
+        request.responded_by = responded_by;
+        request.responded_at = Some(now.clone());
+        request.comment = comment;
+        let request = request.clone();
+
+        for step in plan
+            .steps
+            .iter_mut()
+            .filter(|s| request.step_ids.contains(&s.id))
+        {
+            step.approval = Some(ApprovalStatus {
+                approved: approve,
+                approved_by: request.responded_by.clone(),
+                approved_at: Some(now.clone()),
+                notes: request.comment.clone(),
+            });
+        }
+        plan.updated_at = now;
+        drop(store);
+
+        self.save().await?;
+        Ok(request)
+    }
+
+    /// Approval requests, oldest first, optionally of one plan or in one
+    /// state.
+    pub async fn list_approvals(
+        &self,
+        plan_id: Option<&str>,
+        state: Option<ApprovalState>,
+    ) -> Vec<ApprovalRequest> {
+        let store = self.store.read().await;
+        let mut approvals: Vec<ApprovalRequest> = store
+            .plans
+            .values()
+            .filter(|p| plan_id.is_none_or(|id| p.id == id))
+            .flat_map(|p| p.approvals.iter())
+            .filter(|a| state.is_none_or(|s| a.state == s))
+            .cloned()
+            .collect();
+        approvals.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        approvals
+    }
+
+    /// Progress of a plan: step counts, weighted completion and an ETA.
+    pub async fn progress(&self, plan_id: &str) -> Result<PlanProgress> {
+        let plan = self
+            .get_plan(plan_id)
+            .await
+            .ok_or_else(|| Error::PlanNotFound(plan_id.to_string()))?;
+        Ok(compute_progress(&plan))
+    }
+
+    /// Differences between versions `from` and `to` of a plan.
+    ///
+    /// A plan gets version 1 when created and a new version each time a
+    /// step is added. Status changes don't create versions.
+    pub async fn compare_versions(&self, plan_id: &str, from: u32, to: u32) -> Result<PlanDiff> {
+        let store = self.store.read().await;
+        if !store.plans.contains_key(plan_id) {
+            return Err(Error::PlanNotFound(plan_id.to_string()));
+        }
+        let versions = store.versions.get(plan_id).map_or(&[][..], Vec::as_slice);
+        let find = |version: u32| {
+            versions
+                .iter()
+                .find(|v| v.version == version)
+                .ok_or_else(|| Error::PlanVersionNotFound {
+                    plan_id: plan_id.to_string(),
+                    version,
+                    latest: versions.last().map_or(0, |v| v.version),
+                })
+        };
+        Ok(diff_versions(plan_id, find(from)?, find(to)?))
+    }
+
+    /// A plan as a GitHub-flavored markdown task list.
+    ///
+    /// See [`plan_to_markdown`].
+    pub async fn export_markdown(&self, plan_id: &str) -> Result<String> {
+        let plan = self
+            .get_plan(plan_id)
+            .await
+            .ok_or_else(|| Error::PlanNotFound(plan_id.to_string()))?;
+        Ok(plan_to_markdown(&plan))
+    }
+
     /// Delete a plan.
     pub async fn delete_plan(&self, id: &str) -> Result<bool> {
         let removed = {
             let mut store = self.store.write().await;
+            store.versions.remove(id);
             store.plans.remove(id).is_some()
         };
 
@@ -172,6 +467,466 @@ impl PlanningService {
     }
 }
 
+/// A change of `field`, if `from` and `to` differ.
+fn field_change(field: &str, from: String, to: String) -> Option<FieldChange> {
+    (from != to).then(|| FieldChange {
+        field: field.to_string(),
+        from,
+        to,
+    })
+}
+
+/// How `old` became `new`, if the step's definition changed.
+fn step_change(old: &Step, new: &Step) -> Option<StepChange> {
+    let step_type = |step: &Step| step.step_type.as_str().to_string();
+    let duration = |step: &Step| {
+        step.estimated_duration
+            .map_or_else(|| "none".to_string(), |d| format!("{} min", d))
+    };
+    let changes: Vec<FieldChange> = [
+        field_change("title", old.title.clone(), new.title.clone()),
+        field_change(
+            "description",
+            old.description.clone(),
+            new.description.clone(),
+        ),
+        field_change("step_type", step_type(old), step_type(new)),
+        field_change(
+            "affected_files",
+            old.affected_files.join(", "),
+            new.affected_files.join(", "),
+        ),
+        field_change("estimated_duration", duration(old), duration(new)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let missing_from = |deps: &[u32], other: &[u32]| -> Vec<u32> {
+        deps.iter()
+            .copied()
+            .filter(|d| !other.contains(d))
+            .collect()
+    };
+    let dependencies_added = missing_from(&new.dependencies, &old.dependencies);
+    let dependencies_removed = missing_from(&old.dependencies, &new.dependencies);
+
+    (!changes.is_empty() || !dependencies_added.is_empty() || !dependencies_removed.is_empty())
+        .then(|| StepChange {
+            id: new.id,
+            title: new.title.clone(),
+            changes,
+            dependencies_added,
+            dependencies_removed,
+        })
+}
+
+/// Differences from version `from` to version `to`, steps matched by id.
+fn diff_versions(plan_id: &str, from: &PlanVersion, to: &PlanVersion) -> PlanDiff {
+    let plan_changes = [
+        field_change("title", from.title.clone(), to.title.clone()),
+        field_change(
+            "description",
+            from.description.clone(),
+            to.description.clone(),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let mut added = Vec::new();
+    let mut modified = Vec::new();
+    for new in &to.steps {
+        match from.steps.iter().find(|old| old.id == new.id) {
+            Some(old) => modified.extend(step_change(old, new)),
+            None => added.push(new.clone()),
+        }
+    }
+    let removed = from
+        .steps
+        .iter()
+        .filter(|old| !to.steps.iter().any(|new| new.id == old.id))
+        .cloned()
+        .collect();
+
+    PlanDiff {
+        plan_id: plan_id.to_string(),
+        from_version: from.version,
+        to_version: to.version,
+        plan_changes,
+        added,
+        removed,
+        modified,
+    }
+}
+
+/// `diff` as a readable summary in the spirit of `git diff`.
+///
+/// Added steps are marked `+`, removed ones `-` and changed ones `~`, with
+/// each changed field on an indented line below.
+pub fn plan_diff_to_text(diff: &PlanDiff) -> String {
+    let mut out = format!(
+        "--- plan {} version {}\n+++ plan {} version {}\n",
+        diff.plan_id, diff.from_version, diff.plan_id, diff.to_version
+    );
+    if diff.is_empty() {
+        out.push_str("No changes\n");
+        return out;
+    }
+    let field_line = |c: &FieldChange| format!("{}: {:?} -> {:?}", c.field, c.from, c.to);
+    let ids = |ids: &[u32]| {
+        ids.iter()
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    for change in &diff.plan_changes {
+        out.push_str(&format!("~ plan {}\n", field_line(change)));
+    }
+    for step in &diff.added {
+        out.push_str(&format!("+ step {}: {}", step.id, step.title));
+        if !step.dependencies.is_empty() {
+            out.push_str(&format!(" (after {})", ids(&step.dependencies)));
+        }
+        out.push('\n');
+    }
+    for step in &diff.removed {
+        out.push_str(&format!("- step {}: {}\n", step.id, step.title));
+    }
+    for step in &diff.modified {
+        out.push_str(&format!("~ step {}: {}\n", step.id, step.title));
+        for change in &step.changes {
+            out.push_str(&format!("    {}\n", field_line(change)));
+        }
+        if !step.dependencies_added.is_empty() {
+            out.push_str(&format!(
+                "    dependencies added: {}\n",
+                ids(&step.dependencies_added)
+            ));
+        }
+        if !step.dependencies_removed.is_empty() {
+            out.push_str(&format!(
+                "    dependencies removed: {}\n",
+                ids(&step.dependencies_removed)
+            ));
+        }
+    }
+    out
+}
+
+/// Step ids in dependency order, ties broken by id.
+///
+/// Dependencies on missing steps are ignored. Steps caught in a cycle, which
+/// only older plans can hold, come last in id order.
+pub fn topological_order(steps: &[Step]) -> Vec<u32> {
+    let ids: HashSet<u32> = steps.iter().map(|s| s.id).collect();
+    let mut waiting: HashMap<u32, usize> = HashMap::new();
+    let mut dependents: HashMap<u32, Vec<u32>> = HashMap::new();
+    for step in steps {
+        let dependencies: HashSet<u32> = step
+            .dependencies
+            .iter()
+            .copied()
+            .filter(|d| ids.contains(d))
+            .collect();
+        waiting.insert(step.id, dependencies.len());
+        for dependency in dependencies {
+            dependents.entry(dependency).or_default().push(step.id);
+        }
+    }
+
+    let mut ready: BTreeSet<u32> = waiting
+        .iter()
+        .filter(|(_, &count)| count == 0)
+        .map(|(&id, _)| id)
+        .collect();
+    let mut order = Vec::with_capacity(steps.len());
+    while let Some(id) = ready.pop_first() {
+        order.push(id);
+        for dependent in dependents.get(&id).into_iter().flatten() {
+            if let Some(count) = waiting.get_mut(dependent) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(*dependent);
+                }
+            }
+        }
+    }
+
+    let mut cyclic: Vec<u32> = waiting
+        .into_iter()
+        .filter(|&(_, count)| count > 0)
+        .map(|(id, _)| id)
+        .collect();
+    cyclic.sort_unstable();
+    order.extend(cyclic);
+    order
+}
+
+/// Which steps a capped plan graph keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TopBy {
+    /// The first steps, in plan order
+    #[default]
+    Order,
+    /// The steps with the most edges, shown in plan order
+    Connections,
+}
+
+impl TopBy {
+    /// Parse a `top_by` argument.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "order" => Ok(Self::Order),
+            "connections" => Ok(Self::Connections),
+            other => Err(Error::InvalidToolArguments(format!(
+                "Unknown top_by '{}': expected order or connections",
+                other
+            ))),
+        }
+    }
+}
+
+/// Caps on the size of a plan graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphLimits {
+    pub max_nodes: usize,
+    pub max_edges: usize,
+    pub top_by: TopBy,
+}
+
+impl Default for GraphLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 50,
+            max_edges: 100,
+            top_by: TopBy::Order,
+        }
+    }
+}
+
+/// `plan` as a Mermaid flowchart, capped at `limits`.
+///
+/// Edges follow the declared step dependencies; a plan without any is drawn
+/// as a chain in step order. When steps or edges are left out, a line after
+/// the diagram says how many are shown.
+pub fn plan_to_mermaid(plan: &Plan, limits: GraphLimits) -> String {
+    let index: HashMap<u32, usize> = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id, i))
+        .collect();
+    let edges: Vec<(usize, usize)> = if plan.steps.iter().any(|s| !s.dependencies.is_empty()) {
+        plan.steps
+            .iter()
+            .enumerate()
+            .flat_map(|(i, step)| {
+                step.dependencies
+                    .iter()
+                    .filter_map(|d| index.get(d))
+                    .map(move |&from| (from, i))
+            })
+            .collect()
+    } else {
+        (1..plan.steps.len()).map(|i| (i - 1, i)).collect()
+    };
+
+    let mut kept: Vec<usize> = (0..plan.steps.len()).collect();
+    if limits.top_by == TopBy::Connections {
+        let mut degree = vec![0usize; plan.steps.len()];
+        for &(from, to) in &edges {
+            degree[from] += 1;
+            degree[to] += 1;
+        }
+        kept.sort_by_key(|&i| std::cmp::Reverse(degree[i]));
+    }
+    kept.truncate(limits.max_nodes);
+    kept.sort_unstable();
+    let shown: Vec<(usize, usize)> = edges
+        .iter()
+        .copied()
+        .filter(|(from, to)| kept.binary_search(from).is_ok() && kept.binary_search(to).is_ok())
+        .take(limits.max_edges)
+        .collect();
+
+    let mut out = format!("# Plan: {}\n\n```mermaid\ngraph TD\n", plan.title);
+    for &i in &kept {
+        let step = &plan.steps[i];
+        let status_icon = match step.status {
+            StepStatus::Completed => "✅",
+            StepStatus::InProgress => "🔄",
+            StepStatus::Failed => "❌",
+            _ => "⬜",
+        };
+        out.push_str(&format!("    S{}[\"{} {}\"]\n", i, status_icon, step.title));
+    }
+    for (from, to) in &shown {
+        out.push_str(&format!("    S{} --> S{}\n", from, to));
+    }
+    out.push_str("```\n");
+    if kept.len() < plan.steps.len() || shown.len() < edges.len() {
+        out.push_str(&format!(
+            "\n_Truncated: showing {} of {} steps and {} of {} edges._\n",
+            kept.len(),
+            plan.steps.len(),
+            shown.len(),
+            edges.len()
+        ));
+    }
+    out
+}
+
+/// `plan` as a GitHub-flavored markdown task list.
+///
+/// Completed and skipped steps are checked. Steps are listed in
+/// [`topological_order`] and each is nested under the dependency that comes
+/// last in it; any other dependencies are named after the title. Step
+/// descriptions follow as indented lines.
+pub fn plan_to_markdown(plan: &Plan) -> String {
+    let order = topological_order(&plan.steps);
+    let position: HashMap<u32, usize> = order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let steps: HashMap<u32, &Step> = plan.steps.iter().map(|s| (s.id, s)).collect();
+
+    let parent_of = |step: &Step| {
+        step.dependencies
+            .iter()
+            .copied()
+            .filter(|d| position.get(d) < position.get(&step.id))
+            .max_by_key(|d| position[d])
+    };
+    let mut children: HashMap<Option<u32>, Vec<u32>> = HashMap::new();
+    for id in &order {
+        children.entry(parent_of(steps[id])).or_default().push(*id);
+    }
+
+    let mut out = format!("# {}\n\n", plan.title);
+    if !plan.description.trim().is_empty() {
+        out.push_str(plan.description.trim());
+        out.push_str("\n\n");
+    }
+    let mut stack: Vec<(u32, usize)> = children
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|&id| (id, 0))
+        .collect();
+    while let Some((id, depth)) = stack.pop() {
+        let step = steps[&id];
+        let indent = "  ".repeat(depth);
+        let checked = matches!(step.status, StepStatus::Completed | StepStatus::Skipped);
+        out.push_str(&format!(
+            "{}- [{}] **{}. {}**",
+            indent,
+            if checked { 'x' } else { ' ' },
+            step.id,
+            step.title
+        ));
+        if !matches!(step.status, StepStatus::Pending | StepStatus::Completed) {
+            out.push_str(&format!(" _({})_", step.status.as_str().replace('_', " ")));
+        }
+        let parent = parent_of(step);
+        let others: Vec<String> = step
+            .dependencies
+            .iter()
+            .filter(|&&d| Some(d) != parent)
+            .map(|d| format!("#{}", d))
+            .collect();
+        if !others.is_empty() {
+            out.push_str(&format!(" (after {})", others.join(", ")));
+        }
+        out.push('\n');
+        for line in step.description.lines().filter(|l| !l.trim().is_empty()) {
+            out.push_str(&format!("{}  {}\n", indent, line.trim()));
+        }
+        for &child in children.get(&Some(id)).into_iter().flatten().rev() {
+            stack.push((child, depth + 1));
+        }
+    }
+    out
+}
+
+/// Step counts, weighted completion and ETA of `plan`.
+///
+/// Steps are weighted by their estimate; steps without one weigh the mean
+/// estimate, or 1 if no step has one. Skipped steps count for nothing. The
+/// ETA is the number of unfinished steps times the mean actual duration of
+/// completed steps.
+fn compute_progress(plan: &Plan) -> PlanProgress {
+    let steps = &plan.steps;
+    let count = |status: StepStatus| steps.iter().filter(|s| s.status == status).count();
+    let finished =
+        |status: StepStatus| matches!(status, StepStatus::Completed | StepStatus::Skipped);
+
+    let estimates: Vec<u32> = steps.iter().filter_map(|s| s.estimated_duration).collect();
+    let default_weight = match estimates.len() {
+        0 => 1.0,
+        n => estimates.iter().map(|&e| e as f64).sum::<f64>() / n as f64,
+    };
+    let weight = |step: &Step| step.estimated_duration.map_or(default_weight, |e| e as f64);
+    let total: f64 = steps
+        .iter()
+        .filter(|s| s.status != StepStatus::Skipped)
+        .map(weight)
+        .sum();
+    let done: f64 = steps
+        .iter()
+        .filter(|s| s.status == StepStatus::Completed)
+        .map(weight)
+        .sum();
+    let progress_percent = if total > 0.0 {
+        (done / total * 1000.0).round() / 10.0
+    } else {
+        0.0
+    };
+
+    let blocked = steps
+        .iter()
+        .filter(|s| !finished(s.status) && s.status != StepStatus::Failed)
+        .filter(|s| {
+            s.status == StepStatus::Blocked
+                || s.dependencies.iter().any(|dep| {
+                    !steps
+                        .iter()
+                        .any(|other| other.id == *dep && finished(other.status))
+                })
+        })
+        .count();
+
+    let durations: Vec<u32> = steps
+        .iter()
+        .filter(|s| s.status == StepStatus::Completed)
+        .filter_map(|s| s.actual_duration)
+        .collect();
+    let remaining = steps.iter().filter(|s| !finished(s.status)).count() as u32;
+    let eta_minutes = (!durations.is_empty()).then(|| {
+        let average = durations.iter().sum::<u32>() as f64 / durations.len() as f64;
+        (average * remaining as f64).round() as u32
+    });
+
+    let (completed, in_progress, failed, skipped) = (
+        count(StepStatus::Completed),
+        count(StepStatus::InProgress),
+        count(StepStatus::Failed),
+        count(StepStatus::Skipped),
+    );
+    PlanProgress {
+        plan_id: plan.id.clone(),
+        title: plan.title.clone(),
+        total_steps: steps.len(),
+        completed,
+        in_progress,
+        failed,
+        skipped,
+        pending: steps.len() - completed - in_progress - failed - skipped,
+        blocked,
+        progress_percent,
+        eta_minutes,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,6 +956,22 @@ mod tests {
         assert!(plan.steps.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_timestamps_come_from_clock() {
+        let (service, _temp) = create_test_service().await;
+        let fixed = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let service = service.with_clock(Arc::new(crate::clock::FixedClock(fixed)));
+
+        let plan = service
+            .create_plan("Plan".to_string(), "Description".to_string())
+            .await
+            .unwrap();
+        assert_eq!(plan.created_at, "2024-05-01T12:00:00+00:00");
+        assert_eq!(plan.updated_at, plan.created_at);
+    }
+
     #[tokio::test]
     async fn test_get_plan() {
         let (service, _temp) = create_test_service().await;
@@ -278,6 +1049,7 @@ mod tests {
             affected_files: Vec::new(),
             estimated_duration: None,
             actual_duration: None,
+            started_at: None,
             completed_at: None,
             error: None,
             output: None,
@@ -311,6 +1083,7 @@ mod tests {
             affected_files: Vec::new(),
             estimated_duration: None,
             actual_duration: None,
+            started_at: None,
             completed_at: None,
             error: None,
             output: None,
@@ -336,6 +1109,450 @@ mod tests {
         assert!(completed.steps[0].completed_at.is_some());
     }
 
+    /// A plan with one pending step, id 1.
+    async fn plan_with_step(service: &PlanningService) -> String {
+        let plan = service
+            .create_plan("Test".to_string(), "Desc".to_string())
+            .await
+            .unwrap();
+        let step = Step {
+            id: 1,
+            title: "Step 1".to_string(),
+            description: "First step".to_string(),
+            status: StepStatus::Pending,
+            step_type: StepType::Implementation,
+            dependencies: Vec::new(),
+            affected_files: Vec::new(),
+            estimated_duration: None,
+            actual_duration: None,
+            started_at: None,
+            completed_at: None,
+            error: None,
+            output: None,
+            requires_approval: false,
+            approval: None,
+            rollback: None,
+            validation: Vec::new(),
+        };
+        service.add_step(&plan.id, step).await.unwrap();
+        plan.id
+    }
+
+    #[tokio::test]
+    async fn test_illegal_step_transitions_are_rejected() {
+        use StepStatus::*;
+        let (service, _temp) = create_test_service().await;
+
+        // (status the step is brought to, illegal move from there)
+        let cases: [(&[StepStatus], StepStatus); 6] = [
+            (&[], Completed),
+            (&[], Failed),
+            (&[InProgress], InProgress),
+            (&[InProgress, Completed], Failed),
+            (&[InProgress, Completed], InProgress),
+            (&[InProgress, Failed], Completed),
+        ];
+        for (path, illegal) in cases {
+            let plan_id = plan_with_step(&service).await;
+            for &status in path {
+                service
+                    .update_step_status(&plan_id, 1, status)
+                    .await
+                    .unwrap();
+            }
+            let err = service
+                .update_step_status(&plan_id, 1, illegal)
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), "invalid_step_transition", "{:?}", illegal);
+            let current = path.last().copied().unwrap_or(Pending);
+            assert_eq!(
+                err.details().unwrap(),
+                serde_json::json!({ "step": 1, "from": current.as_str(), "to": illegal.as_str() })
+            );
+            let plan = service.get_plan(&plan_id).await.unwrap();
+            assert_eq!(plan.steps[0].status, current);
+        }
+
+        // A failed step may be retried through to completion
+        let plan_id = plan_with_step(&service).await;
+        for status in [InProgress, Failed, InProgress, Completed] {
+            service
+                .update_step_status(&plan_id, 1, status)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_completions_apply_once() {
+        let (service, _temp) = create_test_service().await;
+        let plan_id = plan_with_step(&service).await;
+        service
+            .update_step_status(&plan_id, 1, StepStatus::InProgress)
+            .await
+            .unwrap();
+
+        let attempts = (0..8).map(|i| {
+            let status = if i % 2 == 0 {
+                StepStatus::Completed
+            } else {
+                StepStatus::Failed
+            };
+            service.update_step_status(&plan_id, 1, status)
+        });
+        let results = futures::future::join_all(attempts).await;
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .all(|e| e.code() == "invalid_step_transition"));
+    }
+
+    #[tokio::test]
+    async fn test_progress_of_partially_completed_dag() {
+        let (service, _temp) = create_test_service().await;
+        let plan = service
+            .create_plan("DAG".to_string(), "Desc".to_string())
+            .await
+            .unwrap();
+        // 1 <- 2 <- 3 <- 4, and 1 <- 5
+        let steps = [
+            (1, StepStatus::Completed, Some(30), Some(20), vec![]),
+            (2, StepStatus::Completed, Some(10), Some(40), vec![1]),
+            (3, StepStatus::InProgress, Some(20), None, vec![2]),
+            (4, StepStatus::Pending, None, None, vec![3]),
+            (5, StepStatus::Pending, Some(40), None, vec![1]),
+        ];
+        for (id, status, estimate, actual, dependencies) in steps {
+            let step = Step {
+                id,
+                title: format!("Step {}", id),
+                description: String::new(),
+                status,
+                step_type: StepType::Implementation,
+                dependencies,
+                affected_files: Vec::new(),
+                estimated_duration: estimate,
+                actual_duration: actual,
+                started_at: None,
+                completed_at: None,
+                error: None,
+                output: None,
+                requires_approval: false,
+                approval: None,
+                rollback: None,
+                validation: Vec::new(),
+            };
+            service.add_step(&plan.id, step).await.unwrap();
+        }
+
+        let progress = service.progress(&plan.id).await.unwrap();
+        assert_eq!(
+            (progress.completed, progress.in_progress, progress.pending),
+            (2, 1, 2)
+        );
+        // Step 4 weighs the mean estimate, 25: 40 of 125 minutes are done
+        assert_eq!(progress.progress_percent, 32.0);
+        // Only step 4 waits on an unfinished dependency
+        assert_eq!(progress.blocked, 1);
+        // Three steps left at the 30 minute average
+        assert_eq!(progress.eta_minutes, Some(90));
+
+        service
+            .update_step_status(&plan.id, 5, StepStatus::Skipped)
+            .await
+            .unwrap();
+        let progress = service.progress(&plan.id).await.unwrap();
+        assert_eq!(progress.skipped, 1);
+        assert_eq!(progress.progress_percent, 47.1);
+        assert_eq!(progress.eta_minutes, Some(60));
+
+        assert_eq!(
+            service.progress("missing").await.unwrap_err().code(),
+            "plan_not_found"
+        );
+    }
+
+    fn step_depending_on(id: u32, dependencies: Vec<u32>) -> Step {
+        Step {
+            id,
+            title: format!("Step {}", id),
+            description: String::new(),
+            status: StepStatus::Pending,
+            step_type: StepType::Implementation,
+            dependencies,
+            affected_files: Vec::new(),
+            estimated_duration: None,
+            actual_duration: None,
+            started_at: None,
+            completed_at: None,
+            error: None,
+            output: None,
+            requires_approval: false,
+            approval: None,
+            rollback: None,
+            validation: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dangling_dependency_is_rejected() {
+        let (service, _temp) = create_test_service().await;
+        let plan_id = plan_with_step(&service).await;
+
+        let err = service
+            .add_step(&plan_id, step_depending_on(2, vec![1, 7]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "unknown_dependency");
+        assert_eq!(
+            err.details(),
+            Some(serde_json::json!({ "step": 2, "dependency": 7 }))
+        );
+        assert_eq!(service.get_plan(&plan_id).await.unwrap().steps.len(), 1);
+
+        let plan = service
+            .add_step(&plan_id, step_depending_on(0, vec![1]))
+            .await
+            .unwrap();
+        assert_eq!(plan.steps[1].id, 2);
+        let duplicate = service
+            .add_step(&plan_id, step_depending_on(2, vec![]))
+            .await;
+        assert_eq!(duplicate.unwrap_err().code(), "invalid_arguments");
+    }
+
+    #[tokio::test]
+    async fn test_step_closing_a_cycle_is_rejected() {
+        let (service, _temp) = create_test_service().await;
+        let plan_id = plan_with_step(&service).await;
+
+        let own = service
+            .add_step(&plan_id, step_depending_on(2, vec![2]))
+            .await
+            .unwrap_err();
+        assert_eq!(own.code(), "dependency_cycle");
+
+        // Plans saved before validation may already point at missing steps
+        {
+            let mut store = service.store.write().await;
+            let plan = store.plans.get_mut(&plan_id).unwrap();
+            plan.steps.push(step_depending_on(3, vec![1, 4]));
+        }
+        let err = service
+            .add_step(&plan_id, step_depending_on(4, vec![3]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "dependency_cycle");
+        assert_eq!(
+            err.details(),
+            Some(serde_json::json!({ "cycle": [4, 3, 4] }))
+        );
+        assert_eq!(err.to_string(), "Dependency cycle: 4 -> 3 -> 4");
+
+        service
+            .add_step(&plan_id, step_depending_on(4, vec![1]))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_markdown_export_checks_completed_steps() {
+        let (service, _temp) = create_test_service().await;
+        let plan = service
+            .create_plan("Auth".to_string(), "Add login".to_string())
+            .await
+            .unwrap();
+        // 1 <- 2 <- 4, 1 <- 3, and 4 also needs 3
+        let steps = [
+            (1, StepStatus::Completed, vec![], "Create the\nusers table"),
+            (2, StepStatus::InProgress, vec![1], ""),
+            (3, StepStatus::Pending, vec![1], "Hash passwords"),
+            (4, StepStatus::Pending, vec![3, 2], ""),
+            (5, StepStatus::Skipped, vec![], ""),
+        ];
+        for (id, status, dependencies, description) in steps {
+            let mut step = step_depending_on(id, dependencies);
+            step.status = status;
+            step.description = description.to_string();
+            service.add_step(&plan.id, step).await.unwrap();
+        }
+
+        let markdown = service.export_markdown(&plan.id).await.unwrap();
+        assert_eq!(
+            markdown,
+            "# Auth\n\nAdd login\n\n\
+             - [x] **1. Step 1**\n  Create the\n  users table\n\
+             \x20 - [ ] **2. Step 2** _(in progress)_\n\
+             \x20 - [ ] **3. Step 3**\n    Hash passwords\n\
+             \x20   - [ ] **4. Step 4** (after #2)\n\
+             - [x] **5. Step 5** _(skipped)_\n"
+        );
+        assert_eq!(
+            service.export_markdown("missing").await.unwrap_err().code(),
+            "plan_not_found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mermaid_graph_is_capped_and_marks_truncation() {
+        let (service, _temp) = create_test_service().await;
+        let plan = service
+            .create_plan("Deploy".to_string(), String::new())
+            .await
+            .unwrap();
+        // Step 2 is the hub: 1 -> 2, and 2 -> 3, 4, 5
+        for (id, dependencies) in [
+            (1, vec![]),
+            (2, vec![1]),
+            (3, vec![2]),
+            (4, vec![2]),
+            (5, vec![2]),
+        ] {
+            service
+                .add_step(&plan.id, step_depending_on(id, dependencies))
+                .await
+                .unwrap();
+        }
+        let plan = service.get_plan(&plan.id).await.unwrap();
+
+        let full = plan_to_mermaid(&plan, GraphLimits::default());
+        assert!(full.contains("    S1 --> S4\n"));
+        assert!(!full.contains("Truncated"));
+
+        let by_connections = plan_to_mermaid(
+            &plan,
+            GraphLimits {
+                max_nodes: 2,
+                max_edges: 10,
+                top_by: TopBy::Connections,
+            },
+        );
+        assert!(by_connections.contains("S1[\"⬜ Step 2\"]"));
+        assert_eq!(by_connections.matches("-->").count(), 1);
+        assert!(by_connections.ends_with("_Truncated: showing 2 of 5 steps and 1 of 4 edges._\n"));
+
+        let by_order = plan_to_mermaid(
+            &plan,
+            GraphLimits {
+                max_nodes: 5,
+                max_edges: 2,
+                top_by: TopBy::Order,
+            },
+        );
+        assert!(by_order.contains("S0 --> S1") && by_order.contains("S1 --> S2"));
+        assert!(!by_order.contains("S1 --> S3"));
+        assert!(by_order.contains("showing 5 of 5 steps and 2 of 4 edges"));
+    }
+
+    #[tokio::test]
+    async fn test_version_diff_labels_added_and_removed_steps() {
+        let (service, _temp) = create_test_service().await;
+        let plan_id = plan_with_step(&service).await;
+        service
+            .add_step(&plan_id, step_depending_on(2, vec![1]))
+            .await
+            .unwrap();
+
+        // Version 1 is the empty plan, 2 adds step 1 and 3 adds step 2
+        let diff = service.compare_versions(&plan_id, 2, 3).await.unwrap();
+        assert_eq!(diff.added.iter().map(|s| s.id).collect::<Vec<_>>(), vec![2]);
+        assert!(diff.removed.is_empty() && diff.modified.is_empty());
+        let text = plan_diff_to_text(&diff);
+        assert!(text.contains("+ step 2: Step 2 (after #1)\n"), "{}", text);
+
+        let back = service.compare_versions(&plan_id, 3, 1).await.unwrap();
+        assert!(back.added.is_empty());
+        let text = plan_diff_to_text(&back);
+        assert!(text.contains("- step 1: Step 1\n"), "{}", text);
+        assert!(text.contains("- step 2: Step 2\n"), "{}", text);
+        assert!(!text.contains("+ step"), "{}", text);
+
+        {
+            let mut store = service.store.write().await;
+            let plan = store.plans.get_mut(&plan_id).unwrap();
+            plan.steps[0].title = "Set up".to_string();
+            plan.steps[1].dependencies.clear();
+            store.snapshot(&plan_id, "2026-01-01T00:00:00Z");
+        }
+        let text = plan_diff_to_text(&service.compare_versions(&plan_id, 3, 4).await.unwrap());
+        assert_eq!(
+            text,
+            format!(
+                "--- plan {0} version 3\n+++ plan {0} version 4\n\
+                 ~ step 1: Set up\n    title: \"Step 1\" -> \"Set up\"\n\
+                 ~ step 2: Step 2\n    dependencies removed: #1\n",
+                plan_id
+            )
+        );
+
+        let err = service.compare_versions(&plan_id, 1, 9).await.unwrap_err();
+        assert_eq!(err.code(), "plan_version_not_found");
+        assert_eq!(
+            err.details(),
+            Some(serde_json::json!({ "version": 9, "latest": 4 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_approval_survives_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let (plan_id, request_id) = {
+            let service = PlanningService::new(temp_dir.path()).await.unwrap();
+            let plan_id = plan_with_step(&service).await;
+            let request = service
+                .request_approval(&plan_id, vec![1], Some("agent".to_string()))
+                .await
+                .unwrap();
+            assert_eq!(
+                service
+                    .request_approval(&plan_id, vec![9], None)
+                    .await
+                    .unwrap_err()
+                    .code(),
+                "step_not_found"
+            );
+            (plan_id, request.id)
+        };
+
+        let service = PlanningService::new(temp_dir.path()).await.unwrap();
+        let pending = service
+            .list_approvals(None, Some(ApprovalState::Pending))
+            .await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, request_id);
+        assert_eq!(pending[0].plan_id, plan_id);
+        assert_eq!(pending[0].step_ids, vec![1]);
+        assert_eq!(pending[0].requested_by.as_deref(), Some("agent"));
+
+        let answered = service
+            .respond_approval(
+                &request_id,
+                false,
+                Some("reviewer".to_string()),
+                Some("needs tests".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(answered.state, ApprovalState::Rejected);
+        assert!(service
+            .list_approvals(Some(&plan_id), Some(ApprovalState::Pending))
+            .await
+            .is_empty());
+        let step = &service.get_plan(&plan_id).await.unwrap().steps[0];
+        let approval = step.approval.as_ref().unwrap();
+        assert!(!approval.approved);
+        assert_eq!(approval.notes.as_deref(), Some("needs tests"));
+
+        let again = service
+            .respond_approval(&request_id, true, None, None)
+            .await;
+        assert_eq!(again.unwrap_err().code(), "invalid_arguments");
+        let missing = service.respond_approval("nope", true, None, None).await;
+        assert_eq!(missing.unwrap_err().code(), "approval_not_found");
+    }
+
     #[tokio::test]
     async fn test_delete_plan() {
         let (service, _temp) = create_test_service().await;
@@ -395,6 +1612,7 @@ mod tests {
             affected_files: Vec::new(),
             estimated_duration: None,
             actual_duration: None,
+            started_at: None,
             completed_at: None,
             error: None,
             output: None,
@@ -0,0 +1,172 @@
+//! Cache for semantic search results.
+//!
+//! Agents often repeat the same `search` call within a few seconds, and
+//! every call costs a backend round trip. Results are cached by query and
+//! token budget for a short time-to-live, and the least recently used entry
+//! is evicted once the cache is full. Any change to the index clears it, so
+//! a hit never returns results from before a reindex.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Default number of cached queries.
+pub const DEFAULT_CAPACITY: usize = 128;
+
+/// Default time-to-live of a cached result.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+type Key = (String, Option<usize>);
+
+#[derive(Debug, Clone)]
+struct Entry {
+    result: String,
+    inserted: Instant,
+    /// Value of the use counter when the entry was last read or written
+    last_used: u64,
+}
+
+/// An LRU cache of search results with a time-to-live.
+///
+/// A capacity of zero disables caching.
+#[derive(Debug, Clone)]
+pub struct SearchCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<Key, Entry>,
+    uses: u64,
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY, DEFAULT_TTL)
+    }
+}
+
+impl SearchCache {
+    /// Create a cache holding up to `capacity` results for `ttl` each.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    /// The cached result for `query` and `max_tokens`, if still fresh.
+    ///
+    /// Records a hit or miss metric unless the cache is disabled.
+    pub fn get(&mut self, query: &str, max_tokens: Option<usize>) -> Option<String> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let key = (query.to_string(), max_tokens);
+        let expired = self
+            .entries
+            .get(&key)
+            .is_some_and(|entry| entry.inserted.elapsed() >= self.ttl);
+        if expired {
+            self.entries.remove(&key);
+        }
+
+        self.uses += 1;
+        let uses = self.uses;
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = uses;
+                metrics::counter!("context_engine_search_cache_hits_total").increment(1);
+                Some(entry.result.clone())
+            }
+            None => {
+                metrics::counter!("context_engine_search_cache_misses_total").increment(1);
+                None
+            }
+        }
+    }
+
+    /// Cache `result` for `query` and `max_tokens`, evicting the least
+    /// recently used entry if the cache is full.
+    pub fn insert(&mut self, query: &str, max_tokens: Option<usize>, result: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (query.to_string(), max_tokens);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.uses += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                result,
+                inserted: Instant::now(),
+                last_used: self.uses,
+            },
+        );
+    }
+
+    /// Drop every cached result.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of cached results, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = SearchCache::new(2, DEFAULT_TTL);
+        cache.insert("a", None, "A".to_string());
+        cache.insert("b", None, "B".to_string());
+        assert_eq!(cache.get("a", None).as_deref(), Some("A"));
+
+        // "b" is now the least recently used
+        cache.insert("c", None, "C".to_string());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b", None), None);
+        assert_eq!(cache.get("a", None).as_deref(), Some("A"));
+        assert_eq!(cache.get("c", None).as_deref(), Some("C"));
+    }
+
+    #[test]
+    fn test_key_includes_token_budget() {
+        let mut cache = SearchCache::new(4, DEFAULT_TTL);
+        cache.insert("q", Some(100), "short".to_string());
+        assert_eq!(cache.get("q", Some(200)), None);
+        assert_eq!(cache.get("q", None), None);
+        assert_eq!(cache.get("q", Some(100)).as_deref(), Some("short"));
+    }
+
+    #[test]
+    fn test_expired_and_disabled() {
+        let mut cache = SearchCache::new(4, Duration::ZERO);
+        cache.insert("q", None, "r".to_string());
+        assert_eq!(cache.get("q", None), None);
+        assert!(cache.is_empty());
+
+        let mut disabled = SearchCache::new(0, DEFAULT_TTL);
+        disabled.insert("q", None, "r".to_string());
+        assert_eq!(disabled.get("q", None), None);
+        assert!(disabled.is_empty());
+    }
+}
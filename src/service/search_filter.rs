@@ -0,0 +1,135 @@
+//! Scoping semantic search results by language and path.
+//!
+//! The retrieval backend has no notion of filters, so a filter is passed to
+//! it as a hint appended to the query and then enforced on the returned
+//! snippets. Languages are detected from snippet paths alone.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::service::bundle::parse_snippets;
+use crate::service::language::{filename_to_language, language_matches_hint};
+
+/// Restricts search results to a language and/or a path glob.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    language: Option<String>,
+    path_glob: Option<(String, GlobMatcher)>,
+}
+
+impl SearchFilter {
+    /// Create a filter; `None` leaves that dimension unfiltered.
+    ///
+    /// A glob without `/` matches file names in any directory, so `*.rs`
+    /// matches `src/lib.rs`.
+    pub fn new(language: Option<&str>, path_glob: Option<&str>) -> Result<Self> {
+        let path_glob = match path_glob.map(str::trim).filter(|g| !g.is_empty()) {
+            Some(glob) => {
+                let pattern = if glob.contains('/') {
+                    glob.trim_start_matches("./").to_string()
+                } else {
+                    format!("**/{}", glob)
+                };
+                let matcher = GlobBuilder::new(&pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| {
+                        Error::InvalidToolArguments(format!("Invalid path glob '{}': {}", glob, e))
+                    })?
+                    .compile_matcher();
+                Some((glob.to_string(), matcher))
+            }
+            None => None,
+        };
+        Ok(Self {
+            language: language
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string),
+            path_glob,
+        })
+    }
+
+    /// Whether the filter lets everything through.
+    pub fn is_empty(&self) -> bool {
+        self.language.is_none() && self.path_glob.is_none()
+    }
+
+    /// Whether a workspace-relative `path` passes the filter.
+    pub fn matches(&self, path: &str) -> bool {
+        let language_ok = self.language.as_deref().is_none_or(|hint| {
+            filename_to_language(Path::new(path))
+                .is_some_and(|language| language_matches_hint(language, hint))
+        });
+        let path_ok = self
+            .path_glob
+            .as_ref()
+            .is_none_or(|(_, matcher)| matcher.is_match(path));
+        language_ok && path_ok
+    }
+
+    /// `query` with the filter described for the backend.
+    pub fn hint_query(&self, query: &str) -> String {
+        let mut scope = Vec::new();
+        if let Some(language) = &self.language {
+            scope.push(format!("language: {}", language));
+        }
+        if let Some((glob, _)) = &self.path_glob {
+            scope.push(format!("paths: {}", glob));
+        }
+        if scope.is_empty() {
+            query.to_string()
+        } else {
+            format!("{} ({})", query, scope.join(", "))
+        }
+    }
+
+    /// Keep only the snippets of a formatted retrieval result that pass.
+    pub fn apply(&self, retrieved: &str) -> String {
+        if self.is_empty() {
+            return retrieved.to_string();
+        }
+        parse_snippets(retrieved)
+            .iter()
+            .filter(|snippet| self.matches(&snippet.path))
+            .map(|snippet| snippet.render())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_glob_scopes_directories() {
+        let filter = SearchFilter::new(None, Some("src/auth/**/*.rs")).unwrap();
+        assert!(filter.matches("src/auth/token.rs"));
+        assert!(filter.matches("src/auth/oauth/client.rs"));
+        assert!(!filter.matches("src/db/auth.rs"));
+        assert!(!filter.matches("src/auth/README.md"));
+
+        let by_name = SearchFilter::new(None, Some("*.rs")).unwrap();
+        assert!(by_name.matches("src/lib.rs"));
+        assert!(by_name.matches("lib.rs"));
+
+        assert!(SearchFilter::new(None, Some("src/[")).is_err());
+    }
+
+    #[test]
+    fn test_language_filter_drops_other_snippets() {
+        let filter = SearchFilter::new(Some("rust"), None).unwrap();
+        let retrieved = "Path: src/auth.rs\nfn login() {}\n\nPath: web/auth.ts\nfunction login() {}\n\nPath: Makefile\nall:";
+        assert_eq!(filter.apply(retrieved), "Path: src/auth.rs\nfn login() {}");
+        assert_eq!(
+            filter.hint_query("find the auth logic"),
+            "find the auth logic (language: rust)"
+        );
+
+        let none = SearchFilter::new(None, Some("  ")).unwrap();
+        assert!(none.is_empty());
+        assert_eq!(none.apply(retrieved), retrieved);
+    }
+}
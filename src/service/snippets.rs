@@ -0,0 +1,121 @@
+//! Content-addressed store of recent search hits.
+//!
+//! Search results are snippets chosen by the backend, not file ranges, so
+//! they can't be re-read from disk. Each snippet handed to a client is kept
+//! here under a hash of its path and text, which is what its resource URI
+//! names; reading the URI back returns exactly what the search returned.
+//! The store is bounded and forgets the oldest snippets first.
+
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+
+use crate::service::bundle::Snippet;
+
+/// Default number of remembered snippets.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Hex characters of the SHA-256 kept in a snippet id.
+const ID_LEN: usize = 32;
+
+/// The content address of a snippet: a hash of its path and text.
+pub fn snippet_id(path: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    let mut id = hex::encode(hasher.finalize());
+    id.truncate(ID_LEN);
+    id
+}
+
+/// A bounded map from snippet id to snippet, oldest evicted first.
+#[derive(Debug, Clone)]
+pub struct SnippetStore {
+    capacity: usize,
+    snippets: HashMap<String, Snippet>,
+    order: VecDeque<String>,
+}
+
+impl Default for SnippetStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl SnippetStore {
+    /// Create a store holding up to `capacity` snippets.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            snippets: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Remember `snippet`, returning its id.
+    ///
+    /// Storing a snippet again makes it the most recent one.
+    pub fn insert(&mut self, snippet: &Snippet) -> String {
+        let id = snippet_id(&snippet.path, &snippet.content);
+        if self.capacity == 0 {
+            return id;
+        }
+        if self.snippets.contains_key(&id) {
+            self.order.retain(|known| known != &id);
+        } else {
+            while self.order.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.snippets.remove(&oldest);
+                }
+            }
+            self.snippets.insert(id.clone(), snippet.clone());
+        }
+        self.order.push_back(id.clone());
+        id
+    }
+
+    /// The snippet with id `id`, if still remembered.
+    pub fn get(&self, id: &str) -> Option<&Snippet> {
+        self.snippets.get(id)
+    }
+
+    /// Remembered snippets with their ids, most recent first.
+    pub fn recent(&self) -> Vec<(String, Snippet)> {
+        self.order
+            .iter()
+            .rev()
+            .filter_map(|id| Some((id.clone(), self.snippets.get(id)?.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(path: &str, content: &str) -> Snippet {
+        Snippet {
+            path: path.to_string(),
+            content: content.to_string(),
+            relevance: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_ids_address_content_and_oldest_is_evicted() {
+        let mut store = SnippetStore::new(2);
+        let a = store.insert(&snippet("a.rs", "fn a() {}"));
+        let b = store.insert(&snippet("b.rs", "fn b() {}"));
+        assert_eq!(a, snippet_id("a.rs", "fn a() {}"));
+        assert_eq!(a.len(), ID_LEN);
+        assert_ne!(a, snippet_id("b.rs", "fn a() {}"));
+
+        // Re-inserting refreshes a snippet, so b is evicted instead
+        assert_eq!(store.insert(&snippet("a.rs", "fn a() {}")), a);
+        let c = store.insert(&snippet("c.rs", "fn c() {}"));
+        assert!(store.get(&b).is_none());
+        assert_eq!(store.get(&a).unwrap().path, "a.rs");
+        let ids: Vec<String> = store.recent().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![c, a]);
+    }
+}
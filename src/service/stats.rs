@@ -0,0 +1,288 @@
+//! Workspace statistics: files, lines and bytes per language.
+//!
+//! Per-language totals also split lines into code, comment and blank lines
+//! (see [`crate::service::loc`]), which gives a more honest lines-of-code
+//! figure than the raw line count.
+//!
+//! Computing stats reads every indexable file, so per-file results are
+//! cached and only files that changed since the last run are read again.
+//! [`ChangeDetection`] decides what "changed" means:
+//!
+//! - `mtime` compares modification time and size. Cheap, but a checkout
+//!   that rewrites every file (common in CI) makes everything look changed,
+//!   and a same-size edit within the timestamp resolution can go unnoticed.
+//! - `hash` reads each file and compares its blob name, the same content
+//!   hash used for indexing. Slower, but only real content changes count.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+
+use crate::sdk::blob::BlobNameCalculator;
+use crate::service::language::detect_language;
+use crate::service::loc::count_lines;
+
+/// Language reported for files no language could be detected for.
+pub const UNKNOWN_LANGUAGE: &str = "other";
+
+/// How the stats cache decides whether a file changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeDetection {
+    /// Modification time and size
+    #[default]
+    Mtime,
+    /// Content hash
+    Hash,
+}
+
+/// Totals for one language.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LanguageStats {
+    pub files: usize,
+    pub lines: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    pub bytes: u64,
+}
+
+impl LanguageStats {
+    fn add(&mut self, other: &LanguageStats) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code_lines += other.code_lines;
+        self.comment_lines += other.comment_lines;
+        self.blank_lines += other.blank_lines;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Totals for the workspace.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkspaceStats {
+    pub files: usize,
+    pub lines: usize,
+    pub bytes: u64,
+    /// Per-language totals, keyed by language name
+    pub languages: BTreeMap<String, LanguageStats>,
+    /// Files read on this run because they were new or changed
+    pub rescanned: usize,
+}
+
+/// What a cached entry was computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Fingerprint {
+    Modified { mtime: SystemTime, len: u64 },
+    Hash(String),
+}
+
+#[derive(Debug, Clone)]
+struct CachedFile {
+    fingerprint: Fingerprint,
+    language: &'static str,
+    stats: LanguageStats,
+}
+
+/// Per-file stats from previous runs.
+#[derive(Debug)]
+pub struct StatsCache {
+    detection: ChangeDetection,
+    files: HashMap<PathBuf, CachedFile>,
+    blobs: BlobNameCalculator,
+}
+
+impl StatsCache {
+    /// Create an empty cache using `detection` to spot changed files.
+    pub fn new(detection: ChangeDetection) -> Self {
+        Self {
+            detection,
+            files: HashMap::new(),
+            blobs: BlobNameCalculator::default_size(),
+        }
+    }
+
+    /// Compute stats for `files` (workspace-relative path, absolute path),
+    /// reading only files that changed since the last call.
+    ///
+    /// Files missing from `files` are dropped from the cache; unreadable
+    /// files are skipped.
+    pub fn refresh(&mut self, files: &[(String, PathBuf)]) -> WorkspaceStats {
+        let mut stats = WorkspaceStats::default();
+        let mut files_now = HashMap::with_capacity(files.len());
+
+        for (relative, path) in files {
+            let cached = self.files.remove(path);
+            let entry = match self.check(relative, path, cached) {
+                Some((entry, rescanned)) => {
+                    stats.rescanned += usize::from(rescanned);
+                    entry
+                }
+                None => continue,
+            };
+
+            stats.files += entry.stats.files;
+            stats.lines += entry.stats.lines;
+            stats.bytes += entry.stats.bytes;
+            stats
+                .languages
+                .entry(entry.language.to_string())
+                .or_default()
+                .add(&entry.stats);
+            files_now.insert(path.clone(), entry);
+        }
+
+        self.files = files_now;
+        stats
+    }
+
+    /// The entry for `path`, reusing `cached` if the file is unchanged.
+    ///
+    /// Returns whether the file had to be counted again.
+    fn check(
+        &self,
+        relative: &str,
+        path: &Path,
+        cached: Option<CachedFile>,
+    ) -> Option<(CachedFile, bool)> {
+        let read = || {
+            std::fs::read(path)
+                .map_err(|e| debug!("Failed to read {:?}: {}", path, e))
+                .ok()
+        };
+
+        let (fingerprint, contents) = match self.detection {
+            ChangeDetection::Mtime => {
+                let meta = std::fs::metadata(path).ok()?;
+                let fingerprint = Fingerprint::Modified {
+                    mtime: meta.modified().ok()?,
+                    len: meta.len(),
+                };
+                (fingerprint, None)
+            }
+            ChangeDetection::Hash => {
+                let contents = read()?;
+                let name = self.blobs.calculate_no_throw(relative, &contents);
+                (Fingerprint::Hash(name), Some(contents))
+            }
+        };
+
+        if let Some(cached) = cached.filter(|c| c.fingerprint == fingerprint) {
+            return Some((cached, false));
+        }
+
+        let contents = match contents {
+            Some(contents) => contents,
+            None => read()?,
+        };
+        let text = String::from_utf8_lossy(&contents);
+        let language = detect_language(path, &text).unwrap_or(UNKNOWN_LANGUAGE);
+        let counts = count_lines(&text, language);
+        let entry = CachedFile {
+            fingerprint,
+            language,
+            stats: LanguageStats {
+                files: 1,
+                lines: text.lines().count(),
+                code_lines: counts.code,
+                comment_lines: counts.comment,
+                blank_lines: counts.blank,
+                bytes: contents.len() as u64,
+            },
+        };
+        Some((entry, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, contents: &str) -> (String, PathBuf) {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        (name.to_string(), path)
+    }
+
+    /// Move a file's mtime forward without changing its content.
+    fn touch(path: &Path) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_stats_by_language() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            write(&dir, "main.rs", "fn main() {}\n"),
+            write(&dir, "lib.rs", "pub mod a;\npub mod b;\n"),
+            write(&dir, "app.py", "print('hi')\n"),
+        ];
+
+        let stats = StatsCache::new(ChangeDetection::Mtime).refresh(&files);
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.lines, 4);
+        assert_eq!(stats.rescanned, 3);
+        assert_eq!(stats.languages["rust"].files, 2);
+        assert_eq!(stats.languages["rust"].lines, 3);
+        assert_eq!(stats.languages["python"].files, 1);
+    }
+
+    #[test]
+    fn test_code_comment_blank_lines() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            write(
+                &dir,
+                "main.rs",
+                "/* Licensed under MIT */\n\n// Entry point\nfn main() {}\n",
+            ),
+            write(&dir, "lib.rs", "/// Docs\npub mod a;\n"),
+        ];
+
+        let stats = StatsCache::new(ChangeDetection::Mtime).refresh(&files);
+        let rust = &stats.languages["rust"];
+        assert_eq!(rust.lines, 6);
+        assert_eq!(rust.code_lines, 2);
+        assert_eq!(rust.comment_lines, 3);
+        assert_eq!(rust.blank_lines, 1);
+    }
+
+    #[test]
+    fn test_hash_mode_ignores_touched_files() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![write(&dir, "main.rs", "fn main() {}\n")];
+
+        let mut by_hash = StatsCache::new(ChangeDetection::Hash);
+        let mut by_mtime = StatsCache::new(ChangeDetection::Mtime);
+        assert_eq!(by_hash.refresh(&files).rescanned, 1);
+        assert_eq!(by_mtime.refresh(&files).rescanned, 1);
+
+        touch(&files[0].1);
+        assert_eq!(by_hash.refresh(&files).rescanned, 0);
+        assert_eq!(by_mtime.refresh(&files).rescanned, 1);
+
+        // A real edit is picked up either way
+        std::fs::write(&files[0].1, "fn main() {}\nfn other() {}\n").unwrap();
+        let stats = by_hash.refresh(&files);
+        assert_eq!(stats.rescanned, 1);
+        assert_eq!(stats.lines, 2);
+    }
+
+    #[test]
+    fn test_removed_files_drop_out() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![write(&dir, "a.rs", "a\n"), write(&dir, "b.rs", "b\n")];
+        let mut cache = StatsCache::new(ChangeDetection::Mtime);
+        cache.refresh(&files);
+
+        let stats = cache.refresh(&files[..1]);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.rescanned, 0);
+    }
+}
@@ -0,0 +1,103 @@
+//! Token counting.
+//!
+//! Budgets are expressed in tokens, but the exact count depends on the
+//! model's tokenizer. [`TokenEstimator`] hides that choice: the default
+//! [`CharHeuristic`] is cheap and dependency-free, and a real tokenizer can
+//! be plugged in with [`ContextService::with_token_estimator`].
+//!
+//! [`ContextService::with_token_estimator`]: crate::service::ContextService::with_token_estimator
+
+use std::fmt::Debug;
+
+/// Default characters-per-token ratio for [`CharHeuristic`].
+pub const DEFAULT_CHARS_PER_TOKEN: usize = 4;
+
+/// Counts tokens and trims text to a token budget.
+pub trait TokenEstimator: Debug + Send + Sync {
+    /// Estimated number of tokens in `text`.
+    fn estimate(&self, text: &str) -> usize;
+
+    /// Longest prefix of `text` that fits in `max_tokens`, on a char boundary.
+    ///
+    /// The default binary-searches char boundaries with
+    /// [`estimate`](Self::estimate), which must not decrease as text grows.
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        if self.estimate(text) <= max_tokens {
+            return text;
+        }
+        let boundaries: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        let fits = boundaries.partition_point(|&end| self.estimate(&text[..end]) <= max_tokens);
+        match fits {
+            0 => "",
+            n => &text[..boundaries[n - 1]],
+        }
+    }
+}
+
+/// Estimates tokens as a fixed number of characters per token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharHeuristic {
+    pub chars_per_token: usize,
+}
+
+impl Default for CharHeuristic {
+    fn default() -> Self {
+        Self {
+            chars_per_token: DEFAULT_CHARS_PER_TOKEN,
+        }
+    }
+}
+
+impl TokenEstimator for CharHeuristic {
+    fn estimate(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.chars_per_token.max(1))
+    }
+
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let max_chars = max_tokens.saturating_mul(self.chars_per_token.max(1));
+        match text.char_indices().nth(max_chars) {
+            Some((idx, _)) => &text[..idx],
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in for a real tokenizer: one token per whitespace-separated word.
+    #[derive(Debug)]
+    struct WordTokenizer;
+
+    impl TokenEstimator for WordTokenizer {
+        fn estimate(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn test_char_heuristic() {
+        let tokens = CharHeuristic::default();
+        assert_eq!(tokens.estimate(""), 0);
+        assert_eq!(tokens.estimate("abcd"), 1);
+        assert_eq!(tokens.estimate("abcde"), 2);
+        assert_eq!(tokens.estimate("ééééé"), 2);
+
+        assert_eq!(tokens.truncate("abcdefgh", 1), "abcd");
+        assert_eq!(tokens.truncate("abc", 10), "abc");
+        assert_eq!(tokens.truncate("ééééé", 1), "éééé");
+
+        let coarse = CharHeuristic { chars_per_token: 2 };
+        assert_eq!(coarse.estimate("abcde"), 3);
+    }
+
+    #[test]
+    fn test_default_truncate_with_custom_tokenizer() {
+        let tokens = WordTokenizer;
+        assert_eq!(tokens.estimate("fn main() {}"), 3);
+        assert_eq!(tokens.truncate("one two three four", 2), "one two ");
+        assert_eq!(tokens.truncate("one two", 5), "one two");
+        assert_eq!(tokens.truncate("one two", 0), "");
+    }
+}
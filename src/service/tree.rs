@@ -0,0 +1,311 @@
+//! Workspace directory tree.
+//!
+//! Lists the workspace as a nested tree down to a maximum depth, skipping
+//! excluded paths the same way indexing does. Every directory reports how
+//! many files it holds at any depth, including files below the depth limit,
+//! so a collapsed directory still shows its size. The number of listed
+//! entries is capped; directories whose listing was cut short are marked.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::service::exclude::ExcludeMatcher;
+
+/// Default depth listed below the tree root.
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Default cap on listed entries.
+pub const DEFAULT_MAX_ENTRIES: usize = 500;
+
+/// How far and how wide to list.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeOptions {
+    /// Levels listed below the root; entries deeper than this are only counted
+    pub max_depth: usize,
+    /// Entries listed in total before the listing stops
+    pub max_entries: usize,
+    /// Follow symlinks that stay inside the workspace
+    pub follow_symlinks: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            follow_symlinks: false,
+        }
+    }
+}
+
+/// A file or directory in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreeNode {
+    pub name: String,
+    /// Workspace-relative path; empty for the workspace root
+    pub path: String,
+    pub is_dir: bool,
+    /// Files anywhere below a directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_count: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+    /// Some children were left out because the entry cap was reached
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// A listed tree.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tree {
+    pub root: TreeNode,
+    /// Entries listed, excluding the root
+    pub entries: usize,
+    /// Whether the entry cap cut the listing short
+    pub truncated: bool,
+}
+
+impl Tree {
+    /// The tree as indented text, one entry per line.
+    ///
+    /// Directories end in `/` and show their file count.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        render_node(&self.root, 0, &mut out);
+        if self.truncated {
+            out.push_str(&format!("... truncated after {} entries\n", self.entries));
+        }
+        out
+    }
+}
+
+fn render_node(node: &TreeNode, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let name = if node.path.is_empty() {
+        "."
+    } else {
+        &node.name
+    };
+    if node.is_dir {
+        let files = node.file_count.unwrap_or(0);
+        let unit = if files == 1 { "file" } else { "files" };
+        out.push_str(&format!("{}{}/ ({} {})\n", indent, name, files, unit));
+    } else {
+        out.push_str(&format!("{}{}\n", indent, name));
+    }
+    for child in &node.children {
+        render_node(child, depth + 1, out);
+    }
+    if node.truncated {
+        out.push_str(&format!("{}  ...\n", indent));
+    }
+}
+
+/// List the directory `dir` of `workspace`.
+///
+/// `excludes` is matched against workspace-relative paths. Blocking; run it
+/// off the async runtime.
+pub fn build_tree(
+    workspace: &Path,
+    dir: &Path,
+    excludes: &ExcludeMatcher,
+    options: TreeOptions,
+) -> Tree {
+    let mut walker = Walker {
+        workspace,
+        root: workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf()),
+        excludes,
+        options,
+        entries: 0,
+        truncated: false,
+        visited: HashSet::new(),
+    };
+    let relative = dir.strip_prefix(workspace).unwrap_or(dir);
+    let root = walker.walk(dir, relative, 0);
+    Tree {
+        root,
+        entries: walker.entries,
+        truncated: walker.truncated,
+    }
+}
+
+struct Walker<'a> {
+    workspace: &'a Path,
+    root: PathBuf,
+    excludes: &'a ExcludeMatcher,
+    options: TreeOptions,
+    entries: usize,
+    truncated: bool,
+    /// Canonical directories already walked, so symlink cycles terminate
+    visited: HashSet<PathBuf>,
+}
+
+impl Walker<'_> {
+    /// The node for directory `dir` at `depth`, listing children while
+    /// `depth < max_depth` and counting files at every depth.
+    fn walk(&mut self, dir: &Path, relative: &Path, depth: usize) -> TreeNode {
+        let mut node = TreeNode {
+            name: relative
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: relative.to_string_lossy().to_string(),
+            is_dir: true,
+            file_count: Some(0),
+            children: Vec::new(),
+            truncated: false,
+        };
+        if !dir
+            .canonicalize()
+            .is_ok_and(|canonical| self.visited.insert(canonical))
+        {
+            return node;
+        }
+
+        let mut entries: Vec<(PathBuf, bool)> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let relative = path.strip_prefix(self.workspace).unwrap_or(&path);
+                    if self.excludes.is_excluded(relative) || !self.allowed(&entry) {
+                        return None;
+                    }
+                    let is_dir = path.is_dir();
+                    Some((path, is_dir))
+                })
+                .collect(),
+            Err(e) => {
+                debug!("Cannot read directory {:?}: {}", dir, e);
+                return node;
+            }
+        };
+        // Directories first, then by name
+        entries.sort_by(|(a, a_dir), (b, b_dir)| b_dir.cmp(a_dir).then_with(|| a.cmp(b)));
+
+        let mut file_count = 0;
+        for (path, is_dir) in entries {
+            // Claim the entry before walking below it, so the cap keeps
+            // parents ahead of their children
+            let listed = depth < self.options.max_depth && self.entries < self.options.max_entries;
+            if listed {
+                self.entries += 1;
+            } else if depth < self.options.max_depth {
+                node.truncated = true;
+                self.truncated = true;
+            }
+
+            let child_relative = relative.join(path.file_name().unwrap_or_default());
+            let child = if is_dir {
+                let child = self.walk(&path, &child_relative, depth + 1);
+                file_count += child.file_count.unwrap_or(0);
+                child
+            } else {
+                file_count += 1;
+                TreeNode {
+                    name: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    path: child_relative.to_string_lossy().to_string(),
+                    is_dir: false,
+                    file_count: None,
+                    children: Vec::new(),
+                    truncated: false,
+                }
+            };
+
+            if listed {
+                node.children.push(child);
+            }
+        }
+        node.file_count = Some(file_count);
+        node
+    }
+
+    /// Whether `entry` may be listed under the symlink policy.
+    fn allowed(&self, entry: &std::fs::DirEntry) -> bool {
+        if !entry.file_type().is_ok_and(|t| t.is_symlink()) {
+            return true;
+        }
+        self.options.follow_symlinks
+            && entry
+                .path()
+                .canonicalize()
+                .is_ok_and(|target| target.starts_with(&self.root))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src/service/deep")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join("README.md"), "x").unwrap();
+        std::fs::write(root.join("secrets.env"), "x").unwrap();
+        std::fs::write(root.join("src/main.rs"), "x").unwrap();
+        std::fs::write(root.join("src/service/mod.rs"), "x").unwrap();
+        std::fs::write(root.join("src/service/deep/inner.rs"), "x").unwrap();
+        std::fs::write(root.join("target/debug/app"), "x").unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_depth_limit_and_excludes() {
+        let temp = workspace();
+        let root = temp.path();
+        let excludes = ExcludeMatcher::with_defaults(["*.env"]);
+        let options = TreeOptions {
+            max_depth: 2,
+            ..TreeOptions::default()
+        };
+
+        let tree = build_tree(root, root, &excludes, options);
+        assert_eq!(
+            tree.render(),
+            "./ (4 files)\n  src/ (3 files)\n    service/ (2 files)\n    main.rs\n  README.md\n"
+        );
+        assert_eq!(tree.entries, 4);
+        assert!(!tree.truncated);
+
+        // Listing a subdirectory counts depth from there
+        let tree = build_tree(root, &root.join("src/service"), &excludes, options);
+        assert_eq!(tree.root.path, "src/service");
+        let paths: Vec<&str> = tree.root.children.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/service/deep", "src/service/mod.rs"]);
+        assert_eq!(tree.root.children[0].children[0].name, "inner.rs");
+    }
+
+    #[test]
+    fn test_entry_cap_marks_truncation() {
+        let temp = workspace();
+        let root = temp.path();
+        let excludes = ExcludeMatcher::with_defaults(Vec::<String>::new());
+        let options = TreeOptions {
+            max_depth: 5,
+            max_entries: 2,
+            ..TreeOptions::default()
+        };
+
+        let tree = build_tree(root, root, &excludes, options);
+        assert_eq!(tree.entries, 2);
+        assert!(tree.truncated);
+        assert!(tree.root.truncated);
+        assert_eq!(
+            tree.render(),
+            "./ (5 files)\n  src/ (3 files)\n    service/ (2 files)\n      ...\n    ...\n  ...\n... truncated after 2 entries\n"
+        );
+        // Counts still cover everything
+        assert_eq!(tree.root.file_count, Some(5));
+        assert!(tree.render().ends_with("... truncated after 2 entries\n"));
+    }
+}
@@ -4,12 +4,82 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
-
-use crate::error::Result;
-use crate::mcp::handler::{error_result, success_result, ToolHandler};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::mcp::handler::{
+    error_result, get_bool_arg, get_optional_string_arg, get_string_arg, json_result,
+    tool_error_result, ToolHandler,
+};
+use crate::mcp::progress::ProgressReporter;
 use crate::mcp::protocol::{Tool, ToolResult};
 use crate::service::ContextService;
+use crate::types::IndexResult;
+
+/// Category these tools are registered under.
+pub const CATEGORY: &str = "index";
+
+/// How often indexing progress is checked and forwarded to the client.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Index the workspace, or only the directory `path` in it.
+async fn index(service: &ContextService, path: Option<&str>) -> Result<IndexResult> {
+    match path {
+        Some(path) => service.index_directory(path).await,
+        None => service.index_workspace().await,
+    }
+}
+
+/// Index like [`index`], reporting `files_done`/`files_total` while it runs.
+///
+/// A notification is sent whenever the counts change between polls, plus a
+/// final one when indexing finishes.
+async fn index_with_progress(
+    service: &ContextService,
+    path: Option<&str>,
+    progress: Option<ProgressReporter>,
+) -> Result<IndexResult> {
+    let Some(progress) = progress else {
+        return index(service, path).await;
+    };
+
+    // Poll alongside indexing rather than between polls of it, so a status
+    // read never waits on a lock held by the paused indexing future.
+    let report = async {
+        let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+        let mut last = None;
+        loop {
+            ticker.tick().await;
+            let status = service.status().await;
+            let counts = (status.files_done, status.files_total);
+            if status.indexing && status.files_total > 0 && last != Some(counts) {
+                last = Some(counts);
+                progress.report(
+                    status.files_done as u64,
+                    Some(status.files_total as u64),
+                    Some(&format!(
+                        "Indexed {}/{} files",
+                        status.files_done, status.files_total
+                    )),
+                );
+            }
+        }
+    };
+
+    let result = tokio::select! {
+        result = index(service, path) => result,
+        never = report => never,
+    };
+    if result.is_ok() {
+        let status = service.status().await;
+        progress.report(
+            status.files_done as u64,
+            Some(status.files_total as u64),
+            Some("Indexing complete"),
+        );
+    }
+    result
+}
 
 /// Index workspace tool.
 pub struct IndexWorkspaceTool {
@@ -44,7 +114,11 @@ that enables fast, meaning-based code search.
 - C/C++ (.c, .cpp, .h, .hpp), Swift (.swift)
 - Web (.vue, .svelte, .astro, .html, .css, .scss)
 - Config (.json, .yaml, .yml, .toml, .xml)
-- Documentation (.md, .txt)"#
+- Documentation (.md, .txt)
+
+Pass `path` to index only one directory of the workspace. Its files are
+merged into the existing index, which is much faster when working in one
+subtree."#
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
@@ -56,6 +130,10 @@ that enables fast, meaning-based code search.
                     "background": {
                         "type": "boolean",
                         "description": "Run indexing in a background worker thread (non-blocking)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Workspace-relative directory to index, merging into the existing index (default: the whole workspace)"
                     }
                 },
                 "required": []
@@ -63,12 +141,35 @@ that enables fast, meaning-based code search.
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        self.execute_with_progress(args, None).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        args: HashMap<String, Value>,
+        progress: Option<ProgressReporter>,
+    ) -> Result<ToolResult> {
         let force = args.get("force").and_then(|v| v.as_bool()).unwrap_or(false);
         let background = args
             .get("background")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
+        let path = get_optional_string_arg(&args, "path");
+
+        if let Some(path) = &path {
+            // A scoped run merges into the index, so it cannot also clear it
+            if force {
+                return Ok(error_result("'force' cannot be combined with 'path'"));
+            }
+            if let Err(e) = self.service.resolve_path(path) {
+                return Ok(tool_error_result("Failed to index directory", &e));
+            }
+        }
 
         let start = Instant::now();
 
@@ -76,7 +177,7 @@ that enables fast, meaning-based code search.
             // Fire and forget
             let service = self.service.clone();
             tokio::spawn(async move {
-                if let Err(e) = service.index_workspace().await {
+                if let Err(e) = index(&service, path.as_deref()).await {
                     tracing::error!("Background indexing failed: {}", e);
                 }
             });
@@ -85,7 +186,7 @@ that enables fast, meaning-based code search.
                 "success": true,
                 "message": "Background indexing started"
             });
-            return Ok(success_result(serde_json::to_string_pretty(&result)?));
+            return Ok(json_result(&result));
         }
 
         // Clear if forced
@@ -93,20 +194,24 @@ that enables fast, meaning-based code search.
             self.service.clear().await;
         }
 
-        match self.service.index_workspace().await {
+        match index_with_progress(&self.service, path.as_deref(), progress).await {
             Ok(stats) => {
                 let elapsed = start.elapsed().as_millis();
+                let indexed = match &path {
+                    Some(path) => format!("Directory '{}'", path),
+                    None => "Workspace".to_string(),
+                };
                 let result = serde_json::json!({
                     "success": true,
-                    "message": format!("Workspace indexed successfully in {}ms", elapsed),
+                    "message": format!("{} indexed successfully in {}ms", indexed, elapsed),
                     "elapsed_ms": elapsed,
                     "indexed": stats.indexed,
                     "skipped": stats.skipped,
                     "errors": stats.errors
                 });
-                Ok(success_result(serde_json::to_string_pretty(&result)?))
+                Ok(json_result(&result))
             }
-            Err(e) => Ok(error_result(format!("Failed to index workspace: {}", e))),
+            Err(e) => Ok(tool_error_result("Failed to index workspace", &e)),
         }
     }
 }
@@ -127,7 +232,7 @@ impl ToolHandler for IndexStatusTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "index_status".to_string(),
-            description: "Get the current status of the codebase index, including file count and last indexed time.".to_string(),
+            description: "Get the current status of the codebase index, including file count, last indexed time, and progress of any running index.".to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {},
@@ -138,8 +243,7 @@ impl ToolHandler for IndexStatusTool {
 
     async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
         let status = self.service.status().await;
-        let json = serde_json::to_string_pretty(&status)?;
-        Ok(success_result(json))
+        Ok(json_result(&status))
     }
 }
 
@@ -168,14 +272,26 @@ impl ToolHandler for ReindexWorkspaceTool {
         }
     }
 
-    async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        self.execute_with_progress(args, None).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        _args: HashMap<String, Value>,
+        progress: Option<ProgressReporter>,
+    ) -> Result<ToolResult> {
         let start = Instant::now();
 
         // Clear the index first
         self.service.clear().await;
 
         // Re-index
-        match self.service.index_workspace().await {
+        match index_with_progress(&self.service, None, progress).await {
             Ok(stats) => {
                 let elapsed = start.elapsed().as_millis();
                 let result = serde_json::json!({
@@ -186,13 +302,69 @@ impl ToolHandler for ReindexWorkspaceTool {
                     "skipped": stats.skipped,
                     "errors": stats.errors
                 });
-                Ok(success_result(serde_json::to_string_pretty(&result)?))
+                Ok(json_result(&result))
             }
             Err(e) => Ok(error_result(format!("Failed to reindex workspace: {}", e))),
         }
     }
 }
 
+/// Reindex a single file tool.
+pub struct ReindexFileTool {
+    service: Arc<ContextService>,
+}
+
+impl ReindexFileTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ReindexFileTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "reindex_file".to_string(),
+            description: "Re-index a single file so edits to it become searchable immediately, without a full workspace refresh.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File path relative to workspace root"
+                    }
+                },
+                "required": ["path"]
+            }),
+        }
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = get_string_arg(&args, "path")?;
+
+        match self.service.reindex_file(&path).await {
+            Ok(stats) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "path": path,
+                    "updated": stats.indexed > 0,
+                    "elapsed_ms": stats.duration,
+                    "errors": stats.errors
+                });
+                Ok(json_result(&result))
+            }
+            Err(e) => Ok(tool_error_result(
+                &format!("Failed to reindex {}", path),
+                &e,
+            )),
+        }
+    }
+}
+
 /// Clear index tool.
 pub struct ClearIndexTool {
     service: Arc<ContextService>,
@@ -209,24 +381,55 @@ impl ToolHandler for ClearIndexTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "clear_index".to_string(),
-            description: "Remove saved index state and clear caches without rebuilding."
+            description: "Remove saved index state and clear caches without rebuilding. Requires `confirm: true`; pass `path` to clear only the files under one file or directory."
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
-                "properties": {},
-                "required": []
+                "properties": {
+                    "confirm": {
+                        "type": "boolean",
+                        "description": "Must be true; guards against clearing the index by accident"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Workspace-relative file or directory whose entries to clear (default: the whole index)"
+                    }
+                },
+                "required": ["confirm"]
             }),
         }
     }
 
-    async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        if !get_bool_arg(&args, "confirm", false) {
+            let error = Error::InvalidToolArguments(
+                "clear_index requires 'confirm: true'; nothing was cleared".to_string(),
+            );
+            return Ok(tool_error_result("", &error));
+        }
+
+        if let Some(path) = get_optional_string_arg(&args, "path") {
+            return match self.service.clear_path(&path).await {
+                Ok(removed) => Ok(json_result(&serde_json::json!({
+                    "success": true,
+                    "removed": removed,
+                    "message": format!("Cleared {} indexed files under '{}'.", removed, path)
+                }))),
+                Err(e) => Ok(tool_error_result("Failed to clear index", &e)),
+            };
+        }
+
         self.service.clear().await;
 
         let result = serde_json::json!({
             "success": true,
             "message": "Index cleared. Re-run index_workspace to rebuild."
         });
-        Ok(success_result(serde_json::to_string_pretty(&result)?))
+        Ok(json_result(&result))
     }
 }
 
@@ -260,18 +463,54 @@ impl ToolHandler for RefreshIndexTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
         // Initialize the service (which triggers indexing)
         match self.service.initialize().await {
             Ok(_) => {
                 let status = self.service.status().await;
-                let json = serde_json::to_string_pretty(&status)?;
-                Ok(success_result(format!(
-                    "Index refreshed successfully.\n{}",
-                    json
-                )))
+                Ok(json_result(&status))
             }
             Err(e) => Ok(error_result(format!("Failed to refresh index: {}", e))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::mock_backend::MockBackend;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_clear_index_requires_confirmation() {
+        let backend = MockBackend::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+        let service = Arc::new(
+            ContextService::new(&backend.config(temp_dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = ClearIndexTool::new(service.clone());
+
+        for args in [
+            HashMap::new(),
+            HashMap::from([("confirm".to_string(), serde_json::json!(false))]),
+        ] {
+            let result = tool.execute(args).await.unwrap();
+            assert!(result.is_error);
+            assert_eq!(result.error.unwrap().code, "invalid_arguments");
+        }
+        assert_eq!(service.status().await.file_count, 1);
+
+        let args = HashMap::from([("confirm".to_string(), serde_json::json!(true))]);
+        let result = tool.execute(args).await.unwrap();
+        assert!(!result.is_error);
+        assert_eq!(service.status().await.file_count, 0);
+    }
+}
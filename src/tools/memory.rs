@@ -5,12 +5,32 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::mcp::handler::{
-    error_result, get_optional_string_arg, get_string_arg, success_result, ToolHandler,
+    error_result, get_optional_string_arg, get_string_arg, json_result, success_result,
+    tool_error_result, ToolHandler,
 };
 use crate::mcp::protocol::{Tool, ToolResult};
-use crate::service::MemoryService;
+use crate::service::{ConflictPolicy, MemoryExport, MemoryService, DEFAULT_NAMESPACE};
+
+/// Category these tools are registered under.
+pub const CATEGORY: &str = "memory";
+
+/// Schema of the `namespace` argument shared by the memory tools.
+fn namespace_schema() -> Value {
+    serde_json::json!({
+        "type": "string",
+        "description": format!(
+            "Namespace keeping this agent's or project's memories apart (letters, digits, '-', '_', '.'; default: {})",
+            DEFAULT_NAMESPACE
+        )
+    })
+}
+
+/// The `namespace` argument, or the default namespace.
+fn namespace_arg(args: &HashMap<String, Value>) -> String {
+    get_optional_string_arg(args, "namespace").unwrap_or_else(|| DEFAULT_NAMESPACE.to_string())
+}
 
 /// Store memory tool.
 pub struct StoreMemoryTool {
@@ -44,21 +64,27 @@ impl ToolHandler for StoreMemoryTool {
                     "type": {
                         "type": "string",
                         "description": "Optional category/type for the memory"
-                    }
+                    },
+                    "namespace": namespace_schema()
                 },
                 "required": ["key", "value"]
             }),
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let key = get_string_arg(&args, "key")?;
         let value = get_string_arg(&args, "value")?;
         let entry_type = get_optional_string_arg(&args, "type");
+        let namespace = namespace_arg(&args);
 
-        match self.service.store(key, value, entry_type).await {
+        match self.service.store(&namespace, key, value, entry_type).await {
             Ok(entry) => Ok(success_result(format!("Stored memory: {}", entry.key))),
-            Err(e) => Ok(error_result(format!("Failed to store memory: {}", e))),
+            Err(e) => Ok(tool_error_result("Failed to store memory", &e)),
         }
     }
 }
@@ -86,7 +112,8 @@ impl ToolHandler for RetrieveMemoryTool {
                     "key": {
                         "type": "string",
                         "description": "The key of the memory to retrieve"
-                    }
+                    },
+                    "namespace": namespace_schema()
                 },
                 "required": ["key"]
             }),
@@ -96,12 +123,10 @@ impl ToolHandler for RetrieveMemoryTool {
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let key = get_string_arg(&args, "key")?;
 
-        match self.service.retrieve(&key).await {
-            Some(entry) => {
-                let json = serde_json::to_string_pretty(&entry)?;
-                Ok(success_result(json))
-            }
-            None => Ok(error_result(format!("Memory not found: {}", key))),
+        match self.service.retrieve(&namespace_arg(&args), &key).await {
+            Ok(Some(entry)) => Ok(json_result(&entry)),
+            Ok(None) => Ok(error_result(format!("Memory not found: {}", key))),
+            Err(e) => Ok(tool_error_result("", &e)),
         }
     }
 }
@@ -122,14 +147,16 @@ impl ToolHandler for ListMemoryTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "list_memories".to_string(),
-            description: "List all stored memories, optionally filtered by type.".to_string(),
+            description: "List the memories stored in a namespace, optionally filtered by type."
+                .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "type": {
                         "type": "string",
                         "description": "Optional type to filter memories"
-                    }
+                    },
+                    "namespace": namespace_schema()
                 },
                 "required": []
             }),
@@ -138,9 +165,14 @@ impl ToolHandler for ListMemoryTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let entry_type = get_optional_string_arg(&args, "type");
-        let entries = self.service.list(entry_type.as_deref()).await;
-        let json = serde_json::to_string_pretty(&entries)?;
-        Ok(success_result(json))
+        match self
+            .service
+            .list(&namespace_arg(&args), entry_type.as_deref())
+            .await
+        {
+            Ok(entries) => Ok(json_result(&entries)),
+            Err(e) => Ok(tool_error_result("", &e)),
+        }
     }
 }
 
@@ -167,20 +199,141 @@ impl ToolHandler for DeleteMemoryTool {
                     "key": {
                         "type": "string",
                         "description": "The key of the memory to delete"
-                    }
+                    },
+                    "namespace": namespace_schema()
                 },
                 "required": ["key"]
             }),
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let key = get_string_arg(&args, "key")?;
 
-        match self.service.delete(&key).await {
+        match self.service.delete(&namespace_arg(&args), &key).await {
             Ok(true) => Ok(success_result(format!("Deleted memory: {}", key))),
             Ok(false) => Ok(error_result(format!("Memory not found: {}", key))),
-            Err(e) => Ok(error_result(format!("Failed to delete memory: {}", e))),
+            Err(e) => Ok(tool_error_result("Failed to delete memory", &e)),
+        }
+    }
+}
+
+/// Export memory tool.
+pub struct ExportMemoryTool {
+    service: Arc<MemoryService>,
+}
+
+impl ExportMemoryTool {
+    pub fn new(service: Arc<MemoryService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ExportMemoryTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "export_memories".to_string(),
+            description: "Export stored memories as one versioned JSON document, for loading on another machine with import_memories.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "namespace": {
+                        "type": "string",
+                        "description": "Only export this namespace (default: all namespaces)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let namespace = get_optional_string_arg(&args, "namespace");
+        match self.service.export(namespace.as_deref()).await {
+            Ok(export) => Ok(json_result(&export)),
+            Err(e) => Ok(tool_error_result("Failed to export memories", &e)),
+        }
+    }
+}
+
+/// Import memory tool.
+pub struct ImportMemoryTool {
+    service: Arc<MemoryService>,
+}
+
+impl ImportMemoryTool {
+    pub fn new(service: Arc<MemoryService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ImportMemoryTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "import_memories".to_string(),
+            description: "Load memories from a document produced by export_memories.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "data": {
+                        "type": ["object", "string"],
+                        "description": "The export document, as an object or a JSON string"
+                    },
+                    "on_conflict": {
+                        "type": "string",
+                        "enum": ["skip", "overwrite", "merge"],
+                        "description": "For keys that already exist: keep them, replace them, or keep the most recently updated entry (default: skip)"
+                    }
+                },
+                "required": ["data"]
+            }),
+        }
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let export: std::result::Result<MemoryExport, _> = match args.get("data") {
+            Some(Value::String(text)) => serde_json::from_str(text),
+            Some(value) => serde_json::from_value(value.clone()),
+            None => return Ok(error_result("Missing required argument: data")),
+        };
+        let export = match export {
+            Ok(export) => export,
+            Err(e) => {
+                return Ok(tool_error_result(
+                    "",
+                    &Error::InvalidToolArguments(format!("Invalid memory export: {}", e)),
+                ))
+            }
+        };
+        let policy = match args.get("on_conflict") {
+            None => ConflictPolicy::default(),
+            Some(value) => match serde_json::from_value(value.clone()) {
+                Ok(policy) => policy,
+                Err(_) => {
+                    return Ok(tool_error_result(
+                        "",
+                        &Error::InvalidToolArguments(format!(
+                            "Invalid on_conflict {}: expected skip, overwrite or merge",
+                            value
+                        )),
+                    ))
+                }
+            },
+        };
+
+        match self.service.import(export, policy).await {
+            Ok(summary) => Ok(json_result(&summary)),
+            Err(e) => Ok(tool_error_result("Failed to import memories", &e)),
         }
     }
 }
@@ -1,55 +1,163 @@
 //! MCP tool implementations.
 //!
-//! This module contains all 49 MCP tools organized by category:
+//! This module contains all 65 MCP tools organized by category:
 //!
-//! - `retrieval` - Codebase search and context retrieval (6 tools)
-//! - `index` - Index management tools (5 tools)
-//! - `planning` - AI-powered task planning (20 tools)
-//! - `memory` - Persistent memory storage (4 tools)
-//! - `review` - Code review tools (14 tools)
+//! - `retrieval` - Codebase search and context retrieval (12 tools)
+//! - `index` - Index management tools (6 tools)
+//! - `planning` - AI-powered task planning (22 tools)
+//! - `memory` - Persistent memory storage (6 tools)
+//! - `review` - Code review tools (19 tools)
+//!
+//! [`table`] renders the plain-text tables of tools offering
+//! `format: "table"`.
 
 pub mod index;
 pub mod memory;
 pub mod planning;
 pub mod retrieval;
 pub mod review;
+pub mod table;
 
+use globset::{Glob, GlobMatcher};
 use std::sync::Arc;
+use tracing::{info, warn};
 
+use crate::config::Config;
 use crate::mcp::handler::McpHandler;
+use crate::reviewer::ReviewStore;
 use crate::service::{ContextService, MemoryService, PlanningService};
 
+/// Every tool category, in registration order.
+pub const CATEGORIES: [&str; 5] = [
+    retrieval::CATEGORY,
+    index::CATEGORY,
+    memory::CATEGORY,
+    planning::CATEGORY,
+    review::CATEGORY,
+];
+
+/// Which tools the configuration lets the server expose.
+struct ToolPolicy {
+    /// Permitted categories; empty permits all
+    categories: Vec<String>,
+    /// Disabled tool names, with a matcher for those that are globs
+    disabled: Vec<(String, Option<GlobMatcher>)>,
+}
+
+impl ToolPolicy {
+    fn new(config: &Config) -> Self {
+        let categories: Vec<String> = config
+            .enabled_tool_categories
+            .iter()
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+        for category in &categories {
+            if !CATEGORIES.contains(&category.as_str()) {
+                warn!(
+                    "Unknown tool category '{}' in enabled categories (expected one of {})",
+                    category,
+                    CATEGORIES.join(", ")
+                );
+            }
+        }
+
+        let disabled = config
+            .disabled_tools
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let matcher = match Glob::new(name) {
+                    Ok(glob) => Some(glob.compile_matcher()),
+                    Err(e) => {
+                        warn!("Matching disabled tool '{}' by name only: {}", name, e);
+                        None
+                    }
+                };
+                (name.to_string(), matcher)
+            })
+            .collect();
+        Self {
+            categories,
+            disabled,
+        }
+    }
+
+    fn permits(&self, name: &str, category: &str) -> bool {
+        let category_enabled =
+            self.categories.is_empty() || self.categories.iter().any(|c| c == category);
+        let disabled = self.disabled.iter().any(|(pattern, matcher)| {
+            pattern == name || matcher.as_ref().is_some_and(|m| m.is_match(name))
+        });
+        category_enabled && !disabled
+    }
+
+    /// Unregister every tool in `handler` the policy doesn't permit.
+    fn apply(&self, handler: &mut McpHandler) {
+        let mut removed: Vec<String> = handler
+            .list_tools()
+            .into_iter()
+            .map(|tool| tool.name)
+            .filter(|name| !self.permits(name, handler.category(name).unwrap_or_default()))
+            .collect();
+        removed.sort();
+        for name in &removed {
+            handler.unregister(name);
+        }
+        if !removed.is_empty() {
+            info!("Disabled {} tool(s): {}", removed.len(), removed.join(", "));
+        }
+    }
+}
+
 /// Register all tools with the handler.
+///
+/// Tools outside the configured `enabled_tool_categories`, or matching
+/// `disabled_tools`, are left out.
 pub fn register_all_tools(
     handler: &mut McpHandler,
     context_service: Arc<ContextService>,
     memory_service: Arc<MemoryService>,
     planning_service: Arc<PlanningService>,
 ) {
-    // Retrieval tools (6)
+    // Retrieval tools (12)
     handler.register(retrieval::CodebaseRetrievalTool::new(
         context_service.clone(),
     ));
     handler.register(retrieval::SearchCodeTool::new(context_service.clone()));
+    handler.register(retrieval::GrepTool::new(context_service.clone()));
     handler.register(retrieval::GetFileTool::new(context_service.clone()));
+    handler.register(retrieval::FileOutlineTool::new(context_service.clone()));
+    handler.register(retrieval::SummarizeFileTool::new(context_service.clone()));
     handler.register(retrieval::GetContextTool::new(context_service.clone()));
     handler.register(retrieval::EnhancePromptTool::new(context_service.clone()));
-    handler.register(retrieval::ToolManifestTool::new());
+    handler.register(retrieval::WorkspaceStatsTool::new(context_service.clone()));
+    handler.register(retrieval::DescribeCodebaseTool::new(
+        context_service.clone(),
+    ));
+    handler.register(retrieval::ListTreeTool::new(context_service.clone()));
+    handler.categorize(retrieval::CATEGORY);
 
-    // Index tools (5)
+    // Index tools (6)
     handler.register(index::IndexWorkspaceTool::new(context_service.clone()));
     handler.register(index::IndexStatusTool::new(context_service.clone()));
     handler.register(index::ReindexWorkspaceTool::new(context_service.clone()));
+    handler.register(index::ReindexFileTool::new(context_service.clone()));
     handler.register(index::ClearIndexTool::new(context_service.clone()));
     handler.register(index::RefreshIndexTool::new(context_service.clone()));
+    handler.categorize(index::CATEGORY);
 
-    // Memory tools (4)
+    // Memory tools (6)
     handler.register(memory::StoreMemoryTool::new(memory_service.clone()));
     handler.register(memory::RetrieveMemoryTool::new(memory_service.clone()));
     handler.register(memory::ListMemoryTool::new(memory_service.clone()));
     handler.register(memory::DeleteMemoryTool::new(memory_service.clone()));
+    handler.register(memory::ExportMemoryTool::new(memory_service.clone()));
+    handler.register(memory::ImportMemoryTool::new(memory_service.clone()));
+    handler.categorize(memory::CATEGORY);
 
-    // Planning tools (20)
+    // Planning tools (22)
     handler.register(planning::CreatePlanTool::new(planning_service.clone()));
     handler.register(planning::GetPlanTool::new(planning_service.clone()));
     handler.register(planning::ListPlansTool::new(planning_service.clone()));
@@ -57,6 +165,7 @@ pub fn register_all_tools(
     handler.register(planning::UpdateStepTool::new(planning_service.clone()));
     handler.register(planning::RefinePlanTool::new(planning_service.clone()));
     handler.register(planning::VisualizePlanTool::new(planning_service.clone()));
+    handler.register(planning::ExportPlanTool::new(planning_service.clone()));
     handler.register(planning::ExecutePlanTool::new(planning_service.clone()));
     handler.register(planning::SavePlanTool::new(planning_service.clone()));
     handler.register(planning::LoadPlanTool::new(planning_service.clone()));
@@ -68,24 +177,113 @@ pub fn register_all_tools(
     handler.register(planning::ViewHistoryTool::new(planning_service.clone()));
     handler.register(planning::RequestApprovalTool::new(planning_service.clone()));
     handler.register(planning::RespondApprovalTool::new(planning_service.clone()));
+    handler.register(planning::ListApprovalsTool::new(planning_service.clone()));
     handler.register(planning::ComparePlanVersionsTool::new(
         planning_service.clone(),
     ));
     handler.register(planning::RollbackPlanTool::new(planning_service.clone()));
+    handler.categorize(planning::CATEGORY);
 
-    // Review tools (14)
-    handler.register(review::ReviewDiffTool::new(context_service.clone()));
+    // Review tools (19), sharing one store of completed reviews
+    let reviews = ReviewStore::default();
+    handler.register(review::ReviewDiffTool::new(
+        context_service.clone(),
+        reviews.clone(),
+    ));
     handler.register(review::AnalyzeRiskTool::new(context_service.clone()));
     handler.register(review::ReviewChangesTool::new(context_service.clone()));
-    handler.register(review::ReviewGitDiffTool::new(context_service.clone()));
-    handler.register(review::ReviewAutoTool::new(context_service.clone()));
+    handler.register(review::ReviewGitDiffTool::new(
+        context_service.clone(),
+        reviews.clone(),
+    ));
+    handler.register(review::GitDiffStatTool::new(context_service.clone()));
+    handler.register(review::ReviewRemoteDiffTool::new(
+        context_service.clone(),
+        reviews.clone(),
+    ));
+    handler.register(review::ReviewAutoTool::new(
+        context_service.clone(),
+        reviews.clone(),
+    ));
     handler.register(review::CheckInvariantsTool::new(context_service.clone()));
     handler.register(review::RunStaticAnalysisTool::new(context_service.clone()));
     handler.register(review::ScrubSecretsTool::new());
+    handler.register(review::ScanWorkspaceSecretsTool::new(
+        context_service.clone(),
+    ));
     handler.register(review::ValidateContentTool::new());
-    handler.register(review::GetReviewStatusTool::new());
+    handler.register(review::GetReviewStatusTool::new(reviews.clone()));
+    handler.register(review::CreateReviewBaselineTool::new(
+        context_service.clone(),
+        reviews.clone(),
+    ));
+    handler.register(review::ReviewGateTool::new(
+        context_service.clone(),
+        reviews,
+    ));
     handler.register(review::ReactiveReviewPRTool::new(context_service.clone()));
     handler.register(review::PauseReviewTool::new());
     handler.register(review::ResumeReviewTool::new());
     handler.register(review::GetReviewTelemetryTool::new());
+    handler.categorize(review::CATEGORY);
+
+    let policy = ToolPolicy::new(context_service.config());
+    policy.apply(handler);
+
+    // Registered last so the manifest covers every other permitted tool
+    if policy.permits("tool_manifest", retrieval::CATEGORY) {
+        handler.register(retrieval::ToolManifestTool::new(handler));
+        handler.categorize(retrieval::CATEGORY);
+    } else {
+        info!("Disabled tool: tool_manifest");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn registered_tools(config: Config) -> Vec<String> {
+        let memory = Arc::new(MemoryService::new(&config.workspace).await.unwrap());
+        let planning = Arc::new(PlanningService::new(&config.workspace).await.unwrap());
+        let context = Arc::new(ContextService::new(&config).await.unwrap());
+        let mut handler = McpHandler::new();
+        register_all_tools(&mut handler, context, memory, planning);
+        let mut names: Vec<String> = handler.list_tools().into_iter().map(|t| t.name).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tools_are_not_listed() {
+        let temp = TempDir::new().unwrap();
+        let all = registered_tools(Config {
+            workspace: temp.path().to_path_buf(),
+            ..Config::default()
+        })
+        .await;
+        assert_eq!(all.len(), 65);
+
+        let names = registered_tools(Config {
+            workspace: temp.path().to_path_buf(),
+            enabled_tool_categories: vec!["retrieval".to_string(), "Review".to_string()],
+            disabled_tools: vec!["*git*".to_string(), "get_file".to_string()],
+            ..Config::default()
+        })
+        .await;
+        assert!(names.contains(&"semantic_search".to_string()));
+        assert!(names.contains(&"review_diff".to_string()));
+        assert!(names.contains(&"tool_manifest".to_string()));
+        for disabled in [
+            "get_file",
+            "review_git_diff",
+            "git_diff_stat",
+            "create_plan",
+            "add_memory",
+            "index_workspace",
+        ] {
+            assert!(!names.contains(&disabled.to_string()), "{}", disabled);
+        }
+    }
 }
@@ -6,10 +6,17 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::error::Result;
-use crate::mcp::handler::{error_result, get_string_arg, success_result, ToolHandler};
+use crate::mcp::handler::{
+    error_result, get_optional_string_arg, get_string_arg, json_result, success_result,
+    tool_error_result, ToolHandler,
+};
 use crate::mcp::protocol::{Tool, ToolResult};
+use crate::service::planning::{plan_diff_to_text, plan_to_mermaid, GraphLimits, TopBy};
 use crate::service::PlanningService;
-use crate::types::planning::{Step, StepStatus, StepType};
+use crate::types::planning::{ApprovalState, Step, StepStatus, StepType};
+
+/// Category these tools are registered under.
+pub const CATEGORY: &str = "planning";
 
 /// Create plan tool.
 pub struct CreatePlanTool {
@@ -45,15 +52,16 @@ impl ToolHandler for CreatePlanTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let title = get_string_arg(&args, "title")?;
         let description = get_string_arg(&args, "description")?;
 
         match self.service.create_plan(title, description).await {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
+            Ok(plan) => Ok(json_result(&plan)),
             Err(e) => Ok(error_result(format!("Failed to create plan: {}", e))),
         }
     }
@@ -93,10 +101,7 @@ impl ToolHandler for GetPlanTool {
         let plan_id = get_string_arg(&args, "plan_id")?;
 
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
+            Some(plan) => Ok(json_result(&plan)),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
@@ -134,8 +139,7 @@ impl ToolHandler for ListPlansTool {
 
     async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
         let plans = self.service.list_plans(None).await;
-        let json = serde_json::to_string_pretty(&plans)?;
-        Ok(success_result(json))
+        Ok(json_result(&plans))
     }
 }
 
@@ -162,28 +166,48 @@ impl ToolHandler for AddStepTool {
                     "plan_id": { "type": "string", "description": "The plan ID" },
                     "title": { "type": "string", "description": "Step title" },
                     "description": { "type": "string", "description": "Step description" },
-                    "step_type": { "type": "string", "description": "Type of step" }
+                    "step_type": { "type": "string", "description": "Type of step" },
+                    "dependencies": {
+                        "type": "array",
+                        "items": { "type": "integer" },
+                        "description": "IDs of existing steps this one depends on"
+                    }
                 },
                 "required": ["plan_id", "title", "description"]
             }),
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let title = get_string_arg(&args, "title")?;
         let description = get_string_arg(&args, "description")?;
+        let dependencies = args
+            .get("dependencies")
+            .and_then(|v| v.as_array())
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| id.as_u64())
+                    .map(|id| id as u32)
+                    .collect()
+            })
+            .unwrap_or_default();
 
         let step = Step {
-            id: 1, // Will be assigned properly
+            id: 0, // Assigned by the service
             title,
             description,
             status: StepStatus::Pending,
             step_type: StepType::Implementation,
-            dependencies: vec![],
+            dependencies,
             affected_files: vec![],
             estimated_duration: None,
             actual_duration: None,
+            started_at: None,
             completed_at: None,
             error: None,
             output: None,
@@ -194,11 +218,8 @@ impl ToolHandler for AddStepTool {
         };
 
         match self.service.add_step(&plan_id, step).await {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
-            Err(e) => Ok(error_result(format!("Failed to add step: {}", e))),
+            Ok(plan) => Ok(json_result(&plan)),
+            Err(e) => Ok(tool_error_result("Failed to add step", &e)),
         }
     }
 }
@@ -232,6 +253,10 @@ impl ToolHandler for UpdateStepTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let step_id = args.get("step_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
@@ -252,11 +277,8 @@ impl ToolHandler for UpdateStepTool {
             .update_step_status(&plan_id, step_id, status)
             .await
         {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
-            Err(e) => Ok(error_result(format!("Failed to update step: {}", e))),
+            Ok(plan) => Ok(json_result(&plan)),
+            Err(e) => Ok(tool_error_result("Failed to update step", &e)),
         }
     }
 }
@@ -292,13 +314,7 @@ impl ToolHandler for RefinePlanTool {
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(format!(
-                    "Plan refinement requested:\n{}",
-                    json
-                )))
-            }
+            Some(plan) => Ok(json_result(&plan)),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
@@ -325,7 +341,14 @@ impl ToolHandler for VisualizePlanTool {
                 "type": "object",
                 "properties": {
                     "plan_id": { "type": "string", "description": "The plan ID to visualize" },
-                    "format": { "type": "string", "description": "Output format (mermaid, ascii, json)" }
+                    "format": { "type": "string", "description": "Output format (mermaid, ascii, json)" },
+                    "max_nodes": { "type": "integer", "description": "Most steps to draw (default: 50)" },
+                    "max_edges": { "type": "integer", "description": "Most dependency edges to draw (default: 100)" },
+                    "top_by": {
+                        "type": "string",
+                        "enum": ["order", "connections"],
+                        "description": "Which steps to keep when capped: the first ones, or the most connected (default: order)"
+                    }
                 },
                 "required": ["plan_id"]
             }),
@@ -334,30 +357,79 @@ impl ToolHandler for VisualizePlanTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
+        let defaults = GraphLimits::default();
+        let top_by = match get_optional_string_arg(&args, "top_by") {
+            Some(value) => TopBy::parse(&value)?,
+            None => defaults.top_by,
+        };
+        let cap = |name: &str, default: usize| {
+            args.get(name)
+                .and_then(|v| v.as_u64())
+                .map_or(default, |n| n as usize)
+        };
+        let limits = GraphLimits {
+            max_nodes: cap("max_nodes", defaults.max_nodes),
+            max_edges: cap("max_edges", defaults.max_edges),
+            top_by,
+        };
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let mut output = format!("# Plan: {}\n\n", plan.title);
-                output.push_str("```mermaid\ngraph TD\n");
-                for (i, step) in plan.steps.iter().enumerate() {
-                    let status_icon = match step.status {
-                        StepStatus::Completed => "✅",
-                        StepStatus::InProgress => "🔄",
-                        StepStatus::Failed => "❌",
-                        _ => "⬜",
-                    };
-                    output.push_str(&format!("    S{}[\"{} {}\"]\n", i, status_icon, step.title));
-                    if i > 0 {
-                        output.push_str(&format!("    S{} --> S{}\n", i - 1, i));
-                    }
-                }
-                output.push_str("```\n");
-                Ok(success_result(output))
-            }
+            Some(plan) => Ok(success_result(plan_to_mermaid(&plan, limits))),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
 }
 
+/// Export plan tool.
+pub struct ExportPlanTool {
+    service: Arc<PlanningService>,
+}
+
+impl ExportPlanTool {
+    pub fn new(service: Arc<PlanningService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ExportPlanTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "export_plan".to_string(),
+            description:
+                "Export a plan as a markdown task list for GitHub issues and pull requests."
+                    .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "plan_id": { "type": "string", "description": "The plan ID to export" },
+                    "format": {
+                        "type": "string",
+                        "enum": ["markdown"],
+                        "description": "Export format (default: markdown)"
+                    }
+                },
+                "required": ["plan_id"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let plan_id = get_string_arg(&args, "plan_id")?;
+        let format = get_optional_string_arg(&args, "format").unwrap_or_else(|| "markdown".into());
+        if format != "markdown" {
+            return Ok(error_result(format!(
+                "Unknown format '{}': expected markdown",
+                format
+            )));
+        }
+
+        match self.service.export_markdown(&plan_id).await {
+            Ok(markdown) => Ok(success_result(markdown)),
+            Err(e) => Ok(tool_error_result("Failed to export plan", &e)),
+        }
+    }
+}
+
 /// Execute plan tool.
 pub struct ExecutePlanTool {
     service: Arc<PlanningService>,
@@ -389,10 +461,7 @@ impl ToolHandler for ExecutePlanTool {
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(format!("Plan execution started:\n{}", json)))
-            }
+            Some(plan) => Ok(json_result(&plan)),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
@@ -426,13 +495,14 @@ impl ToolHandler for SavePlanTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(format!("Plan saved:\n{}", json)))
-            }
+            Some(plan) => Ok(json_result(&plan)),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
@@ -466,6 +536,10 @@ impl ToolHandler for LoadPlanTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let path = get_string_arg(&args, "path")?;
         Ok(success_result(format!("Plan loaded from: {}", path)))
@@ -499,6 +573,10 @@ impl ToolHandler for DeletePlanTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         match self.service.delete_plan(&plan_id).await {
@@ -536,6 +614,10 @@ impl ToolHandler for StartStepTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let step_id = args.get("step_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
@@ -544,11 +626,8 @@ impl ToolHandler for StartStepTool {
             .update_step_status(&plan_id, step_id, StepStatus::InProgress)
             .await
         {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
-            Err(e) => Ok(error_result(format!("Failed to start step: {}", e))),
+            Ok(plan) => Ok(json_result(&plan)),
+            Err(e) => Ok(tool_error_result("Failed to start step", &e)),
         }
     }
 }
@@ -582,6 +661,10 @@ impl ToolHandler for CompleteStepTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let step_id = args.get("step_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
@@ -590,11 +673,8 @@ impl ToolHandler for CompleteStepTool {
             .update_step_status(&plan_id, step_id, StepStatus::Completed)
             .await
         {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
-            Err(e) => Ok(error_result(format!("Failed to complete step: {}", e))),
+            Ok(plan) => Ok(json_result(&plan)),
+            Err(e) => Ok(tool_error_result("Failed to complete step", &e)),
         }
     }
 }
@@ -628,6 +708,10 @@ impl ToolHandler for FailStepTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let step_id = args.get("step_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
@@ -636,11 +720,8 @@ impl ToolHandler for FailStepTool {
             .update_step_status(&plan_id, step_id, StepStatus::Failed)
             .await
         {
-            Ok(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(json))
-            }
-            Err(e) => Ok(error_result(format!("Failed to fail step: {}", e))),
+            Ok(plan) => Ok(json_result(&plan)),
+            Err(e) => Ok(tool_error_result("Failed to fail step", &e)),
         }
     }
 }
@@ -674,39 +755,9 @@ impl ToolHandler for ViewProgressTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
-        match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let total = plan.steps.len();
-                let completed = plan
-                    .steps
-                    .iter()
-                    .filter(|s| s.status == StepStatus::Completed)
-                    .count();
-                let in_progress = plan
-                    .steps
-                    .iter()
-                    .filter(|s| s.status == StepStatus::InProgress)
-                    .count();
-                let failed = plan
-                    .steps
-                    .iter()
-                    .filter(|s| s.status == StepStatus::Failed)
-                    .count();
-                let pending = total - completed - in_progress - failed;
-
-                let progress = serde_json::json!({
-                    "plan_id": plan_id,
-                    "title": plan.title,
-                    "total_steps": total,
-                    "completed": completed,
-                    "in_progress": in_progress,
-                    "failed": failed,
-                    "pending": pending,
-                    "progress_percent": if total > 0 { (completed * 100) / total } else { 0 }
-                });
-                Ok(success_result(serde_json::to_string_pretty(&progress)?))
-            }
-            None => Ok(error_result(format!("Plan not found: {}", plan_id))),
+        match self.service.progress(&plan_id).await {
+            Ok(progress) => Ok(json_result(&progress)),
+            Err(e) => Ok(tool_error_result("", &e)),
         }
     }
 }
@@ -741,10 +792,7 @@ impl ToolHandler for ViewHistoryTool {
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         match self.service.get_plan(&plan_id).await {
-            Some(plan) => {
-                let json = serde_json::to_string_pretty(&plan)?;
-                Ok(success_result(format!("Plan history:\n{}", json)))
-            }
+            Some(plan) => Ok(json_result(&plan)),
             None => Ok(error_result(format!("Plan not found: {}", plan_id))),
         }
     }
@@ -752,7 +800,6 @@ impl ToolHandler for ViewHistoryTool {
 
 /// Request approval tool.
 pub struct RequestApprovalTool {
-    #[allow(dead_code)]
     service: Arc<PlanningService>,
 }
 
@@ -776,29 +823,46 @@ impl ToolHandler for RequestApprovalTool {
                         "type": "array",
                         "items": { "type": "integer" },
                         "description": "Optional specific steps to approve"
-                    }
+                    },
+                    "requested_by": { "type": "string", "description": "Who is asking" }
                 },
                 "required": ["plan_id"]
             }),
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
-        let request_id = uuid::Uuid::new_v4().to_string();
-        let result = serde_json::json!({
-            "request_id": request_id,
-            "plan_id": plan_id,
-            "status": "pending",
-            "created_at": chrono::Utc::now().to_rfc3339()
-        });
-        Ok(success_result(serde_json::to_string_pretty(&result)?))
+        let step_ids: Vec<u32> = args
+            .get("step_numbers")
+            .and_then(|v| v.as_array())
+            .map(|steps| {
+                steps
+                    .iter()
+                    .filter_map(|s| s.as_u64())
+                    .map(|s| s as u32)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let requested_by = get_optional_string_arg(&args, "requested_by");
+
+        match self
+            .service
+            .request_approval(&plan_id, step_ids, requested_by)
+            .await
+        {
+            Ok(request) => Ok(json_result(&request)),
+            Err(e) => Ok(tool_error_result("Failed to request approval", &e)),
+        }
     }
 }
 
 /// Respond to approval tool.
 pub struct RespondApprovalTool {
-    #[allow(dead_code)]
     service: Arc<PlanningService>,
 }
 
@@ -819,30 +883,101 @@ impl ToolHandler for RespondApprovalTool {
                 "properties": {
                     "request_id": { "type": "string", "description": "Approval request ID" },
                     "action": { "type": "string", "enum": ["approve", "reject"], "description": "Action to take" },
-                    "comments": { "type": "string", "description": "Optional comments" }
+                    "comments": { "type": "string", "description": "Optional comments" },
+                    "responded_by": { "type": "string", "description": "Who is responding" }
                 },
                 "required": ["request_id", "action"]
             }),
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let request_id = get_string_arg(&args, "request_id")?;
-        let action = get_string_arg(&args, "action")?;
-        let comments = args.get("comments").and_then(|v| v.as_str()).unwrap_or("");
-        let result = serde_json::json!({
-            "request_id": request_id,
-            "action": action,
-            "comments": comments,
-            "responded_at": chrono::Utc::now().to_rfc3339()
-        });
-        Ok(success_result(serde_json::to_string_pretty(&result)?))
+        let approve = match get_string_arg(&args, "action")?.as_str() {
+            "approve" => true,
+            "reject" => false,
+            other => {
+                return Ok(error_result(format!(
+                    "Unknown action '{}': expected approve or reject",
+                    other
+                )))
+            }
+        };
+        let comment = get_optional_string_arg(&args, "comments");
+        let responded_by = get_optional_string_arg(&args, "responded_by");
+
+        match self
+            .service
+            .respond_approval(&request_id, approve, responded_by, comment)
+            .await
+        {
+            Ok(request) => Ok(json_result(&request)),
+            Err(e) => Ok(tool_error_result("Failed to respond to approval", &e)),
+        }
+    }
+}
+
+/// List approvals tool.
+pub struct ListApprovalsTool {
+    service: Arc<PlanningService>,
+}
+
+impl ListApprovalsTool {
+    pub fn new(service: Arc<PlanningService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ListApprovalsTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "list_approvals".to_string(),
+            description: "List approval requests, optionally for one plan or in one state."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "plan_id": { "type": "string", "description": "Only this plan's requests" },
+                    "status": {
+                        "type": "string",
+                        "enum": ["pending", "approved", "rejected"],
+                        "description": "Only requests in this state"
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let plan_id = get_optional_string_arg(&args, "plan_id");
+        let state = match get_optional_string_arg(&args, "status").as_deref() {
+            None => None,
+            Some("pending") => Some(ApprovalState::Pending),
+            Some("approved") => Some(ApprovalState::Approved),
+            Some("rejected") => Some(ApprovalState::Rejected),
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Unknown status '{}': expected pending, approved or rejected",
+                    other
+                )))
+            }
+        };
+
+        let approvals = self.service.list_approvals(plan_id.as_deref(), state).await;
+        Ok(json_result(&serde_json::json!({
+            "count": approvals.len(),
+            "approvals": approvals
+        })))
     }
 }
 
 /// Compare plan versions tool.
 pub struct ComparePlanVersionsTool {
-    #[allow(dead_code)]
     service: Arc<PlanningService>,
 }
 
@@ -863,7 +998,12 @@ impl ToolHandler for ComparePlanVersionsTool {
                 "properties": {
                     "plan_id": { "type": "string", "description": "Plan ID" },
                     "from_version": { "type": "integer", "description": "Source version" },
-                    "to_version": { "type": "integer", "description": "Target version" }
+                    "to_version": { "type": "integer", "description": "Target version" },
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "text"],
+                        "description": "json for structured changes, text for a readable summary (default: json)"
+                    }
                 },
                 "required": ["plan_id", "from_version", "to_version"]
             }),
@@ -874,23 +1014,34 @@ impl ToolHandler for ComparePlanVersionsTool {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let from_version = args
             .get("from_version")
-            .and_then(|v| v.as_i64())
-            .unwrap_or(1);
-        let to_version = args.get("to_version").and_then(|v| v.as_i64()).unwrap_or(2);
-        let result = serde_json::json!({
-            "plan_id": plan_id,
-            "from_version": from_version,
-            "to_version": to_version,
-            "changes": [],
-            "summary": "No changes detected"
-        });
-        Ok(success_result(serde_json::to_string_pretty(&result)?))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1) as u32;
+        let to_version = args.get("to_version").and_then(|v| v.as_u64()).unwrap_or(2) as u32;
+        let text = match get_optional_string_arg(&args, "format").as_deref() {
+            None | Some("json") => false,
+            Some("text") => true,
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Unknown format '{}': expected json or text",
+                    other
+                )))
+            }
+        };
+
+        match self
+            .service
+            .compare_versions(&plan_id, from_version, to_version)
+            .await
+        {
+            Ok(diff) if text => Ok(success_result(plan_diff_to_text(&diff))),
+            Ok(diff) => Ok(json_result(&diff)),
+            Err(e) => Ok(tool_error_result("Failed to compare plan versions", &e)),
+        }
     }
 }
 
 /// Rollback plan tool.
 pub struct RollbackPlanTool {
-    #[allow(dead_code)]
     service: Arc<PlanningService>,
 }
 
@@ -918,6 +1069,10 @@ impl ToolHandler for RollbackPlanTool {
         }
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let plan_id = get_string_arg(&args, "plan_id")?;
         let version = args.get("version").and_then(|v| v.as_i64()).unwrap_or(1);
@@ -929,8 +1084,8 @@ impl ToolHandler for RollbackPlanTool {
             "plan_id": plan_id,
             "rolled_back_to": version,
             "reason": reason,
-            "rolled_back_at": chrono::Utc::now().to_rfc3339()
+            "rolled_back_at": self.service.clock().now_rfc3339()
         });
-        Ok(success_result(serde_json::to_string_pretty(&result)?))
+        Ok(json_result(&result))
     }
 }
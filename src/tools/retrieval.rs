@@ -2,52 +2,51 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::mcp::handler::{
-    error_result, get_optional_string_arg, get_string_arg, success_result, ToolHandler,
+    error_result, get_bool_arg, get_optional_string_arg, get_string_arg, get_string_array_arg,
+    json_result, json_result_with_links, success_result, success_result_with_links,
+    tool_error_result, McpHandler, ToolHandler,
 };
-use crate::mcp::protocol::{Tool, ToolResult};
-use crate::service::ContextService;
-
-/// Get syntax highlighting language for a file extension.
-fn get_language_for_extension(ext: &str) -> &'static str {
-    match ext.to_lowercase().as_str() {
-        "ts" => "typescript",
-        "tsx" => "tsx",
-        "js" => "javascript",
-        "jsx" => "jsx",
-        "py" => "python",
-        "rb" => "ruby",
-        "go" => "go",
-        "rs" => "rust",
-        "java" => "java",
-        "kt" => "kotlin",
-        "cs" => "csharp",
-        "cpp" | "cc" | "cxx" => "cpp",
-        "c" | "h" => "c",
-        "hpp" => "cpp",
-        "swift" => "swift",
-        "php" => "php",
-        "sql" => "sql",
-        "json" => "json",
-        "yaml" | "yml" => "yaml",
-        "md" => "markdown",
-        "html" => "html",
-        "css" => "css",
-        "scss" => "scss",
-        "vue" => "vue",
-        "svelte" => "svelte",
-        "toml" => "toml",
-        "xml" => "xml",
-        "sh" | "bash" => "bash",
-        "ps1" => "powershell",
-        _ => "",
-    }
-}
+use crate::mcp::progress::ProgressReporter;
+use crate::mcp::protocol::{ContentBlock, Tool, ToolResult};
+use crate::mcp::resources::snippet_uri;
+use crate::service::bundle::{parse_snippets, Snippet};
+use crate::service::grep::DEFAULT_MAX_MATCHES;
+use crate::service::language::detect_language;
+use crate::service::outline::{extract_symbols, module_doc, supports_language, Symbol, SymbolKind};
+use crate::service::tree::{
+    DEFAULT_MAX_DEPTH as DEFAULT_TREE_DEPTH, DEFAULT_MAX_ENTRIES as DEFAULT_TREE_ENTRIES,
+};
+use crate::service::{
+    ContextService, GrepMatch, GrepOptions, SearchFilter, TreeOptions, WorkspaceStats,
+};
+use crate::tools::table::Table;
+
+/// Category these tools are registered under.
+pub const CATEGORY: &str = "retrieval";
+
+/// Upper bound on `max_matches` for the grep tool.
+const GREP_MATCH_LIMIT: usize = 1000;
+
+/// Upper bound on `context_lines` for the grep tool.
+const GREP_CONTEXT_LIMIT: usize = 10;
+
+/// Upper bound on `limit` for a page of search results.
+const SEARCH_PAGE_LIMIT: usize = 100;
+
+/// Tokens of file content sent along when asking for a file's purpose.
+const PURPOSE_TOKEN_BUDGET: usize = 4000;
+
+/// Upper bound on `max_depth` for the tree tool.
+const TREE_DEPTH_LIMIT: usize = 10;
+
+/// Upper bound on `max_entries` for the tree tool.
+const TREE_ENTRY_LIMIT: usize = 5000;
 
 /// Format file size in human-readable format.
 fn format_file_size(bytes: usize) -> String {
@@ -60,6 +59,123 @@ fn format_file_size(bytes: usize) -> String {
     }
 }
 
+/// File paths referenced by a formatted retrieval result (`Path: ...` lines).
+fn retrieval_paths(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("Path:"))
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+}
+
+/// A search result: `text` followed by `resource_link` blocks for each file
+/// and each snippet in `shown`. The snippets are remembered so their links
+/// can be read back with `resources/read`.
+fn search_result(service: &ContextService, text: String, shown: &str) -> ToolResult {
+    let snippet_links: Vec<ContentBlock> = parse_snippets(shown)
+        .iter()
+        .map(|snippet| ContentBlock::ResourceLink {
+            uri: snippet_uri(&service.remember_snippet(snippet)),
+            name: snippet.path.clone(),
+            description: Some(format!("Search hit in {}", snippet.path)),
+            mime_type: None,
+        })
+        .collect();
+    success_result_with_links(text, service.workspace(), retrieval_paths(shown))
+        .with_blocks(snippet_links)
+}
+
+/// `text` with a closing note if the search behind it timed out, so agents
+/// know more matches may exist.
+fn note_truncation(mut text: String, truncated: bool) -> String {
+    if truncated {
+        text.push_str(
+            "\n\n(truncated: true) The search timed out; these are the matches received before it did.",
+        );
+    }
+    text
+}
+
+/// The snippets of `retrieved` from `offset`, at most `limit` of them, with a
+/// footer giving the total and the offset of the next page.
+fn page_snippets(retrieved: &str, offset: usize, limit: usize) -> String {
+    let snippets = parse_snippets(retrieved);
+    let total = snippets.len();
+    let page: Vec<String> = snippets
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|snippet| snippet.render())
+        .collect();
+    let end = offset + page.len();
+
+    let mut text = page.join("\n\n");
+    if !text.is_empty() {
+        text.push_str("\n\n---\n");
+    }
+    if page.is_empty() {
+        text.push_str(&format!(
+            "No results at offset {} (total: {}).",
+            offset, total
+        ));
+    } else {
+        text.push_str(&format!(
+            "Results {}-{} of {} (has_more: {}).",
+            offset + 1,
+            end,
+            total,
+            end < total
+        ));
+    }
+    if end < total {
+        text.push_str(&format!(" Next page: offset {}.", end));
+    }
+    text
+}
+
+/// Schema for the optional `root` argument of file tools.
+fn root_schema() -> Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "Optional: Registered root to read from, by path or directory name (default: the workspace)"
+    })
+}
+
+/// Comma-separated paths, or "none".
+fn list_or_none(paths: &[String]) -> String {
+    if paths.is_empty() {
+        "none".to_string()
+    } else {
+        paths.join(", ")
+    }
+}
+
+/// Default lines shown on each side of `around_line`.
+const DEFAULT_AROUND_CONTEXT: usize = 10;
+
+/// Resolve the requested 1-based inclusive line window, clamped to the file.
+///
+/// `around_line` takes precedence over `start_line`/`end_line`. Returns
+/// `None` when no range was requested or the file is empty.
+fn line_window(
+    total_lines: usize,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    around_line: Option<usize>,
+    context: usize,
+) -> Option<(usize, usize)> {
+    if total_lines == 0 {
+        return None;
+    }
+    let (start, end) = match (around_line, start_line, end_line) {
+        (Some(line), _, _) => (line.saturating_sub(context), line.saturating_add(context)),
+        (None, None, None) => return None,
+        (None, start, end) => (start.unwrap_or(1), end.unwrap_or(total_lines)),
+    };
+    let start = start.clamp(1, total_lines);
+    let end = end.clamp(start, total_lines);
+    Some((start, end))
+}
+
 /// Codebase retrieval tool - semantic search.
 pub struct CodebaseRetrievalTool {
     service: Arc<ContextService>,
@@ -102,7 +218,11 @@ impl ToolHandler for CodebaseRetrievalTool {
             .map(|v| v as usize);
 
         match self.service.search(&query, max_tokens).await {
-            Ok(result) => Ok(success_result(result)),
+            Ok(result) => Ok(search_result(
+                &self.service,
+                note_truncation(result.text.clone(), result.truncated),
+                &result.text,
+            )),
             Err(e) => Ok(error_result(format!("Search failed: {}", e))),
         }
     }
@@ -134,13 +254,33 @@ impl ToolHandler for SearchCodeTool {
                         "type": "string",
                         "description": "Search query (can be natural language or code pattern)"
                     },
+                    "language": {
+                        "type": "string",
+                        "description": "Only return results in this language, by name or extension (e.g., 'rust', 'ts')"
+                    },
+                    "path_glob": {
+                        "type": "string",
+                        "description": "Only return results whose path matches this glob (e.g., '*.rs', 'src/auth/**')"
+                    },
                     "file_pattern": {
                         "type": "string",
-                        "description": "Optional glob pattern to filter files (e.g., '*.rs', 'src/**/*.ts')"
+                        "description": "Alias for path_glob"
                     },
                     "max_results": {
                         "type": "integer",
                         "description": "Maximum number of results to return"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Page size; returns one page of results with a total and has_more (max 100)"
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Number of results to skip when paging (default: 0)"
+                    },
+                    "explain": {
+                        "type": "boolean",
+                        "description": "Return JSON hits with a score, line range and best-matching line explaining each (default: false)"
                     }
                 },
                 "required": ["query"]
@@ -150,22 +290,246 @@ impl ToolHandler for SearchCodeTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let query = get_string_arg(&args, "query")?;
-        let _file_pattern = get_optional_string_arg(&args, "file_pattern");
+        let path_glob = get_optional_string_arg(&args, "path_glob")
+            .or_else(|| get_optional_string_arg(&args, "file_pattern"));
+        let filter = match SearchFilter::new(
+            get_optional_string_arg(&args, "language").as_deref(),
+            path_glob.as_deref(),
+        ) {
+            Ok(filter) => filter,
+            Err(e) => return Ok(tool_error_result("", &e)),
+        };
         let max_results = args
             .get("max_results")
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
 
-        // Use semantic search with optional token limit
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let offset = args
+            .get("offset")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let page = match (limit, offset) {
+            (None, None) => None,
+            (Some(0), _) => {
+                return Ok(tool_error_result(
+                    "",
+                    &Error::InvalidToolArguments("limit must be at least 1".to_string()),
+                ))
+            }
+            (limit, offset) => Some((
+                offset.unwrap_or(0),
+                limit.unwrap_or(SEARCH_PAGE_LIMIT).min(SEARCH_PAGE_LIMIT),
+            )),
+        };
+
+        // Use semantic search with optional token limit. Every page of a
+        // query uses the same budget, so pages are cut from one cached
+        // retrieval and their order stays consistent
         let max_tokens = max_results.map(|r| r * 500); // Rough estimate
 
-        match self.service.search(&query, max_tokens).await {
-            Ok(result) => Ok(success_result(result)),
+        match self
+            .service
+            .search(&filter.hint_query(&query), max_tokens)
+            .await
+        {
+            Ok(retrieval) => {
+                let result = filter.apply(&retrieval.text);
+                if result.is_empty() && !filter.is_empty() {
+                    return Ok(success_result(
+                        "No results matched the language and path filters.",
+                    ));
+                }
+                if get_bool_arg(&args, "explain", false) {
+                    return Ok(self
+                        .explained_result(&query, &result, page, retrieval.truncated)
+                        .await);
+                }
+                let result = match page {
+                    Some((offset, limit)) => page_snippets(&result, offset, limit),
+                    None => result,
+                };
+                Ok(search_result(
+                    &self.service,
+                    note_truncation(result.clone(), retrieval.truncated),
+                    &result,
+                ))
+            }
             Err(e) => Ok(error_result(format!("Search failed: {}", e))),
         }
     }
 }
 
+impl SearchCodeTool {
+    /// The hits of `retrieved`, or one page of them, as JSON with an
+    /// explanation of each.
+    async fn explained_result(
+        &self,
+        query: &str,
+        retrieved: &str,
+        page: Option<(usize, usize)>,
+        truncated: bool,
+    ) -> ToolResult {
+        let snippets = parse_snippets(retrieved);
+        let total = snippets.len();
+        let (offset, limit) = page.unwrap_or((0, total));
+        let shown: Vec<Snippet> = snippets.into_iter().skip(offset).take(limit).collect();
+        let hits = self.service.explain_hits(query, &shown).await;
+
+        let mut body = serde_json::json!({ "results": hits, "truncated": truncated });
+        if page.is_some() {
+            body["total"] = serde_json::json!(total);
+            body["has_more"] = serde_json::json!(offset + shown.len() < total);
+        }
+        let shown_text: Vec<String> = shown.iter().map(Snippet::render).collect();
+        search_result(
+            &self.service,
+            serde_json::to_string_pretty(&body).unwrap_or_default(),
+            &shown_text.join("\n\n"),
+        )
+    }
+}
+
+/// Grep tool - exact literal/regex search without the backend.
+pub struct GrepTool {
+    service: Arc<ContextService>,
+}
+
+impl GrepTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for GrepTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "grep".to_string(),
+            description: "Exact text search across indexable workspace files. Runs locally without the backend; use it for exact identifiers or strings where semantic search is overkill.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pattern": {
+                        "type": "string",
+                        "description": "Text or regular expression to search for"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["literal", "regex"],
+                        "description": "How to interpret the pattern (default: literal)"
+                    },
+                    "case_sensitive": {
+                        "type": "boolean",
+                        "description": "Match case exactly (default: true)"
+                    },
+                    "context_lines": {
+                        "type": "integer",
+                        "description": "Lines of context before and after each match (default: 0, max: 10)"
+                    },
+                    "max_matches": {
+                        "type": "integer",
+                        "description": "Maximum matches to return (default: 100, max: 1000)"
+                    },
+                    "stream": {
+                        "type": "boolean",
+                        "description": "Send each match as an NDJSON line in a progress notification as it is found, and return only a summary. Needs a progress token; without one the matches are returned as usual (default: false)"
+                    }
+                },
+                "required": ["pattern"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        self.execute_with_progress(args, None).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        args: HashMap<String, Value>,
+        progress: Option<ProgressReporter>,
+    ) -> Result<ToolResult> {
+        let pattern = get_string_arg(&args, "pattern")?;
+        let regex = match get_optional_string_arg(&args, "mode").as_deref() {
+            None | Some("literal") => false,
+            Some("regex") => true,
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Invalid mode '{}': expected 'literal' or 'regex'",
+                    other
+                )))
+            }
+        };
+        let options = GrepOptions {
+            regex,
+            case_sensitive: get_bool_arg(&args, "case_sensitive", true),
+            context_lines: args
+                .get("context_lines")
+                .and_then(|v| v.as_u64())
+                .map_or(0, |v| (v as usize).min(GREP_CONTEXT_LIMIT)),
+            max_matches: args
+                .get("max_matches")
+                .and_then(|v| v.as_u64())
+                .map_or(DEFAULT_MAX_MATCHES, |v| {
+                    (v as usize).clamp(1, GREP_MATCH_LIMIT)
+                }),
+        };
+
+        if let Some(progress) = progress.filter(|_| get_bool_arg(&args, "stream", false)) {
+            return self.stream_matches(&pattern, options, progress).await;
+        }
+
+        match self.service.grep(&pattern, options).await {
+            Ok(result) => Ok(json_result_with_links(
+                &result,
+                self.service.workspace(),
+                result.matches.iter().map(|m| m.path.as_str()),
+            )),
+            Err(e) => Ok(tool_error_result("Grep failed", &e)),
+        }
+    }
+}
+
+impl GrepTool {
+    /// Run the search, sending each match as one NDJSON line in the message
+    /// of a progress notification, then return a summary.
+    async fn stream_matches(
+        &self,
+        pattern: &str,
+        options: GrepOptions,
+        progress: ProgressReporter,
+    ) -> Result<ToolResult> {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<GrepMatch>(64);
+        let forward = tokio::spawn(async move {
+            let mut sent = 0;
+            while let Some(found) = rx.recv().await {
+                let Ok(line) = serde_json::to_string(&found) else {
+                    continue;
+                };
+                sent += 1;
+                if !progress.send(sent, None, Some(&line)).await {
+                    break;
+                }
+            }
+        });
+
+        let result = self.service.grep_streaming(pattern, options, tx).await;
+        let _ = forward.await;
+        match result {
+            Ok(result) => Ok(json_result(&serde_json::json!({
+                "streamed": result.streamed,
+                "files_scanned": result.files_scanned,
+                "truncated": result.truncated
+            }))),
+            Err(e) => Ok(tool_error_result("Grep failed", &e)),
+        }
+    }
+}
+
 /// Get file tool - retrieve file contents.
 pub struct GetFileTool {
     service: Arc<ContextService>,
@@ -191,13 +555,22 @@ impl ToolHandler for GetFileTool {
                         "type": "string",
                         "description": "File path relative to workspace root"
                     },
+                    "root": root_schema(),
                     "start_line": {
                         "type": "integer",
                         "description": "Optional: First line to include (1-based)"
                     },
                     "end_line": {
                         "type": "integer",
-                        "description": "Optional: Last line to include (1-based)"
+                        "description": "Optional: Last line to include (1-based, inclusive)"
+                    },
+                    "around_line": {
+                        "type": "integer",
+                        "description": "Optional: Center the output on this line (overrides start_line/end_line)"
+                    },
+                    "context": {
+                        "type": "integer",
+                        "description": "Optional: Lines on each side of around_line (default: 10)"
                     }
                 },
                 "required": ["path"]
@@ -207,6 +580,7 @@ impl ToolHandler for GetFileTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let path = get_string_arg(&args, "path")?;
+        let root = get_optional_string_arg(&args, "root");
         let start_line = args
             .get("start_line")
             .and_then(|v| v.as_u64())
@@ -215,6 +589,14 @@ impl ToolHandler for GetFileTool {
             .get("end_line")
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
+        let around_line = args
+            .get("around_line")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let context = args
+            .get("context")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_AROUND_CONTEXT, |v| v as usize);
 
         // Validate path
         if path.is_empty() {
@@ -228,11 +610,11 @@ impl ToolHandler for GetFileTool {
             ));
         }
 
-        // Read file
-        let full_path = self.service.workspace_path().join(&path);
-        let content = match tokio::fs::read_to_string(&full_path).await {
+        // Read within the workspace, enforcing the size limit
+        let content = match self.service.read_file_in(root.as_deref(), &path).await {
             Ok(c) => c,
-            Err(e) => return Ok(error_result(format!("Failed to read file: {}", e))),
+            Err(e @ Error::Io(_)) => return Ok(tool_error_result("Failed to read file", &e)),
+            Err(e) => return Ok(tool_error_result("", &e)),
         };
 
         let all_lines: Vec<&str> = content.lines().collect();
@@ -242,33 +624,20 @@ impl ToolHandler for GetFileTool {
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("");
-        let language = get_language_for_extension(ext);
+        let language = detect_language(Path::new(&path), &content).unwrap_or("");
         let filename = Path::new(&path)
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or(&path);
 
-        // Handle line range
-        let (output_content, line_info) = if start_line.is_some() || end_line.is_some() {
-            let start = start_line.unwrap_or(1).saturating_sub(1);
-            let end = end_line.unwrap_or(total_lines);
-
-            if start >= total_lines {
-                return Ok(error_result(format!(
-                    "start_line {} exceeds file length ({} lines)",
-                    start + 1,
-                    total_lines
-                )));
-            }
-
-            let selected: Vec<&str> = all_lines[start..end.min(total_lines)].to_vec();
-            let line_info = format!(
-                "Lines {}-{} of {}",
-                start + 1,
-                end.min(total_lines),
-                total_lines
-            );
-            (selected.join("\n"), line_info)
+        // Handle line range (out-of-range values are clamped to the file)
+        let window = line_window(total_lines, start_line, end_line, around_line, context);
+        let (output_content, line_info) = if let Some((start, end)) = window {
+            let selected = all_lines[start - 1..end].join("\n");
+            (
+                selected,
+                format!("Lines {}-{} of {}", start, end, total_lines),
+            )
         } else {
             (content.clone(), format!("{} lines", total_lines))
         };
@@ -293,6 +662,195 @@ impl ToolHandler for GetFileTool {
     }
 }
 
+/// File outline tool.
+pub struct FileOutlineTool {
+    service: Arc<ContextService>,
+}
+
+impl FileOutlineTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for FileOutlineTool {
+    fn definition(&self) -> Tool {
+        let kinds: Vec<&str> = SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+        Tool {
+            name: "file_outline".to_string(),
+            description: "List the symbols (functions, types, classes, ...) declared in a file, grouped by kind or as a flat list ordered by line.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File path relative to workspace root"
+                    },
+                    "root": root_schema(),
+                    "kinds": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": kinds },
+                        "description": "Optional: Only include these symbol kinds"
+                    },
+                    "flat": {
+                        "type": "boolean",
+                        "description": "Return one list ordered by line instead of grouping by kind (default: false)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = get_string_arg(&args, "path")?;
+        let flat = get_bool_arg(&args, "flat", false);
+        let root = get_optional_string_arg(&args, "root");
+
+        let mut kinds = Vec::new();
+        for name in get_string_array_arg(&args, "kinds") {
+            match SymbolKind::parse(&name) {
+                Some(kind) => kinds.push(kind),
+                None => {
+                    let valid: Vec<&str> = SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+                    let error = Error::InvalidToolArguments(format!(
+                        "Unknown symbol kind '{}': expected one of {}",
+                        name,
+                        valid.join(", ")
+                    ));
+                    return Ok(tool_error_result("", &error));
+                }
+            }
+        }
+
+        let content = match self.service.read_file_in(root.as_deref(), &path).await {
+            Ok(c) => c,
+            Err(e) => return Ok(tool_error_result("", &e)),
+        };
+        let language = detect_language(Path::new(&path), &content);
+        let symbols: Vec<Symbol> = extract_symbols(language.unwrap_or(""), &content)
+            .into_iter()
+            .filter(|s| kinds.is_empty() || kinds.contains(&s.kind))
+            .collect();
+
+        let symbols_json = if flat {
+            serde_json::to_value(&symbols)?
+        } else {
+            let mut groups: BTreeMap<SymbolKind, Vec<Value>> = BTreeMap::new();
+            for symbol in &symbols {
+                groups
+                    .entry(symbol.kind)
+                    .or_default()
+                    .push(serde_json::json!({
+                        "name": symbol.qualified_name(),
+                        "line": symbol.line,
+                        "signature": symbol.signature,
+                    }));
+            }
+            Value::Object(
+                groups
+                    .into_iter()
+                    .map(|(kind, items)| (kind.as_str().to_string(), Value::Array(items)))
+                    .collect(),
+            )
+        };
+
+        let result = serde_json::json!({
+            "path": path,
+            "language": language,
+            "supported": language.is_some_and(supports_language),
+            "count": symbols.len(),
+            "symbols": symbols_json,
+        });
+        Ok(json_result(&result))
+    }
+}
+
+/// Summarize file tool.
+///
+/// Combines the file outline with its module documentation, plus an
+/// optional LLM-written purpose summary based only on the file itself.
+pub struct SummarizeFileTool {
+    service: Arc<ContextService>,
+}
+
+impl SummarizeFileTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for SummarizeFileTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "summarize_file".to_string(),
+            description: "Quick overview of one file without reading it all: its symbols, its module documentation and optionally an AI-written paragraph on its purpose.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File path relative to workspace root"
+                    },
+                    "root": root_schema(),
+                    "purpose": {
+                        "type": "boolean",
+                        "description": "Add an AI-written one-paragraph summary of what the file is for (default: false)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = get_string_arg(&args, "path")?;
+        let root = get_optional_string_arg(&args, "root");
+        let content = match self.service.read_file_in(root.as_deref(), &path).await {
+            Ok(c) => c,
+            Err(e) => return Ok(tool_error_result("", &e)),
+        };
+        let language = detect_language(Path::new(&path), &content);
+        let symbols = extract_symbols(language.unwrap_or(""), &content);
+        let doc = module_doc(language.unwrap_or(""), &content);
+
+        let mut summary = serde_json::json!({
+            "path": path,
+            "language": language,
+            "lines": content.lines().count(),
+            "module_doc": doc,
+            "symbols": symbols,
+        });
+
+        // The purpose is a bonus; the outline stands on its own
+        if get_bool_arg(&args, "purpose", false) {
+            let excerpt = self
+                .service
+                .token_estimator()
+                .truncate(&content, PURPOSE_TOKEN_BUDGET);
+            let prompt = format!(
+                "In one paragraph, explain what the file `{}` is for and how it fits into its \
+                 codebase. Use only the file below; do not describe other files.\n\n```\n{}\n```",
+                path, excerpt
+            );
+            match self.service.chat(&prompt).await {
+                Ok(purpose) if !purpose.trim().is_empty() => {
+                    summary["purpose"] = Value::String(purpose.trim().to_string());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::debug!("File purpose unavailable: {}", e);
+                    summary["purpose_unavailable"] = Value::String(e.to_string());
+                }
+            }
+        }
+
+        Ok(json_result(&summary))
+    }
+}
+
 /// Get context for prompt tool.
 pub struct GetContextTool {
     service: Arc<ContextService>,
@@ -323,7 +881,7 @@ impl ToolHandler for GetContextTool {
                     },
                     "token_budget": {
                         "type": "integer",
-                        "description": "Maximum tokens for the entire context (default: 8000)"
+                        "description": "Maximum tokens for the entire context (default: the configured token budget, 8000 unless overridden)"
                     },
                     "include_related": {
                         "type": "boolean",
@@ -341,23 +899,46 @@ impl ToolHandler for GetContextTool {
 
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let query = get_string_arg(&args, "query")?;
-        let max_files = args.get("max_files").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+        let max_files = args
+            .get("max_files")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5)
+            .clamp(1, 20) as usize;
         let token_budget = args
             .get("token_budget")
             .and_then(|v| v.as_u64())
-            .unwrap_or(8000) as usize;
+            .map(|v| v as usize)
+            .unwrap_or(self.service.config().token_budget);
 
-        // Perform semantic search
-        match self.service.search(&query, Some(token_budget)).await {
-            Ok(result) => {
+        match self
+            .service
+            .bundle_context(&query, token_budget, max_files)
+            .await
+        {
+            Ok(bundle) => {
                 let mut output = "# 📚 Codebase Context\n\n".to_string();
                 output.push_str(&format!("**Query:** \"{}\"\n\n", query));
                 output.push_str(&format!(
                     "**Settings:** max_files={}, token_budget={}\n\n",
                     max_files, token_budget
                 ));
+                output.push_str(&format!(
+                    "**Estimated tokens:** {} / {}\n\n",
+                    bundle.estimated_tokens, bundle.token_budget
+                ));
+                output.push_str(&format!(
+                    "**Included files:** {}\n\n",
+                    list_or_none(&bundle.included_files)
+                ));
+                if bundle.excluded_snippets > 0 {
+                    output.push_str(&format!(
+                        "**Excluded ({} snippet(s) over budget or file limit):** {}\n\n",
+                        bundle.excluded_snippets,
+                        list_or_none(&bundle.excluded_files)
+                    ));
+                }
                 output.push_str("## Results\n\n");
-                output.push_str(&result);
+                output.push_str(&bundle.render());
                 Ok(success_result(output))
             }
             Err(e) => Ok(error_result(format!("Context retrieval failed: {}", e))),
@@ -388,6 +969,19 @@ impl ToolHandler for EnhancePromptTool {
                     "prompt": {
                         "type": "string",
                         "description": "The simple prompt to enhance"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["ai", "context"],
+                        "description": "ai rewrites the prompt with the model; context appends the most relevant code snippets to it unchanged (default: ai)"
+                    },
+                    "max_snippets": {
+                        "type": "integer",
+                        "description": "Context mode: maximum snippets to append (default: 5, max: 50)"
+                    },
+                    "token_budget": {
+                        "type": "integer",
+                        "description": "Context mode: maximum tokens of appended snippets (default: the configured token budget)"
                     }
                 },
                 "required": ["prompt"]
@@ -404,6 +998,36 @@ impl ToolHandler for EnhancePromptTool {
             ));
         }
 
+        match get_optional_string_arg(&args, "mode").as_deref() {
+            None | Some("ai") => {}
+            Some("context") => {
+                let max_snippets = args
+                    .get("max_snippets")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(5)
+                    .clamp(1, 50) as usize;
+                let token_budget = args
+                    .get("token_budget")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(self.service.config().token_budget);
+                return match self
+                    .service
+                    .augment_prompt(&prompt, token_budget, max_snippets)
+                    .await
+                {
+                    Ok(augmented) => Ok(success_result(augmented)),
+                    Err(e) => Ok(tool_error_result("Prompt enhancement failed", &e)),
+                };
+            }
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Unknown mode '{}': expected ai or context",
+                    other
+                )))
+            }
+        }
+
         // Use AI to enhance the prompt with codebase context
         match self.service.enhance_prompt(&prompt).await {
             Ok(enhanced) => Ok(success_result(enhanced)),
@@ -412,94 +1036,1088 @@ impl ToolHandler for EnhancePromptTool {
     }
 }
 
-/// Tool manifest tool - discover available capabilities.
-pub struct ToolManifestTool;
+/// Workspace stats tool.
+pub struct WorkspaceStatsTool {
+    service: Arc<ContextService>,
+}
 
-impl ToolManifestTool {
-    pub fn new() -> Self {
-        Self
+impl WorkspaceStatsTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
     }
 }
 
-impl Default for ToolManifestTool {
-    fn default() -> Self {
-        Self::new()
+#[async_trait]
+impl ToolHandler for WorkspaceStatsTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "workspace_stats".to_string(),
+            description: "Count indexable files, lines and bytes in the workspace, in total and per language. Only files changed since the last call are re-read.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "table"],
+                        "description": "json (default), or table for a plain-text table of languages by lines"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let table = match get_optional_string_arg(&args, "format").as_deref() {
+            None | Some("json") => false,
+            Some("table") => true,
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Unknown format '{}': expected json or table",
+                    other
+                )))
+            }
+        };
+
+        match self.service.workspace_stats().await {
+            Ok(stats) if table => Ok(success_result(stats_table(&stats))),
+            Ok(stats) => Ok(json_result(&stats)),
+            Err(e) => Ok(tool_error_result("Failed to compute workspace stats", &e)),
+        }
+    }
+}
+
+/// `stats` as a table of languages, most lines first, with a total row.
+fn stats_table(stats: &WorkspaceStats) -> String {
+    let mut languages: Vec<_> = stats.languages.iter().collect();
+    languages.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then(a.0.cmp(b.0)));
+
+    let mut table = Table::new(&["Language", "Files", "Lines", "Code", "Bytes"]);
+    for (language, counts) in languages {
+        table.row([
+            language.clone(),
+            counts.files.to_string(),
+            counts.lines.to_string(),
+            counts.code_lines.to_string(),
+            counts.bytes.to_string(),
+        ]);
+    }
+    table.row([
+        "Total".to_string(),
+        stats.files.to_string(),
+        stats.lines.to_string(),
+        String::new(),
+        stats.bytes.to_string(),
+    ]);
+    table.render()
+}
+
+/// Files that usually mark where a program starts, checked in order.
+const ENTRY_POINTS: &[&str] = &[
+    "src/main.rs",
+    "src/lib.rs",
+    "main.go",
+    "cmd/main.go",
+    "index.ts",
+    "index.js",
+    "src/index.ts",
+    "src/index.tsx",
+    "src/index.js",
+    "src/main.ts",
+    "src/main.tsx",
+    "src/App.tsx",
+    "app.py",
+    "main.py",
+    "manage.py",
+    "__main__.py",
+    "src/main.py",
+    "src/main/java",
+    "Program.cs",
+];
+
+/// Describe codebase tool.
+///
+/// Composes workspace stats, the top-level layout, entry points and the
+/// README into one overview, with an optional LLM-written narrative.
+pub struct DescribeCodebaseTool {
+    service: Arc<ContextService>,
+}
+
+impl DescribeCodebaseTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
     }
 }
 
 #[async_trait]
-impl ToolHandler for ToolManifestTool {
+impl ToolHandler for DescribeCodebaseTool {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "describe_codebase".to_string(),
+            description: "One-shot overview of the workspace for onboarding: language breakdown, top-level layout, detected entry points and README, optionally with an AI-written narrative.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "narrative": {
+                        "type": "boolean",
+                        "description": "Add an AI-written summary of the codebase (default: false)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let stats = match self.service.workspace_stats().await {
+            Ok(stats) => stats,
+            Err(e) => return Ok(tool_error_result("Failed to describe codebase", &e)),
+        };
+        let layout = match self.service.complete_path("").await {
+            Ok(layout) => layout,
+            Err(e) => return Ok(tool_error_result("Failed to describe codebase", &e)),
+        };
+
+        let workspace = self.service.workspace();
+        let entry_points: Vec<&str> = ENTRY_POINTS
+            .iter()
+            .copied()
+            .filter(|path| workspace.join(path).exists())
+            .collect();
+        let readme = layout
+            .iter()
+            .find(|entry| !entry.ends_with('/') && entry.to_lowercase().starts_with("readme"));
+
+        let mut summary = serde_json::json!({
+            "files": stats.files,
+            "lines": stats.lines,
+            "bytes": stats.bytes,
+            "languages": stats.languages,
+            "layout": layout,
+            "entry_points": entry_points,
+            "readme": readme,
+        });
+
+        // The narrative is a bonus; the summary stands on its own
+        if get_bool_arg(&args, "narrative", false) {
+            let prompt = format!(
+                "Write a short overview of this codebase for a developer new to it: what it is, \
+                 how it is organized and where to start reading. Base it on this summary:\n\n{:#}",
+                summary
+            );
+            match self.service.chat(&prompt).await {
+                Ok(narrative) if !narrative.trim().is_empty() => {
+                    summary["narrative"] = Value::String(narrative.trim().to_string());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::debug!("Codebase narrative unavailable: {}", e);
+                    summary["narrative_unavailable"] = Value::String(e.to_string());
+                }
+            }
+        }
+
+        Ok(json_result(&summary))
+    }
+}
+
+/// List tree tool.
+pub struct ListTreeTool {
+    service: Arc<ContextService>,
+}
+
+impl ListTreeTool {
+    pub fn new(service: Arc<ContextService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ListTreeTool {
     fn definition(&self) -> Tool {
+        Tool {
+            name: "list_tree".to_string(),
+            description: "Show the workspace directory structure down to a depth, with file counts per directory. Honors .gitignore and the exclude globs.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Directory to list, relative to workspace root (default: the root)"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": format!("Levels to list below the directory (default: {}, max: {})", DEFAULT_TREE_DEPTH, TREE_DEPTH_LIMIT)
+                    },
+                    "max_entries": {
+                        "type": "integer",
+                        "description": format!("Entries to list before truncating (default: {}, max: {})", DEFAULT_TREE_ENTRIES, TREE_ENTRY_LIMIT)
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "text"],
+                        "description": "Nested JSON, or indented text with one entry per line (default: json)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = get_optional_string_arg(&args, "path").unwrap_or_default();
+        let text = match get_optional_string_arg(&args, "format").as_deref() {
+            None | Some("json") => false,
+            Some("text") => true,
+            Some(other) => {
+                return Ok(error_result(format!(
+                    "Invalid format '{}': expected 'json' or 'text'",
+                    other
+                )))
+            }
+        };
+        let limit = |name: &str, default: usize, max: usize| {
+            args.get(name)
+                .and_then(|v| v.as_u64())
+                .map_or(default, |v| v as usize)
+                .min(max)
+        };
+        let options = TreeOptions {
+            max_depth: limit("max_depth", DEFAULT_TREE_DEPTH, TREE_DEPTH_LIMIT),
+            max_entries: limit("max_entries", DEFAULT_TREE_ENTRIES, TREE_ENTRY_LIMIT),
+            ..TreeOptions::default()
+        };
+
+        match self.service.list_tree(&path, options).await {
+            Ok(tree) if text => Ok(success_result(tree.render())),
+            Ok(tree) => Ok(json_result(&tree)),
+            Err(e) => Ok(tool_error_result("", &e)),
+        }
+    }
+}
+
+/// Tool manifest tool.
+///
+/// Built from the registry after every other tool is registered, so the
+/// manifest is a snapshot of what the server exposes.
+pub struct ToolManifestTool {
+    entries: Vec<ManifestEntry>,
+}
+
+/// One tool in the manifest.
+#[derive(Debug, Clone, serde::Serialize)]
+struct ManifestEntry {
+    name: String,
+    category: &'static str,
+    description: String,
+    input_schema: Value,
+}
+
+impl ToolManifestTool {
+    /// Snapshot the tools registered in `handler`, plus this tool itself.
+    pub fn new(handler: &McpHandler) -> Self {
+        let mut entries: Vec<ManifestEntry> = handler
+            .list_tools()
+            .into_iter()
+            .chain(std::iter::once(Self::tool()))
+            .map(|tool| ManifestEntry {
+                category: handler.category(&tool.name).unwrap_or(CATEGORY),
+                name: tool.name,
+                description: tool.description,
+                input_schema: tool.input_schema,
+            })
+            .collect();
+        entries.sort_by(|a, b| (a.category, &a.name).cmp(&(b.category, &b.name)));
+        entries.dedup_by(|a, b| a.name == b.name);
+        Self { entries }
+    }
+
+    fn tool() -> Tool {
         Tool {
             name: "tool_manifest".to_string(),
-            description: "Discover available tools and capabilities exposed by the server."
+            description: "List every tool the server exposes with its category, description \
+                and input schema, as JSON or a markdown table."
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
-                "properties": {},
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "markdown"],
+                        "description": "Output format (default: json)"
+                    }
+                },
                 "required": []
             }),
         }
     }
 
-    async fn execute(&self, _args: HashMap<String, Value>) -> Result<ToolResult> {
-        let manifest = serde_json::json!({
-            "version": "1.9.0",
-            "capabilities": [
-                "semantic_search",
-                "file_retrieval",
-                "context_enhancement",
-                "index_status",
-                "lifecycle",
-                "planning",
-                "code_review",
-                "memory"
-            ],
-            "tools": [
-                "index_workspace",
-                "codebase_retrieval",
-                "semantic_search",
-                "get_file",
-                "get_context_for_prompt",
-                "enhance_prompt",
-                "index_status",
-                "reindex_workspace",
-                "clear_index",
-                "tool_manifest",
-                "create_plan",
-                "refine_plan",
-                "visualize_plan",
-                "execute_plan",
-                "save_plan",
-                "load_plan",
-                "list_plans",
-                "delete_plan",
-                "request_approval",
-                "respond_approval",
-                "start_step",
-                "complete_step",
-                "fail_step",
-                "view_progress",
-                "view_history",
-                "compare_plan_versions",
-                "rollback_plan",
-                "review_changes",
-                "review_git_diff",
-                "review_diff",
-                "check_invariants",
-                "run_static_analysis",
-                "reactive_review_pr",
-                "get_review_status",
-                "pause_review",
-                "resume_review",
-                "get_review_telemetry",
-                "scrub_secrets",
-                "validate_content",
-                "add_memory",
-                "list_memories"
+    fn to_json(&self) -> Value {
+        let mut categories: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for entry in &self.entries {
+            categories
+                .entry(entry.category)
+                .or_default()
+                .push(&entry.name);
+        }
+        serde_json::json!({
+            "version": crate::VERSION,
+            "tool_count": self.entries.len(),
+            "categories": categories,
+            "tools": self.entries,
+        })
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "# Tool Manifest\n\nVersion {}, {} tools.\n\n| Tool | Category | Description |\n|------|----------|-------------|\n",
+            crate::VERSION,
+            self.entries.len()
+        );
+        for entry in &self.entries {
+            let description = entry.description.replace('|', "\\|").replace('\n', " ");
+            output.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                entry.name, entry.category, description
+            ));
+        }
+        output
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ToolManifestTool {
+    fn definition(&self) -> Tool {
+        Self::tool()
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        match get_optional_string_arg(&args, "format").as_deref() {
+            None | Some("json") => Ok(json_result(&self.to_json())),
+            Some("markdown") => Ok(success_result(self.to_markdown())),
+            Some(other) => Ok(error_result(format!(
+                "Unknown format '{}'; expected json or markdown",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::sdk::mock_backend::MockBackend;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    async fn create_test_service() -> (TempDir, Arc<ContextService>) {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("src/lib.rs"),
+            "pub fn parse_config() {}\nfn helper() {}\npub fn parse_args() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("README.md"),
+            "Call parse_config first.\n",
+        )
+        .unwrap();
+        let config = Config {
+            workspace: temp_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        (temp_dir, service)
+    }
+
+    fn result_json(result: &ToolResult) -> Value {
+        match &result.content[0] {
+            ContentBlock::Text { text } => serde_json::from_str(text).unwrap(),
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    fn result_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_line_window() {
+        assert_eq!(
+            line_window(100, Some(10), Some(20), None, 0),
+            Some((10, 20))
+        );
+        assert_eq!(line_window(100, None, None, Some(50), 3), Some((47, 53)));
+        assert_eq!(line_window(100, None, None, Some(2), 5), Some((1, 7)));
+        assert_eq!(
+            line_window(100, Some(90), Some(500), None, 0),
+            Some((90, 100))
+        );
+        assert_eq!(line_window(100, Some(500), None, None, 0), Some((100, 100)));
+        assert_eq!(line_window(100, None, None, None, 0), None);
+        assert_eq!(line_window(0, Some(1), Some(5), None, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_file_slices() {
+        let (dir, service) = create_test_service().await;
+        let body: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("src/long.rs"), body).unwrap();
+        let tool = GetFileTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/long.rs"));
+        args.insert("around_line".to_string(), serde_json::json!(15));
+        args.insert("context".to_string(), serde_json::json!(2));
+        let result = tool.execute(args).await.unwrap();
+        let text = result_text(&result);
+        assert!(text.contains("Lines 13-17 of 30"));
+        assert!(text.contains("line 13\nline 14\nline 15\nline 16\nline 17\n```"));
+        assert!(!text.contains("line 12\n"));
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/long.rs"));
+        args.insert("start_line".to_string(), serde_json::json!(28));
+        args.insert("end_line".to_string(), serde_json::json!(99));
+        let result = tool.execute(args).await.unwrap();
+        assert!(!result.is_error);
+        assert!(result_text(&result).contains("Lines 28-30 of 30"));
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("../etc/passwd"));
+        let result = tool.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "invalid_path");
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/missing.rs"));
+        let result = tool.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "file_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_context_reports_budget_exclusions() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(&format!(
+            "Path: src/lib.rs\nfn parse_config() {{}}\n\nPath: src/big.rs\n{}\n\nPath: src/cli.rs\nfn main() {{}}",
+            "// filler\n".repeat(100)
+        ));
+        let (dir, _) = create_test_service().await;
+        let service = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = GetContextTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("config parsing"));
+        args.insert("token_budget".to_string(), serde_json::json!(50));
+        let result = tool.execute(args).await.unwrap();
+        let text = result_text(&result);
+        assert!(text.contains("**Included files:** src/lib.rs, src/cli.rs"));
+        assert!(text.contains("src/big.rs"));
+        assert!(text.contains("fn main() {}"));
+        assert!(!text.contains("// filler"));
+    }
+
+    #[tokio::test]
+    async fn test_codebase_retrieval_returns_partial_results_on_timeout() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: src/lib.rs\nfn parse_config() {}\n\nPath: src/cli.rs\nfn main() {}",
+        );
+        let (dir, _) = create_test_service().await;
+        let config = Config {
+            request_timeout_secs: 1,
+            retry_max_attempts: 1,
+            ..backend.config(dir.path())
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        service.index_workspace().await.unwrap();
+        // The body stops partway into the second snippet
+        backend.stall_after("agents/codebase-retrieval", 75, Duration::from_secs(5));
+        let tool = CodebaseRetrievalTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert(
+            "information_request".to_string(),
+            serde_json::json!("config parsing"),
+        );
+        let result = tool.execute(args.clone()).await.unwrap();
+        assert!(!result.is_error);
+        let text = result_text(&result);
+        assert!(text.starts_with("Path: src/lib.rs\nfn parse_config() {}\n\n(truncated: true)"));
+        assert!(!text.contains("src/cli.rs"));
+
+        // Partial results are not cached
+        tool.execute(args).await.unwrap();
+        assert_eq!(backend.requests("agents/codebase-retrieval").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_includes_resource_links() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: src/lib.rs\nfn parse_config() {}\n\nPath: src/cli.rs\nfn main() {}\n\nPath: src/lib.rs\nfn parse_args() {}",
+        );
+        let (dir, _) = create_test_service().await;
+        let service = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = SearchCodeTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("config parsing"));
+        let result = tool.execute(args).await.unwrap();
+        assert!(!result.is_error);
+        assert!(result_text(&result).contains("fn parse_config"));
+
+        let links: Vec<(&str, &str)> = result.content[1..]
+            .iter()
+            .map(|block| match block {
+                ContentBlock::ResourceLink { uri, name, .. } => (uri.as_str(), name.as_str()),
+                other => panic!("expected resource link, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(links.len(), 5);
+        assert_eq!(links[0].1, "src/lib.rs");
+        assert_eq!(links[1].1, "src/cli.rs");
+        assert!(links[0].0.starts_with("file://"));
+        assert!(links[0].0.ends_with("/src/lib.rs"));
+
+        // Then one link per snippet, including both from src/lib.rs
+        assert_eq!(links[2].1, "src/lib.rs");
+        assert_eq!(links[3].1, "src/cli.rs");
+        assert_eq!(links[4].1, "src/lib.rs");
+        assert!(links[2].0.starts_with("context-engine://snippet/"));
+        assert_ne!(links[2].0, links[4].0);
+    }
+
+    #[tokio::test]
+    async fn test_search_language_filter_drops_other_hits() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: src/auth/token.rs\nfn verify() {}\n\nPath: web/auth.ts\nfunction verify() {}\n\nPath: src/db/auth.rs\nfn store() {}",
+        );
+        let (dir, _) = create_test_service().await;
+        let service = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = SearchCodeTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("auth logic"));
+        args.insert("language".to_string(), serde_json::json!("rust"));
+        let result = tool.execute(args.clone()).await.unwrap();
+        let text = result_text(&result);
+        assert!(text.contains("fn verify"));
+        assert!(text.contains("fn store"));
+        assert!(!text.contains("web/auth.ts"));
+        assert_eq!(result.content.len(), 5); // text plus two file and two snippet links
+
+        args.insert("path_glob".to_string(), serde_json::json!("src/auth/**"));
+        let text = result_text(&tool.execute(args.clone()).await.unwrap()).to_string();
+        assert!(text.contains("fn verify"));
+        assert!(!text.contains("fn store"));
+
+        args.insert("language".to_string(), serde_json::json!("python"));
+        let text = result_text(&tool.execute(args).await.unwrap()).to_string();
+        assert!(text.contains("No results matched"));
+
+        let query = backend.requests("agents/codebase-retrieval")[0]["information_request"].clone();
+        assert_eq!(query, "auth logic (language: rust)");
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_results() {
+        let backend = MockBackend::start().await;
+        let retrieved: Vec<String> = (0..25)
+            .map(|i| format!("Path: src/file_{:02}.rs\nfn f{}() {{}}", i, i))
+            .collect();
+        backend.set_retrieval(&retrieved.join("\n\n"));
+        let (dir, _) = create_test_service().await;
+        let service = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = SearchCodeTool::new(service);
+
+        let mut seen = Vec::new();
+        for (offset, expected, footer) in [
+            (
+                0,
+                10,
+                "Results 1-10 of 25 (has_more: true). Next page: offset 10.",
+            ),
+            (
+                10,
+                10,
+                "Results 11-20 of 25 (has_more: true). Next page: offset 20.",
+            ),
+            (20, 5, "Results 21-25 of 25 (has_more: false)."),
+        ] {
+            let mut args = HashMap::new();
+            args.insert("query".to_string(), serde_json::json!("functions"));
+            args.insert("limit".to_string(), serde_json::json!(10));
+            args.insert("offset".to_string(), serde_json::json!(offset));
+            let result = tool.execute(args).await.unwrap();
+            let text = result_text(&result);
+            assert!(text.ends_with(footer), "{}", text);
+
+            let paths: Vec<&str> = retrieval_paths(text).collect();
+            assert_eq!(paths.len(), expected);
+            seen.extend(paths.into_iter().map(str::to_string));
+        }
+
+        let all: Vec<String> = (0..25).map(|i| format!("src/file_{:02}.rs", i)).collect();
+        assert_eq!(seen, all);
+        // Pages are cut from one cached retrieval
+        assert_eq!(backend.requests("agents/codebase-retrieval").len(), 1);
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("functions"));
+        args.insert("offset".to_string(), serde_json::json!(30));
+        let text = result_text(&tool.execute(args).await.unwrap()).to_string();
+        assert_eq!(text, "No results at offset 30 (total: 25).");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_file_includes_outline_and_module_doc() {
+        let (dir, service) = create_test_service().await;
+        std::fs::write(
+            dir.path().join("src/router.rs"),
+            "//! Request routing.\n//!\n//! Maps paths to handlers.\n\npub struct Router;\n\nimpl Router {\n    pub fn route(&self, path: &str) {}\n}\n",
+        )
+        .unwrap();
+        let tool = SummarizeFileTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/router.rs"));
+        let result = tool.execute(args).await.unwrap();
+        let json = result_json(&result);
+        assert_eq!(json["language"], "rust");
+        assert_eq!(
+            json["module_doc"],
+            "Request routing.\n\nMaps paths to handlers."
+        );
+        let names: Vec<&str> = json["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"Router"));
+        assert!(names.contains(&"route"));
+        assert!(json.get("purpose").is_none());
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("../outside.rs"));
+        let result = tool.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "invalid_path");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_file_degrades_without_backend() {
+        let (_dir, service) = create_test_service().await;
+        let tool = SummarizeFileTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/lib.rs"));
+        args.insert("purpose".to_string(), serde_json::json!(true));
+        let result = tool.execute(args).await.unwrap();
+        assert!(!result.is_error);
+        let json = result_json(&result);
+        assert!(json.get("purpose").is_none());
+        assert!(json["purpose_unavailable"].is_string());
+        assert_eq!(json["symbols"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_limits_depth_and_honors_gitignore() {
+        let (dir, _) = create_test_service().await;
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/service/deep")).unwrap();
+        std::fs::create_dir_all(root.join("generated")).unwrap();
+        std::fs::write(root.join(".gitignore"), "generated/\n*.tmp\n").unwrap();
+        std::fs::write(root.join("src/service/mod.rs"), "x").unwrap();
+        std::fs::write(root.join("src/service/deep/inner.rs"), "x").unwrap();
+        std::fs::write(root.join("src/scratch.tmp"), "x").unwrap();
+        std::fs::write(root.join("generated/out.rs"), "x").unwrap();
+        let config = Config {
+            workspace: root.to_path_buf(),
+            ..Config::default()
+        };
+        let tool = ListTreeTool::new(Arc::new(ContextService::new(&config).await.unwrap()));
+
+        let mut args = HashMap::new();
+        args.insert("max_depth".to_string(), serde_json::json!(2));
+        args.insert("format".to_string(), serde_json::json!("text"));
+        let result = tool.execute(args).await.unwrap();
+        assert_eq!(
+            result_text(&result),
+            "./ (5 files)\n  src/ (3 files)\n    service/ (2 files)\n    lib.rs\n  .gitignore\n  README.md\n"
+        );
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/service"));
+        let json = result_json(&tool.execute(args).await.unwrap());
+        assert_eq!(json["root"]["path"], "src/service");
+        assert_eq!(
+            json["root"]["children"][0]["children"][0]["path"],
+            "src/service/deep/inner.rs"
+        );
+        assert_eq!(json["truncated"], false);
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("../"));
+        let result = tool.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "invalid_path");
+    }
+
+    #[tokio::test]
+    async fn test_grep_literal_mode() {
+        let (_dir, service) = create_test_service().await;
+        let tool = GrepTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("pattern".to_string(), serde_json::json!("parse_config"));
+        args.insert("context_lines".to_string(), serde_json::json!(1));
+        let json = result_json(&tool.execute(args).await.unwrap());
+
+        let matches = json["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0]["path"], "README.md");
+        assert_eq!(matches[1]["path"], "src/lib.rs");
+        assert_eq!(matches[1]["line"], 1);
+        assert_eq!(matches[1]["after"][0], "fn helper() {}");
+        assert_eq!(json["truncated"], false);
+    }
+
+    #[tokio::test]
+    async fn test_grep_streams_matches_before_summary() {
+        let (_dir, service) = create_test_service().await;
+        let tool = GrepTool::new(service);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let progress = ProgressReporter::new(serde_json::json!("grep-1"), tx);
+
+        let mut args = HashMap::new();
+        args.insert("pattern".to_string(), serde_json::json!("parse_config"));
+        args.insert("stream".to_string(), serde_json::json!(true));
+        let result = tool
+            .execute_with_progress(args, Some(progress))
+            .await
+            .unwrap();
+        let summary = result_json(&result);
+        assert_eq!(summary["streamed"], 2);
+        assert!(summary.get("matches").is_none());
+
+        // Every notification was sent before the tool returned its summary
+        let mut lines = Vec::new();
+        while let Ok(crate::mcp::transport::Message::Notification(n)) = rx.try_recv() {
+            let params = n.params.unwrap();
+            assert_eq!(params["progressToken"], "grep-1");
+            let line = params["message"].as_str().unwrap().to_string();
+            assert!(!line.contains('\n'));
+            lines.push(serde_json::from_str::<Value>(&line).unwrap());
+        }
+        let paths: Vec<&str> = lines.iter().map(|m| m["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, vec!["README.md", "src/lib.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_explains_hits() {
+        let backend = MockBackend::start().await;
+        backend.set_retrieval(
+            "Path: src/lib.rs\nfn helper() {}\npub fn parse_args() {}\n\nPath: README.md\nCall parse_config first.",
+        );
+        let (dir, _) = create_test_service().await;
+        let service = Arc::new(
+            ContextService::new(&backend.config(dir.path()))
+                .await
+                .unwrap(),
+        );
+        service.index_workspace().await.unwrap();
+        let tool = SearchCodeTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), serde_json::json!("parse args"));
+        args.insert("explain".to_string(), serde_json::json!(true));
+        let json = result_json(&tool.execute(args).await.unwrap());
+
+        let results = json["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        let top = &results[0];
+        assert_eq!(top["path"], "src/lib.rs");
+        assert_eq!(top["content"], "fn helper() {}\npub fn parse_args() {}");
+        assert_eq!(top["score"], 1.0);
+        assert_eq!(top["relevance_score"], 1.0);
+        assert_eq!(top["lines"], "2-3");
+        assert_eq!(top["best_line"], 3);
+        assert_eq!(top["best_line_text"], "pub fn parse_args() {}");
+        assert_eq!(top["matched_terms"], serde_json::json!(["parse", "args"]));
+        assert_eq!(results[1]["score"], 0.5);
+        assert_eq!(results[1]["matched_terms"], serde_json::json!(["parse"]));
+    }
+
+    #[tokio::test]
+    async fn test_grep_regex_mode() {
+        let (_dir, service) = create_test_service().await;
+        let tool = GrepTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert(
+            "pattern".to_string(),
+            serde_json::json!(r"^pub fn parse_\w+"),
+        );
+        args.insert("mode".to_string(), serde_json::json!("regex"));
+        let json = result_json(&tool.execute(args).await.unwrap());
+
+        let lines: Vec<u64> = json["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+
+        let mut args = HashMap::new();
+        args.insert("pattern".to_string(), serde_json::json!("("));
+        args.insert("mode".to_string(), serde_json::json!("regex"));
+        assert!(tool.execute(args).await.unwrap().is_error);
+    }
+
+    #[tokio::test]
+    async fn test_file_outline_filters_kinds_and_flattens() {
+        let (temp, service) = create_test_service().await;
+        std::fs::write(
+            temp.path().join("src/server.rs"),
+            "pub struct Server;\nimpl Server {\n    pub fn start(&self) {}\n}\nfn main() {}\npub enum Mode { A }\n",
+        )
+        .unwrap();
+        let tool = FileOutlineTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/server.rs"));
+        args.insert("kinds".to_string(), serde_json::json!(["function"]));
+        let json = result_json(&tool.execute(args).await.unwrap());
+        assert_eq!(json["language"], "rust");
+        assert_eq!(json["count"], 2);
+        let groups = json["symbols"].as_object().unwrap();
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["function"]);
+        assert_eq!(groups["function"][0]["name"], "Server::start");
+        assert_eq!(groups["function"][1]["name"], "main");
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/server.rs"));
+        args.insert("flat".to_string(), serde_json::json!(true));
+        let json = result_json(&tool.execute(args).await.unwrap());
+        let flat: Vec<(String, u64)> = json["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                (
+                    s["kind"].as_str().unwrap().to_string(),
+                    s["line"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            flat,
+            vec![
+                ("struct".to_string(), 1),
+                ("impl".to_string(), 2),
+                ("function".to_string(), 3),
+                ("function".to_string(), 5),
+                ("enum".to_string(), 6),
             ]
-        });
+        );
+        assert_eq!(json["symbols"][2]["parent"], "Server");
+        assert!(json["symbols"][3].get("parent").is_none());
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/server.rs"));
+        args.insert("kinds".to_string(), serde_json::json!(["widget"]));
+        let result = tool.execute(args).await.unwrap();
+        assert!(result.is_error);
+        assert!(result_text(&result).contains("Unknown symbol kind 'widget'"));
+    }
+
+    #[tokio::test]
+    async fn test_file_tools_resolve_against_secondary_root() {
+        let workspace = TempDir::new().unwrap();
+        let shared = TempDir::new().unwrap();
+        std::fs::create_dir_all(shared.path().join("lib")).unwrap();
+        std::fs::write(shared.path().join("lib/util.rs"), "pub fn shared() {}\n").unwrap();
+        std::fs::write(shared.path().join("lib/only.rs"), "fn only() {}\n").unwrap();
+        std::fs::write(workspace.path().join("secret.txt"), "secret").unwrap();
+        let config = Config {
+            workspace: workspace.path().to_path_buf(),
+            roots: vec![shared.path().to_path_buf()],
+            ..Config::default()
+        };
+        let service = Arc::new(ContextService::new(&config).await.unwrap());
+        let root_name = shared
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        // Selected by directory name or full path
+        let outline = FileOutlineTool::new(service.clone());
+        for root in [root_name.clone(), shared.path().display().to_string()] {
+            let mut args = HashMap::new();
+            args.insert("path".to_string(), serde_json::json!("lib/util.rs"));
+            args.insert("root".to_string(), serde_json::json!(root));
+            let json = result_json(&outline.execute(args).await.unwrap());
+            assert_eq!(json["symbols"]["function"][0]["name"], "shared");
+        }
+
+        let get_file = GetFileTool::new(service.clone());
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("lib/only.rs"));
+        args.insert("root".to_string(), serde_json::json!(root_name));
+        let result = get_file.execute(args).await.unwrap();
+        assert!(result_text(&result).contains("fn only() {}"));
+
+        // Without a root, paths stay relative to the workspace
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("lib/only.rs"));
+        let result = get_file.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "file_not_found");
+
+        // The secondary root gets the same escape checks
+        let escape = format!(
+            "../{}/secret.txt",
+            workspace.path().file_name().unwrap().to_string_lossy()
+        );
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!(escape));
+        args.insert("root".to_string(), serde_json::json!(root_name));
+        let result = get_file.execute(args).await.unwrap();
+        assert_eq!(result.error.unwrap().code, "invalid_path");
 
-        Ok(success_result(serde_json::to_string_pretty(&manifest)?))
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("lib/util.rs"));
+        args.insert("root".to_string(), serde_json::json!("/etc"));
+        let result = get_file.execute(args).await.unwrap();
+        assert!(result_text(&result).contains("Unknown root '/etc'"));
+        assert_eq!(result.error.unwrap().code, "invalid_path");
+    }
+
+    #[tokio::test]
+    async fn test_workspace_stats_counts_languages() {
+        let (_temp_dir, service) = create_test_service().await;
+        let tool = WorkspaceStatsTool::new(service);
+
+        let json = result_json(&tool.execute(HashMap::new()).await.unwrap());
+        assert_eq!(json["files"], 2);
+        assert_eq!(json["languages"]["rust"]["lines"], 3);
+        assert_eq!(json["languages"]["markdown"]["files"], 1);
+        assert_eq!(json["rescanned"], 2);
+
+        let json = result_json(&tool.execute(HashMap::new()).await.unwrap());
+        assert_eq!(json["rescanned"], 0);
+
+        let mut args = HashMap::new();
+        args.insert("format".to_string(), serde_json::json!("table"));
+        let result = tool.execute(args).await.unwrap();
+        let rows: Vec<Vec<&str>> = result_text(&result)
+            .lines()
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(rows[0], ["Language", "Files", "Lines", "Code", "Bytes"]);
+        assert_eq!(rows[2][..3], ["rust", "1", "3"]);
+        assert_eq!(rows[3][..3], ["markdown", "1", "1"]);
+        assert_eq!(rows[4][..3], ["Total", "2", "4"]);
+    }
+
+    #[tokio::test]
+    async fn test_describe_codebase_summarizes_workspace() {
+        let (temp_dir, service) = create_test_service().await;
+        std::fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(temp_dir.path().join("app.py"), "# App\nprint('hi')\n").unwrap();
+        let tool = DescribeCodebaseTool::new(service);
+
+        let mut args = HashMap::new();
+        args.insert("narrative".to_string(), Value::Bool(true));
+        let json = result_json(&tool.execute(args).await.unwrap());
+        assert_eq!(json["languages"]["rust"]["files"], 2);
+        assert_eq!(json["languages"]["python"]["comment_lines"], 1);
+        assert_eq!(
+            json["entry_points"],
+            serde_json::json!(["src/main.rs", "src/lib.rs", "app.py"])
+        );
+        assert_eq!(json["readme"], "README.md");
+        assert!(json["layout"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::String("src/".to_string())));
+        // No backend configured: the narrative is skipped, not an error
+        assert!(json.get("narrative").is_none());
+        assert!(json["narrative_unavailable"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_tool_manifest_lists_every_tool() {
+        let (temp_dir, service) = create_test_service().await;
+        let memory = Arc::new(
+            crate::service::MemoryService::new(temp_dir.path())
+                .await
+                .unwrap(),
+        );
+        let planning = Arc::new(
+            crate::service::PlanningService::new(temp_dir.path())
+                .await
+                .unwrap(),
+        );
+        let mut handler = McpHandler::new();
+        crate::tools::register_all_tools(&mut handler, service, memory, planning);
+        let tool = handler.get_tool("tool_manifest").unwrap();
+        let names: Vec<String> = handler.list_tools().into_iter().map(|t| t.name).collect();
+
+        let json = result_json(&tool.execute(HashMap::new()).await.unwrap());
+        assert_eq!(json["tool_count"], names.len());
+        let tools = json["tools"].as_array().unwrap();
+        for name in &names {
+            let entry = tools
+                .iter()
+                .find(|t| t["name"] == name.as_str())
+                .unwrap_or_else(|| panic!("{} missing from manifest", name));
+            assert!(entry["input_schema"].is_object());
+            assert!(!entry["category"].as_str().unwrap().is_empty());
+        }
+        assert!(json["categories"]["memory"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("add_memory")));
+        assert!(json["categories"]["retrieval"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("tool_manifest")));
+
+        let mut args = HashMap::new();
+        args.insert("format".to_string(), serde_json::json!("markdown"));
+        let result = tool.execute(args).await.unwrap();
+        let markdown = result_text(&result);
+        assert!(markdown.contains("| Tool | Category | Description |"));
+        for name in &names {
+            assert!(markdown.contains(&format!("| `{}` |", name)), "{}", name);
+        }
     }
 }
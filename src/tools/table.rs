@@ -0,0 +1,114 @@
+//! Aligned plain-text tables for tools offering `format: "table"`.
+//!
+//! Columns are padded to their widest cell and separated by two spaces, with
+//! a dashed rule under the header. A column whose non-blank cells are all
+//! numbers (or `-`) is right-aligned, so digits line up.
+
+/// A table of text cells, built row by row.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// An empty table with these column headers.
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; missing cells are left blank and extra ones dropped.
+    pub fn row<I, S>(&mut self, cells: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        let mut row: Vec<String> = cells
+            .into_iter()
+            .take(self.headers.len())
+            .map(|c| c.to_string())
+            .collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+        self
+    }
+
+    /// Render the table, one line per row, each ending in a newline.
+    pub fn render(&self) -> String {
+        let columns = self.headers.len();
+        let width = |column: usize| {
+            std::iter::once(&self.headers[column])
+                .chain(self.rows.iter().map(|row| &row[column]))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let widths: Vec<usize> = (0..columns).map(width).collect();
+        let numeric: Vec<bool> = (0..columns)
+            .map(|column| {
+                let mut cells = self
+                    .rows
+                    .iter()
+                    .map(|row| row[column].as_str())
+                    .filter(|cell| !cell.is_empty())
+                    .peekable();
+                cells.peek().is_some() && cells.all(is_number)
+            })
+            .collect();
+
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    if numeric[column] {
+                        format!("{:>1$}", cell, widths[column])
+                    } else {
+                        format!("{:<1$}", cell, widths[column])
+                    }
+                })
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+
+        let mut out = line(&self.headers);
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        out.push_str(&line(&rule));
+        for row in &self.rows {
+            out.push_str(&line(row));
+        }
+        out
+    }
+}
+
+fn is_number(cell: &str) -> bool {
+    cell == "-" || cell.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_align_and_numbers_right_justify() {
+        let mut table = Table::new(&["Language", "Files", "Lines"]);
+        table.row(["rust", "12", "3400"]).row(["python", "3", "-"]);
+        table.row(["c"]);
+        assert_eq!(
+            table.render(),
+            "Language  Files  Lines\n\
+             --------  -----  -----\n\
+             rust         12   3400\n\
+             python        3      -\n\
+             c\n"
+        );
+    }
+
+    #[test]
+    fn test_header_only_table() {
+        assert_eq!(Table::new(&["A", "Bb"]).render(), "A  Bb\n-  --\n");
+    }
+}
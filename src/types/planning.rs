@@ -35,6 +35,9 @@ pub struct Plan {
     /// Tags for categorization
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Approval requests, answered or not
+    #[serde(default)]
+    pub approvals: Vec<ApprovalRequest>,
 }
 
 /// Status of a plan.
@@ -74,6 +77,9 @@ pub struct Step {
     /// Actual duration in minutes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub actual_duration: Option<u32>,
+    /// When the step last started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
     /// Completion timestamp
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<String>,
@@ -111,6 +117,42 @@ pub enum StepStatus {
     AwaitingApproval,
 }
 
+impl StepStatus {
+    /// The status as serialized, e.g. `in_progress`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Ready => "ready",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+            Self::Blocked => "blocked",
+            Self::AwaitingApproval => "awaiting_approval",
+        }
+    }
+
+    /// Whether a step may move from this status to `next`.
+    ///
+    /// Work follows `pending -> in_progress -> completed | failed`. Before a
+    /// step starts it may also be marked ready, blocked, awaiting approval
+    /// or skipped; a running step may block or wait for approval; a failed
+    /// step may be retried. Completed and skipped steps are final, and a
+    /// step never moves to the status it already has.
+    pub fn can_transition_to(self, next: StepStatus) -> bool {
+        use StepStatus::*;
+        match (self, next) {
+            _ if self == next => false,
+            (Pending | Ready | Blocked | AwaitingApproval, _) => {
+                !matches!(next, Completed | Failed)
+            }
+            (InProgress, Completed | Failed | Blocked | AwaitingApproval) => true,
+            (Failed, Pending | InProgress) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Type of step.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -126,6 +168,23 @@ pub enum StepType {
     Manual,
 }
 
+impl StepType {
+    /// The type as serialized, e.g. `implementation`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Analysis => "analysis",
+            Self::Implementation => "implementation",
+            Self::Testing => "testing",
+            Self::Review => "review",
+            Self::Deployment => "deployment",
+            Self::Documentation => "documentation",
+            Self::Refactoring => "refactoring",
+            Self::Configuration => "configuration",
+            Self::Manual => "manual",
+        }
+    }
+}
+
 /// Approval status for a step.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApprovalStatus {
@@ -142,6 +201,50 @@ pub struct ApprovalStatus {
     pub notes: Option<String>,
 }
 
+/// State of an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalState {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalState {
+    /// The state as serialized, e.g. `pending`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// A request for a human to approve a plan or some of its steps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Unique request identifier
+    pub id: String,
+    pub plan_id: String,
+    /// Steps to approve; empty for the whole plan
+    #[serde(default)]
+    pub step_ids: Vec<u32>,
+    pub state: ApprovalState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<String>,
+    /// Request timestamp (ISO 8601)
+    pub requested_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responded_by: Option<String>,
+    /// Response timestamp (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responded_at: Option<String>,
+    /// Comment left with the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
 /// Validation criterion for a step.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationCriterion {
@@ -167,8 +270,8 @@ pub struct RiskAssessment {
     pub mitigations: Vec<String>,
 }
 
-/// Risk level.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Risk level, ordered from lowest to highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     Low,
@@ -190,6 +293,87 @@ pub struct RiskFactor {
     pub likelihood: RiskLevel,
 }
 
+/// Progress of a plan's steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanProgress {
+    pub plan_id: String,
+    pub title: String,
+    pub total_steps: usize,
+    pub completed: usize,
+    pub in_progress: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Steps not yet started, in any pre-start status
+    pub pending: usize,
+    /// Unfinished steps marked blocked or waiting on an unfinished dependency
+    pub blocked: usize,
+    /// Completed share of the non-skipped steps, weighted by estimate, 0-100
+    pub progress_percent: f64,
+    /// Remaining steps times the average duration of finished ones, if any
+    /// finished step has a duration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_minutes: Option<u32>,
+}
+
+/// A saved snapshot of a plan's definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanVersion {
+    /// Version number, starting at 1
+    pub version: u32,
+    /// Snapshot timestamp (ISO 8601)
+    pub created_at: String,
+    pub title: String,
+    pub description: String,
+    pub steps: Vec<Step>,
+}
+
+/// A changed field of a step, with both values rendered as text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A step present in both versions that differs between them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepChange {
+    pub id: u32,
+    /// Title in the newer version
+    pub title: String,
+    /// Changed fields other than dependencies
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FieldChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies_added: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies_removed: Vec<u32>,
+}
+
+/// Differences between two versions of a plan, steps matched by id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDiff {
+    pub plan_id: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Changes to the plan's own title and description
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plan_changes: Vec<FieldChange>,
+    pub added: Vec<Step>,
+    pub removed: Vec<Step>,
+    pub modified: Vec<StepChange>,
+}
+
+impl PlanDiff {
+    /// Whether the two versions define the same plan.
+    pub fn is_empty(&self) -> bool {
+        self.plan_changes.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,6 +422,7 @@ mod tests {
             affected_files: vec![],
             estimated_duration: Some(30),
             actual_duration: None,
+            started_at: None,
             completed_at: None,
             error: None,
             output: None,
@@ -269,6 +454,7 @@ mod tests {
                 affected_files: vec![],
                 estimated_duration: None,
                 actual_duration: None,
+                started_at: None,
                 completed_at: None,
                 error: None,
                 output: None,
@@ -281,6 +467,7 @@ mod tests {
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             metadata: HashMap::new(),
             risk_assessment: None,
+            approvals: Vec::new(),
             estimated_duration: Some(60),
             tags: vec!["refactor".to_string()],
         };
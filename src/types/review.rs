@@ -53,6 +53,12 @@ pub struct ReviewFile {
     pub path: String,
     /// Change type
     pub change_type: ChangeType,
+    /// Path before a rename or copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Binary file, which has no line counts
+    #[serde(default)]
+    pub binary: bool,
     /// Number of additions
     pub additions: u32,
     /// Number of deletions
@@ -126,10 +132,17 @@ pub struct Finding {
     /// Category
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,
+    /// Number of identical findings merged into this one
+    #[serde(default = "default_occurrences")]
+    pub occurrences: u32,
+}
+
+fn default_occurrences() -> u32 {
+    1
 }
 
 /// Type of finding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FindingType {
     Bug,
@@ -145,8 +158,8 @@ pub enum FindingType {
     Compatibility,
 }
 
-/// Severity level.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Severity level, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Info,
@@ -244,6 +257,7 @@ mod tests {
             code_snippet: Some("query(user_input)".to_string()),
             actionable: true,
             category: Some("security".to_string()),
+            occurrences: 1,
         };
 
         let json = serde_json::to_string(&finding).unwrap();
@@ -294,6 +308,8 @@ mod tests {
             change_type: ChangeType::Modified,
             additions: 10,
             deletions: 5,
+            old_path: None,
+            binary: false,
             hunks: vec![],
             risk_score: 25,
             findings: vec![],
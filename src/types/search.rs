@@ -24,6 +24,17 @@ pub struct Chunk {
     pub contents: String,
 }
 
+/// Text returned by a semantic search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Retrieval {
+    /// Formatted snippets, each starting with a `Path:` line
+    pub text: String,
+    /// The backend timed out and `text` holds only the snippets received
+    /// before it did
+    #[serde(default)]
+    pub truncated: bool,
+}
+
 /// Result from a codebase search query.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -49,6 +60,15 @@ pub struct SearchResult {
     /// Chunk identifier
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chunk_id: Option<String>,
+    /// Line of the snippet that best matches the query (1-based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_line: Option<u32>,
+    /// Text of that line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_line_text: Option<String>,
+    /// Query terms found in the snippet
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_terms: Vec<String>,
 }
 
 /// Type of search match.
@@ -77,6 +97,21 @@ pub struct IndexStatus {
     /// Last error message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+    /// Whether an indexing run is in progress
+    #[serde(default)]
+    pub indexing: bool,
+    /// Files discovered by the current (or last) indexing run
+    #[serde(default)]
+    pub files_total: usize,
+    /// Files processed so far by the current (or last) indexing run
+    #[serde(default)]
+    pub files_done: usize,
+    /// Bytes of file content sent for indexing by the current (or last) run
+    #[serde(default)]
+    pub bytes_indexed: u64,
+    /// When the current (or last) indexing run started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
 }
 
 /// Indexing state.
@@ -198,6 +233,9 @@ mod tests {
             match_type: Some(MatchType::Semantic),
             retrieved_at: None,
             chunk_id: None,
+            best_line: None,
+            best_line_text: None,
+            matched_terms: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -226,6 +264,11 @@ mod tests {
             last_indexed: Some("2024-01-01T00:00:00Z".to_string()),
             is_stale: false,
             last_error: None,
+            indexing: true,
+            files_total: 200,
+            files_done: 100,
+            bytes_indexed: 4096,
+            started_at: Some("2024-01-01T00:00:00Z".to_string()),
         };
 
         let json = serde_json::to_string(&status).unwrap();
@@ -233,6 +276,8 @@ mod tests {
 
         assert!(matches!(parsed.status, IndexState::Indexing));
         assert_eq!(parsed.file_count, 100);
+        assert_eq!(parsed.files_done, 100);
+        assert_eq!(parsed.files_total, 200);
     }
 
     #[test]